@@ -46,6 +46,22 @@ pub mod did {
     pub const USER: [u8; 5] = *b"USER:";
     /// prefix for security token dids
     pub const SECURITY_TOKEN: [u8; 15] = *b"SECURITY_TOKEN:";
+    /// prefix for escrow dids
+    pub const ESCROW: [u8; 7] = *b"ESCROW:";
+    /// How long the first confirmation of a dual unfreeze stays valid, in milliseconds.
+    pub const DUAL_UNFREEZE_WINDOW: u64 = 24 * 60 * 60 * 1000;
+    /// How long the master key can cancel a recovery before it can be completed, in milliseconds.
+    pub const RECOVERY_TIMELOCK: u64 = 7 * 24 * 60 * 60 * 1000;
+    /// A skipped authorization does not exist.
+    pub const AUTH_SKIPPED_NOT_FOUND: u8 = 0x01;
+    /// A skipped authorization has a type that cannot be accepted.
+    pub const AUTH_SKIPPED_UNKNOWN_TYPE: u8 = 0x02;
+    /// The handler of a skipped authorization failed to accept it.
+    pub const AUTH_SKIPPED_HANDLER_FAILED: u8 = 0x03;
+    /// Maximum number of call identifiers a signing key can be restricted to.
+    pub const MAX_ALLOWED_CALLS: usize = 32;
+    /// Maximum length, in bytes, of a call identifier.
+    pub const MAX_CALL_ID_LEN: usize = 32;
 }
 
 // ERC1400 transfer status codes
@@ -69,5 +85,11 @@ pub const APP_TX_VOLUME_LIMIT_REACHED: u8 = 0xa5;
 pub const APP_BLACKLISTED_TX: u8 = 0xa6;
 pub const APP_FUNDS_LOCKED: u8 = 0xa7;
 pub const APP_INVALID_GRANULARITY: u8 = 0xa8;
+pub const APP_RECEIVER_KYC_INVALID: u8 = 0xa9;
+pub const APP_SENDER_CLAIMS_MISSING: u8 = 0xaa;
+pub const APP_NOT_ALLOWLISTED: u8 = 0xab;
+pub const APP_SUPPLY_FLOOR_BREACHED: u8 = 0xac;
 
 pub const KYC_EXPIRY_CLAIM_KEY: [u8; 18] = *b"KYCExpiryTimestamp";
+/// Key of the claim naming the custodian DID of a token holder.
+pub const CUSTODIAN_CLAIM_KEY: [u8; 9] = *b"Custodian";
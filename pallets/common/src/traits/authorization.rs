@@ -0,0 +1,65 @@
+use polymesh_primitives::{AuthorizationData, Signatory};
+
+use frame_support::dispatch::{DispatchError, DispatchResult};
+
+/// This trait is used by the identity module to accept the authorizations of the kinds that a
+/// handler declares. Handlers are combined as tuples, where the first handler of the tuple that
+/// handles an authorization accepts it.
+pub trait AuthorizationHandler {
+    /// Whether this handler accepts authorizations carrying `authorization_data`.
+    fn handles(authorization_data: &AuthorizationData) -> bool;
+
+    /// Accept and process an authorization
+    ///
+    /// # Arguments
+    /// * `signer` did/key which received the authorization
+    /// * `authorization_data` Data of the authorization
+    /// * `auth_id` Authorization id of the authorization
+    fn accept(
+        signer: Signatory,
+        authorization_data: &AuthorizationData,
+        auth_id: u64,
+    ) -> DispatchResult;
+}
+
+impl AuthorizationHandler for () {
+    fn handles(_: &AuthorizationData) -> bool {
+        false
+    }
+
+    fn accept(_: Signatory, _: &AuthorizationData, _: u64) -> DispatchResult {
+        Err(DispatchError::Other("Unknown authorization"))
+    }
+}
+
+macro_rules! impl_authorization_handler_for_tuples {
+    ($($handler:ident),+) => {
+        impl<$($handler: AuthorizationHandler),+> AuthorizationHandler for ($($handler,)+) {
+            fn handles(authorization_data: &AuthorizationData) -> bool {
+                $($handler::handles(authorization_data))||+
+            }
+
+            fn accept(
+                signer: Signatory,
+                authorization_data: &AuthorizationData,
+                auth_id: u64,
+            ) -> DispatchResult {
+                $(
+                    if $handler::handles(authorization_data) {
+                        return $handler::accept(signer, authorization_data, auth_id);
+                    }
+                )+
+                Err(DispatchError::Other("Unknown authorization"))
+            }
+        }
+    };
+}
+
+impl_authorization_handler_for_tuples!(A);
+impl_authorization_handler_for_tuples!(A, B);
+impl_authorization_handler_for_tuples!(A, B, C);
+impl_authorization_handler_for_tuples!(A, B, C, D);
+impl_authorization_handler_for_tuples!(A, B, C, D, E);
+impl_authorization_handler_for_tuples!(A, B, C, D, E, F);
+impl_authorization_handler_for_tuples!(A, B, C, D, E, F, G);
+impl_authorization_handler_for_tuples!(A, B, C, D, E, F, G, H);
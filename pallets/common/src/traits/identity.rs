@@ -1,11 +1,18 @@
 use crate::traits::{
-    balances, group::GroupTrait, multisig::AddSignerMultiSig, CommonTrait, NegativeImbalance,
+    authorization::AuthorizationHandler, balances, group::GroupTrait, multisig::AddSignerMultiSig,
+    CommonTrait, NegativeImbalance,
 };
 use polymesh_primitives::{
-    AccountKey, AuthorizationData, IdentityId, LinkData, Permission, Signatory, SigningItem, Ticker,
+    AccountKey, AuthorizationData, AuthorizationType, IdentityId, LinkData, Permission, Signatory,
+    SigningItem, Ticker,
 };
 
-use frame_support::{decl_event, weights::GetDispatchInfo, Parameter};
+use frame_support::{
+    decl_event,
+    traits::{Get, OnUnbalanced},
+    weights::GetDispatchInfo,
+    Parameter,
+};
 use frame_system;
 use sp_core::H512;
 use sp_runtime::traits::Dispatchable;
@@ -66,6 +73,23 @@ pub enum LinkedKeyInfo {
     Group(Vec<IdentityId>),
 }
 
+/// Result of checking the identities a key is linked to in `KeyToIdentityIds` against the
+/// records of those identities.
+#[derive(codec::Encode, codec::Decode, Default, Clone, PartialEq, Eq, Debug)]
+pub struct KeyLinkConsistency {
+    /// Linked identities that list the key as master key or signing key.
+    pub linked_dids: Vec<IdentityId>,
+    /// Linked identities that do not list the key, or do not exist.
+    pub dangling_dids: Vec<IdentityId>,
+}
+
+impl KeyLinkConsistency {
+    /// It returns true if no linked identity is dangling.
+    pub fn is_consistent(&self) -> bool {
+        self.dangling_dids.is_empty()
+    }
+}
+
 pub type AuthorizationNonce = u64;
 
 /// It represents an authorization that any account could sing to allow operations related with a
@@ -117,6 +141,12 @@ pub trait Trait: CommonTrait + pallet_timestamp::Trait + balances::Trait {
         <Self as CommonTrait>::Balance,
         NegativeImbalance<Self>,
     >;
+    /// The maximum number of items accepted by a batch extrinsic.
+    type MaxBatchSize: Get<u32>;
+    /// Handlers of the authorizations that are not built into the identity module.
+    type AuthorizationHandlers: AuthorizationHandler;
+    /// Handler for the claim fees withdrawn from claim issuers.
+    type ClaimFeeCollector: OnUnbalanced<NegativeImbalance<Self>>;
 }
 // rustfmt adds a commna after Option<Moment> in NewAuthorization and it breaks compilation
 #[rustfmt::skip]
@@ -125,10 +155,19 @@ decl_event!(
     where
         AccountId = <T as frame_system::Trait>::AccountId,
         Moment = <T as pallet_timestamp::Trait>::Moment,
+        BlockNumber = <T as frame_system::Trait>::BlockNumber,
+        Balance = <T as CommonTrait>::Balance,
     {
         /// DID, master key account ID, signing keys
         NewDid(IdentityId, AccountId, Vec<SigningItem>),
 
+        /// DID registration was rejected because the master key is already linked.
+        /// (master key, existing DID)
+        DidRegistrationRejected(AccountKey, IdentityId),
+
+        /// The minimum number of blocks between DID registrations of an account was changed.
+        DidRegistrationCooldownChanged(BlockNumber),
+
         /// DID, new keys
         NewSigningItems(IdentityId, Vec<SigningItem>),
 
@@ -138,6 +177,15 @@ decl_event!(
         /// DID, updated signing key, previous permissions
         SigningPermissionsUpdated(IdentityId, SigningItem, Vec<Permission>),
 
+        /// DID, signer, identifiers of the calls the signer can be used for
+        SigningItemAllowedCallsUpdated(IdentityId, Signatory, Option<Vec<Vec<u8>>>),
+
+        /// DID, replaced signer, signing item with the new key
+        SigningKeyRotated(IdentityId, Signatory, SigningItem),
+
+        /// A signing item was moved between identities. (from DID, to DID, moved signing item)
+        SigningKeyMoved(IdentityId, IdentityId, SigningItem),
+
         /// DID, old master key account ID, new key
         NewMasterKey(IdentityId, AccountId, AccountKey),
 
@@ -147,12 +195,42 @@ decl_event!(
         /// DID, removed claim issuer DID
         RemovedClaimIssuer(IdentityId, IdentityId),
 
+        /// The module owner was changed. (old owner, new owner)
+        OwnerChanged(AccountId, AccountId),
+
+        /// The fee to create a DID was changed. (old fee, new fee)
+        DidCreationFeeChanged(Balance, Balance),
+
         /// DID, claim issuer DID, claims
         NewClaims(IdentityId, ClaimMetaData, Claim<Moment>),
 
         /// DID, claim issuer DID, claim
         RevokedClaim(IdentityId, ClaimMetaData),
 
+        /// DID, ticker, claim issuer DID, claim that only applies to the ticker
+        NewTickerClaim(IdentityId, Ticker, ClaimMetaData, Claim<Moment>),
+
+        /// DID, ticker, revoked claim issuer DID and claim key
+        RevokedTickerClaim(IdentityId, Ticker, ClaimMetaData),
+
+        /// Subject DID, KYC issuer DID, KYC expiry
+        KycExpirySet(IdentityId, IdentityId, Moment),
+
+        /// DID, claim metadata of an expired claim used within its grace period
+        ClaimInGracePeriod(IdentityId, ClaimMetaData),
+
+        /// DID, claim metadata, numeric value, expiry
+        NewNumericClaim(IdentityId, ClaimMetaData, u128, Moment),
+
+        /// DID, claim metadata of the removed numeric claim
+        RevokedNumericClaim(IdentityId, ClaimMetaData),
+
+        /// DID, claim metadata, new value type of the claim
+        ClaimValueTypeSet(IdentityId, ClaimMetaData, DataTypes),
+
+        /// Value type assigned to untyped claims, number of claims migrated
+        ClaimTypesMigrated(DataTypes, u32),
+
         /// DID
         NewIssuer(IdentityId),
 
@@ -165,6 +243,9 @@ decl_event!(
         /// To query the status of DID
         MyKycStatus(IdentityId, bool, Option<IdentityId>),
 
+        /// To query the KYC status of any DID (target DID, has valid KYC, KYC provider)
+        KycStatus(IdentityId, bool, Option<IdentityId>),
+
         /// New authorization added (auth_id, from, to, authorization_data, expiry)
         NewAuthorization(
             u64,
@@ -177,6 +258,16 @@ decl_event!(
         /// Authorization revoked or consumed. (auth_id, authorized_identity)
         AuthorizationRemoved(u64, Signatory),
 
+        /// Authorizations of a retiring issuer were reassigned. (from DID, to DID, number of authorizations)
+        AuthorizationsReassigned(IdentityId, IdentityId, u32),
+
+        /// Authorization accepted by its target. (auth_id, target, authorization type)
+        AuthorizationAccepted(u64, Signatory, AuthorizationType),
+
+        /// Authorization skipped by a batch acceptance. (auth_id, reason code)
+        /// See the `AUTH_SKIPPED_*` constants for the reason codes.
+        AuthorizationSkipped(u64, u8),
+
         /// MasterKey changed (Requestor DID, New MasterKey)
         MasterKeyChanged(IdentityId, AccountKey),
 
@@ -196,16 +287,76 @@ decl_event!(
 
         /// Signatory approved a previous request to join to a target identity.
         SignerJoinedToIdentityApproved( Signatory, IdentityId),
+
+        /// Expired pre-authorizations to join an identity were removed. (signer, number removed)
+        ExpiredPreAuthorizationsRemoved(Signatory, u32),
+
+        /// A forwarded call was accepted. (current DID, consumed nonce)
+        ForwardedCallNonceConsumed(IdentityId, u64),
+
+        /// The set of identities a group key is linked to has changed. (key, current DIDs)
+        KeyGroupMembershipChanged(AccountKey, Vec<IdentityId>),
+
+        /// All signing keys of an identity were frozen. (DID)
+        DidFrozen(IdentityId),
+
+        /// The signing keys of an identity were unfrozen. (DID)
+        DidUnfrozen(IdentityId),
+
+        /// A single signing key of an identity was frozen. (DID, frozen signer)
+        SigningKeyFrozen(IdentityId, Signatory),
+
+        /// A single signing key of an identity was unfrozen. (DID, unfrozen signer)
+        SigningKeyUnfrozen(IdentityId, Signatory),
+
+        /// The permissions of a signing key were suspended. (DID, suspended signer)
+        SigningKeySuspended(IdentityId, Signatory),
+
+        /// The permissions of a suspended signing key were restored. (DID, restored signer)
+        SigningKeyRestored(IdentityId, Signatory),
+
+        /// Dual unfreeze was configured. (DID, recovery key or `None` if disabled)
+        DualUnfreezeSet(IdentityId, Option<AccountKey>),
+
+        /// The first of the two confirmations of a dual unfreeze was given. (DID, confirming key)
+        UnfreezeConfirmed(IdentityId, AccountKey),
+
+        /// The recovery key of an identity was set. (DID, recovery key or `None` if removed)
+        RecoveryKeySet(IdentityId, Option<AccountKey>),
+
+        /// The recovery key started a recovery of an identity. (DID, recovery key)
+        RecoveryInitiated(IdentityId, AccountKey),
+
+        /// The master key cancelled a recovery of its identity. (DID)
+        RecoveryCancelled(IdentityId),
+
+        /// The recovery key replaced the master key of an identity. (DID, old master key, new master key)
+        RecoveryCompleted(IdentityId, AccountKey, AccountKey),
+
+        /// The master key of an identity was queried. (DID, master key)
+        MasterKeyQuery(IdentityId, AccountKey),
+
+        /// Dangling links of a key were removed. (key, unlinked DIDs)
+        KeyLinksRepaired(AccountKey, Vec<IdentityId>),
+
+        /// The signing items of an identity changed. (DID, current signing items)
+        DidKeysetUpdated(IdentityId, Vec<SigningItem>),
+
+        /// An off-chain authorization was revoked. (signer, authorization)
+        OffChainAuthorizationRevoked(Signatory, TargetIdAuthorization<Moment>),
     }
 );
 
 pub trait IdentityTrait {
     fn get_identity(key: &AccountKey) -> Option<IdentityId>;
     fn is_signer_authorized(did: IdentityId, signer: &Signatory) -> bool;
+    fn signer_to_did(signer: &Signatory) -> Option<IdentityId>;
+    fn are_signers_authorized(checks: Vec<(IdentityId, Signatory)>) -> Vec<bool>;
     fn is_signer_authorized_with_permissions(
         did: IdentityId,
         signer: &Signatory,
         permissions: Vec<Permission>,
     ) -> bool;
     fn is_master_key(did: IdentityId, key: &AccountKey) -> bool;
+    fn is_signer_authorized_for_call(did: IdentityId, signer: &Signatory, call: &[u8]) -> bool;
 }
@@ -48,6 +48,7 @@ pub mod imbalances;
 pub use imbalances::{NegativeImbalance, PositiveImbalance};
 
 pub mod asset;
+pub mod authorization;
 pub mod balances;
 pub mod group;
 pub mod identity;
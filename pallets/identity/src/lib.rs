@@ -32,6 +32,11 @@
 //! see [freeze_signing_keys](./struct.Module.html#method.freeze_signing_keys)
 //! see [unfreeze_signing_keys](./struct.Module.html#method.unfreeze_signing_keys)
 //!
+//! A single signing key can also be frozen, keeping the other signing keys usable.
+//!
+//! see [freeze_signing_key](./struct.Module.html#method.freeze_signing_key)
+//! see [unfreeze_signing_key](./struct.Module.html#method.unfreeze_signing_key)
+//!
 //! # TODO
 //!  - KYC is mocked: see [has_valid_kyc](./struct.Module.html#method.has_valid_kyc)
 
@@ -39,22 +44,28 @@
 #![recursion_limit = "256"]
 
 use polymesh_primitives::{
-    AccountKey, Authorization, AuthorizationData, AuthorizationError, Identity as DidRecord,
-    IdentityId, Link, LinkData, Permission, PreAuthorizedKeyInfo, Signatory, SignatoryType,
-    SigningItem, Ticker,
+    AccountKey, Authorization, AuthorizationData, AuthorizationError, AuthorizationType,
+    Identity as DidRecord, IdentityId, IssuedAuthorizationLinks, Link, LinkData, LinkDataTag,
+    Permission, PreAuthorizedKeyInfo, Signatory, SignatoryType, SigningItem, Ticker,
 };
 use polymesh_runtime_common::{
     constants::{
-        did::{SECURITY_TOKEN, USER},
+        did::{
+            AUTH_SKIPPED_HANDLER_FAILED, AUTH_SKIPPED_NOT_FOUND, AUTH_SKIPPED_UNKNOWN_TYPE,
+            DUAL_UNFREEZE_WINDOW, MAX_ALLOWED_CALLS, MAX_CALL_ID_LEN, RECOVERY_TIMELOCK,
+            SECURITY_TOKEN, USER,
+        },
         KYC_EXPIRY_CLAIM_KEY,
     },
     traits::{
         asset::AcceptTransfer,
+        authorization::AuthorizationHandler,
         balances::BalancesTrait,
         group::GroupTrait,
         identity::{
-            AuthorizationNonce, Claim, ClaimMetaData, ClaimRecord, ClaimValue, LinkedKeyInfo,
-            RawEvent, SigningItemWithAuth, TargetIdAuthorization,
+            AuthorizationNonce, Claim, ClaimMetaData, ClaimRecord, ClaimValue, DataTypes,
+            KeyLinkConsistency, LinkedKeyInfo, RawEvent, SigningItemWithAuth,
+            TargetIdAuthorization,
         },
         multisig::AddSignerMultiSig,
     },
@@ -70,19 +81,22 @@ use core::{
 use sp_core::sr25519::{Public, Signature};
 use sp_io::hashing::blake2_256;
 use sp_runtime::{
-    traits::{Dispatchable, Hash, SaturatedConversion, Verify},
+    traits::{Dispatchable, Hash, SaturatedConversion, Saturating, Verify, Zero},
     AnySignature,
 };
-use sp_std::{convert::TryFrom, mem::swap, prelude::*, vec};
+use sp_std::{
+    collections::btree_map::BTreeMap, convert::TryFrom, marker::PhantomData, mem::swap, prelude::*,
+    vec,
+};
 
 use frame_support::{
     decl_error, decl_module, decl_storage,
     dispatch::{DispatchError, DispatchResult},
     ensure,
-    traits::{ExistenceRequirement, WithdrawReason},
+    traits::{ExistenceRequirement, Get, OnUnbalanced, WithdrawReason},
     weights::SimpleDispatchInfo,
 };
-use frame_system::{self as system, ensure_signed};
+use frame_system::{self as system, ensure_root, ensure_signed};
 
 pub use polymesh_runtime_common::traits::identity::{IdentityTrait, Trait};
 pub type Event<T> = polymesh_runtime_common::traits::identity::Event<T>;
@@ -96,24 +110,77 @@ decl_storage! {
         /// DID -> identity info
         pub DidRecords get(fn did_records): map IdentityId => DidRecord;
 
+        /// Master key -> DID. Reverse index of the master keys of `DidRecords`.
+        pub MasterKeyToDid get(fn master_key_to_did): map AccountKey => Option<IdentityId>;
+
         /// DID -> bool that indicates if signing keys are frozen.
         pub IsDidFrozen get(fn is_did_frozen): map IdentityId => bool;
 
+        /// (DID, signer) -> bool that indicates if that signing key of the DID is frozen.
+        pub FrozenSigningKeys get(fn frozen_signing_keys): map (IdentityId, Signatory) => bool;
+
+        /// (DID, signer) -> permissions of a suspended signing key, restored by `restore_signing_key`.
+        pub SuspendedPermissions get(fn suspended_permissions): map (IdentityId, Signatory) => Option<Vec<Permission>>;
+
+        /// (DID, signer) -> identifiers of the calls that signing key of the DID can be used for.
+        /// A signing key without an entry can be used for any call.
+        pub SigningItemAllowedCalls get(fn signing_item_allowed_calls): map (IdentityId, Signatory) => Option<Vec<Vec<u8>>>;
+
+        /// DID -> recovery key that has to confirm, together with the master key, any unfreeze
+        /// of the signing keys. If it is not set, the master key alone can unfreeze.
+        pub DualUnfreezeKey get(fn dual_unfreeze_key): map IdentityId => Option<AccountKey>;
+
+        /// DID -> first unfreeze confirmation of a dual unfreeze (confirming key, timestamp).
+        pub PendingUnfreeze get(fn pending_unfreeze): map IdentityId => Option<(AccountKey, T::Moment)>;
+
+        /// DID -> key that can replace the master key after `RECOVERY_TIMELOCK`.
+        pub RecoveryKey get(fn recovery_key): map IdentityId => Option<AccountKey>;
+
+        /// DID -> time at which the recovery key started a recovery.
+        pub PendingRecovery get(fn pending_recovery): map IdentityId => Option<T::Moment>;
+
         /// It stores the current identity for current transaction.
         pub CurrentDid get(fn current_did): Option<IdentityId>;
 
         /// (DID, claim_key, claim_issuer) -> Associated claims
         pub Claims get(fn claims): map(IdentityId, ClaimMetaData) => Claim<T::Moment>;
 
+        /// (DID, ticker, claim_key, claim_issuer) -> Claim about the DID that only applies to the ticker
+        pub TickerScopedClaims get(fn ticker_scoped_claims): map(IdentityId, Ticker, ClaimMetaData) => Claim<T::Moment>;
+
         /// DID -> array of (claim_key and claim_issuer)
         pub ClaimKeys get(fn claim_keys): map IdentityId => Vec<ClaimMetaData>;
 
+        /// (DID, claim_key, claim_issuer) -> whether the value type of the claim is declared.
+        /// Claims added before value types existed are not declared until they are migrated.
+        pub TypedClaims get(fn is_claim_typed): map(IdentityId, ClaimMetaData) => bool;
+
+        /// (DID, claim_key, claim_issuer) -> (numeric claim value, expiry)
+        pub NumericClaims get(fn numeric_claims): map(IdentityId, ClaimMetaData) => (u128, T::Moment);
+
         // Account => DID
         pub KeyToIdentityIds get(fn key_to_identity_ids): map AccountKey => Option<LinkedKeyInfo>;
 
         /// How much does creating a DID cost
         pub DidCreationFee get(fn did_creation_fee) config(): T::Balance;
 
+        /// Minimum number of blocks between two `register_did` attempts of the same account.
+        /// Zero disables the limit.
+        pub DidRegistrationCooldown get(fn did_registration_cooldown): T::BlockNumber;
+
+        /// Account -> block of its latest `register_did` attempt that passed the cooldown.
+        pub LastDidRegistration get(fn last_did_registration): map T::AccountId => T::BlockNumber;
+
+        /// How much does adding a claim of any kind cost its issuer. Zero disables the fee.
+        pub ClaimFee get(fn claim_fee) config(): T::Balance;
+
+        /// How long an expired claim is still treated as valid.
+        pub ClaimExpiryGracePeriod get(fn claim_expiry_grace_period) config(): T::Moment;
+
+        /// (DID, claim_key, claim_issuer) -> expiry of the claim when it was first used in its
+        /// grace period.
+        pub ClaimGraceNotified get(fn claim_grace_notified): map(IdentityId, ClaimMetaData) => Option<T::Moment>;
+
         /// It stores validated identities by any KYC.
         pub KYCValidation get(fn has_valid_kyc): map IdentityId => bool;
 
@@ -123,6 +190,10 @@ decl_storage! {
         /// Pre-authorize join to Identity.
         pub PreAuthorizedJoinDid get(fn pre_authorized_join_did): map Signatory => Vec<PreAuthorizedKeyInfo>;
 
+        /// (signer, target DID) -> time when the pre-authorization of the signer to join the target
+        /// identity expires. Pre-authorizations without an entry do not expire.
+        pub PreAuthorizedJoinDidExpiry get(fn pre_authorized_join_did_expiry): map (Signatory, IdentityId) => Option<T::Moment>;
+
         /// Authorization nonce per Identity. Initially is 0.
         pub OffChainAuthorizationNonce get(fn offchain_authorization_nonce): map IdentityId => AuthorizationNonce;
 
@@ -132,6 +203,13 @@ decl_storage! {
         /// All authorizations that an identity has
         pub Authorizations get(fn authorizations): map(Signatory, u64) => Authorization<T::Moment>;
 
+        /// (target, auth id) -> position of the authorization among the pending authorizations
+        /// of its issuer. Used to allow iterating over the authorizations of an issuer.
+        pub AuthorizationsByIssuer get(fn issued_authorization_links): map (Signatory, u64) => Option<IssuedAuthorizationLinks>;
+
+        /// (target, auth id) of the latest pending authorization issued by an identity or key.
+        pub LastIssuedAuthorization get(fn last_issued_authorization): map Signatory => Option<(Signatory, u64)>;
+
         /// Auth id of the latest auth of an identity. Used to allow iterating over auths
         pub LastAuthorization get(fn last_authorization): map Signatory => u64;
 
@@ -140,6 +218,28 @@ decl_storage! {
 
         /// Link id of the latest auth of an identity/key. Used to allow iterating over links
         pub LastLink get(fn last_link): map Signatory => u64;
+
+        /// (DID, signer) -> permissions of that signing item.
+        /// It is a cache of `DidRecords`, which holds the authoritative signing items.
+        pub SigningItemPermissions get(fn cached_signing_item_permissions): map (IdentityId, Signatory) => Option<Vec<Permission>>;
+
+        /// Nonce of the next forwarded call per current identity. Initially is 0.
+        pub ForwardedCallNonce get(fn forwarded_call_nonce): map IdentityId => u64;
+
+        /// Ticker -> DID of the asset, stored when the asset DID is registered.
+        pub TickerToAssetDid get(fn ticker_to_asset_did): map Ticker => Option<IdentityId>;
+
+        /// Claim issuer DID -> number of claims issued by that DID which are not revoked.
+        pub ClaimsIssuedCount get(fn claims_issued_count): map IdentityId => u64;
+    }
+    add_extra_genesis {
+        // Identities as (DID, account of the master key).
+        config(identities): Vec<(IdentityId, T::AccountId)>;
+        build(|config: &GenesisConfig<T>| {
+            for (did, account) in &config.identities {
+                <Module<T>>::seed_genesis_identity(*did, account);
+            }
+        })
     }
 }
 
@@ -163,6 +263,15 @@ decl_module! {
         /// - External signing keys can be linked to just one identity.
         pub fn register_did(origin, signing_items: Vec<SigningItem>) -> DispatchResult {
             let sender = ensure_signed(origin)?;
+            let now = <system::Module<T>>::block_number();
+            let cooldown = Self::did_registration_cooldown();
+            if !cooldown.is_zero() && <LastDidRegistration<T>>::exists(&sender) {
+                ensure!(
+                    now >= Self::last_did_registration(&sender).saturating_add(cooldown),
+                    Error::<T>::RegistrationRateLimited
+                );
+            }
+            <LastDidRegistration<T>>::insert(&sender, now);
             // TODO: Subtract proper fee.
             let _imbalance = <T::Balances>::withdraw(
                 &sender,
@@ -170,7 +279,33 @@ decl_module! {
                 WithdrawReason::Fee.into(),
                 ExistenceRequirement::KeepAlive,
             )?;
-            Self::_register_did(sender, signing_items)
+            Self::_register_did(sender, signing_items)?;
+            Ok(())
+        }
+
+        /// Transfers the ownership of the module to `new_owner`. Only called by the current
+        /// module owner or root.
+        pub fn set_module_owner(origin, new_owner: T::AccountId) -> DispatchResult {
+            let old_owner = Self::owner();
+            match ensure_signed(origin.clone()) {
+                Ok(sender) => ensure!(sender == old_owner, Error::<T>::Unauthorized),
+                Err(_) => ensure_root(origin)?,
+            }
+            <Owner<T>>::put(new_owner.clone());
+            Self::deposit_event(RawEvent::OwnerChanged(old_owner, new_owner));
+            Ok(())
+        }
+
+        /// Sets the fee charged by `register_did`. Only called by the module owner or root.
+        pub fn set_did_creation_fee(origin, fee: T::Balance) -> DispatchResult {
+            match ensure_signed(origin.clone()) {
+                Ok(sender) => ensure!(sender == Self::owner(), Error::<T>::Unauthorized),
+                Err(_) => ensure_root(origin)?,
+            }
+            let old_fee = Self::did_creation_fee();
+            <DidCreationFee<T>>::put(fee);
+            Self::deposit_event(RawEvent::DidCreationFeeChanged(old_fee, fee));
+            Ok(())
         }
 
         /// Adds new signing keys for a DID. Only called by master key owner.
@@ -180,6 +315,22 @@ decl_module! {
         ///  - If any signing key is already linked to any identity, it will fail.
         ///  - If any signing key is already
         pub fn add_signing_items(origin, did: IdentityId, signing_items: Vec<SigningItem>) -> DispatchResult {
+            Self::add_signing_items_with_expiry(origin, did, signing_items, None)
+        }
+
+        /// Adds new signing keys for a DID like `add_signing_items`, but their pending
+        /// pre-authorizations to join expire at `expiry`. Expired pre-authorizations cannot be
+        /// accepted and can be removed by anyone with `cleanup_expired_pre_auths`.
+        ///
+        /// # Failure
+        ///  - It can only called by master key owner.
+        ///  - If any signing key is already linked to any identity, it will fail.
+        pub fn add_signing_items_with_expiry(
+            origin,
+            did: IdentityId,
+            signing_items: Vec<SigningItem>,
+            expiry: Option<T::Moment>
+        ) -> DispatchResult {
             let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
             let _grants_checked = Self::grant_check_only_master_key(&sender_key, did)?;
 
@@ -196,7 +347,7 @@ decl_module! {
             let authorized_signing_items = Self::did_records( did).signing_items;
             signing_items.iter()
                 .filter( |si| authorized_signing_items.contains(si) == false)
-                .for_each( |si| Self::add_pre_join_identity( si, did));
+                .for_each( |si| Self::add_pre_join_identity( si, did, expiry));
 
             Self::deposit_event(RawEvent::NewSigningItems(did, signing_items));
             Ok(())
@@ -205,14 +356,26 @@ decl_module! {
         /// Removes specified signing keys of a DID if present.
         ///
         /// # Failure
-        /// It can only called by master key owner.
+        ///  - It can only called by master key owner.
+        ///  - The identity acting in the current transaction, e.g. the target of a
+        ///  `forwarded_call`, cannot be removed.
         pub fn remove_signing_items(origin, did: IdentityId, signers_to_remove: Vec<Signatory>) -> DispatchResult {
             let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
             let _grants_checked = Self::grant_check_only_master_key(&sender_key, did)?;
+            // The sender is the master key, which is never a signing item.
+            if let Some(current_did) = Self::current_did() {
+                ensure!(
+                    !signers_to_remove.contains(&Signatory::Identity(current_did)),
+                    Error::<T>::CannotRemoveActiveSigner
+                );
+            }
 
             // Remove any Pre-Authentication & link
             signers_to_remove.iter().for_each( |signer| {
                 Self::remove_pre_join_identity( signer, did);
+                <FrozenSigningKeys>::remove((did, *signer));
+                <SuspendedPermissions>::remove((did, *signer));
+                <SigningItemAllowedCalls>::remove((did, *signer));
                 if let Signatory::AccountKey(ref key) = signer {
                     Self::unlink_key_to_did(key, did);
                 }
@@ -222,8 +385,132 @@ decl_module! {
             <DidRecords>::mutate(did, |record| {
                 (*record).remove_signing_items( &signers_to_remove);
             });
+            Self::sync_signing_item_permissions(did, &signers_to_remove);
 
             Self::deposit_event(RawEvent::RevokedSigningItems(did, signers_to_remove));
+            Self::deposit_keyset_updated(did);
+            Ok(())
+        }
+
+        /// Replaces the key of a signing item of a DID with `new_key`, keeping its signer type
+        /// and permissions. Only called by master key owner.
+        ///
+        /// # Failure
+        ///  - It can only called by master key owner.
+        ///  - `old_signer` must be one of the signing items of `did`.
+        ///  - `new_key` has to be linkable to `did` with the signer type of `old_signer`.
+        pub fn rotate_signing_key(origin, did: IdentityId, old_signer: Signatory, new_key: AccountKey) -> DispatchResult {
+            let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+            let record = Self::grant_check_only_master_key(&sender_key, did)?;
+
+            let old_item = record.signing_items.iter()
+                .find(|si| si.signer == old_signer)
+                .cloned()
+                .ok_or(Error::<T>::InvalidSender)?;
+            let new_signer = Signatory::AccountKey(new_key);
+            ensure!(
+                Self::can_key_be_linked_to_did(&new_key, old_item.signer_type)
+                    && !record.signing_items.iter().any(|si| si.signer == new_signer),
+                Error::<T>::AlreadyLinked
+            );
+
+            // Unlink the old signer.
+            Self::remove_pre_join_identity(&old_signer, did);
+            <FrozenSigningKeys>::remove((did, old_signer));
+            <SuspendedPermissions>::remove((did, old_signer));
+            if let Some(allowed_calls) = <SigningItemAllowedCalls>::take((did, old_signer)) {
+                <SigningItemAllowedCalls>::insert((did, new_signer), allowed_calls);
+            }
+            if let Signatory::AccountKey(ref key) = old_signer {
+                Self::unlink_key_to_did(key, did);
+            }
+
+            // Replace the signing item in place and link the new key.
+            let new_item = SigningItem {
+                signer: new_signer,
+                ..old_item
+            };
+            <DidRecords>::mutate(did, |record| {
+                (*record).signing_items.iter_mut()
+                    .filter(|si| si.signer == old_signer)
+                    .for_each(|si| *si = new_item.clone());
+            });
+            Self::sync_signing_item_permissions(did, &[old_signer, new_signer]);
+            Self::link_key_to_did(&new_key, new_item.signer_type, did);
+
+            Self::deposit_event(RawEvent::SigningKeyRotated(did, old_signer, new_item));
+            Self::deposit_keyset_updated(did);
+            Ok(())
+        }
+
+        /// Moves the `signer` signing item of `from_did` to `to_did`, keeping its signer type
+        /// and permissions. Only called by the master key of `from_did`.
+        ///
+        /// `to_did` consents through the `MoveSigningKey(signer)` authorization `auth_id` that it
+        /// gave to `from_did`, which is consumed by this call.
+        ///
+        /// # Failure
+        ///  - The caller has to be the master key of `from_did`.
+        ///  - `auth_id` has to be a `MoveSigningKey` authorization of `signer` given by `to_did`.
+        ///  - `signer` must be one of the signing items of `from_did`.
+        ///  - `signer` cannot already be a signing item of `to_did`.
+        pub fn move_signing_key(
+            origin,
+            from_did: IdentityId,
+            to_did: IdentityId,
+            signer: Signatory,
+            auth_id: u64
+        ) -> DispatchResult {
+            let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+            let from_record = Self::grant_check_only_master_key(&sender_key, from_did)?;
+            ensure!(<DidRecords>::exists(to_did), "DID does not exist");
+            ensure!(from_did != to_did, Error::<T>::Unauthorized);
+            let to_record = <DidRecords>::get(to_did);
+
+            let item = from_record.signing_items.iter()
+                .find(|si| si.signer == signer)
+                .cloned()
+                .ok_or(Error::<T>::InvalidSender)?;
+            ensure!(
+                !to_record.signing_items.iter().any(|si| si.signer == signer),
+                Error::<T>::AlreadyLinked
+            );
+
+            let from = Signatory::from(from_did);
+            ensure!(<Authorizations<T>>::exists((from, auth_id)), "Invalid auth");
+            ensure!(
+                Self::authorizations((from, auth_id)).authorization_data == AuthorizationData::MoveSigningKey(signer),
+                Error::<T>::UnknownAuthorization
+            );
+            Self::consume_auth(Signatory::from(to_did), from, auth_id)?;
+
+            // Unlink the signer from `from_did`.
+            Self::remove_pre_join_identity(&signer, from_did);
+            <FrozenSigningKeys>::remove((from_did, signer));
+            <SuspendedPermissions>::remove((from_did, signer));
+            if let Some(allowed_calls) = <SigningItemAllowedCalls>::take((from_did, signer)) {
+                <SigningItemAllowedCalls>::insert((to_did, signer), allowed_calls);
+            }
+            if let Signatory::AccountKey(ref key) = signer {
+                Self::unlink_key_to_did(key, from_did);
+            }
+            <DidRecords>::mutate(from_did, |record| {
+                (*record).remove_signing_items(&[signer]);
+            });
+            Self::sync_signing_item_permissions(from_did, &[signer]);
+
+            // Link it to `to_did`.
+            <DidRecords>::mutate(to_did, |record| {
+                (*record).add_signing_items(&[item.clone()]);
+            });
+            Self::sync_signing_item_permissions(to_did, &[signer]);
+            if let Signatory::AccountKey(ref key) = signer {
+                Self::link_key_to_did(key, item.signer_type, to_did);
+            }
+
+            Self::deposit_event(RawEvent::SigningKeyMoved(from_did, to_did, item));
+            Self::deposit_keyset_updated(from_did);
+            Self::deposit_keyset_updated(to_did);
             Ok(())
         }
 
@@ -238,15 +525,60 @@ decl_module! {
 
             ensure!( Self::can_key_be_linked_to_did(&new_key, SignatoryType::External), "Master key can only belong to one DID");
 
-            <DidRecords>::mutate(did,
-            |record| {
-                (*record).master_key = new_key.clone();
-            });
+            Self::set_did_master_key(did, new_key);
 
             Self::deposit_event(RawEvent::NewMasterKey(did, sender, new_key));
             Ok(())
         }
 
+        /// Emits the master key of `did` in a `MasterKeyQuery` event, for light clients.
+        ///
+        /// # Failure
+        /// It fails with `NoDIDFound` if `did` does not exist.
+        pub fn emit_master_key(origin, did: IdentityId) -> DispatchResult {
+            ensure_signed(origin)?;
+            let master_key = Self::master_key(did).ok_or(Error::<T>::NoDIDFound)?;
+            Self::deposit_event(RawEvent::MasterKeyQuery(did, master_key));
+            Ok(())
+        }
+
+        /// Sets the minimum number of blocks between two `register_did` attempts of the same
+        /// account. Zero disables the limit. Only called by root.
+        pub fn set_did_registration_cooldown(origin, cooldown: T::BlockNumber) -> DispatchResult {
+            ensure_root(origin)?;
+            <DidRegistrationCooldown<T>>::put(cooldown);
+            Self::deposit_event(RawEvent::DidRegistrationCooldownChanged(cooldown));
+            Ok(())
+        }
+
+        /// Unlinks `key` from the identities it is linked to in `KeyToIdentityIds` that do not
+        /// list it as master key or signing key. Only called by root.
+        pub fn repair_key_link(origin, key: AccountKey) -> DispatchResult {
+            ensure_root(origin)?;
+            let report = Self::verify_key_links(&key);
+            if !report.is_consistent() {
+                for did in report.dangling_dids.iter() {
+                    Self::unlink_key_to_did(&key, *did);
+                }
+                Self::deposit_event(RawEvent::KeyLinksRepaired(key, report.dangling_dids));
+            }
+            Ok(())
+        }
+
+        /// Adds the master keys of `dids` to `MasterKeyToDid`, which is only kept up to date for
+        /// identities created or rotated after it was introduced. Identities that do not exist
+        /// are skipped. Only called by root.
+        pub fn backfill_master_key_to_did(origin, dids: Vec<IdentityId>) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::ensure_batch_size(dids.len())?;
+            for did in dids {
+                if <DidRecords>::exists(did) {
+                    <MasterKeyToDid>::insert(Self::did_records(did).master_key, did);
+                }
+            }
+            Ok(())
+        }
+
         /// Call this with the new master key. By invoking this method, caller accepts authorization
         /// with the new master key. If a KYC service provider approved this change, master key of
         /// the DID is updated.
@@ -300,9 +632,7 @@ decl_module! {
                     Self::consume_auth(kyc_auth.authorized_by, signer, kyc_auth_id)?;
 
                     // Replace master key of the owner that initiated key rotation
-                    <DidRecords>::mutate(rotation_for_did, |record| {
-                        (*record).master_key = sender_key.clone();
-                    });
+                    Self::set_did_master_key(rotation_for_did, sender_key);
 
                     Self::deposit_event(RawEvent::MasterKeyChanged(rotation_for_did, sender_key));
                 } else {
@@ -335,6 +665,7 @@ decl_module! {
             // Verify that sender key is one of did_issuer's signing keys
             let sender_signer = Signatory::AccountKey(sender_key);
             ensure!(Self::is_signer_authorized(did_issuer, &sender_signer), "Sender must hold a claim issuer's signing key");
+            Self::charge_claim_fee(&sender, 1)?;
 
             let claim_meta_data = ClaimMetaData {
                 claim_key: claim_key,
@@ -349,6 +680,9 @@ decl_module! {
                 claim_value: claim_value,
             };
 
+            if !<Claims<T>>::exists((did, claim_meta_data.clone())) {
+                <ClaimsIssuedCount>::mutate(did_issuer, |count| *count = count.saturating_add(1));
+            }
             <Claims<T>>::insert((did.clone(), claim_meta_data.clone()), claim.clone());
 
             <ClaimKeys>::mutate(&did, |old_claim_data| {
@@ -356,12 +690,60 @@ decl_module! {
                     old_claim_data.push(claim_meta_data.clone());
                 }
             });
+            <TypedClaims>::insert((did, claim_meta_data.clone()), true);
 
             Self::deposit_event(RawEvent::NewClaims(did, claim_meta_data, claim));
 
             Ok(())
         }
 
+        /// Sets the KYC expiry of `subject_did` as attested by the `did` issuer. The expiry is
+        /// stored under `KYC_EXPIRY_CLAIM_KEY` in the encoding that `is_identity_has_valid_kyc`
+        /// expects. Only called by `did`'s signing key.
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn set_kyc_expiry(
+            origin,
+            did: IdentityId,
+            subject_did: IdentityId,
+            expiry: <T as pallet_timestamp::Trait>::Moment
+        ) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let sender_key = AccountKey::try_from(sender.encode())?;
+
+            ensure!(<DidRecords>::exists(subject_did), "DID must already exist");
+            ensure!(<DidRecords>::exists(did), "claim issuer DID must already exist");
+            // Verify that sender key is one of did's signing keys
+            ensure!(Self::is_signer_authorized(did, &Signatory::AccountKey(sender_key)), "Sender must hold a claim issuer's signing key");
+            Self::charge_claim_fee(&sender, 1)?;
+
+            let claim_meta_data = ClaimMetaData {
+                claim_key: KYC_EXPIRY_CLAIM_KEY.to_vec(),
+                claim_issuer: did,
+            };
+            let claim = Claim {
+                issuance_date: <pallet_timestamp::Module<T>>::get(),
+                expiry,
+                claim_value: ClaimValue {
+                    data_type: DataTypes::U64,
+                    value: expiry.saturated_into::<u64>().to_be_bytes().to_vec(),
+                },
+            };
+
+            if !<Claims<T>>::exists((subject_did, claim_meta_data.clone())) {
+                <ClaimsIssuedCount>::mutate(did, |count| *count = count.saturating_add(1));
+            }
+            <Claims<T>>::insert((subject_did, claim_meta_data.clone()), claim);
+            <TypedClaims>::insert((subject_did, claim_meta_data.clone()), true);
+            <ClaimKeys>::mutate(&subject_did, |old_claim_data| {
+                if !old_claim_data.contains(&claim_meta_data) {
+                    old_claim_data.push(claim_meta_data);
+                }
+            });
+
+            Self::deposit_event(RawEvent::KycExpirySet(subject_did, did, expiry));
+            Ok(())
+        }
+
         /// Adds a new batch of claim records or edits an existing one. Only called by
         /// `did_issuer`'s signing key.
         #[weight = BatchDispatchInfo::new_normal(3_000, 10_000)]
@@ -370,6 +752,7 @@ decl_module! {
             did_issuer: IdentityId,
             claims: Vec<ClaimRecord<<T as pallet_timestamp::Trait>::Moment>>
         ) -> DispatchResult {
+            Self::ensure_batch_size(claims.len())?;
             let sender = ensure_signed(origin)?;
             ensure!(<DidRecords>::exists(did_issuer), "claim issuer DID must already exist");
             let sender_key = AccountKey::try_from(sender.encode())?;
@@ -400,39 +783,115 @@ decl_module! {
                 };
                 checked_claims.push((did.clone(), claim_meta_data, claim));
             }
+            Self::charge_claim_fee(&sender, checked_claims.len() as u32)?;
             // Register the claims.
             for (did, claim_meta_data, claim) in checked_claims {
+                if !<Claims<T>>::exists((did, claim_meta_data.clone())) {
+                    <ClaimsIssuedCount>::mutate(did_issuer, |count| *count = count.saturating_add(1));
+                }
                 <Claims<T>>::insert((did.clone(), claim_meta_data.clone()), claim.clone());
                 <ClaimKeys>::mutate(&did, |old_claim_data| {
                     if !old_claim_data.contains(&claim_meta_data) {
                         old_claim_data.push(claim_meta_data.clone());
                     }
                 });
+                <TypedClaims>::insert((did, claim_meta_data.clone()), true);
                 Self::deposit_event(RawEvent::NewClaims(did, claim_meta_data, claim));
             }
             Ok(())
         }
 
-        fn forwarded_call(origin, target_did: IdentityId, proposal: Box<T::Proposal>) -> DispatchResult {
+        /// Adds a new numeric claim record or edits an existing one. Only called by `did_issuer`'s
+        /// signing key.
+        ///
+        /// Numeric claims are stored as plain `u128` values so that transfer rules can compare them
+        /// against a threshold (see `numeric_claim_at_least`).
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn add_numeric_claim(
+            origin,
+            did: IdentityId,
+            claim_key: Vec<u8>,
+            did_issuer: IdentityId,
+            expiry: <T as pallet_timestamp::Trait>::Moment,
+            value: u128
+        ) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let sender_signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+
+            ensure!(<DidRecords>::exists(did), "DID must already exist");
+            ensure!(<DidRecords>::exists(did_issuer), "claim issuer DID must already exist");
+
+            // Verify that sender key is one of did_issuer's signing keys
+            ensure!(Self::is_signer_authorized(did_issuer, &sender_signer), "Sender must hold a claim issuer's signing key");
+            Self::charge_claim_fee(&sender, 1)?;
+
+            let claim_meta_data = ClaimMetaData {
+                claim_key,
+                claim_issuer: did_issuer,
+            };
+
+            <NumericClaims<T>>::insert((did, claim_meta_data.clone()), (value, expiry));
+
+            Self::deposit_event(RawEvent::NewNumericClaim(did, claim_meta_data, value, expiry));
+
+            Ok(())
+        }
+
+        /// Removes the specified numeric claim. Only called by `did_issuer`'s signing key.
+        pub fn revoke_numeric_claim(origin, did: IdentityId, claim_key: Vec<u8>, did_issuer: IdentityId) -> DispatchResult {
+            let sender = Signatory::AccountKey(AccountKey::try_from(ensure_signed(origin)?.encode())?);
+
+            ensure!(<DidRecords>::exists(&did), "DID must already exist");
+            ensure!(<DidRecords>::exists(&did_issuer), "claim issuer DID must already exist");
+
+            // Verify that sender key is one of did_issuer's signing keys
+            ensure!(Self::is_signer_authorized(did_issuer, &sender), "Sender must hold a claim issuer's signing key");
+
+            let claim_meta_data = ClaimMetaData {
+                claim_key,
+                claim_issuer: did_issuer,
+            };
+
+            ensure!(<NumericClaims<T>>::exists((did, claim_meta_data.clone())), Error::<T>::NumericClaimNotFound);
+            <NumericClaims<T>>::remove((did, claim_meta_data.clone()));
+
+            Self::deposit_event(RawEvent::RevokedNumericClaim(did, claim_meta_data));
+
+            Ok(())
+        }
+
+        /// Dispatches `proposal` on behalf of `target_did`.
+        ///
+        /// `nonce` has to match the forwarded call nonce of the current identity, so each forwarded
+        /// proposal is executed at most once.
+        pub fn forwarded_call(origin, target_did: IdentityId, nonce: u64, proposal: Box<T::Proposal>) -> DispatchResult {
             let sender = ensure_signed(origin)?;
 
             // 1. Constraints.
             // 1.1. A valid current identity.
-            if let Some(current_did) = <CurrentDid>::get() {
+            let current_did = if let Some(current_did) = <CurrentDid>::get() {
                 // 1.2. Check that current_did is a signing key of target_did
                 ensure!( Self::is_signer_authorized(current_did, &Signatory::Identity(target_did)),
                     "Current identity cannot be forwarded, it is not a signing key of target identity");
+                current_did
             } else {
                 return Err(Error::<T>::MissingCurrentIdentity.into());
-            }
+            };
 
-            // 1.3. Check that target_did has a KYC.
+            // 1.3. Replay protection.
+            ensure!(nonce == Self::forwarded_call_nonce(current_did), Error::<T>::InvalidForwardedCallNonce);
+
+            // 1.4. Check that target_did has a KYC.
             // Please keep in mind that `current_did` is double-checked:
             //  - by `SignedExtension` (`update_did_signed_extension`) on 0 level nested call, or
             //  - by next code, as `target_did`, on N-level nested call, where N is equal or greater that 1.
             ensure!(Self::has_valid_kyc(target_did), "Invalid KYC validation on target did");
 
             // 2. Actions
+            let next_nonce = nonce.checked_add(1).ok_or("overflow in forwarded call nonce")?;
+            <ForwardedCallNonce>::insert(current_did, next_nonce);
+            Self::deposit_event(RawEvent::ForwardedCallNonceConsumed(current_did, nonce));
+
             <CurrentDid>::put(target_did);
 
             // Also set current_did roles when acting as a signing key for target_did
@@ -466,7 +925,11 @@ decl_module! {
                 claim_issuer: did_issuer,
             };
 
+            if <Claims<T>>::exists((did, claim_meta_data.clone())) {
+                <ClaimsIssuedCount>::mutate(did_issuer, |count| *count = count.saturating_sub(1));
+            }
             <Claims<T>>::remove((did, claim_meta_data.clone()));
+            <TypedClaims>::remove((did, claim_meta_data.clone()));
 
             <ClaimKeys>::mutate(&did, |old_claim_metadata| {
                 *old_claim_metadata = old_claim_metadata
@@ -481,6 +944,122 @@ decl_module! {
             Ok(())
         }
 
+        /// Sets the value type of an existing claim. Only called by `did_issuer`'s signing key.
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn set_claim_value_type(
+            origin,
+            did: IdentityId,
+            claim_key: Vec<u8>,
+            did_issuer: IdentityId,
+            data_type: DataTypes
+        ) -> DispatchResult {
+            let sender = Signatory::AccountKey(AccountKey::try_from(ensure_signed(origin)?.encode())?);
+
+            ensure!(<DidRecords>::exists(&did_issuer), "claim issuer DID must already exist");
+            // Verify that sender key is one of did_issuer's signing keys
+            ensure!(Self::is_signer_authorized(did_issuer, &sender), "Sender must hold a claim issuer's signing key");
+
+            let claim_meta_data = ClaimMetaData {
+                claim_key,
+                claim_issuer: did_issuer,
+            };
+            ensure!(<Claims<T>>::exists((did, claim_meta_data.clone())), Error::<T>::ClaimNotFound);
+            <Claims<T>>::mutate((did, claim_meta_data.clone()), |claim| {
+                claim.claim_value.data_type = data_type;
+            });
+            <TypedClaims>::insert((did, claim_meta_data.clone()), true);
+
+            Self::deposit_event(RawEvent::ClaimValueTypeSet(did, claim_meta_data, data_type));
+            Ok(())
+        }
+
+        /// Declares the value type of the claims about `dids` that were added before value types
+        /// existed. Those claims still carrying the default `DataTypes::VecU8`, i.e. raw bytes,
+        /// are tagged with `default_type`. Claims whose type is already declared, including
+        /// claims explicitly typed `VecU8`, are left untouched, so each claim is migrated at most
+        /// once. The claims of all `dids` together must fit in a batch. Only called by root.
+        pub fn migrate_claim_types(origin, dids: Vec<IdentityId>, default_type: DataTypes) -> DispatchResult {
+            ensure_root(origin)?;
+            let keys = dids
+                .into_iter()
+                .flat_map(|did| Self::claim_keys(did).into_iter().map(move |claim_meta_data| (did, claim_meta_data)))
+                .collect::<Vec<_>>();
+            Self::ensure_batch_size(keys.len())?;
+
+            let mut migrated = 0u32;
+            for key in keys {
+                if !<Claims<T>>::exists(&key) || Self::is_claim_typed(&key) {
+                    continue;
+                }
+                <Claims<T>>::mutate(&key, |claim| {
+                    if claim.claim_value.data_type == DataTypes::default() {
+                        claim.claim_value.data_type = default_type;
+                        migrated = migrated.saturating_add(1);
+                    }
+                });
+                <TypedClaims>::insert(&key, true);
+            }
+
+            Self::deposit_event(RawEvent::ClaimTypesMigrated(default_type, migrated));
+            Ok(())
+        }
+
+        /// Adds a claim that only applies to `ticker` or edits an existing one. Only called by
+        /// did_issuer's signing key
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn add_ticker_claim(
+            origin,
+            did: IdentityId,
+            ticker: Ticker,
+            claim_key: Vec<u8>,
+            did_issuer: IdentityId,
+            expiry: <T as pallet_timestamp::Trait>::Moment,
+            claim_value: ClaimValue
+        ) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let sender_signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+
+            ensure!(<DidRecords>::exists(did), "DID must already exist");
+            ensure!(<DidRecords>::exists(did_issuer), "claim issuer DID must already exist");
+            // Verify that sender key is one of did_issuer's signing keys
+            ensure!(Self::is_signer_authorized(did_issuer, &sender_signer), "Sender must hold a claim issuer's signing key");
+            Self::charge_claim_fee(&sender, 1)?;
+
+            ticker.canonize();
+            let claim_meta_data = ClaimMetaData {
+                claim_key,
+                claim_issuer: did_issuer,
+            };
+            let claim = Claim {
+                issuance_date: <pallet_timestamp::Module<T>>::get(),
+                expiry,
+                claim_value,
+            };
+            <TickerScopedClaims<T>>::insert((did, ticker, claim_meta_data.clone()), claim.clone());
+
+            Self::deposit_event(RawEvent::NewTickerClaim(did, ticker, claim_meta_data, claim));
+            Ok(())
+        }
+
+        /// Removes a claim that only applies to `ticker`. Only called by did_issuer's signing key
+        pub fn revoke_ticker_claim(origin, did: IdentityId, ticker: Ticker, claim_key: Vec<u8>, did_issuer: IdentityId) -> DispatchResult {
+            let sender = Signatory::AccountKey(AccountKey::try_from(ensure_signed(origin)?.encode())?);
+
+            ensure!(<DidRecords>::exists(did_issuer), "claim issuer DID must already exist");
+            // Verify that sender key is one of did_issuer's signing keys
+            ensure!(Self::is_signer_authorized(did_issuer, &sender), "Sender must hold a claim issuer's signing key");
+
+            ticker.canonize();
+            let claim_meta_data = ClaimMetaData {
+                claim_key,
+                claim_issuer: did_issuer,
+            };
+            <TickerScopedClaims<T>>::remove((did, ticker, claim_meta_data.clone()));
+
+            Self::deposit_event(RawEvent::RevokedTickerClaim(did, ticker, claim_meta_data));
+            Ok(())
+        }
+
         /// It sets permissions for an specific `target_key` key.
         /// Only the master key of an identity is able to set signing key permissions.
         pub fn set_permission_to_signer(origin, did: IdentityId, signer: Signatory, permissions: Vec<Permission>) -> DispatchResult {
@@ -496,22 +1075,242 @@ decl_module! {
 
             // Find key in `DidRecord::signing_keys`
             if record.signing_items.iter().find(|&si| si.signer == signer).is_some() {
-                Self::update_signing_item_permissions(did, &signer, permissions)
+                Self::update_signing_item_permissions(did, &signer, permissions)?;
+                Self::deposit_keyset_updated(did);
+                Ok(())
             } else {
                 Err(Error::<T>::InvalidSender.into())
             }
         }
 
-        /// It disables all signing keys at `did` identity.
+        /// It restricts the `signer` signing key of `did` to the calls identified by
+        /// `allowed_calls`. `None` lets the key be used for any call.
+        /// A call identifier is a prefix of the SCALE encoded call, e.g. the module and call
+        /// indexes of one call, or only the module index to allow the whole module.
+        /// Only the master key of an identity is able to restrict its signing keys.
         ///
         /// # Errors
-        ///
-        pub fn freeze_signing_keys(origin, did: IdentityId) -> DispatchResult {
-            Self::set_frozen_signing_key_flags( origin, did, true)
+        /// - `TooManyAllowedCalls` if there are more than `MAX_ALLOWED_CALLS` identifiers.
+        /// - `InvalidCallId` if any identifier is empty or longer than `MAX_CALL_ID_LEN`.
+        pub fn set_signing_item_allowed_calls(origin, did: IdentityId, signer: Signatory, allowed_calls: Option<Vec<Vec<u8>>>) -> DispatchResult {
+            let sender_key = AccountKey::try_from( ensure_signed(origin)?.encode())?;
+            let record = Self::grant_check_only_master_key( &sender_key, did)?;
+            ensure!(
+                record.signing_items.iter().any(|si| si.signer == signer),
+                Error::<T>::InvalidSender
+            );
+
+            let allowed_calls = allowed_calls.map(|mut calls| {
+                // Remove duplicates.
+                calls.sort();
+                calls.dedup();
+                calls
+            });
+            if let Some(ref calls) = allowed_calls {
+                ensure!(calls.len() <= MAX_ALLOWED_CALLS, Error::<T>::TooManyAllowedCalls);
+                ensure!(
+                    calls.iter().all(|call| !call.is_empty() && call.len() <= MAX_CALL_ID_LEN),
+                    Error::<T>::InvalidCallId
+                );
+            }
+
+            match allowed_calls {
+                Some(ref calls) => <SigningItemAllowedCalls>::insert((did, signer), calls.clone()),
+                None => <SigningItemAllowedCalls>::remove((did, signer)),
+            }
+
+            Self::deposit_event(RawEvent::SigningItemAllowedCallsUpdated(did, signer, allowed_calls));
+            Self::deposit_keyset_updated(did);
+            Ok(())
+        }
+
+        /// It disables all signing keys at `did` identity.
+        ///
+        /// # Errors
+        /// Only called by the master key or a signing key with `Operator` or `EmergencyFreeze`
+        /// permission.
+        pub fn freeze_signing_keys(origin, did: IdentityId) -> DispatchResult {
+            Self::set_frozen_signing_key_flags( origin, did, true)
+        }
+
+        /// It disables only the `signer` signing key at `did` identity. Other signing keys remain
+        /// usable.
+        ///
+        /// # Errors
+        /// Only called by the master key. `signer` must be one of the signing keys of `did`.
+        pub fn freeze_signing_key(origin, did: IdentityId, signer: Signatory) -> DispatchResult {
+            let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+            let record = Self::grant_check_only_master_key(&sender_key, did)?;
+            ensure!(
+                record.signing_items.iter().any(|si| si.signer == signer),
+                Error::<T>::InvalidSender
+            );
+
+            <FrozenSigningKeys>::insert((did, signer), true);
+            Self::deposit_event(RawEvent::SigningKeyFrozen(did, signer));
+            Ok(())
+        }
+
+        /// It re-enables the `signer` signing key at `did` identity disabled by
+        /// `freeze_signing_key`.
+        ///
+        /// # Errors
+        /// Only called by the master key.
+        pub fn unfreeze_signing_key(origin, did: IdentityId, signer: Signatory) -> DispatchResult {
+            let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+            let _grants_checked = Self::grant_check_only_master_key(&sender_key, did)?;
+
+            <FrozenSigningKeys>::remove((did, signer));
+            Self::deposit_event(RawEvent::SigningKeyUnfrozen(did, signer));
+            Ok(())
         }
 
+        /// It removes all permissions of the `signer` signing key at `did` identity, keeping
+        /// them aside so that `restore_signing_key` can give them back. A suspended key is not
+        /// authorized for `did` until it is restored.
+        ///
+        /// # Errors
+        /// Only called by the master key. `signer` must be one of the signing keys of `did` and
+        /// cannot be already suspended.
+        pub fn suspend_signing_key(origin, did: IdentityId, signer: Signatory) -> DispatchResult {
+            let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+            let record = Self::grant_check_only_master_key(&sender_key, did)?;
+            let permissions = record.signing_items.iter()
+                .find(|si| si.signer == signer)
+                .map(|si| si.permissions.clone())
+                .ok_or(Error::<T>::InvalidSender)?;
+            ensure!(
+                !<SuspendedPermissions>::exists((did, signer)),
+                Error::<T>::SigningKeyAlreadySuspended
+            );
+
+            <SuspendedPermissions>::insert((did, signer), permissions);
+            Self::update_signing_item_permissions(did, &signer, vec![])?;
+            Self::deposit_event(RawEvent::SigningKeySuspended(did, signer));
+            Self::deposit_keyset_updated(did);
+            Ok(())
+        }
+
+        /// It gives back to the `signer` signing key at `did` identity the permissions it had
+        /// when it was suspended by `suspend_signing_key`, keeping any permission granted to it
+        /// while it was suspended.
+        ///
+        /// # Errors
+        /// Only called by the master key. `signer` has to be suspended.
+        pub fn restore_signing_key(origin, did: IdentityId, signer: Signatory) -> DispatchResult {
+            let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+            let record = Self::grant_check_only_master_key(&sender_key, did)?;
+            let mut permissions = <SuspendedPermissions>::take((did, signer))
+                .ok_or(Error::<T>::SigningKeyNotSuspended)?;
+            if let Some(si) = record.signing_items.iter().find(|si| si.signer == signer) {
+                permissions.extend(si.permissions.iter().cloned());
+            }
+
+            Self::update_signing_item_permissions(did, &signer, permissions)?;
+            Self::deposit_event(RawEvent::SigningKeyRestored(did, signer));
+            Self::deposit_keyset_updated(did);
+            Ok(())
+        }
+
+        /// It re-enables all signing keys at `did` identity. Only called by the master key or a
+        /// signing key with `EmergencyFreeze` permission.
+        ///
+        /// If dual unfreeze is required, the master key and the recovery key each have to call it
+        /// within `DUAL_UNFREEZE_WINDOW` for the unfreeze to take effect.
         pub fn unfreeze_signing_keys(origin, did: IdentityId) -> DispatchResult {
-            Self::set_frozen_signing_key_flags( origin, did, false)
+            match Self::dual_unfreeze_key(did) {
+                Some(recovery_key) => Self::confirm_dual_unfreeze(origin, did, recovery_key),
+                None => Self::set_frozen_signing_key_flags( origin, did, false),
+            }
+        }
+
+        /// Requires the master key and `recovery_key` to confirm any unfreeze of `did`. Passing
+        /// `None` restores the single call unfreeze. Only called by the master key and it cannot
+        /// be changed while the signing keys of `did` are frozen.
+        pub fn set_require_dual_unfreeze(origin, did: IdentityId, recovery_key: Option<AccountKey>) -> DispatchResult {
+            let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+            let record = Self::grant_check_only_master_key(&sender_key, did)?;
+            ensure!(!Self::is_did_frozen(did), Error::<T>::IdentityFrozen);
+
+            match recovery_key {
+                Some(key) => {
+                    ensure!(key != record.master_key, Error::<T>::InvalidRecoveryKey);
+                    <DualUnfreezeKey>::insert(did, key);
+                },
+                None => <DualUnfreezeKey>::remove(did),
+            }
+            <PendingUnfreeze<T>>::remove(did);
+            Self::deposit_event(RawEvent::DualUnfreezeSet(did, recovery_key));
+            Ok(())
+        }
+
+        /// Sets the key that can replace the master key of `did` through a recovery. Passing
+        /// `None` removes it. Any pending recovery is cancelled. Only called by the master key.
+        pub fn set_recovery_key(origin, did: IdentityId, recovery_key: Option<AccountKey>) -> DispatchResult {
+            let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+            let record = Self::grant_check_only_master_key(&sender_key, did)?;
+
+            match recovery_key {
+                Some(key) => {
+                    ensure!(key != record.master_key, Error::<T>::InvalidRecoveryKey);
+                    <RecoveryKey>::insert(did, key);
+                },
+                None => <RecoveryKey>::remove(did),
+            }
+            <PendingRecovery<T>>::remove(did);
+            Self::deposit_event(RawEvent::RecoveryKeySet(did, recovery_key));
+            Ok(())
+        }
+
+        /// Starts the recovery of `did`. The recovery key can complete it once
+        /// `RECOVERY_TIMELOCK` has elapsed, unless the master key cancels it before.
+        /// Only called by the recovery key.
+        pub fn initiate_recovery(origin, did: IdentityId) -> DispatchResult {
+            let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+            ensure!(Self::recovery_key(did) == Some(sender_key), Error::<T>::Unauthorized);
+            ensure!(Self::pending_recovery(did).is_none(), Error::<T>::RecoveryAlreadyInitiated);
+
+            <PendingRecovery<T>>::insert(did, <pallet_timestamp::Module<T>>::get());
+            Self::deposit_event(RawEvent::RecoveryInitiated(did, sender_key));
+            Ok(())
+        }
+
+        /// Cancels the pending recovery of `did`. Only called by the master key.
+        pub fn cancel_recovery(origin, did: IdentityId) -> DispatchResult {
+            let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+            let _grants_checked = Self::grant_check_only_master_key(&sender_key, did)?;
+            ensure!(Self::pending_recovery(did).is_some(), Error::<T>::NoPendingRecovery);
+
+            <PendingRecovery<T>>::remove(did);
+            Self::deposit_event(RawEvent::RecoveryCancelled(did));
+            Ok(())
+        }
+
+        /// Completes the pending recovery of `did`, making the recovery key its master key.
+        /// Only called by the recovery key once `RECOVERY_TIMELOCK` has elapsed.
+        pub fn complete_recovery(origin, did: IdentityId) -> DispatchResult {
+            let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+            ensure!(Self::recovery_key(did) == Some(sender_key), Error::<T>::Unauthorized);
+            let initiated_at = Self::pending_recovery(did).ok_or(Error::<T>::NoPendingRecovery)?;
+            let timelock = RECOVERY_TIMELOCK.saturated_into::<T::Moment>();
+            ensure!(
+                <pallet_timestamp::Module<T>>::get() >= initiated_at.saturating_add(timelock),
+                Error::<T>::RecoveryTimelockNotElapsed
+            );
+            ensure!(
+                Self::can_key_be_linked_to_did(&sender_key, SignatoryType::External),
+                Error::<T>::MasterKeyAlreadyLinked
+            );
+
+            let old_master_key = <DidRecords>::get(did).master_key;
+            Self::unlink_key_to_did(&old_master_key, did);
+            Self::set_did_master_key(did, sender_key);
+            Self::link_key_to_did(&sender_key, SignatoryType::External, did);
+            <RecoveryKey>::remove(did);
+            <PendingRecovery<T>>::remove(did);
+
+            Self::deposit_event(RawEvent::RecoveryCompleted(did, old_master_key, sender_key));
+            Ok(())
         }
 
         pub fn get_my_did(origin) -> DispatchResult {
@@ -553,7 +1352,8 @@ decl_module! {
                 }
             };
 
-            Self::add_auth(Signatory::from(from_did), target, authorization_data, expiry);
+            Self::ensure_auth_target_exists(&target)?;
+            Self::add_auth(Signatory::from(from_did), target, authorization_data, expiry)?;
 
             Ok(())
         }
@@ -568,7 +1368,8 @@ decl_module! {
         ) -> DispatchResult {
             let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
 
-            Self::add_auth(Signatory::from(sender_key), target, authorization_data, expiry);
+            Self::ensure_auth_target_exists(&target)?;
+            Self::add_auth(Signatory::from(sender_key), target, authorization_data, expiry)?;
 
             Ok(())
         }
@@ -580,6 +1381,7 @@ decl_module! {
             // Vec<(target_did, auth_data, expiry)>
             auths: Vec<(Signatory, AuthorizationData, Option<T::Moment>)>
         ) -> DispatchResult {
+            Self::ensure_batch_size(auths.len())?;
             let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
             let from_did =  match Self::current_did() {
                 Some(x) => x,
@@ -592,8 +1394,20 @@ decl_module! {
                 }
             };
 
+            // Reject the whole batch if any authorization could not be accepted later.
+            ensure!(
+                auths.iter().all(|auth| Self::is_acceptable_authorization(&auth.1)),
+                Error::<T>::UnknownAuthorization
+            );
+            for auth in &auths {
+                Self::ensure_auth_target_exists(&auth.0)?;
+            }
+            Self::multi_purpose_nonce()
+                .checked_add(auths.len() as u64)
+                .ok_or(Error::<T>::NonceOverflow)?;
+
             for auth in auths {
-                Self::add_auth(Signatory::from(from_did), auth.0, auth.1, auth.2);
+                Self::add_auth(Signatory::from(from_did), auth.0, auth.1, auth.2)?;
             }
 
             Ok(())
@@ -634,6 +1448,7 @@ decl_module! {
             // Vec<(target_did, auth_id)>
             auth_identifiers: Vec<(Signatory, u64)>
         ) -> DispatchResult {
+            Self::ensure_batch_size(auth_identifiers.len())?;
             let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
             let from_did =  match Self::current_did() {
                 Some(x) => x,
@@ -680,35 +1495,57 @@ decl_module! {
             };
 
             ensure!(<Authorizations<T>>::exists((signer, auth_id)), "Invalid auth");
-            let auth = Self::authorizations((signer, auth_id));
+            Self::accept_auth(signer, auth_id)
+        }
 
-            match signer {
-                Signatory::Identity(did) => {
-                    match auth.authorization_data {
-                        AuthorizationData::TransferTicker(_) =>
-                            T::AcceptTransferTarget::accept_ticker_transfer(did, auth_id),
-                        AuthorizationData::TransferTokenOwnership(_) =>
-                            T::AcceptTransferTarget::accept_token_ownership_transfer(did, auth_id),
-                        AuthorizationData::AddMultiSigSigner =>
-                            T::AddSignerMultiSigTarget::accept_multisig_signer(Signatory::from(did), auth_id),
-                        _ => return Err(Error::<T>::UnknownAuthorization.into())
-                    }
-                },
-                Signatory::AccountKey(key) => {
-                    match auth.authorization_data {
-                        AuthorizationData::AddMultiSigSigner =>
-                            T::AddSignerMultiSigTarget::accept_multisig_signer(Signatory::from(key), auth_id),
-                        _ => return Err(Error::<T>::UnknownAuthorization.into())
+        /// Accepts an array of authorizations. Authorizations that cannot be accepted are skipped
+        /// and reported with an `AuthorizationSkipped` event.
+        pub fn batch_accept_authorization(
+            origin,
+            auth_ids: Vec<u64>
+        ) -> DispatchResult {
+            Self::ensure_batch_size(auth_ids.len())?;
+            let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+            let signer = match Self::current_did() {
+                Some(x) => Signatory::from(x),
+                None => {
+                    if let Some(did) = Self::get_identity(&sender_key) {
+                        Signatory::from(did)
+                    } else {
+                        Signatory::from(sender_key)
                     }
                 }
+            };
+
+            for auth_id in auth_ids {
+                // NB: Even if an auth is invalid (due to any reason), this batch function does NOT return an error.
+                // It will just skip that particular authorization and report why.
+                let skip_reason = if !<Authorizations<T>>::exists((signer, auth_id)) {
+                    Some(AUTH_SKIPPED_NOT_FOUND)
+                } else if !Self::is_acceptable_authorization(&Self::authorizations((signer, auth_id)).authorization_data) {
+                    Some(AUTH_SKIPPED_UNKNOWN_TYPE)
+                } else if Self::accept_auth(signer, auth_id).is_err() {
+                    Some(AUTH_SKIPPED_HANDLER_FAILED)
+                } else {
+                    None
+                };
+                if let Some(reason) = skip_reason {
+                    Self::deposit_event(RawEvent::AuthorizationSkipped(auth_id, reason));
+                }
             }
+
+            Ok(())
         }
 
-        /// Accepts an array of authorizations
-        pub fn batch_accept_authorization(
+        /// Inspects up to `limit` authorizations of the caller, starting from the most recent one,
+        /// and accepts those whose type is `authorization_type`. Authorizations of other types
+        /// are left untouched but count towards the limit, so the walk is bounded.
+        pub fn accept_authorizations_by_type(
             origin,
-            auth_ids: Vec<u64>
+            authorization_type: AuthorizationType,
+            limit: u32
         ) -> DispatchResult {
+            Self::ensure_batch_size(limit as usize)?;
             let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
             let signer = match Self::current_did() {
                 Some(x) => Signatory::from(x),
@@ -721,46 +1558,92 @@ decl_module! {
                 }
             };
 
-            match signer {
-                Signatory::Identity(did) => {
-                    for auth_id in auth_ids {
-                        // NB: Even if an auth is invalid (due to any reason), this batch function does NOT return an error.
-                        // It will just skip that particular authorization.
-                        if <Authorizations<T>>::exists((signer, auth_id)) {
-                            let auth = Self::authorizations((signer, auth_id));
-                            // NB: Result is not handled, invalid auths are just ignored to let the batch function continue.
-                            let _result = match auth.authorization_data {
-                                AuthorizationData::TransferTicker(_) =>
-                                    T::AcceptTransferTarget::accept_ticker_transfer(did, auth_id),
-                                AuthorizationData::TransferTokenOwnership(_) =>
-                                    T::AcceptTransferTarget::accept_token_ownership_transfer(did, auth_id),
-                                AuthorizationData::AddMultiSigSigner =>
-                                    T::AddSignerMultiSigTarget::accept_multisig_signer(Signatory::from(did), auth_id),
-                                _ => Err(Error::<T>::UnknownAuthorization.into())
-                            };
-                        }
-                    }
-                },
-                Signatory::AccountKey(key) => {
-                    for auth_id in auth_ids {
-                        // NB: Even if an auth is invalid (due to any reason), this batch function does NOT return an error.
-                        // It will just skip that particular authorization.
-                        if <Authorizations<T>>::exists((signer, auth_id)) {
-                            let auth = Self::authorizations((signer, auth_id));
-                            //NB: Result is not handled, invalid auths are just ignored to let the batch function continue.
-                            let _result = match auth.authorization_data {
-                                AuthorizationData::AddMultiSigSigner =>
-                                    T::AddSignerMultiSigTarget::accept_multisig_signer(Signatory::from(key), auth_id),
-                                _ => Err(Error::<T>::UnknownAuthorization.into())
-                            };
-                        }
-                    }
+            // Collect the ids first, as accepting an authorization removes it from the list.
+            let mut auth_ids = Vec::new();
+            let mut auth_id = Self::last_authorization(signer);
+            let mut inspected = 0u32;
+            while auth_id != 0 && inspected < limit {
+                let auth = Self::authorizations((signer, auth_id));
+                if AuthorizationType::from(&auth.authorization_data) == authorization_type {
+                    auth_ids.push(auth_id);
                 }
+                auth_id = auth.previous_authorization;
+                inspected += 1;
+            }
+
+            for auth_id in auth_ids {
+                // NB: Result is not handled, invalid auths are just ignored to let the batch function continue.
+                let _result = Self::accept_auth(signer, auth_id);
             }
 
             Ok(())
         }
 
+        /// Makes `to_did` the issuer of the latest `limit` authorizations issued by `from_did`, so
+        /// they remain usable once `from_did` is retired. Only called by the master key of
+        /// `from_did`.
+        ///
+        /// `to_did` consents through the `ReassignAuthorizations` authorization `auth_id` that it
+        /// gave to `from_did`, which is consumed by this call.
+        pub fn reassign_authorizations(
+            origin,
+            from_did: IdentityId,
+            to_did: IdentityId,
+            auth_id: u64,
+            limit: u32
+        ) -> DispatchResult {
+            Self::ensure_batch_size(limit as usize)?;
+            let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+            let _grants_checked = Self::grant_check_only_master_key(&sender_key, from_did)?;
+            ensure!(<DidRecords>::exists(to_did), "DID does not exist");
+            ensure!(from_did != to_did, Error::<T>::Unauthorized);
+
+            let from = Signatory::from(from_did);
+            let to = Signatory::from(to_did);
+            ensure!(<Authorizations<T>>::exists((from, auth_id)), "Invalid auth");
+            ensure!(
+                Self::authorizations((from, auth_id)).authorization_data == AuthorizationData::ReassignAuthorizations,
+                Error::<T>::UnknownAuthorization
+            );
+            Self::consume_auth(to, from, auth_id)?;
+
+            let mut reassigned = Vec::new();
+            let mut next = Self::last_issued_authorization(from);
+            while let Some(key) = next {
+                if reassigned.len() >= limit as usize {
+                    break;
+                }
+                next = Self::issued_authorization_links(key).and_then(|links| links.previous);
+                reassigned.push(key);
+            }
+            // Oldest first, so `to_did` keeps the issuance order.
+            for &(target, id) in reassigned.iter().rev() {
+                Self::unlink_issued_authorization(from, target, id);
+                <Authorizations<T>>::mutate((target, id), |auth| auth.authorized_by = to);
+                Self::link_issued_authorization(to, target, id);
+            }
+
+            Self::deposit_event(RawEvent::AuthorizationsReassigned(from_did, to_did, reassigned.len() as u32));
+            Ok(())
+        }
+
+        /// Adds the given pending authorizations, as (target, auth id) pairs, to the
+        /// `AuthorizationsByIssuer` index of their issuers. Authorizations that do not exist or
+        /// are already indexed are skipped. Only called by root.
+        pub fn backfill_authorizations_by_issuer(origin, auths: Vec<(Signatory, u64)>) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::ensure_batch_size(auths.len())?;
+            for (target, auth_id) in auths {
+                if <Authorizations<T>>::exists((target, auth_id))
+                    && !<AuthorizationsByIssuer>::exists((target, auth_id))
+                {
+                    let issuer = Self::authorizations((target, auth_id)).authorized_by;
+                    Self::link_issued_authorization(issuer, target, auth_id);
+                }
+            }
+            Ok(())
+        }
+
         // Manage Authorizations to join to an Identity
         // ================================================
 
@@ -803,6 +1686,9 @@ decl_module! {
                 if let Some(pre_auth) = Self::pre_authorized_join_did( signer.clone())
                         .iter()
                         .find( |pre_auth_item| pre_auth_item.target_id == target_id) {
+                    if let Some(expiry) = Self::pre_authorized_join_did_expiry((signer, target_id)) {
+                        ensure!(expiry > <pallet_timestamp::Module<T>>::get(), Error::<T>::Unauthorized);
+                    }
                     // Remove pre-auth, link key to identity and update identity record.
                     Self::remove_pre_join_identity(&signer, target_id);
                     if let Signatory::AccountKey(key) = signer {
@@ -811,7 +1697,9 @@ decl_module! {
                     <DidRecords>::mutate( target_id, |identity| {
                         identity.add_signing_items( &[pre_auth.signing_item.clone()]);
                     });
+                    Self::sync_signing_item_permissions(target_id, &[signer]);
                     Self::deposit_event( RawEvent::SignerJoinedToIdentityApproved( signer, target_id));
+                    Self::deposit_keyset_updated(target_id);
                     Ok(())
                 } else {
                     Err(Error::<T>::Unauthorized.into())
@@ -844,6 +1732,35 @@ decl_module! {
             }
         }
 
+        /// Removes up to `limit` expired pre-authorizations to join an identity of `signer`.
+        /// Anyone can call it, as expired pre-authorizations cannot be accepted anymore.
+        pub fn cleanup_expired_pre_auths(origin, signer: Signatory, limit: u32) -> DispatchResult {
+            ensure_signed(origin)?;
+            let now = <pallet_timestamp::Module<T>>::get();
+            let mut removed = 0u32;
+            let mut pre_auth_list = Self::pre_authorized_join_did(signer);
+            pre_auth_list.retain(|pre_auth| {
+                let expiry_key = (signer, pre_auth.target_id);
+                let is_expired = Self::pre_authorized_join_did_expiry(expiry_key)
+                    .map_or(false, |expiry| expiry <= now);
+                if is_expired && removed < limit {
+                    removed += 1;
+                    <PreAuthorizedJoinDidExpiry<T>>::remove(expiry_key);
+                    false
+                } else {
+                    true
+                }
+            });
+
+            if pre_auth_list.is_empty() {
+                <PreAuthorizedJoinDid>::remove(signer);
+            } else {
+                <PreAuthorizedJoinDid>::insert(signer, pre_auth_list);
+            }
+            Self::deposit_event(RawEvent::ExpiredPreAuthorizationsRemoved(signer, removed));
+            Ok(())
+        }
+
 
         /// It adds signing keys to target identity `id`.
         /// Keys are directly added to identity because each of them has an authorization.
@@ -873,40 +1790,10 @@ decl_module! {
                 nonce: Self::offchain_authorization_nonce(id),
                 expires_at
             };
-            let auth_encoded= authorization.encode();
 
             // 1. Verify signatures.
             for si_with_auth in additional_keys.iter() {
-                let si = &si_with_auth.signing_item;
-
-                // Get account_id from signer
-                let account_id_found = match si.signer {
-                    Signatory::AccountKey(ref key) =>  Public::try_from(key.as_slice()).ok(),
-                    Signatory::Identity(ref id) if <DidRecords>::exists(id) => {
-                        let master_key = <DidRecords>::get(id).master_key;
-                        Public::try_from( master_key.as_slice()).ok()
-                    },
-                    _ => None
-                };
-
-                if let Some(account_id) = account_id_found {
-                    if let Signatory::AccountKey(ref key) = si.signer {
-                        // 1.1. Constraint 1-to-1 account to DID
-                        ensure!( Self::can_key_be_linked_to_did( key, si.signer_type),
-                        "One signing key can only belong to one identity");
-                    }
-
-                    // 1.2. Offchain authorization is not revoked explicitly.
-                    ensure!( Self::is_offchain_authorization_revoked((si.signer.clone(), authorization.clone())) == false,
-                        "Authorization has been explicitly revoked");
-
-                    // 1.3. Verify the signature.
-                    let signature = AnySignature::from( Signature::from_h512(si_with_auth.auth_signature));
-                    ensure!( signature.verify( auth_encoded.as_slice(), &account_id),
-                        "Invalid Authorization signature");
-                } else {
-                    return Err(Error::<T>::InvalidAccountKey.into());
-                }
+                Self::verify_signing_item_auth(id, si_with_auth, &authorization)?;
             }
 
             // 2.1. Link keys to identity
@@ -923,9 +1810,13 @@ decl_module! {
                     .collect::<Vec<_>>();
                 (*record).add_signing_items( &keys[..]);
             });
+            let signers = additional_keys.iter().map( |si_with_auth| si_with_auth.signing_item.signer)
+                .collect::<Vec<_>>();
+            Self::sync_signing_item_permissions(id, &signers);
             <OffChainAuthorizationNonce>::mutate( id, |offchain_nonce| {
                 *offchain_nonce = authorization.nonce + 1;
             });
+            Self::deposit_keyset_updated(id);
 
             Ok(())
         }
@@ -934,13 +1825,30 @@ decl_module! {
         /// the authorized transaction is not yet executed.
         pub fn revoke_offchain_authorization(origin, signer: Signatory, auth: TargetIdAuthorization<T::Moment>) -> DispatchResult {
             let sender_key = AccountKey::try_from( ensure_signed(origin)?.encode())?;
+            Self::ensure_offchain_authorization_revoker(&sender_key, &signer)?;
+
+            <RevokeOffChainAuthorization<T>>::insert( (signer,auth.clone()), true);
+            Self::deposit_event(RawEvent::OffChainAuthorizationRevoked(signer, auth));
+            Ok(())
+        }
 
-            match signer {
-                Signatory::AccountKey(ref key) => ensure!( sender_key == *key, "This key is not allowed to revoke this off-chain authorization"),
-                Signatory::Identity(id) => ensure!( Self::is_master_key(id, &sender_key), "Only master key is allowed to revoke an Identity Signatory off-chain authorization"),
+        /// It revokes several off-chain authorizations at once. The caller has to be allowed to
+        /// revoke every item, otherwise none of them is revoked.
+        ///
+        /// # Arguments
+        /// * `origin` - the key of the signer, or the master key of the identity signer
+        /// * `items` - the signers and their off-chain authorizations to revoke
+        pub fn batch_revoke_offchain_authorization(origin, items: Vec<(Signatory, TargetIdAuthorization<T::Moment>)>) -> DispatchResult {
+            let sender_key = AccountKey::try_from( ensure_signed(origin)?.encode())?;
+            Self::ensure_batch_size(items.len())?;
+            for (signer, _) in items.iter() {
+                Self::ensure_offchain_authorization_revoker(&sender_key, signer)?;
             }
 
-            <RevokeOffChainAuthorization<T>>::insert( (signer,auth), true);
+            for (signer, auth) in items {
+                <RevokeOffChainAuthorization<T>>::insert( (signer,auth.clone()), true);
+                Self::deposit_event(RawEvent::OffChainAuthorizationRevoked(signer, auth));
+            }
             Ok(())
         }
 
@@ -966,6 +1874,20 @@ decl_module! {
             Self::deposit_event(RawEvent::MyKycStatus(my_did, is_kyced, kyc_provider));
             Ok(())
         }
+
+        /// Query whether the given identity has valid KYC or not
+        ///
+        /// # Arguments
+        /// * `origin` Any signed origin
+        /// * `target_did` Identity that gets checked
+        /// * `buffer_time` Buffer time corresponds to which kyc expiry need to check
+        pub fn query_kyc_status(origin, target_did: IdentityId, buffer_time: u64) -> DispatchResult {
+            ensure_signed(origin)?;
+            ensure!(<DidRecords>::exists(target_did), "DID must already exist");
+            let (is_kyced, kyc_provider) = Self::is_identity_has_valid_kyc(target_did, buffer_time);
+            Self::deposit_event(RawEvent::KycStatus(target_did, is_kyced, kyc_provider));
+            Ok(())
+        }
     }
 }
 
@@ -985,18 +1907,90 @@ decl_error! {
         UnknownAuthorization,
         /// Account Id cannot be extracted from signer
         InvalidAccountKey,
+        /// The numeric claim does not exist
+        NumericClaimNotFound,
+        /// The nonce of a forwarded call does not match the expected one
+        InvalidForwardedCallNonce,
+        /// The multi purpose nonce cannot be increased any further
+        NonceOverflow,
+        /// The batch exceeds the maximum batch size
+        BatchTooLarge,
+        /// A recovery key cannot be the master key
+        InvalidRecoveryKey,
+        /// The master key already belongs to a DID
+        MasterKeyAlreadyLinked,
+        /// A recovery of the identity is already pending
+        RecoveryAlreadyInitiated,
+        /// There is no pending recovery of the identity
+        NoPendingRecovery,
+        /// The recovery cannot be completed before its timelock has elapsed
+        RecoveryTimelockNotElapsed,
+        /// The account attempted to register a DID too soon after its previous attempt
+        RegistrationRateLimited,
+        /// The signing key is already suspended
+        SigningKeyAlreadySuspended,
+        /// The signing key is not suspended
+        SigningKeyNotSuspended,
+        /// The signer acting in the current transaction cannot be removed
+        CannotRemoveActiveSigner,
+        /// The claim does not exist
+        ClaimNotFound,
+        /// The operation is not allowed while the signing keys of the identity are frozen
+        IdentityFrozen,
+        /// The signing keys of the identity are not frozen
+        IdentityNotFrozen,
+        /// A signing key cannot be restricted to more than `MAX_ALLOWED_CALLS` calls
+        TooManyAllowedCalls,
+        /// A call identifier is empty or longer than `MAX_CALL_ID_LEN`
+        InvalidCallId,
     }
 }
 
 impl<T: Trait> Module<T> {
+    /// Fails with `BatchTooLarge` if a batch of `len` items exceeds `T::MaxBatchSize`.
+    pub fn ensure_batch_size(len: usize) -> DispatchResult {
+        ensure!(
+            len <= T::MaxBatchSize::get() as usize,
+            Error::<T>::BatchTooLarge
+        );
+        Ok(())
+    }
+
+    /// Fails if `sender_key` is not allowed to revoke the off-chain authorizations of `signer`,
+    /// i.e. it is neither that key nor the master key of that identity.
+    fn ensure_offchain_authorization_revoker(
+        sender_key: &AccountKey,
+        signer: &Signatory,
+    ) -> DispatchResult {
+        match signer {
+            Signatory::AccountKey(key) => ensure!(
+                sender_key == key,
+                "This key is not allowed to revoke this off-chain authorization"
+            ),
+            Signatory::Identity(id) => ensure!(
+                Self::is_master_key(*id, sender_key),
+                "Only master key is allowed to revoke an Identity Signatory off-chain authorization"
+            ),
+        }
+        Ok(())
+    }
+
+    /// Fails with `NoDIDFound` if `target` is an identity that has not been registered.
+    /// Key targets are not restricted.
+    fn ensure_auth_target_exists(target: &Signatory) -> DispatchResult {
+        if let Signatory::Identity(did) = target {
+            ensure!(<DidRecords>::exists(did), Error::<T>::NoDIDFound);
+        }
+        Ok(())
+    }
+
     pub fn add_auth(
         from: Signatory,
         target: Signatory,
         authorization_data: AuthorizationData,
         expiry: Option<T::Moment>,
-    ) {
-        let new_nonce = Self::multi_purpose_nonce() + 1u64;
-        <MultiPurposeNonce>::put(&new_nonce);
+    ) -> DispatchResult {
+        let new_nonce = Self::next_multi_purpose_nonce()?;
 
         let last_auth = Self::last_authorization(&target);
 
@@ -1018,6 +2012,7 @@ impl<T: Trait> Module<T> {
 
         <LastAuthorization>::insert(&target, new_nonce);
         <Authorizations<T>>::insert((target, new_nonce), auth);
+        Self::link_issued_authorization(from, target, new_nonce);
 
         Self::deposit_event(RawEvent::NewAuthorization(
             new_nonce,
@@ -1026,6 +2021,7 @@ impl<T: Trait> Module<T> {
             authorization_data,
             expiry,
         ));
+        Ok(())
     }
 
     /// Remove any authorization. No questions asked.
@@ -1046,6 +2042,8 @@ impl<T: Trait> Module<T> {
                 prev_auth.next_authorization = next_auth
             });
         }
+        let issuer = Self::authorizations((target, auth_id)).authorized_by;
+        Self::unlink_issued_authorization(issuer, target, auth_id);
         <Authorizations<T>>::remove((target, auth_id));
         Self::deposit_event(RawEvent::AuthorizationRemoved(auth_id, target));
     }
@@ -1077,11 +2075,77 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    /// It checks if `authorization_data` is one of the authorizations that `accept_authorization`
+    /// knows how to handle, either by a built-in handler or by one of `T::AuthorizationHandlers`.
+    pub fn is_acceptable_authorization(authorization_data: &AuthorizationData) -> bool {
+        AuthorizationHandlersOf::<T>::handles(authorization_data)
+    }
+
+    /// Dispatches the existing authorization `auth_id` of `signer` to the handler of its kind
+    /// and emits `AuthorizationAccepted` once it is accepted.
+    fn accept_auth(signer: Signatory, auth_id: u64) -> DispatchResult {
+        let auth = Self::authorizations((signer, auth_id));
+        ensure!(
+            Self::is_acceptable_authorization(&auth.authorization_data),
+            Error::<T>::UnknownAuthorization
+        );
+        AuthorizationHandlersOf::<T>::accept(signer, &auth.authorization_data, auth_id)?;
+        Self::deposit_event(RawEvent::AuthorizationAccepted(
+            auth_id,
+            signer,
+            AuthorizationType::from(&auth.authorization_data),
+        ));
+        Ok(())
+    }
+
+    /// Accepts a `JoinIdentity` authorization: `signer` joins `target_did` as a signing key
+    /// without permissions.
+    ///
+    /// # Errors
+    /// - The authorization has to be issued by `target_did` and must not be expired.
+    /// - A key signer has to be linkable to `target_did`.
+    fn join_identity(signer: Signatory, target_did: IdentityId, auth_id: u64) -> DispatchResult {
+        ensure!(<DidRecords>::exists(target_did), "DID does not exist");
+        if let Signatory::AccountKey(ref key) = signer {
+            ensure!(
+                Self::can_key_be_linked_to_did(key, SignatoryType::External),
+                Error::<T>::AlreadyLinked
+            );
+        }
+        Self::consume_auth(Signatory::from(target_did), signer, auth_id)?;
+
+        if let Signatory::AccountKey(ref key) = signer {
+            Self::link_key_to_did(key, SignatoryType::External, target_did);
+        }
+        <DidRecords>::mutate(target_did, |identity| {
+            if !identity.signing_items.iter().any(|si| si.signer == signer) {
+                identity.add_signing_items(&[SigningItem::new(signer, vec![])]);
+            }
+        });
+        Self::sync_signing_item_permissions(target_did, &[signer]);
+        Self::deposit_event(RawEvent::SignerJoinedToIdentityApproved(signer, target_did));
+        Self::deposit_keyset_updated(target_did);
+        Ok(())
+    }
+
+    /// Increases the multi purpose nonce and returns its new value.
+    /// It fails with `NonceOverflow` if the nonce cannot be increased.
+    fn next_multi_purpose_nonce() -> StdResult<u64, DispatchError> {
+        let new_nonce = Self::multi_purpose_nonce()
+            .checked_add(1u64)
+            .ok_or(Error::<T>::NonceOverflow)?;
+        <MultiPurposeNonce>::put(&new_nonce);
+        Ok(new_nonce)
+    }
+
     /// Adds a link to a key or an identity
     /// NB: Please do all the required checks before calling this function.
-    pub fn add_link(target: Signatory, link_data: LinkData, expiry: Option<T::Moment>) -> u64 {
-        let new_nonce = Self::multi_purpose_nonce() + 1u64;
-        <MultiPurposeNonce>::put(&new_nonce);
+    pub fn add_link(
+        target: Signatory,
+        link_data: LinkData,
+        expiry: Option<T::Moment>,
+    ) -> StdResult<u64, DispatchError> {
+        let new_nonce = Self::next_multi_purpose_nonce()?;
 
         let last_link = Self::last_link(&target);
 
@@ -1104,7 +2168,29 @@ impl<T: Trait> Module<T> {
         <Links<T>>::insert((target, new_nonce), link);
 
         Self::deposit_event(RawEvent::NewLink(new_nonce, target, link_data, expiry));
-        new_nonce
+        Ok(new_nonce)
+    }
+
+    /// It returns the links of `target` whose data is a `wanted` variant, with their ids, newest
+    /// first. Expired links are skipped unless `include_expired` is set.
+    pub fn get_links_of_type(
+        target: Signatory,
+        wanted: LinkDataTag,
+        include_expired: bool,
+    ) -> Vec<(u64, Link<T::Moment>)> {
+        let now = <pallet_timestamp::Module<T>>::get();
+        let mut links = Vec::new();
+        let mut link_id = Self::last_link(&target);
+        while link_id != 0 {
+            let link = Self::links((target, link_id));
+            let previous_link = link.previous_link;
+            let is_expired = link.expiry.map_or(false, |expiry| expiry <= now);
+            if LinkDataTag::from(&link.link_data) == wanted && (include_expired || !is_expired) {
+                links.push((link_id, link));
+            }
+            link_id = previous_link;
+        }
+        links
     }
 
     /// Remove a link (if it exists) from a key or identity
@@ -1168,6 +2254,7 @@ impl<T: Trait> Module<T> {
                 new_s_item = Some(signing_item);
             }
         });
+        Self::sync_signing_item_permissions(target_did, &[*signer]);
 
         if let Some(s) = new_s_item {
             Self::deposit_event(RawEvent::SigningPermissionsUpdated(
@@ -1179,20 +2266,75 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    /// It returns the permissions of the `signer` signing item of `did`, or `None` if `signer` is
+    /// not a signing item of `did`.
+    pub fn signing_item_permissions(did: IdentityId, signer: Signatory) -> Option<Vec<Permission>> {
+        Self::cached_signing_item_permissions((did, signer)).or_else(|| {
+            <DidRecords>::get(did)
+                .signing_items
+                .into_iter()
+                .find(|si| si.signer == signer)
+                .map(|si| si.permissions)
+        })
+    }
+
+    /// It refreshes the cached permissions of `signers` from the `DidRecords` of `did`.
+    fn sync_signing_item_permissions(did: IdentityId, signers: &[Signatory]) {
+        let record = <DidRecords>::get(did);
+        for signer in signers {
+            let did_signer = (did, *signer);
+            match record.signing_items.iter().find(|si| si.signer == *signer) {
+                Some(si) => <SigningItemPermissions>::insert(&did_signer, si.permissions.clone()),
+                None => <SigningItemPermissions>::remove(&did_signer),
+            }
+        }
+    }
+
+    /// It emits the current set of signing items of `did` in a `DidKeysetUpdated` event. Called
+    /// by the operations that change the signing items of `did`.
+    fn deposit_keyset_updated(did: IdentityId) {
+        Self::deposit_event(RawEvent::DidKeysetUpdated(
+            did,
+            <DidRecords>::get(did).signing_items,
+        ));
+    }
+
     /// It checks if `key` is a signing key of `did` identity.
     /// # IMPORTANT
     /// If signing keys are frozen this function always returns false.
     /// Master key cannot be frozen.
     pub fn is_signer_authorized(did: IdentityId, signer: &Signatory) -> bool {
-        let record = <DidRecords>::get(did);
+        Self::is_signer_authorized_by_record(did, &<DidRecords>::get(did), signer)
+    }
+
+    /// It checks several `(did, signer)` pairs like `is_signer_authorized`, decoding the
+    /// record of each DID only once.
+    pub fn are_signers_authorized(checks: Vec<(IdentityId, Signatory)>) -> Vec<bool> {
+        let mut records = BTreeMap::new();
+        checks
+            .into_iter()
+            .map(|(did, signer)| {
+                let record = records.entry(did).or_insert_with(|| <DidRecords>::get(did));
+                Self::is_signer_authorized_by_record(did, record, &signer)
+            })
+            .collect()
+    }
 
+    /// It checks if `signer` is authorized for `did`, whose identity record is `record`.
+    fn is_signer_authorized_by_record(
+        did: IdentityId,
+        record: &DidRecord,
+        signer: &Signatory,
+    ) -> bool {
         // Check master id or key
         match signer {
             Signatory::AccountKey(ref signer_key) if record.master_key == *signer_key => true,
             Signatory::Identity(ref signer_id) if did == *signer_id => true,
             _ => {
-                // Check signing items if DID is not frozen.
+                // Check signing items if neither the DID nor the key is frozen or suspended.
                 !Self::is_did_frozen(did)
+                    && !Self::frozen_signing_keys((did, *signer))
+                    && !<SuspendedPermissions>::exists((did, *signer))
                     && record.signing_items.iter().any(|si| si.signer == *signer)
             }
         }
@@ -1209,7 +2351,10 @@ impl<T: Trait> Module<T> {
             Signatory::AccountKey(ref signer_key) if record.master_key == *signer_key => true,
             Signatory::Identity(ref signer_id) if did == *signer_id => true,
             _ => {
-                if !Self::is_did_frozen(did) {
+                if !Self::is_did_frozen(did)
+                    && !Self::frozen_signing_keys((did, *signer))
+                    && !<SuspendedPermissions>::exists((did, *signer))
+                {
                     if let Some(signing_item) =
                         record.signing_items.iter().find(|&si| &si.signer == signer)
                     {
@@ -1220,17 +2365,138 @@ impl<T: Trait> Module<T> {
                     }
                 }
                 // Signatory is not part of signing items of `did`, or
-                // Did is frozen.
+                // Did or signatory is frozen.
                 false
             }
         }
     }
 
+    /// It checks if `signer` is authorized for `did`, like `is_signer_authorized`, and can be
+    /// used for the `call` encoded call.
+    pub fn is_signer_authorized_for_call(did: IdentityId, signer: &Signatory, call: &[u8]) -> bool {
+        Self::is_signer_authorized(did, signer) && Self::is_call_allowed(did, signer, call)
+    }
+
+    /// It checks if the `signer` key of `did` can be used for the `call` encoded call, i.e. it
+    /// has no restriction or one of its allowed call identifiers is a prefix of `call`.
+    /// The master key and the identity itself are never restricted.
+    pub fn is_call_allowed(did: IdentityId, signer: &Signatory, call: &[u8]) -> bool {
+        <SigningItemAllowedCalls>::get((did, *signer))
+            .map_or(true, |calls| calls.iter().any(|id| call.starts_with(id)))
+    }
+
     /// Use `did` as reference.
     pub fn is_master_key(did: IdentityId, key: &AccountKey) -> bool {
         key == &<DidRecords>::get(did).master_key
     }
 
+    /// It verifies, without any state change, that `si_with_auth` carries a valid signature
+    /// of `authorization` to join `id` identity, as `add_signing_items_with_authorization`
+    /// requires. The expiry of `authorization` is not checked.
+    ///
+    /// # Failure
+    ///  - `authorization` is not for `id` or its nonce is not the current one of `id`.
+    ///  - The signing key cannot be linked to `id` because it belongs to another identity.
+    ///  - The authorization has been explicitly revoked by the signer.
+    ///  - The signature is not a signature of `authorization` by the signer.
+    pub fn verify_signing_item_auth(
+        id: IdentityId,
+        si_with_auth: &SigningItemWithAuth,
+        authorization: &TargetIdAuthorization<T::Moment>,
+    ) -> StdResult<(), &'static str> {
+        ensure!(
+            authorization.target_id == id
+                && authorization.nonce == Self::offchain_authorization_nonce(id),
+            "Authorization is not valid for the target identity"
+        );
+        let si = &si_with_auth.signing_item;
+
+        // Get account_id from signer
+        let account_id_found = match si.signer {
+            Signatory::AccountKey(ref key) => Public::try_from(key.as_slice()).ok(),
+            Signatory::Identity(ref id) if <DidRecords>::exists(id) => {
+                let master_key = <DidRecords>::get(id).master_key;
+                Public::try_from(master_key.as_slice()).ok()
+            }
+            _ => None,
+        };
+        let account_id = account_id_found.ok_or(Error::<T>::InvalidAccountKey)?;
+
+        if let Signatory::AccountKey(ref key) = si.signer {
+            // Constraint 1-to-1 account to DID
+            ensure!(
+                Self::can_key_be_linked_to_did(key, si.signer_type),
+                "One signing key can only belong to one identity"
+            );
+        }
+
+        // Offchain authorization is not revoked explicitly.
+        ensure!(
+            !Self::is_offchain_authorization_revoked((si.signer, authorization.clone())),
+            "Authorization has been explicitly revoked"
+        );
+
+        // Verify the signature.
+        let signature = AnySignature::from(Signature::from_h512(si_with_auth.auth_signature));
+        ensure!(
+            signature.verify(authorization.encode().as_slice(), &account_id),
+            "Invalid Authorization signature"
+        );
+        Ok(())
+    }
+
+    /// It validates, without any state change, a batch of signing items to be added to `id`
+    /// through `add_signing_items_with_authorization` with an authorization expiring at
+    /// `expires_at`. Unlike the extrinsic, it does not stop at the first invalid item.
+    ///
+    /// Returns the index and the failure reason of every invalid item, so an empty result
+    /// means the whole batch is valid.
+    pub fn validate_signing_items_auth(
+        id: IdentityId,
+        expires_at: T::Moment,
+        items: &[SigningItemWithAuth],
+    ) -> Vec<(usize, &'static str)> {
+        let now = <pallet_timestamp::Module<T>>::get();
+        let authorization = TargetIdAuthorization {
+            target_id: id,
+            nonce: Self::offchain_authorization_nonce(id),
+            expires_at,
+        };
+
+        items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, si_with_auth)| {
+                if now >= expires_at {
+                    return Some((index, "Offchain authorization has expired"));
+                }
+                Self::verify_signing_item_auth(id, si_with_auth, &authorization)
+                    .err()
+                    .map(|reason| (index, reason))
+            })
+            .collect()
+    }
+
+    /// It checks if `key` is the master key of any identity.
+    pub fn is_any_master_key(key: &AccountKey) -> bool {
+        <MasterKeyToDid>::exists(key)
+    }
+
+    /// It replaces the master key of `did` with `new_key`, keeping `MasterKeyToDid` in sync.
+    fn set_did_master_key(did: IdentityId, new_key: AccountKey) {
+        <DidRecords>::mutate(did, |record| {
+            <MasterKeyToDid>::remove(&record.master_key);
+            (*record).master_key = new_key;
+        });
+        <MasterKeyToDid>::insert(new_key, did);
+    }
+
+    /// Whether a claim that expires at `expiry` is valid at `now`, that is, it has not expired
+    /// yet or it expired less than `ClaimExpiryGracePeriod` ago.
+    fn is_claim_valid(expiry: T::Moment, now: T::Moment) -> bool {
+        expiry > now || now - expiry < Self::claim_expiry_grace_period()
+    }
+
     pub fn fetch_claim_value(
         did: IdentityId,
         claim_key: Vec<u8>,
@@ -1242,14 +2508,56 @@ impl<T: Trait> Module<T> {
         };
         if <Claims<T>>::exists((did, claim_meta_data.clone())) {
             let now = <pallet_timestamp::Module<T>>::get();
-            let claim = <Claims<T>>::get((did, claim_meta_data));
-            if claim.expiry > now {
+            let claim = <Claims<T>>::get((did, claim_meta_data.clone()));
+            if Self::is_claim_valid(claim.expiry, now) {
                 return Some(claim.claim_value);
             }
         }
         None
     }
 
+    /// Deposits `ClaimInGracePeriod` the first time an expired claim of `did` that meets one of
+    /// `requirements` is used within its grace period. Claims are read without side effects so
+    /// that queries and dry-runs do not consume the notice, hence only dispatched calls that
+    /// rely on the claims call this.
+    pub fn note_claims_in_grace_period(
+        did: IdentityId,
+        requirements: &[(Vec<u8>, Vec<IdentityId>)],
+    ) {
+        let now = <pallet_timestamp::Module<T>>::get();
+        for (claim_key, claim_issuers) in requirements {
+            for claim_issuer in claim_issuers {
+                let did_claim = (
+                    did,
+                    ClaimMetaData {
+                        claim_key: claim_key.clone(),
+                        claim_issuer: *claim_issuer,
+                    },
+                );
+                if !<Claims<T>>::exists(&did_claim) {
+                    continue;
+                }
+                let expiry = <Claims<T>>::get(&did_claim).expiry;
+                if expiry <= now
+                    && Self::is_claim_valid(expiry, now)
+                    && Self::claim_grace_notified(&did_claim) != Some(expiry)
+                {
+                    <ClaimGraceNotified<T>>::insert(&did_claim, expiry);
+                    Self::deposit_event(RawEvent::ClaimInGracePeriod(did_claim.0, did_claim.1));
+                }
+            }
+        }
+    }
+
+    /// Returns the claim key and the issuers of the KYC claims accepted by
+    /// `is_identity_has_valid_kyc`.
+    pub fn kyc_claim_requirement() -> (Vec<u8>, Vec<IdentityId>) {
+        (
+            KYC_EXPIRY_CLAIM_KEY.to_vec(),
+            T::KycServiceProviders::get_members(),
+        )
+    }
+
     pub fn fetch_claim_value_multiple_issuers(
         did: IdentityId,
         claim_key: Vec<u8>,
@@ -1264,6 +2572,264 @@ impl<T: Trait> Module<T> {
         None
     }
 
+    /// It returns the value of the first valid claim under `claim_key` that any of
+    /// `claim_issuers` has issued to `did` for `ticker` only. Ticker claims expire like the
+    /// claims of the identity, including their grace period.
+    pub fn fetch_ticker_claim(
+        did: IdentityId,
+        ticker: Ticker,
+        claim_key: Vec<u8>,
+        claim_issuers: Vec<IdentityId>,
+    ) -> Option<ClaimValue> {
+        let now = <pallet_timestamp::Module<T>>::get();
+        claim_issuers.into_iter().find_map(|claim_issuer| {
+            let key = (
+                did,
+                ticker,
+                ClaimMetaData {
+                    claim_key: claim_key.clone(),
+                    claim_issuer,
+                },
+            );
+            if <TickerScopedClaims<T>>::exists(&key) {
+                let claim = <TickerScopedClaims<T>>::get(&key);
+                if Self::is_claim_valid(claim.expiry, now) {
+                    return Some(claim.claim_value);
+                }
+            }
+            None
+        })
+    }
+
+    /// Withdraws the fee of `claims` new claims from `payer` and hands it to
+    /// `T::ClaimFeeCollector`. It does nothing if the claim fee is zero.
+    fn charge_claim_fee(payer: &T::AccountId, claims: u32) -> DispatchResult {
+        let fee = Self::claim_fee().saturating_mul(claims.into());
+        if !fee.is_zero() {
+            let imbalance = <T::Balances>::withdraw(
+                payer,
+                fee,
+                WithdrawReason::Fee.into(),
+                ExistenceRequirement::KeepAlive,
+            )?;
+            T::ClaimFeeCollector::on_unbalanced(imbalance);
+        }
+        Ok(())
+    }
+
+    /// It returns the (target, auth id) pairs of the pending authorizations issued by `did`, in
+    /// issuance order.
+    pub fn authorizations_issued_by(did: IdentityId) -> Vec<(Signatory, u64)> {
+        Self::authorizations_by_issuer(Signatory::from(did))
+    }
+
+    /// It returns the (target, auth id) pairs of the pending authorizations issued by `issuer`,
+    /// in issuance order.
+    pub fn authorizations_by_issuer(issuer: Signatory) -> Vec<(Signatory, u64)> {
+        let mut issued = Vec::new();
+        let mut next = Self::last_issued_authorization(issuer);
+        while let Some(key) = next {
+            next = Self::issued_authorization_links(key).and_then(|links| links.previous);
+            issued.push(key);
+        }
+        issued.reverse();
+        issued
+    }
+
+    /// Appends the authorization `auth_id` of `target` to the pending authorizations issued by
+    /// `issuer`.
+    fn link_issued_authorization(issuer: Signatory, target: Signatory, auth_id: u64) {
+        let key = (target, auth_id);
+        let previous = Self::last_issued_authorization(issuer);
+        if let Some(previous_key) = previous {
+            <AuthorizationsByIssuer>::mutate(previous_key, |links| {
+                if let Some(links) = links {
+                    links.next = Some(key);
+                }
+            });
+        }
+        <AuthorizationsByIssuer>::insert(
+            key,
+            IssuedAuthorizationLinks {
+                previous,
+                next: None,
+            },
+        );
+        <LastIssuedAuthorization>::insert(issuer, key);
+    }
+
+    /// Removes the authorization `auth_id` of `target` from the pending authorizations issued by
+    /// `issuer`. It does nothing if that authorization is not indexed.
+    fn unlink_issued_authorization(issuer: Signatory, target: Signatory, auth_id: u64) {
+        let key = (target, auth_id);
+        let links = match Self::issued_authorization_links(key) {
+            Some(links) => links,
+            None => return,
+        };
+        if let Some(previous_key) = links.previous {
+            <AuthorizationsByIssuer>::mutate(previous_key, |previous| {
+                if let Some(previous) = previous {
+                    previous.next = links.next;
+                }
+            });
+        }
+        match links.next {
+            Some(next_key) => <AuthorizationsByIssuer>::mutate(next_key, |next| {
+                if let Some(next) = next {
+                    next.previous = links.previous;
+                }
+            }),
+            None => match links.previous {
+                Some(previous_key) => <LastIssuedAuthorization>::insert(issuer, previous_key),
+                None => <LastIssuedAuthorization>::remove(issuer),
+            },
+        }
+        <AuthorizationsByIssuer>::remove(key);
+    }
+
+    /// It returns who added the authorization `auth_id` of `target` and whether it is still
+    /// non-expired, or `None` if that authorization does not exist.
+    pub fn authorization_issuer(target: Signatory, auth_id: u64) -> Option<(Signatory, bool)> {
+        if !<Authorizations<T>>::exists((target, auth_id)) {
+            return None;
+        }
+        let auth = Self::authorizations((target, auth_id));
+        let now = <pallet_timestamp::Module<T>>::get();
+        let is_valid = auth.expiry.map_or(true, |expiry| expiry > now);
+        Some((auth.authorized_by, is_valid))
+    }
+
+    /// It returns the non-expired authorizations of `signer` that `accept_authorization` knows
+    /// how to handle, most recent first.
+    pub fn actionable_authorizations(signer: Signatory) -> Vec<(u64, AuthorizationData)> {
+        let now = <pallet_timestamp::Module<T>>::get();
+        Self::authorizations_list(signer)
+            .into_iter()
+            .filter(|(_, auth)| {
+                let is_expired = auth.expiry.map_or(false, |expiry| expiry <= now);
+                !is_expired && Self::is_acceptable_authorization(&auth.authorization_data)
+            })
+            .map(|(auth_id, auth)| (auth_id, auth.authorization_data))
+            .collect()
+    }
+
+    /// It returns the authorizations of `target` whose data is of type `authorization_type`,
+    /// most recent first. Expired authorizations are only included if `include_expired` is set.
+    pub fn authorizations_of_type(
+        target: Signatory,
+        authorization_type: AuthorizationType,
+        include_expired: bool,
+    ) -> Vec<(u64, Authorization<T::Moment>)> {
+        let now = <pallet_timestamp::Module<T>>::get();
+        Self::authorizations_list(target)
+            .into_iter()
+            .filter(|(_, auth)| {
+                AuthorizationType::from(&auth.authorization_data) == authorization_type
+                    && (include_expired || auth.expiry.map_or(true, |expiry| expiry > now))
+            })
+            .collect()
+    }
+
+    /// It walks the authorization list of `signer` and returns its authorizations with their ids,
+    /// most recent first.
+    fn authorizations_list(signer: Signatory) -> Vec<(u64, Authorization<T::Moment>)> {
+        let mut auths = Vec::new();
+        let mut auth_id = Self::last_authorization(signer);
+        while auth_id != 0 {
+            let auth = Self::authorizations((signer, auth_id));
+            let previous_auth = auth.previous_authorization;
+            auths.push((auth_id, auth));
+            auth_id = previous_auth;
+        }
+        auths
+    }
+
+    /// It returns the identities that `signer` has been pre-authorized to join.
+    pub fn pending_joins(signer: Signatory) -> Vec<IdentityId> {
+        Self::pre_authorized_join_did(signer)
+            .into_iter()
+            .map(|pre_auth| pre_auth.target_id)
+            .collect()
+    }
+
+    /// It returns the distinct issuers of the non-expired claims of `did`.
+    pub fn claim_issuers_of(did: IdentityId) -> Vec<IdentityId> {
+        let now = <pallet_timestamp::Module<T>>::get();
+        let mut issuers = Self::claim_keys(did)
+            .into_iter()
+            .filter(|claim_meta_data| <Claims<T>>::get((did, claim_meta_data.clone())).expiry > now)
+            .map(|claim_meta_data| claim_meta_data.claim_issuer)
+            .collect::<Vec<_>>();
+        issuers.sort();
+        issuers.dedup();
+        issuers
+    }
+
+    /// It returns `true` if, for each `(claim_key, acceptable_issuers)` of `requirements`, at
+    /// least one of the acceptable issuers has issued a valid claim under `claim_key` to `did`,
+    /// counting claims within their grace period as `fetch_claim_value` does.
+    pub fn has_all_claims(did: IdentityId, requirements: &[(Vec<u8>, Vec<IdentityId>)]) -> bool {
+        let now = <pallet_timestamp::Module<T>>::get();
+        requirements.iter().all(|(claim_key, acceptable_issuers)| {
+            acceptable_issuers.iter().any(|claim_issuer| {
+                let did_claim = (
+                    did,
+                    ClaimMetaData {
+                        claim_key: claim_key.clone(),
+                        claim_issuer: *claim_issuer,
+                    },
+                );
+                <Claims<T>>::exists(&did_claim)
+                    && Self::is_claim_valid(<Claims<T>>::get(&did_claim).expiry, now)
+            })
+        })
+    }
+
+    /// It returns the issuer, value and expiry of every non-expired claim under `claim_key`
+    /// that `did` holds, whoever issued it.
+    pub fn fetch_all_claim_values(
+        did: IdentityId,
+        claim_key: Vec<u8>,
+    ) -> Vec<(IdentityId, ClaimValue, T::Moment)> {
+        let now = <pallet_timestamp::Module<T>>::get();
+        Self::claim_keys(did)
+            .into_iter()
+            .filter(|claim_meta_data| claim_meta_data.claim_key == claim_key)
+            .filter_map(|claim_meta_data| {
+                let claim_issuer = claim_meta_data.claim_issuer;
+                let claim = <Claims<T>>::get((did, claim_meta_data));
+                if claim.expiry > now {
+                    Some((claim_issuer, claim.claim_value, claim.expiry))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// It returns `true` if any of `claim_issuers` has issued a non-expired numeric claim under
+    /// `claim_key` to `did` whose value is greater than or equal to `threshold`.
+    pub fn numeric_claim_at_least(
+        did: IdentityId,
+        claim_key: Vec<u8>,
+        claim_issuers: Vec<IdentityId>,
+        threshold: u128,
+    ) -> bool {
+        let now = <pallet_timestamp::Module<T>>::get();
+        claim_issuers.into_iter().any(|claim_issuer| {
+            let claim_meta_data = ClaimMetaData {
+                claim_key: claim_key.clone(),
+                claim_issuer,
+            };
+            if <NumericClaims<T>>::exists((did, claim_meta_data.clone())) {
+                let (value, expiry) = <NumericClaims<T>>::get((did, claim_meta_data));
+                expiry > now && value >= threshold
+            } else {
+                false
+            }
+        })
+    }
+
     pub fn is_identity_has_valid_kyc(
         claim_for: IdentityId,
         buffer: u64,
@@ -1322,22 +2888,134 @@ impl<T: Trait> Module<T> {
         return None;
     }
 
+    /// It returns the master key of `did`, or `None` if `did` does not exist.
+    pub fn master_key(did: IdentityId) -> Option<AccountKey> {
+        if <DidRecords>::exists(did) {
+            Some(Self::did_records(did).master_key)
+        } else {
+            None
+        }
+    }
+
+    /// It returns the identity that `signer` belongs to: the identity itself if it exists, or
+    /// the identity linked to the key.
+    pub fn signer_to_did(signer: &Signatory) -> Option<IdentityId> {
+        match signer {
+            Signatory::Identity(did) if <DidRecords>::exists(did) => Some(*did),
+            Signatory::Identity(_) => None,
+            Signatory::AccountKey(key) => Self::get_identity(key),
+        }
+    }
+
+    /// It checks that `sender_key` is the master key of `did` Identifier or one of its signing
+    /// keys with `Operator` permission, and that did exists.
+    /// # Return
+    /// A result object containing the `DidRecord` of `did`.
+    pub fn grant_check_master_or_operator_key(
+        sender_key: &AccountKey,
+        did: IdentityId,
+    ) -> sp_std::result::Result<DidRecord, &'static str> {
+        ensure!(<DidRecords>::exists(did), "DID does not exist");
+        ensure!(
+            Self::is_signer_authorized_with_permissions(
+                did,
+                &Signatory::AccountKey(*sender_key),
+                vec![Permission::Operator]
+            ),
+            "Only master key or an operator key of an identity is able to execute this operation"
+        );
+
+        Ok(<DidRecords>::get(did))
+    }
+
+    /// It freezes `did` without any key check, as other modules do when `did` must not be used
+    /// anymore. An already frozen `did` is left unchanged.
+    pub fn freeze_did(did: IdentityId) {
+        if !Self::is_did_frozen(did) {
+            <IsDidFrozen>::insert(did, true);
+            Self::deposit_event(RawEvent::DidFrozen(did));
+        }
+    }
+
+    /// It checks if `signer` is a signing key of `did` with `Permission::EmergencyFreeze`.
+    /// The signing keys of a frozen identity are not authorized for anything else, so the
+    /// permission is checked on the signing item itself when unfreezing.
+    fn has_emergency_freeze_permission(did: IdentityId, signer: &Signatory, freeze: bool) -> bool {
+        if freeze {
+            return Self::is_signer_authorized_with_permissions(
+                did,
+                signer,
+                vec![Permission::EmergencyFreeze],
+            );
+        }
+        !Self::frozen_signing_keys((did, *signer))
+            && !<SuspendedPermissions>::exists((did, *signer))
+            && <DidRecords>::get(did)
+                .signing_items
+                .iter()
+                .any(|si| si.signer == *signer && si.has_permission(Permission::EmergencyFreeze))
+    }
+
     /// It freezes/unfreezes the target `did` identity.
     ///
     /// # Errors
-    /// Only master key can freeze/unfreeze an identity.
+    /// Only master key, an operator key or an emergency freeze key can freeze an identity.
+    /// Only master key or an emergency freeze key can unfreeze an identity.
     fn set_frozen_signing_key_flags(
         origin: T::Origin,
         did: IdentityId,
         freeze: bool,
     ) -> DispatchResult {
         let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
-        let _grants_checked = Self::grant_check_only_master_key(&sender_key, did)?;
+        let sender = Signatory::AccountKey(sender_key);
+        if !Self::has_emergency_freeze_permission(did, &sender, freeze) {
+            if freeze {
+                Self::grant_check_master_or_operator_key(&sender_key, did)?;
+            } else {
+                Self::grant_check_only_master_key(&sender_key, did)?;
+            }
+        }
 
         if freeze {
             <IsDidFrozen>::insert(did, true);
+            Self::deposit_event(RawEvent::DidFrozen(did));
         } else {
             <IsDidFrozen>::remove(did);
+            Self::deposit_event(RawEvent::DidUnfrozen(did));
+        }
+        <PendingUnfreeze<T>>::remove(did);
+        Ok(())
+    }
+
+    /// It records an unfreeze confirmation of `did` from its master key or `recovery_key`.
+    /// The identity is unfrozen once both keys have confirmed within `DUAL_UNFREEZE_WINDOW`.
+    fn confirm_dual_unfreeze(
+        origin: T::Origin,
+        did: IdentityId,
+        recovery_key: AccountKey,
+    ) -> DispatchResult {
+        let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+        ensure!(<DidRecords>::exists(did), "DID does not exist");
+        ensure!(
+            sender_key == <DidRecords>::get(did).master_key || sender_key == recovery_key,
+            Error::<T>::Unauthorized
+        );
+        ensure!(Self::is_did_frozen(did), Error::<T>::IdentityNotFrozen);
+
+        let now = <pallet_timestamp::Module<T>>::get();
+        let window = DUAL_UNFREEZE_WINDOW.saturated_into::<T::Moment>();
+        match Self::pending_unfreeze(did) {
+            Some((confirmed_by, confirmed_at))
+                if confirmed_by != sender_key && now <= confirmed_at.saturating_add(window) =>
+            {
+                <IsDidFrozen>::remove(did);
+                <PendingUnfreeze<T>>::remove(did);
+                Self::deposit_event(RawEvent::DidUnfrozen(did));
+            }
+            _ => {
+                <PendingUnfreeze<T>>::insert(did, (sender_key, now));
+                Self::deposit_event(RawEvent::UnfreezeConfirmed(did, sender_key));
+            }
         }
         Ok(())
     }
@@ -1355,6 +3033,32 @@ impl<T: Trait> Module<T> {
         }
     }
 
+    /// It checks, without any state change, whether each identity that `key` is linked to in
+    /// `KeyToIdentityIds` lists `key` as its master key or as one of its signing keys.
+    pub fn verify_key_links(key: &AccountKey) -> KeyLinkConsistency {
+        let dids = match <KeyToIdentityIds>::get(key) {
+            Some(LinkedKeyInfo::Unique(did)) => vec![did],
+            Some(LinkedKeyInfo::Group(dids)) => dids,
+            None => vec![],
+        };
+        let signer = Signatory::AccountKey(*key);
+
+        let mut report = KeyLinkConsistency::default();
+        for did in dids {
+            let is_listed = <DidRecords>::exists(did) && {
+                let record = <DidRecords>::get(did);
+                record.master_key == *key
+                    || record.signing_items.iter().any(|si| si.signer == signer)
+            };
+            if is_listed {
+                report.linked_dids.push(did);
+            } else {
+                report.dangling_dids.push(did);
+            }
+        }
+        report
+    }
+
     /// It links `key` key to `did` identity as a `key_type` type.
     /// # Errors
     /// This function can be used if `can_key_be_linked_to_did` returns true. Otherwise, it will do
@@ -1367,7 +3071,8 @@ impl<T: Trait> Module<T> {
                         dids.push(did);
                         dids.sort();
 
-                        <KeyToIdentityIds>::insert(key, LinkedKeyInfo::Group(dids));
+                        <KeyToIdentityIds>::insert(key, LinkedKeyInfo::Group(dids.clone()));
+                        Self::deposit_event(RawEvent::KeyGroupMembershipChanged(*key, dids));
                     }
                 }
                 _ => {
@@ -1378,7 +3083,10 @@ impl<T: Trait> Module<T> {
             // AccountKey is not yet linked to any identity, so no constraints.
             let linked_key_info = match key_type {
                 SignatoryType::External => LinkedKeyInfo::Unique(did),
-                _ => LinkedKeyInfo::Group(vec![did]),
+                _ => {
+                    Self::deposit_event(RawEvent::KeyGroupMembershipChanged(*key, vec![did]));
+                    LinkedKeyInfo::Group(vec![did])
+                }
             };
             <KeyToIdentityIds>::insert(key, linked_key_info);
         }
@@ -1391,11 +3099,15 @@ impl<T: Trait> Module<T> {
             match linked_key_info {
                 LinkedKeyInfo::Unique(..) => <KeyToIdentityIds>::remove(key),
                 LinkedKeyInfo::Group(mut dids) => {
+                    let previous_len = dids.len();
                     dids.retain(|ref_did| *ref_did != did);
                     if dids.is_empty() {
                         <KeyToIdentityIds>::remove(key);
                     } else {
-                        <KeyToIdentityIds>::insert(key, LinkedKeyInfo::Group(dids));
+                        <KeyToIdentityIds>::insert(key, LinkedKeyInfo::Group(dids.clone()));
+                    }
+                    if dids.len() != previous_len {
+                        Self::deposit_event(RawEvent::KeyGroupMembershipChanged(*key, dids));
                     }
                 }
             }
@@ -1410,8 +3122,12 @@ impl<T: Trait> Module<T> {
             <CurrentDid>::kill();
         }
     }
-    /// It adds `signing_item` to pre authorized items for `id` identity.
-    fn add_pre_join_identity(signing_item: &SigningItem, id: IdentityId) {
+    /// It adds `signing_item` to pre authorized items for `id` identity, valid until `expiry`.
+    fn add_pre_join_identity(
+        signing_item: &SigningItem,
+        id: IdentityId,
+        expiry: Option<T::Moment>,
+    ) {
         let signer = &signing_item.signer;
         let new_pre_auth = PreAuthorizedKeyInfo::new(signing_item.clone(), id);
 
@@ -1423,6 +3139,10 @@ impl<T: Trait> Module<T> {
                 pre_auth_list.push(new_pre_auth);
             });
         }
+        match expiry {
+            Some(expiry) => <PreAuthorizedJoinDidExpiry<T>>::insert((*signer, id), expiry),
+            None => <PreAuthorizedJoinDidExpiry<T>>::remove((*signer, id)),
+        }
     }
 
     /// It removes `signing_item` to pre authorized items for `id` identity.
@@ -1436,32 +3156,49 @@ impl<T: Trait> Module<T> {
         if is_pre_auth_list_empty {
             <PreAuthorizedJoinDid>::remove(signer);
         }
+        <PreAuthorizedJoinDidExpiry<T>>::remove((*signer, id));
     }
 
     /// It registers a did for a new asset. Only called by create_token function.
     pub fn register_asset_did(ticker: &Ticker) -> DispatchResult {
-        let did = Self::get_token_did(ticker)?;
+        let did = Self::compute_token_did(ticker)?;
         Self::deposit_event(RawEvent::AssetDid(*ticker, did));
         // Making sure there's no pre-existing entry for the DID
         // This should never happen but just being defensive here
         ensure!(!<DidRecords>::exists(did), "DID must be unique");
         <DidRecords>::insert(did, DidRecord::default());
+        <TickerToAssetDid>::insert(ticker, did);
         Ok(())
     }
 
-    /// It is a helper function that can be used to get did for any asset
+    /// It is a helper function that can be used to get did for any asset. The stored DID is
+    /// used if present, otherwise the DID is computed from the ticker.
     pub fn get_token_did(ticker: &Ticker) -> StdResult<IdentityId, &'static str> {
+        match Self::ticker_to_asset_did(ticker) {
+            Some(did) => Ok(did),
+            None => Self::compute_token_did(ticker),
+        }
+    }
+
+    /// Computes the DID of an asset from its ticker.
+    fn compute_token_did(ticker: &Ticker) -> StdResult<IdentityId, &'static str> {
         let mut buf = Vec::new();
         buf.extend_from_slice(&SECURITY_TOKEN.encode());
         buf.extend_from_slice(&ticker.encode());
         IdentityId::try_from(T::Hashing::hash(&buf[..]).as_ref())
     }
 
-    pub fn _register_did(sender: T::AccountId, signing_items: Vec<SigningItem>) -> DispatchResult {
+    /// It registers a new identity with `sender` as its master key and returns its DID. The
+    /// `NewDid` event is emitted as well.
+    pub fn _register_did(
+        sender: T::AccountId,
+        signing_items: Vec<SigningItem>,
+    ) -> StdResult<IdentityId, DispatchError> {
         // Adding extrensic count to did nonce for some unpredictability
         // NB: this does not guarantee randomness
-        let new_nonce =
-            Self::multi_purpose_nonce() + u64::from(<system::Module<T>>::extrinsic_count()) + 7u64;
+        let new_nonce = Self::multi_purpose_nonce()
+            .checked_add(u64::from(<system::Module<T>>::extrinsic_count()) + 7u64)
+            .ok_or(Error::<T>::NonceOverflow)?;
         // Even if this transaction fails, nonce should be increased for added unpredictability of dids
         <MultiPurposeNonce>::put(&new_nonce);
 
@@ -1469,10 +3206,12 @@ impl<T: Trait> Module<T> {
 
         // 1 Check constraints.
         // 1.1. Master key is not linked to any identity.
-        ensure!(
-            Self::can_key_be_linked_to_did(&master_key, SignatoryType::External),
-            "Master key already belong to one DID"
-        );
+        if !Self::can_key_be_linked_to_did(&master_key, SignatoryType::External) {
+            if let Some(existing_did) = Self::get_identity(&master_key) {
+                Self::deposit_event(RawEvent::DidRegistrationRejected(master_key, existing_did));
+            }
+            return Err(Error::<T>::MasterKeyAlreadyLinked.into());
+        }
         // 1.2. Master key is not part of signing keys.
         ensure!(
             signing_items.iter().find(|sk| **sk == master_key).is_none(),
@@ -1500,7 +3239,7 @@ impl<T: Trait> Module<T> {
         Self::link_key_to_did(&master_key, SignatoryType::External, did);
         signing_items
             .iter()
-            .for_each(|s_item| Self::add_pre_join_identity(s_item, did));
+            .for_each(|s_item| Self::add_pre_join_identity(s_item, did, None));
 
         // 2.2. Create a new identity record.
         let record = DidRecord {
@@ -1508,12 +3247,31 @@ impl<T: Trait> Module<T> {
             ..Default::default()
         };
         <DidRecords>::insert(did, record);
+        <MasterKeyToDid>::insert(master_key, did);
 
         // TODO KYC is valid by default.
         KYCValidation::insert(did, true);
 
         Self::deposit_event(RawEvent::NewDid(did, sender, signing_items));
-        Ok(())
+        Ok(did)
+    }
+
+    /// Creates the `did` identity at genesis, with the key of `account` as master key.
+    fn seed_genesis_identity(did: IdentityId, account: &T::AccountId) {
+        let master_key = AccountKey::try_from(account.encode()).expect("Invalid master key");
+        assert!(
+            !<DidRecords>::exists(did)
+                && Self::can_key_be_linked_to_did(&master_key, SignatoryType::External),
+            "Genesis identity already exists"
+        );
+        Self::link_key_to_did(&master_key, SignatoryType::External, did);
+        let record = DidRecord {
+            master_key,
+            ..Default::default()
+        };
+        <DidRecords>::insert(did, record);
+        <MasterKeyToDid>::insert(master_key, did);
+        KYCValidation::insert(did, true);
     }
 }
 
@@ -1526,6 +3284,14 @@ impl<T: Trait> IdentityTrait for Module<T> {
         Self::is_signer_authorized(did, signer)
     }
 
+    fn signer_to_did(signer: &Signatory) -> Option<IdentityId> {
+        Self::signer_to_did(signer)
+    }
+
+    fn are_signers_authorized(checks: Vec<(IdentityId, Signatory)>) -> Vec<bool> {
+        Self::are_signers_authorized(checks)
+    }
+
     fn is_master_key(did: IdentityId, key: &AccountKey) -> bool {
         Self::is_master_key(did, &key)
     }
@@ -1537,4 +3303,89 @@ impl<T: Trait> IdentityTrait for Module<T> {
     ) -> bool {
         Self::is_signer_authorized_with_permissions(did, signer, permissions)
     }
+
+    fn is_signer_authorized_for_call(did: IdentityId, signer: &Signatory, call: &[u8]) -> bool {
+        Self::is_signer_authorized_for_call(did, signer, call)
+    }
+}
+
+/// The authorization handlers built into the identity module. They take precedence over
+/// `T::AuthorizationHandlers`.
+pub type BuiltinAuthorizationHandlers<T> = (
+    AcceptTransferHandler<T>,
+    AddMultiSigSignerHandler<T>,
+    JoinIdentityHandler<T>,
+);
+
+/// All the authorization handlers available to `accept_authorization`.
+type AuthorizationHandlersOf<T> = (
+    BuiltinAuthorizationHandlers<T>,
+    <T as Trait>::AuthorizationHandlers,
+);
+
+/// Accepts `TransferTicker` and `TransferTokenOwnership` authorizations received by an identity.
+pub struct AcceptTransferHandler<T>(PhantomData<T>);
+
+impl<T: Trait> AuthorizationHandler for AcceptTransferHandler<T> {
+    fn handles(authorization_data: &AuthorizationData) -> bool {
+        match authorization_data {
+            AuthorizationData::TransferTicker(..)
+            | AuthorizationData::TransferTokenOwnership(..) => true,
+            _ => false,
+        }
+    }
+
+    fn accept(
+        signer: Signatory,
+        authorization_data: &AuthorizationData,
+        auth_id: u64,
+    ) -> DispatchResult {
+        match (signer, authorization_data) {
+            (Signatory::Identity(did), AuthorizationData::TransferTicker(..)) => {
+                T::AcceptTransferTarget::accept_ticker_transfer(did, auth_id)
+            }
+            (Signatory::Identity(did), AuthorizationData::TransferTokenOwnership(..)) => {
+                T::AcceptTransferTarget::accept_token_ownership_transfer(did, auth_id)
+            }
+            _ => Err(Error::<T>::UnknownAuthorization.into()),
+        }
+    }
+}
+
+/// Accepts `AddMultiSigSigner` authorizations.
+pub struct AddMultiSigSignerHandler<T>(PhantomData<T>);
+
+impl<T: Trait> AuthorizationHandler for AddMultiSigSignerHandler<T> {
+    fn handles(authorization_data: &AuthorizationData) -> bool {
+        *authorization_data == AuthorizationData::AddMultiSigSigner
+    }
+
+    fn accept(signer: Signatory, _: &AuthorizationData, auth_id: u64) -> DispatchResult {
+        T::AddSignerMultiSigTarget::accept_multisig_signer(signer, auth_id)
+    }
+}
+
+/// Accepts `JoinIdentity` authorizations.
+pub struct JoinIdentityHandler<T>(PhantomData<T>);
+
+impl<T: Trait> AuthorizationHandler for JoinIdentityHandler<T> {
+    fn handles(authorization_data: &AuthorizationData) -> bool {
+        match authorization_data {
+            AuthorizationData::JoinIdentity(..) => true,
+            _ => false,
+        }
+    }
+
+    fn accept(
+        signer: Signatory,
+        authorization_data: &AuthorizationData,
+        auth_id: u64,
+    ) -> DispatchResult {
+        match authorization_data {
+            AuthorizationData::JoinIdentity(target_did) => {
+                <Module<T>>::join_identity(signer, *target_did, auth_id)
+            }
+            _ => Err(Error::<T>::UnknownAuthorization.into()),
+        }
+    }
 }
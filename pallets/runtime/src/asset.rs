@@ -21,29 +21,91 @@
 //! ### Dispatchable Functions
 //!
 //! - `register_ticker` - Used to either register a new ticker or extend registration of an existing ticker
+//! - `register_ticker_for` - Used to register a ticker for a custom duration up to the maximum registration length
+//! - `set_max_ticker_registration_length` - Sets the maximum duration of `register_ticker_for`. Only called by root
+//! - `batch_update_identifiers` - Updates the asset identifiers of several tokens of the same owner at once
+//! - `set_global_kyc_required` - Sets whether receivers of any token must have a valid KYC. Only called by root
+//! - `set_auto_freeze_on_kyc_loss` - Sets whether receivers are frozen when a transfer to them is blocked for lack of KYC. Only called by root
+//! - `set_enforce_unique_token_names` - Sets whether new token names must differ from the names of existing tokens. Only called by root
+//! - `set_max_tokens_per_block` - Sets the maximum number of tokens that can be created in a block. Only called by root
+//! - `set_allowed_asset_types` - Sets the asset types that new tokens can have. Only called by root
+//! - `set_burn_did` - Sets the DID whose incoming transfers redeem the tokens instead. Only called by root
 //! - `accept_ticker_transfer` - Used to accept a ticker transfer authorization
 //! - `create_token` - Initializes a new security token
+//! - `create_token_with_hard_max_supply` - Initializes a new security token whose total supply can never exceed a given maximum
 //! - `transfer` - Transfer tokens from one DID to another DID as tokens are stored/managed on the DID level
+//! - `transfer_batch_same_sender` - Atomically transfer tokens of several tickers from one DID
 //! - `controller_transfer` - Forces a transfer between two DIDs.
 //! - `approve` - Approve token transfer from one DID to DID
 //! - `transfer_from` - If sufficient allowance provided, transfer from a DID to another DID without token owner's signature.
 //! - `create_checkpoint` - Function used to create the checkpoint
 //! - `issue` - Function is used to issue(or mint) new tokens for the given DID
 //! - `batch_issue` - Batch version of issue function
+//! - `issue_to_claim_holder` - Issues new tokens to a DID that holds a given claim
+//! - `issue_to_custody` - Issues new tokens to a holder under the custody of a given custodian
+//! - `set_funding_round_window` - Sets the time window in which tokens can be issued in a funding round. Only called by the token owner
+//! - `merge_funding_rounds` - Moves the issuance total of one funding round into another. Only called by the token owner
 //! - `redeem` - Used to redeem the security tokens
 //! - `redeem_from` - Used to redeem the security tokens by some other DID who has approval
+//! - `redeem_from_funding_round` - Redeems the security tokens and reduces the total issued in a funding round. Only called by the token owner
+//! - `redeem_all` - Redeems the whole balance of a holder that is not allowed to its custodians
 //! - `controller_redeem` - Forces a redemption of an DID's tokens. Can only be called by token owner
+//! - `request_redemption` - Proposes the redemption of a holder's tokens. Only called by the token owner
+//! - `approve_redemption` - Redeems the tokens of a pending redemption request. Only called by the token holder
+//! - `controller_freeze_holder` - Freezes the outgoing transfers of a single holder. Only called by the token owner
+//! - `controller_unfreeze_holder` - Unfreezes the outgoing transfers of a single holder. Only called by the token owner
+//! - `controller_freeze_holder_directional` - Freezes the incoming, outgoing or all transfers of a single holder. Only called by the token owner
+//! - `cancel_token_ownership_transfer` - Cancels a pending token ownership transfer. Only called by the token owner
+//! - `set_approve_allowlist_enabled` - Restricts approvals to allowlisted spenders. Only called by the token owner
+//! - `set_approved_spender` - Adds or removes a spender from the approve allowlist. Only called by the token owner
+//! - `set_max_allowance_per_spender` - Sets the maximum allowance or custody allowance of a single spender. Only called by the token owner
+//! - `check_my_transfer` - Checks whether the caller's DID can transfer an amount of tokens right now
+//! - `set_attribute` - Sets a key-value attribute of the token. Only called by the token owner
+//! - `remove_attribute` - Removes a key-value attribute of the token. Only called by the token owner
+//! - `approve_with_expiry` - Approves a token transfer by a spender until a given time. Only called by the token holder
+//! - `prune_expired_allowances` - Removes the expired allowances granted to several spenders. Only called by the token holder
+//! - `set_aggregate_transfer_events` - Replaces the transfer events of a token by a single event per block. Only called by the token owner
+//! - `set_operation_pause_flags` - Pauses or resumes specific operations of a token. Only called by the token owner
+//! - `set_require_kyc_on_issue` - Sets whether issuing the token requires a valid KYC of the recipient. Only called by the token owner
+//! - `set_require_kyc_on_transfer_from` - Sets whether `transfer_from` requires a valid KYC of the recipient. Only called by the token owner
+//! - `set_issuance_rounding` - Sets whether issued amounts of an indivisible token are rounded down to a whole unit. Only called by the token owner
+//! - `set_auto_custodian` - Sets whether receivers of the token are assigned the custodian named in their custodian claim. Only called by the token owner
+//! - `set_asset_classification` - Sets the secondary asset types of the token. Only called by the token owner
+//! - `set_supply_floor` - Raises the total supply that redemptions cannot go below. Only called by the token owner
+//! - `force_set_supply_floor` - Sets the total supply that redemptions cannot go below. Only called by root
+//! - `set_lot_size` - Sets the lot size that transferred, issued and redeemed amounts must be multiples of. Only called by the token owner
+//! - `set_block_transfers_to_frozen_dids` - Sets whether transfers of the token to frozen DIDs are rejected. Only called by the token owner
+//! - `set_required_sender_claims` - Sets the claims that senders of the token must hold. Only called by the token owner
+//! - `set_allowlist_enforced` - Sets whether only allowlisted DIDs can send and receive the token. Only called by the token owner or its compliance officer
+//! - `set_allowlisted` - Adds DIDs to or removes them from the transfer allowlist of the token. Only called by the token owner or its compliance officer
+//! - `set_compliance_officer` - Sets the DID that can manage the allowlist and holder freezes of the token. Only called by the token owner
+//! - `set_issuance_limit` - Limits the amount of the token issued per period to a percentage of its supply. Only called by the token owner
+//! - `set_trading_start_time` - Delays the time from which holders other than the owner can transfer the token. Only called by the token owner
 //! - `make_divisible` - Change the divisibility of the token to divisible. Only called by the token owner
 //! - `can_transfer` - Checks whether a transaction with given parameters can take place or not
+//! - `can_transfer_detailed` - Like `can_transfer`, and also reports the balance of the sender not allowed to its custodians
+//! - `can_redeem` - Checks whether a redemption with given parameters can take place or not
 //! - `transfer_with_data` - This function can be used by the exchanges of other third parties to dynamically validate the transaction by passing the data blob
+//! - `transfer_with_memo` - Transfers tokens and stores a memo on-chain, retrievable with `transfer_memo`
 //! - `transfer_from_with_data` - This function can be used by the exchanges of other third parties to dynamically validate the transaction by passing the data blob
 //! - `is_issuable` - Used to know whether the given token will issue new tokens or not
 //! - `get_document` - Used to get the documents details attach with the token
 //! - `set_document` - Used to set the details of the document, Only be called by the token owner
 //! - `remove_document` - Used to remove the document details for the given token, Only be called by the token owner
+//! - `update_document` - Used to update a single document of the given token in place, Only be called by the token owner
 //! - `increase_custody_allowance` - Used to increase the allowance for a given custodian
 //! - `increase_custody_allowance_of` - Used to increase the allowance for a given custodian by providing the off chain signature
 //! - `transfer_by_custodian` - Used to transfer the tokens by the approved custodian
+//! - `batch_transfer_by_custodian` - Batch version of transfer_by_custodian
+//! - `transfer_to_escrow` - Used to move tokens of a holder into the escrow of a custodian
+//! - `release_escrow` - Used by the custodian to release tokens held in escrow
+//! - `repair_total_custody_allowance` - Recomputes the total custody allowance of a holder from its custodians. Only called by root or the token owner
+//! - `backfill_custodians_of` - Adds custodians with an allowance that predates `CustodiansOf` to the index. Only called by root
+//! - `backfill_escrow_custodians_of` - Adds escrow custodians that predate `EscrowCustodiansOf` to the index. Only called by root
+//! - `backfill_extension_tickers` - Adds smart extensions attached before `ExtensionTickers` existed to the index. Only called by root
+//! - `backfill_identifier_types` - Adds identifiers set before `IdentifierTypesOf` existed to the index. Only called by root
+//! - `backfill_spenders_of` - Adds spenders with an allowance that predates `SpendersOf` to the index. Only called by root
+//! - `backfill_token_names` - Adds tokens created before `TokenNames` existed to the index. Only called by root
 //!
 //! ### Public Functions
 //!
@@ -53,10 +115,38 @@
 //! - `balance_of` - Returns the balance of the DID corresponds to the ticker
 //! - `total_checkpoints_of` - Returns the checkpoint Id
 //! - `total_supply_at` - Returns the total supply at a given checkpoint
+//! - `total_supply_at_safe` - Returns the total supply at a given checkpoint, falling back to the nearest prior one
 //! - `custodian_allowance`- Returns the allowance provided to a custodian for a given ticker and token holder
 //! - `total_custody_allowance` - Returns the total allowance approved by the token holder.
-
-use crate::{general_tm, percentage_tm, statistics, utils};
+//! - `available_balance` - Returns the balance of a token holder that is not allowed to its custodians.
+//! - `spendable_balance` - Returns the balance that a token holder is able to transfer right now.
+//! - `total_locked` - Returns the tokens of a holder reserved for custodians or held in escrow.
+//! - `total_allowance_granted` - Returns the allowances granted by a token holder to all its spenders and their sum.
+//! - `balances_of` - Returns the balances of several (ticker, DID) pairs at once.
+//! - `controller_actions_paged` - Returns a page of the logged controller transfers and redemptions of a token.
+//! - `holders_paged` - Returns a page of the holders of a ticker with their balances.
+//! - `balances_at_checkpoint` - Returns the non-zero balances of all holders of a ticker at a checkpoint.
+//! - `balances_at_checkpoint_paged` - Returns the non-zero balances at a checkpoint of a page of the holders of a ticker.
+//! - `balance_delta` - Returns how much the balance of a DID changed between two checkpoints.
+//! - `transfer_touches_checkpoint` - Returns whether a balance change of a DID would record its balance at the latest checkpoint.
+//! - `transfer_rules_summary` - Returns the transfer restrictions of both transfer managers for a ticker.
+//! - `tickers_owned_by` - Returns the tickers of the tokens owned by a DID.
+//! - `owned_tickers_state` - Returns the frozen flag and operation pause flags of each token owned by a DID.
+//! - `all_extensions` - Returns the smart extensions of a ticker with their type and archive status.
+//! - `extension_tickers` - Returns the tickers a smart extension is attached to.
+//! - `lot_size` - Returns the lot size of a ticker, in units for indivisible tokens.
+//! - `is_allowlisted` - Returns whether a DID is allowlisted for transfers of a ticker.
+//! - `is_owner_or_compliance_officer` - Returns whether a DID can act as compliance officer of a ticker.
+//! - `token_creation_time` - Returns the time at which a token was created.
+//! - `caller_did` - Returns the DID a key acts for, i.e. the current identity or the identity linked to the key.
+//! - `transfer_preflight_status` - Returns the status code of a transfer made now, including the spendable balance of the sender.
+//! - `get_attribute` - Returns the value of a key-value attribute of a token.
+//! - `identifier_types_of` - Returns the identifier types set for a ticker.
+//! - `token_config` - Returns the owner-configurable settings of a token in a single read.
+//! - `ownership_info` - Returns the ticker owner, token owner and asset DID of a ticker at once.
+//! - `transfer_aggregates` - Returns the number and volume of the aggregated transfers of a ticker in the current block.
+
+use crate::{exemption, general_tm, percentage_tm, statistics, utils};
 
 use polymesh_primitives::{
     AccountKey, AuthorizationData, AuthorizationError, Document, IdentityId, LinkData, Signatory,
@@ -64,8 +154,12 @@ use polymesh_primitives::{
 };
 use polymesh_runtime_balances as balances;
 use polymesh_runtime_common::{
-    asset::AcceptTransfer, balances::Trait as BalancesTrait, constants::*,
-    identity::Trait as IdentityTrait, CommonTrait,
+    asset::AcceptTransfer,
+    balances::Trait as BalancesTrait,
+    constants::did::ESCROW,
+    constants::*,
+    identity::{ClaimValue, Trait as IdentityTrait},
+    CommonTrait,
 };
 use polymesh_runtime_identity as identity;
 
@@ -74,13 +168,15 @@ use core::result::Result as StdResult;
 use currency::*;
 use frame_support::{
     decl_error, decl_event, decl_module, decl_storage,
-    dispatch::DispatchResult,
+    dispatch::{DispatchError, DispatchResult},
     ensure,
-    traits::{Currency, ExistenceRequirement, WithdrawReason},
+    traits::{Currency, ExistenceRequirement, Get, WithdrawReason},
 };
-use frame_system::{self as system, ensure_signed};
+use frame_system::{self as system, ensure_root, ensure_signed};
 use pallet_session;
-use sp_runtime::traits::{CheckedAdd, CheckedSub, Verify};
+use sp_io::hashing::blake2_256;
+use sp_runtime::traits::{CheckedAdd, CheckedSub, Saturating, Verify, Zero};
+use sp_runtime::Perbill;
 #[cfg(feature = "std")]
 use sp_runtime::{Deserialize, Serialize};
 use sp_std::{convert::TryFrom, prelude::*};
@@ -99,6 +195,10 @@ pub trait Trait:
     /// The overarching event type.
     type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
     type Currency: Currency<Self::AccountId>;
+    /// The maximum number of custodians of a holder for a single ticker.
+    type MaxCustodiansPerHolder: Get<u32>;
+    /// The maximum number of identifiers of a single token.
+    type MaxIdentifiersPerToken: Get<u32>;
 }
 
 /// The type of an asset represented by a token.
@@ -117,6 +217,17 @@ impl Default for AssetType {
     }
 }
 
+/// The transfers of a holder blocked by a directional freeze.
+#[derive(codec::Encode, codec::Decode, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FreezeDirection {
+    /// Both incoming and outgoing transfers.
+    Both,
+    /// Transfers to the holder.
+    Incoming,
+    /// Transfers from the holder.
+    Outgoing,
+}
+
 /// The type of an identifier associated with a token.
 #[derive(codec::Encode, codec::Decode, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum IdentifierType {
@@ -131,6 +242,62 @@ impl Default for IdentifierType {
     }
 }
 
+/// Bit of `OperationPauseFlags` that pauses transfers.
+pub const PAUSE_TRANSFER: u8 = 1;
+/// Bit of `OperationPauseFlags` that pauses issuance.
+pub const PAUSE_ISSUE: u8 = 1 << 1;
+/// Bit of `OperationPauseFlags` that pauses redemptions.
+pub const PAUSE_REDEEM: u8 = 1 << 2;
+/// Bit of `OperationPauseFlags` that pauses approvals.
+pub const PAUSE_APPROVE: u8 = 1 << 3;
+
+/// Transfer manager id of the global KYC requirement in the exemption list.
+pub const GLOBAL_KYC_TM: u16 = 3;
+
+/// Time in milliseconds that a redemption request can be approved by the holder.
+pub const REDEMPTION_REQUEST_LENGTH: u32 = 7 * 24 * 60 * 60 * 1000;
+
+/// Maximum length in bytes of the memo of `transfer_with_memo`.
+pub const MAX_TRANSFER_MEMO_LENGTH: usize = 256;
+
+/// Maximum length in bytes of the key of a token attribute.
+pub const MAX_ATTRIBUTE_KEY_LENGTH: usize = 64;
+
+/// Maximum length in bytes of the value of a token attribute.
+pub const MAX_ATTRIBUTE_VALUE_LENGTH: usize = 1024;
+
+/// Number of latest custody allowance changes kept in `CustodyAllowanceHistory`.
+pub const CUSTODY_ALLOWANCE_HISTORY_LEN: usize = 10;
+
+/// Number of latest controller actions of a token kept in `ControllerActionLog`.
+pub const CONTROLLER_ACTION_LOG_LEN: u64 = 1_000;
+
+/// The kind of a forced action of the token owner.
+#[derive(codec::Encode, codec::Decode, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ControllerActionType {
+    Transfer,
+    Redeem,
+}
+
+impl Default for ControllerActionType {
+    fn default() -> Self {
+        ControllerActionType::Transfer
+    }
+}
+
+/// A forced transfer or redemption of the token owner.
+#[derive(codec::Encode, codec::Decode, Clone, Default, PartialEq, Debug)]
+pub struct ControllerAction<U, V> {
+    pub action_type: ControllerActionType,
+    pub from_did: IdentityId,
+    /// Receiver of a transfer. `None` for redemptions.
+    pub to_did: Option<IdentityId>,
+    pub value: U,
+    /// The operator data describing the reason of the action.
+    pub reason: Vec<u8>,
+    pub timestamp: V,
+}
+
 /// struct to store the token details
 #[derive(codec::Encode, codec::Decode, Default, Clone, PartialEq, Debug)]
 pub struct SecurityToken<U> {
@@ -168,6 +335,87 @@ pub struct TickerRegistrationConfig<U> {
     pub registration_length: Option<U>,
 }
 
+/// A change of the custody allowance of a token holder.
+#[derive(codec::Encode, codec::Decode, Clone, Default, PartialEq, Debug)]
+pub struct CustodyAllowanceChange<U> {
+    /// Value of `CustodyAllowanceNonce` when the change was made.
+    pub nonce: u32,
+    pub custodian_did: IdentityId,
+    pub old_allowance: U,
+    pub new_allowance: U,
+}
+
+/// A redemption of a holder's tokens proposed by the token owner.
+#[derive(codec::Encode, codec::Decode, Clone, Default, PartialEq, Debug)]
+pub struct PendingRedemption<U, V> {
+    pub value: U,
+    /// Time after which the holder can no longer approve the redemption.
+    pub expiry: V,
+}
+
+/// Issuance of a token within its current issuance limit period.
+#[derive(codec::Encode, codec::Decode, Clone, Default, PartialEq, Debug)]
+pub struct IssuanceWindow<U, V> {
+    /// Time at which the period started.
+    pub start: V,
+    /// Total supply of the token at the start of the period.
+    pub supply_at_start: U,
+    /// Amount issued since the start of the period.
+    pub issued: U,
+}
+
+/// Aggregated view of the transfer restrictions that apply to a token.
+#[derive(codec::Encode, codec::Decode, Clone, Default, PartialEq, Debug)]
+pub struct RulesSummary {
+    /// Active asset rules of the general transfer manager.
+    pub active_rules: Vec<general_tm::AssetRule>,
+    /// Maximum percentage of the token that a single investor can hold. Zero if unrestricted.
+    pub max_percentage: u16,
+    /// Claim keys that receivers are checked against by any active asset rule.
+    pub required_receiver_claims: Vec<Vec<u8>>,
+}
+
+/// Owners of a ticker and of its token, as returned by `ownership_info`.
+#[derive(codec::Encode, codec::Decode, Clone, Default, PartialEq, Debug)]
+pub struct OwnershipInfo<U> {
+    /// DID that registered the ticker.
+    pub ticker_owner: IdentityId,
+    /// DID that owns the token. `None` if no token was created for the ticker.
+    pub token_owner: Option<IdentityId>,
+    /// DID of the asset. `None` if no token was created for the ticker.
+    pub asset_did: Option<IdentityId>,
+    /// Expiry of the ticker registration.
+    pub expiry: Option<U>,
+}
+
+/// Owner-configurable settings of a token, as returned by `token_config`.
+#[derive(codec::Encode, codec::Decode, Clone, Default, PartialEq, Debug)]
+pub struct TokenConfig<U, V> {
+    pub frozen: bool,
+    /// Bitmask of the paused operations.
+    pub operation_pause_flags: u8,
+    /// Maximum total supply set at creation.
+    pub hard_max_supply: Option<U>,
+    pub supply_floor: U,
+    pub lot_size: U,
+    pub funding_round: Vec<u8>,
+    pub trading_start_time: V,
+    pub require_kyc_on_issue: bool,
+    pub require_kyc_on_transfer_from: bool,
+    pub issuance_rounding_enabled: bool,
+    pub auto_custodian: bool,
+    pub block_transfers_to_frozen_dids: bool,
+    pub approve_allowlist_enabled: bool,
+    pub max_allowance_per_spender: Option<U>,
+    pub allowlist_enforced: bool,
+    pub compliance_officer: Option<IdentityId>,
+    pub aggregate_transfer_events: bool,
+    /// Number of claims that senders are required to hold.
+    pub required_sender_claims: u32,
+    /// Number of claim keys that receivers are checked against by the active asset rules.
+    pub required_receiver_claims: u32,
+}
+
 /// Enum that represents the current status of a ticker
 #[derive(codec::Encode, codec::Decode, Clone, Eq, PartialEq, Debug)]
 pub enum TickerRegistrationStatus {
@@ -186,17 +434,73 @@ decl_storage! {
         /// Ticker registration config
         /// (ticker) -> TickerRegistrationConfig
         pub TickerConfig get(fn ticker_registration_config) config(): TickerRegistrationConfig<T::Moment>;
+        /// Shortest ticker that can be registered. Zero means no minimum.
+        pub MinTickerLength get(fn min_ticker_length) config(): u8;
+        /// Longest registration that can be requested with `register_ticker_for`. `None` means
+        /// no limit.
+        pub MaxTickerRegistrationLength get(fn max_ticker_registration_length): Option<T::Moment>;
+        /// Whether receivers of any token must have a valid KYC, unless exempted under
+        /// `GLOBAL_KYC_TM` for that token.
+        pub GlobalKycRequired get(fn global_kyc_required): bool;
+        /// Whether the DID of a receiver is frozen when a transfer to it is blocked because it
+        /// has no valid KYC.
+        pub AutoFreezeOnKycLoss get(fn auto_freeze_on_kyc_loss): bool;
+        /// Whether tokens cannot be created or renamed with the name of an existing token.
+        pub EnforceUniqueTokenNames get(fn enforce_unique_token_names): bool;
+        /// Tokens with a given name.
+        /// name -> tickers of the tokens
+        pub TokenNames get(fn token_names): map Vec<u8> => Vec<Ticker>;
+        /// Maximum number of tokens that can be created in a single block. `None` means no limit.
+        pub MaxTokensPerBlock get(fn max_tokens_per_block): Option<u32>;
+        /// Asset types that new tokens can have. An empty list allows any asset type.
+        pub AllowedAssetTypes get(fn allowed_asset_types): Vec<AssetType>;
+        /// DID to which transfers are treated as redemptions. `None` disables the treatment.
+        pub BurnDid get(fn burn_did): Option<IdentityId>;
+        /// Number of tokens created in the current block. Reset at the start of every block.
+        pub TokensCreatedThisBlock get(fn tokens_created_this_block): u32;
+        /// Tokens whose transfers emit a single `AggregatedTransfers` event per block instead of
+        /// a `Transfer` event each.
+        /// ticker -> bool
+        pub AggregateTransferEvents get(fn aggregate_transfer_events): map Ticker => bool;
+        /// Number and total volume of the aggregated transfers of a ticker in the current block.
+        /// Emitted and cleared at the end of every block.
+        /// ticker -> (number of transfers, total volume)
+        pub TransferAggregates get(fn transfer_aggregates): map Ticker => (u64, T::Balance);
+        /// Tickers with aggregated transfers in the current block.
+        pub TransferAggregateTickers get(fn transfer_aggregate_tickers): Vec<Ticker>;
+        /// Number of transfers with a memo of a ticker. It is the sequence number of the latest memo.
+        /// (ticker) -> number of memos
+        pub TransferMemoCount get(fn transfer_memo_count): map Ticker => u64;
+        /// Memos of transfers with a memo, indexed by their per-ticker sequence number starting at 1.
+        /// (ticker, sequence number) -> memo
+        pub TransferMemos get(fn transfer_memo): map (Ticker, u64) => Vec<u8>;
         /// details of the token corresponding to the token ticker
         /// (ticker) -> SecurityToken details [returns SecurityToken struct]
         pub Tokens get(fn token_details): map Ticker => SecurityToken<T::Balance>;
+        /// Maximum total supply set at creation. It can never be raised.
+        /// ticker -> maximum total supply, if any
+        pub HardMaxSupply get(fn hard_max_supply): map Ticker => Option<T::Balance>;
         /// Used to store the securityToken balance corresponds to ticker and Identity
         /// (ticker, DID) -> balance
         pub BalanceOf get(fn balance_of): map (Ticker, IdentityId) => T::Balance;
         /// A map of asset identifiers whose keys are pairs of a ticker name and an `IdentifierType`
         /// and whose values are byte vectors.
         pub Identifiers get(fn identifiers): map (Ticker, IdentifierType) => Vec<u8>;
+        /// The identifier types set for a ticker, used to bound the number of identifiers.
+        /// ticker -> identifier types
+        pub IdentifierTypesOf get(fn identifier_types_of): map Ticker => Vec<IdentifierType>;
+        /// Arbitrary attributes of a token set by its owner, e.g. "sector" => "tech".
+        /// (ticker, key) -> value
+        pub TokenAttributes get(fn get_attribute): map (Ticker, Vec<u8>) => Vec<u8>;
         /// (ticker, sender (DID), spender(DID)) -> allowance amount
         Allowance get(fn allowance): map (Ticker, IdentityId, IdentityId) => T::Balance;
+        /// Spenders with a non-zero allowance granted by a token holder.
+        /// (ticker, sender (DID)) -> spenders (DID)
+        pub SpendersOf get(fn spenders_of): map (Ticker, IdentityId) => Vec<IdentityId>;
+        /// Time after which an allowance can no longer be spent. Allowances without an entry
+        /// never expire.
+        /// (ticker, sender (DID), spender(DID)) -> expiry
+        pub AllowanceExpiry get(fn allowance_expiry): map (Ticker, IdentityId, IdentityId) => Option<T::Moment>;
         /// cost in base currency to create a token
         AssetCreationFee get(fn asset_creation_fee) config(): T::Balance;
         /// cost in base currency to register a ticker
@@ -207,18 +511,36 @@ decl_storage! {
         /// Total supply of the token at the checkpoint
         /// (ticker, checkpointId) -> total supply at given checkpoint
         pub CheckpointTotalSupply get(fn total_supply_at): map (Ticker, u64) => T::Balance;
+        /// Holders of the token when the checkpoint was created
+        /// (ticker, checkpointId) -> holder DIDs
+        pub CheckpointHolders get(fn checkpoint_holders): map (Ticker, u64) => Option<Vec<IdentityId>>;
         /// Balance of a DID at a checkpoint
         /// (ticker, DID, checkpoint ID) -> Balance of a DID at a checkpoint
         CheckpointBalance get(fn balance_at_checkpoint): map (Ticker, IdentityId, u64) => T::Balance;
         /// Last checkpoint updated for a DID's balance
         /// (ticker, DID) -> List of checkpoints where user balance changed
-        UserCheckpoints get(fn user_checkpoints): map (Ticker, IdentityId) => Vec<u64>;
+        pub UserCheckpoints get(fn user_checkpoints): map (Ticker, IdentityId) => Vec<u64>;
         /// Allowance provided to the custodian
         /// (ticker, token holder, custodian) -> balance
         pub CustodianAllowance get(fn custodian_allowance): map(Ticker, IdentityId, IdentityId) => T::Balance;
         /// Total custodian allowance for a given token holder
         /// (ticker, token holder) -> balance
         pub TotalCustodyAllowance get(fn total_custody_allowance): map(Ticker, IdentityId) => T::Balance;
+        /// Custodians with a non-zero allowance of a given token holder
+        /// (ticker, token holder) -> custodian DIDs
+        pub CustodiansOf get(fn custodians_of): map(Ticker, IdentityId) => Vec<IdentityId>;
+        /// Tokens held in escrow by a custodian on behalf of a token holder
+        /// (ticker, custodian, token holder) -> balance
+        pub EscrowBalance get(fn escrow_balance): map(Ticker, IdentityId, IdentityId) => T::Balance;
+        /// Custodians holding tokens of a given token holder in escrow
+        /// (ticker, token holder) -> custodian DIDs
+        pub EscrowCustodiansOf get(fn escrow_custodians_of): map(Ticker, IdentityId) => Vec<IdentityId>;
+        /// Number of custody allowance changes of a token holder
+        /// (ticker, token holder) -> nonce
+        pub CustodyAllowanceNonce get(fn custody_allowance_nonce): map(Ticker, IdentityId) => u32;
+        /// Latest `CUSTODY_ALLOWANCE_HISTORY_LEN` custody allowance changes of a token holder, oldest first
+        /// (ticker, token holder) -> changes
+        pub CustodyAllowanceHistory get(fn custody_allowance_history): map(Ticker, IdentityId) => Vec<CustodyAllowanceChange<T::Balance>>;
         /// Store the nonce for off chain signature to increase the custody allowance
         /// (ticker, token holder, nonce) -> bool
         AuthenticationNonce get(fn authentication_nonce): map(Ticker, IdentityId, u16) => bool;
@@ -228,15 +550,119 @@ decl_storage! {
         /// The total balances of tokens issued in all recorded funding rounds.
         /// (ticker, funding round) -> balance
         IssuedInFundingRound get(fn issued_in_funding_round): map (Ticker, Vec<u8>) => T::Balance;
+        /// The time window in which tokens can be issued in a funding round, if any.
+        /// (ticker, funding round) -> (start, end)
+        FundingRoundWindow get(fn funding_round_window): map (Ticker, Vec<u8>) => (T::Moment, T::Moment);
         /// List of Smart extension added for the given tokens
         /// ticker, AccountId (SE address) -> SmartExtension detail
         pub ExtensionDetails get(fn extension_details): map (Ticker, T::AccountId) => SmartExtension<T::AccountId>;
         /// List of Smart extension added for the given tokens and for the given type
         /// ticker, type of SE -> address/AccountId of SE
         pub Extensions get(fn extensions): map (Ticker, SmartExtensionType) => Vec<T::AccountId>;
+        /// Tickers a smart extension is attached to. Reverse index of `Extensions`.
+        /// address/AccountId of SE -> tickers
+        pub ExtensionTickers get(fn extension_tickers): map T::AccountId => Vec<Ticker>;
         /// The set of frozen assets implemented as a membership map.
         /// ticker -> bool
         pub Frozen get(fn frozen): map Ticker => bool;
+        /// Bitmask of paused operations of a token. See `PAUSE_*` constants.
+        /// ticker -> flags
+        pub OperationPauseFlags get(fn operation_pause_flags): map Ticker => u8;
+        /// Tokens whose issuance requires a valid KYC of the recipient.
+        /// ticker -> bool
+        pub RequireKycOnIssue get(fn require_kyc_on_issue): map Ticker => bool;
+        /// Tokens whose `transfer_from` requires a valid KYC of the recipient.
+        /// ticker -> bool
+        pub RequireKycOnTransferFrom get(fn require_kyc_on_transfer_from): map Ticker => bool;
+        /// Identities holding a non-zero balance of a token.
+        /// ticker -> holder DIDs
+        pub TickerHolders get(fn ticker_holders): map Ticker => Vec<IdentityId>;
+        /// Holders whose outgoing transfers were frozen by the token owner.
+        /// (ticker, holder DID) -> bool
+        pub ControllerFrozenHolder get(fn controller_frozen_holder): map (Ticker, IdentityId) => bool;
+        /// Holders whose transfers in one or both directions were frozen by the token owner.
+        /// (ticker, holder DID) -> frozen direction
+        pub HolderFreezeDirection get(fn holder_freeze_direction): map (Ticker, IdentityId) => Option<FreezeDirection>;
+        /// Tokens whose spenders must be allowlisted in `ApprovedSpenders` to be approved.
+        /// ticker -> bool
+        pub ApproveAllowlistEnabled get(fn approve_allowlist_enabled): map Ticker => bool;
+        /// Spenders that can be approved when the approve allowlist of the token is enabled.
+        /// (ticker, spender DID) -> bool
+        pub ApprovedSpenders get(fn approved_spenders): map (Ticker, IdentityId) => bool;
+        /// Maximum allowance and custody allowance that a single spender can be given by a holder.
+        /// ticker -> maximum allowance
+        pub MaxAllowancePerSpender get(fn max_allowance_per_spender): map Ticker => Option<T::Balance>;
+        /// Indivisible tokens whose issued amounts are rounded down to a whole unit.
+        /// ticker -> bool
+        pub IssuanceRoundingEnabled get(fn issuance_rounding_enabled): map Ticker => bool;
+        /// Secondary classifications of a token in addition to its primary `asset_type`.
+        /// ticker -> asset types
+        pub AssetClassification get(fn asset_classification): map Ticker => Vec<AssetType>;
+        /// Time from which holders other than the owner can transfer the token.
+        /// ticker -> moment
+        pub TradingStartTime get(fn trading_start_time): map Ticker => T::Moment;
+        /// Time at which the token was created.
+        /// ticker -> moment
+        pub TokenCreationTime get(fn token_creation_time): map Ticker => T::Moment;
+        /// Total supply that redemptions cannot reduce the token below.
+        /// ticker -> balance
+        pub SupplyFloor get(fn supply_floor): map Ticker => T::Balance;
+        /// Amounts of the token must be multiples of its lot size, counted in whole units for
+        /// indivisible tokens. Zero means the default lot size of 1.
+        /// ticker -> balance
+        pub LotSize: map Ticker => T::Balance;
+        /// Tokens that cannot be transferred to frozen DIDs.
+        /// ticker -> bool
+        pub BlockTransfersToFrozenDids get(fn block_transfers_to_frozen_dids): map Ticker => bool;
+        /// Claims that senders of a token must hold, as (claim key, acceptable issuers) pairs.
+        /// ticker -> claim requirements
+        pub RequiredSenderClaims get(fn required_sender_claims): map Ticker => Vec<(Vec<u8>, Vec<IdentityId>)>;
+        /// Whether both parties of a transfer of a token must be in its transfer allowlist.
+        /// ticker -> enforced
+        pub AllowlistEnforced get(fn allowlist_enforced): map Ticker => bool;
+        /// DIDs that can send and receive a token while its allowlist is enforced.
+        /// (ticker, DID) -> allowlisted
+        pub TransferAllowlist get(fn transfer_allowlist): map (Ticker, IdentityId) => bool;
+        /// DID that can manage the transfer allowlist and holder freezes of a token, besides its
+        /// owner.
+        /// ticker -> compliance officer DID
+        pub ComplianceOfficer get(fn compliance_officer): map Ticker => Option<IdentityId>;
+        /// Maximum amount of a token that can be issued per issuance limit period, relative to
+        /// the total supply at the start of the period.
+        /// ticker -> percentage
+        pub MaxIssuancePercentPerPeriod get(fn max_issuance_percent_per_period): map Ticker => Perbill;
+        /// Length of the issuance limit period of a token. Zero means issuance is not limited.
+        /// ticker -> moment
+        pub IssuanceLimitPeriod get(fn issuance_limit_period): map Ticker => T::Moment;
+        /// Issuance of a token within its current issuance limit period.
+        /// ticker -> issuance window
+        pub CurrentIssuanceWindow get(fn issuance_window): map Ticker => IssuanceWindow<T::Balance, T::Moment>;
+        /// Tokens whose receivers get a custody allowance to the custodian named in their
+        /// `CUSTODIAN_CLAIM_KEY` claim issued by the token owner.
+        /// ticker -> bool
+        pub AutoCustodian get(fn auto_custodian): map Ticker => bool;
+        /// Redemptions proposed by the token owner and awaiting the approval of the holder.
+        /// (ticker, holder DID) -> pending redemption
+        pub PendingRedemptions get(fn pending_redemption): map (Ticker, IdentityId) => PendingRedemption<T::Balance, T::Moment>;
+        /// Number of controller actions performed on a token. It is the sequence number of the
+        /// next action.
+        /// ticker -> count
+        pub ControllerActionCount get(fn controller_action_count): map Ticker => u64;
+        /// Latest `CONTROLLER_ACTION_LOG_LEN` controller actions of a token.
+        /// (ticker, sequence number) -> controller action
+        pub ControllerActionLog get(fn controller_action): map (Ticker, u64) => ControllerAction<T::Balance, T::Moment>;
+    }
+    add_extra_genesis {
+        // Balances of tokens as (ticker, holder DID, balance). A token that does not exist yet
+        // is created with the DID of its first balance as owner. Holders have to be identities
+        // of the identity genesis.
+        config(initial_balances): Vec<(Ticker, IdentityId, T::Balance)>;
+        build(|config: &GenesisConfig<T>| {
+            for &(ticker, did, balance) in &config.initial_balances {
+                <Module<T>>::seed_genesis_balance(ticker, did, balance)
+                    .expect("Invalid initial balance");
+            }
+        })
     }
 }
 
@@ -249,6 +675,19 @@ decl_module! {
         /// initialize the default event for this module
         fn deposit_event() = default;
 
+        /// Resets the number of tokens created in the block.
+        fn on_initialize(_n: T::BlockNumber) {
+            <TokensCreatedThisBlock>::kill();
+        }
+
+        /// Emits the aggregated transfers of the block.
+        fn on_finalize(_n: T::BlockNumber) {
+            for ticker in <TransferAggregateTickers>::take() {
+                let (count, total_volume) = <TransferAggregates<T>>::take(&ticker);
+                Self::deposit_event(RawEvent::AggregatedTransfers(ticker, count, total_volume));
+            }
+        }
+
         /// This function is used to either register a new ticker or extend validity of an exisitng ticker
         /// NB Ticker validity does not get carryforward when renewing ticker
         ///
@@ -257,39 +696,112 @@ decl_module! {
         /// * `ticker` ticker to register
         pub fn register_ticker(origin, ticker: Ticker) -> DispatchResult {
             let sender = ensure_signed(origin)?;
-            let sender_key = AccountKey::try_from(sender.encode())?;
-            let signer = Signatory::AccountKey(sender_key.clone());
-            let to_did =  match <identity::Module<T>>::current_did() {
-                Some(x) => x,
-                None => {
-                    if let Some(did) = <identity::Module<T>>::get_identity(&sender_key) {
-                        did
-                    } else {
-                        return Err(Error::<T>::DIDNotFound.into());
-                    }
-                }
-            };
+            let registration_length = Self::ticker_registration_config().registration_length;
+            Self::register_ticker_with_length(sender, ticker, registration_length)
+        }
 
-            ticker.canonize();
-            ensure!(<identity::Module<T>>::is_signer_authorized(to_did, &signer), "sender must be a signing key for DID");
+        /// Registers a new ticker or extends validity of an existing ticker for a custom duration
+        /// instead of the default registration length.
+        ///
+        /// # Arguments
+        /// * `origin` It contains the signing key of the caller (i.e who signed the transaction to execute this function)
+        /// * `ticker` ticker to register
+        /// * `duration` how long the registration is valid for. Fails with
+        /// `TickerRegistrationTooLong` if it exceeds `MaxTickerRegistrationLength`
+        pub fn register_ticker_for(origin, ticker: Ticker, duration: T::Moment) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            if let Some(max_length) = Self::max_ticker_registration_length() {
+                ensure!(duration <= max_length, Error::<T>::TickerRegistrationTooLong);
+            }
+            Self::register_ticker_with_length(sender, ticker, Some(duration))
+        }
 
-            ensure!(!<Tokens<T>>::exists(&ticker), "token already created");
+        /// Sets the longest duration that can be requested with `register_ticker_for`. Only
+        /// called by root.
+        ///
+        /// # Arguments
+        /// * `origin` Root
+        /// * `max_length` the longest duration, or `None` to remove the limit
+        pub fn set_max_ticker_registration_length(origin, max_length: Option<T::Moment>) -> DispatchResult {
+            ensure_root(origin)?;
+            <MaxTickerRegistrationLength<T>>::put(max_length);
+            Self::deposit_event(RawEvent::MaxTickerRegistrationLengthChanged(max_length));
+            Ok(())
+        }
 
-            let ticker_config = Self::ticker_registration_config();
+        /// Sets whether receivers of any token must have a valid KYC. Only called by root.
+        ///
+        /// # Arguments
+        /// * `origin` Root
+        /// * `required` whether a valid KYC of the receiver is required
+        pub fn set_global_kyc_required(origin, required: bool) -> DispatchResult {
+            ensure_root(origin)?;
+            <GlobalKycRequired>::put(required);
+            Self::deposit_event(RawEvent::GlobalKycRequiredChanged(required));
+            Ok(())
+        }
 
-            ensure!(ticker.len() <= usize::try_from(ticker_config.max_ticker_length).unwrap_or_default(), "ticker length over the limit");
+        /// Sets whether the DID of a receiver is frozen when a transfer to it is blocked because
+        /// it has no valid KYC. Only called by root.
+        ///
+        /// # Arguments
+        /// * `origin` Root
+        /// * `enabled` whether receivers without a valid KYC are frozen
+        pub fn set_auto_freeze_on_kyc_loss(origin, enabled: bool) -> DispatchResult {
+            ensure_root(origin)?;
+            <AutoFreezeOnKycLoss>::put(enabled);
+            Self::deposit_event(RawEvent::AutoFreezeOnKycLossChanged(enabled));
+            Ok(())
+        }
 
-            // Ensure that the ticker is not registered by someone else
-            ensure!(
-                Self::is_ticker_available_or_registered_to(&ticker, to_did) != TickerRegistrationStatus::RegisteredByOther,
-                "ticker registered to someone else"
-            );
+        /// Sets whether tokens can only be created or renamed with a name that no other token
+        /// has. Only called by root.
+        ///
+        /// # Arguments
+        /// * `origin` Root
+        /// * `enabled` whether token names must be unique
+        pub fn set_enforce_unique_token_names(origin, enabled: bool) -> DispatchResult {
+            ensure_root(origin)?;
+            <EnforceUniqueTokenNames>::put(enabled);
+            Self::deposit_event(RawEvent::EnforceUniqueTokenNamesChanged(enabled));
+            Ok(())
+        }
 
-            let now = <pallet_timestamp::Module<T>>::get();
-            let expiry = if let Some(exp) = ticker_config.registration_length { Some(now + exp) } else { None };
+        /// Sets the maximum number of tokens that can be created in a single block. Only called
+        /// by root.
+        ///
+        /// # Arguments
+        /// * `origin` Root origin
+        /// * `max_tokens` Maximum number of token creations per block, or `None` for no limit
+        pub fn set_max_tokens_per_block(origin, max_tokens: Option<u32>) -> DispatchResult {
+            ensure_root(origin)?;
+            <MaxTokensPerBlock>::put(max_tokens);
+            Self::deposit_event(RawEvent::MaxTokensPerBlockChanged(max_tokens));
+            Ok(())
+        }
 
-            Self::_register_ticker(&ticker, sender, to_did, expiry);
+        /// Sets the asset types that new tokens can have. Only called by root.
+        ///
+        /// # Arguments
+        /// * `origin` Root origin
+        /// * `asset_types` Allowed asset types, or an empty list to allow any asset type
+        pub fn set_allowed_asset_types(origin, asset_types: Vec<AssetType>) -> DispatchResult {
+            ensure_root(origin)?;
+            <AllowedAssetTypes>::put(asset_types.clone());
+            Self::deposit_event(RawEvent::AllowedAssetTypesChanged(asset_types));
+            Ok(())
+        }
 
+        /// Sets the DID to which transfers redeem the transferred tokens, reducing the total
+        /// supply, instead of moving them. Only called by root.
+        ///
+        /// # Arguments
+        /// * `origin` Root origin
+        /// * `burn_did` The burn DID, or `None` to treat transfers to any DID as plain transfers
+        pub fn set_burn_did(origin, burn_did: Option<IdentityId>) -> DispatchResult {
+            ensure_root(origin)?;
+            <BurnDid>::put(burn_did);
+            Self::deposit_event(RawEvent::BurnDidChanged(burn_did));
             Ok(())
         }
 
@@ -302,16 +814,7 @@ decl_module! {
         pub fn accept_ticker_transfer(origin, auth_id: u64) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             let sender_key = AccountKey::try_from(sender.encode())?;
-            let to_did =  match <identity::Module<T>>::current_did() {
-                Some(x) => x,
-                None => {
-                    if let Some(did) = <identity::Module<T>>::get_identity(&sender_key) {
-                        did
-                    } else {
-                        return Err(Error::<T>::DIDNotFound.into());
-                    }
-                }
-            };
+            let to_did = Self::caller_did(&sender_key)?;
             Self::_accept_ticker_transfer(to_did, auth_id)
         }
 
@@ -324,16 +827,7 @@ decl_module! {
         pub fn accept_token_ownership_transfer(origin, auth_id: u64) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             let sender_key = AccountKey::try_from(sender.encode())?;
-            let to_did =  match <identity::Module<T>>::current_did() {
-                Some(x) => x,
-                None => {
-                    if let Some(did) = <identity::Module<T>>::get_identity(&sender_key) {
-                        did
-                    } else {
-                        return Err(Error::<T>::DIDNotFound.into());
-                    }
-                }
-            };
+            let to_did = Self::caller_did(&sender_key)?;
             Self::_accept_token_ownership_transfer(to_did, auth_id)
         }
 
@@ -361,6 +855,47 @@ decl_module! {
             asset_type: AssetType,
             identifiers: Vec<(IdentifierType, Vec<u8>)>,
             funding_round: Option<Vec<u8>>
+        ) -> DispatchResult {
+            Self::create_token_with_hard_max_supply(
+                origin,
+                did,
+                name,
+                ticker,
+                total_supply,
+                divisible,
+                asset_type,
+                identifiers,
+                funding_round,
+                None
+            )
+        }
+
+        /// Initializes a new security token like `create_token`, whose total supply can never
+        /// exceed `hard_max_supply`. Unlike the global `MAX_SUPPLY`, the maximum is specific to
+        /// the token, and there is no way to raise it after creation.
+        ///
+        /// # Arguments
+        /// * `origin` - contains the signing key of the caller (i.e who signed the transaction to execute this function).
+        /// * `did` - the DID of the creator of the token or the owner of the token.
+        /// * `name` - the name of the token.
+        /// * `ticker` - the ticker symbol of the token.
+        /// * `total_supply` - the total supply of the token.
+        /// * `divisible` - a boolean to identify the divisibility status of the token.
+        /// * `asset_type` - the asset type.
+        /// * `identifiers` - a vector of asset identifiers.
+        /// * `funding_round` - name of the funding round
+        /// * `hard_max_supply` - maximum total supply of the token, or `None` for no maximum
+        pub fn create_token_with_hard_max_supply(
+            origin,
+            did: IdentityId,
+            name: Vec<u8>,
+            ticker: Ticker,
+            total_supply: T::Balance,
+            divisible: bool,
+            asset_type: AssetType,
+            identifiers: Vec<(IdentifierType, Vec<u8>)>,
+            funding_round: Option<Vec<u8>>,
+            hard_max_supply: Option<T::Balance>
         ) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
@@ -368,25 +903,22 @@ decl_module! {
             // Check that sender is allowed to act on behalf of `did`
             ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
             ticker.canonize();
-            ensure!(!<Tokens<T>>::exists(&ticker), "token already created");
-
-            let ticker_config = Self::ticker_registration_config();
-
-            ensure!(ticker.len() <= usize::try_from(ticker_config.max_ticker_length).unwrap_or_default(), "ticker length over the limit");
-
-            // checking max size for name and ticker
-            // byte arrays (vecs) with no max size should be avoided
-            ensure!(name.len() <= 64, "token name cannot exceed 64 bytes");
 
-            let is_ticker_available_or_registered_to = Self::is_ticker_available_or_registered_to(&ticker, did);
-
-            ensure!(is_ticker_available_or_registered_to != TickerRegistrationStatus::RegisteredByOther, "Ticker registered to someone else");
-
-            if !divisible {
-                ensure!(total_supply % ONE_UNIT.into() == 0.into(), "Invalid Total supply");
+            let tokens_created = Self::tokens_created_this_block();
+            if let Some(max_tokens) = Self::max_tokens_per_block() {
+                ensure!(tokens_created < max_tokens, Error::<T>::TokenCreationRateLimited);
             }
 
-            ensure!(total_supply <= MAX_SUPPLY.into(), "Total supply above the limit");
+            let is_ticker_available_or_registered_to = Self::ensure_token_creatable(
+                did,
+                &name,
+                &ticker,
+                total_supply,
+                divisible,
+                &asset_type,
+                &identifiers,
+                hard_max_supply,
+            )?;
 
             // Alternative way to take a fee - fee is proportionaly paid to the validators and dust is burned
             let validators = <pallet_session::Module<T>>::validators();
@@ -408,43 +940,27 @@ decl_module! {
             }
             let remainder_fee = fee - (proportional_fee * validator_len);
             let _withdraw_result = <balances::Module<T>>::withdraw(&sender, remainder_fee, WithdrawReason::Fee.into(), ExistenceRequirement::KeepAlive)?;
-            <identity::Module<T>>::register_asset_did(&ticker)?;
 
             if is_ticker_available_or_registered_to == TickerRegistrationStatus::Available {
                 // ticker not registered by anyone (or registry expired). we can charge fee and register this ticker
-                Self::_register_ticker(&ticker, sender, did, None);
+                Self::_register_ticker(&ticker, sender, did, None)?;
             } else {
                 // Ticker already registered by the user
                 <Tickers<T>>::mutate(&ticker, |tr| tr.expiry = None);
             }
 
-            let link = <identity::Module<T>>::add_link(Signatory::from(did), LinkData::TokenOwned(ticker), None);
-
-            let token = SecurityToken {
+            Self::_create_token(
+                did,
                 name,
-                total_supply,
-                owner_did: did,
-                divisible,
-                asset_type: asset_type.clone(),
-                link_id: link,
-            };
-            <Tokens<T>>::insert(&ticker, token);
-            <BalanceOf<T>>::insert((ticker, did), total_supply);
-            Self::deposit_event(RawEvent::IssuedToken(
                 ticker,
                 total_supply,
-                did,
                 divisible,
                 asset_type,
-            ));
-            for (typ, val) in &identifiers {
-                <Identifiers>::insert((ticker, typ.clone()), val.clone());
-            }
-            // Add funding round name
-            if let Some(round) = funding_round {
-                <FundingRound>::insert(ticker, round);
-            }
-            Self::deposit_event(RawEvent::IdentifiersUpdated(ticker, identifiers));
+                identifiers,
+                funding_round,
+                hard_max_supply,
+            )?;
+            <TokensCreatedThisBlock>::put(tokens_created.saturating_add(1));
 
             Ok(())
         }
@@ -469,6 +985,42 @@ decl_module! {
             Ok(())
         }
 
+        /// Cancels the pending ownership transfers of a given token to `to_did`.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `to_did` - the DID that was authorized to accept the ownership of the token
+        pub fn cancel_token_ownership_transfer(origin, ticker: Ticker, to_did: IdentityId) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            ticker.canonize();
+            ensure!(<Tokens<T>>::exists(&ticker), "token doesn't exist");
+            let token = <Tokens<T>>::get(&ticker);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(token.owner_did, &signer),
+                    "sender must be a signing key for the token owner DID");
+
+            // Walk the authorizations of `to_did` and remove the matching transfers.
+            let target = Signatory::from(to_did);
+            let owner = Signatory::from(token.owner_did);
+            let mut cancelled = false;
+            let mut auth_id = <identity::Module<T>>::last_authorization(&target);
+            while auth_id != 0 {
+                let auth = <identity::Module<T>>::authorizations((target, auth_id));
+                if auth.authorized_by == owner
+                    && auth.authorization_data == AuthorizationData::TransferTokenOwnership(ticker) {
+                    <identity::Module<T>>::remove_auth(target, auth_id, auth.next_authorization, auth.previous_authorization);
+                    cancelled = true;
+                }
+                auth_id = auth.previous_authorization;
+            }
+            ensure!(cancelled, Error::<T>::NoPendingOwnershipTransfer);
+
+            Self::deposit_event(RawEvent::TokenOwnershipTransferCancelled(ticker, to_did));
+            Ok(())
+        }
+
         /// Unfreezes transfers and minting of a given token.
         ///
         /// # Arguments
@@ -489,105 +1041,779 @@ decl_module! {
             Ok(())
         }
 
-        /// Renames a given token.
+        /// Freezes the outgoing transfers of a single holder of a given token. Only called by the
+        /// token owner or its compliance officer.
         ///
         /// # Arguments
-        /// * `origin` - the signing key of the sender
+        /// * `origin` - the signing key of the token owner or compliance officer
+        /// * `did` - the DID of the token owner or compliance officer
         /// * `ticker` - the ticker of the token
-        /// * `name` - the new name of the token
-        pub fn rename_token(origin, ticker: Ticker, name: Vec<u8>) -> DispatchResult {
+        /// * `holder_did` - the DID of the holder to freeze
+        /// * `reason` - an off chain reason of the freeze
+        pub fn controller_freeze_holder(origin, did: IdentityId, ticker: Ticker, holder_did: IdentityId, reason: Vec<u8>) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
-            ticker.canonize();
-            ensure!(<Tokens<T>>::exists(&ticker), "token doesn't exist");
-            let token = <Tokens<T>>::get(&ticker);
             // Check that sender is allowed to act on behalf of `did`
-            ensure!(<identity::Module<T>>::is_signer_authorized(token.owner_did, &signer), "sender must be a signing key for the token owner DID");
-            <Tokens<T>>::mutate(&ticker, |token| token.name = name.clone());
-            Self::deposit_event(RawEvent::TokenRenamed(ticker, name));
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner_or_compliance_officer(&ticker, did), "user is not authorized");
+            ensure!(!Self::controller_frozen_holder((ticker, holder_did)), "holder is already frozen");
+            <ControllerFrozenHolder>::insert((ticker, holder_did), true);
+            Self::deposit_event(RawEvent::HolderFrozenByController(ticker, holder_did, reason));
             Ok(())
         }
 
-        /// Transfer tokens from one DID to another DID as tokens are stored/managed on the DID level
+        /// Unfreezes the transfers of a holder frozen by `controller_freeze_holder` or
+        /// `controller_freeze_holder_directional`. Only called by the token owner or its
+        /// compliance officer.
         ///
         /// # Arguments
-        /// * `_origin` signing key of the sender
-        /// * `did` DID of the `from` token holder, from whom tokens needs to transferred
-        /// * `ticker` Ticker of the token
-        /// * `to_did` DID of the `to` token holder, to whom token needs to transferred
-        /// * `value` Value that needs to transferred
-        pub fn transfer(_origin, did: IdentityId, ticker: Ticker, to_did: IdentityId, value: T::Balance) -> DispatchResult {
-            let sender = ensure_signed(_origin)?;
+        /// * `origin` - the signing key of the token owner or compliance officer
+        /// * `did` - the DID of the token owner or compliance officer
+        /// * `ticker` - the ticker of the token
+        /// * `holder_did` - the DID of the holder to unfreeze
+        /// * `reason` - an off chain reason of the unfreeze
+        pub fn controller_unfreeze_holder(origin, did: IdentityId, ticker: Ticker, holder_did: IdentityId, reason: Vec<u8>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
             let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
-
             // Check that sender is allowed to act on behalf of `did`
             ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
             ticker.canonize();
-            // Check whether the custody allowance remain intact or not
-            Self::_check_custody_allowance(&ticker, did, value)?;
-            ensure!(Self::_is_valid_transfer(&ticker, Some(did), Some(to_did), value)? == ERC1400_TRANSFER_SUCCESS, "Transfer restrictions failed");
-
-            Self::_transfer(&ticker, did, to_did, value)
+            ensure!(Self::is_owner_or_compliance_officer(&ticker, did), "user is not authorized");
+            ensure!(
+                Self::controller_frozen_holder((ticker, holder_did))
+                    || <HolderFreezeDirection>::exists((ticker, holder_did)),
+                "holder is not frozen"
+            );
+            <ControllerFrozenHolder>::remove((ticker, holder_did));
+            <HolderFreezeDirection>::remove((ticker, holder_did));
+            Self::deposit_event(RawEvent::HolderUnfrozenByController(ticker, holder_did, reason));
+            Ok(())
         }
 
-        /// Forces a transfer between two DIDs & This can only be called by security token owner.
-        /// This function doesn't validate any type of restriction beside a valid KYC check
+        /// Freezes the transfers of a single holder of a given token in the given direction,
+        /// replacing any previous directional freeze of the holder. An incoming freeze also blocks
+        /// issuance to the holder. Only called by the token owner or its compliance officer.
         ///
         /// # Arguments
-        /// * `_origin` signing key of the token owner DID.
-        /// * `did` Token owner DID.
-        /// * `ticker` symbol of the token
-        /// * `from_did` DID of the token holder from whom balance token will be transferred.
-        /// * `to_did` DID of token holder to whom token balance will be transferred.
-        /// * `value` Amount of tokens.
-        /// * `data` Some off chain data to validate the restriction.
-        /// * `operator_data` It is a string which describes the reason of this control transfer call.
-        pub fn controller_transfer(_origin, did: IdentityId, ticker: Ticker, from_did: IdentityId, to_did: IdentityId, value: T::Balance, data: Vec<u8>, operator_data: Vec<u8>) -> DispatchResult {
-            let sender = ensure_signed(_origin)?;
-            let signer = Signatory::AccountKey( AccountKey::try_from(sender.encode())?);
+        /// * `origin` - the signing key of the token owner or compliance officer
+        /// * `did` - the DID of the token owner or compliance officer
+        /// * `ticker` - the ticker of the token
+        /// * `holder_did` - the DID of the holder to freeze
+        /// * `direction` - the transfers of the holder to freeze
+        pub fn controller_freeze_holder_directional(origin, did: IdentityId, ticker: Ticker, holder_did: IdentityId, direction: FreezeDirection) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner_or_compliance_officer(&ticker, did), "user is not authorized");
+            <HolderFreezeDirection>::insert((ticker, holder_did), direction);
+            Self::deposit_event(RawEvent::HolderFreezeDirectionSet(ticker, holder_did, direction));
+            Ok(())
+        }
 
+        /// Enables or disables the approve allowlist of a given token. Only called by the token owner.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `enabled` - whether only allowlisted spenders can be approved
+        pub fn set_approve_allowlist_enabled(origin, did: IdentityId, ticker: Ticker, enabled: bool) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
             // Check that sender is allowed to act on behalf of `did`
             ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
             ticker.canonize();
             ensure!(Self::is_owner(&ticker, did), "user is not authorized");
-
-            Self::_transfer(&ticker, from_did, to_did, value.clone())?;
-
-            Self::deposit_event(RawEvent::ControllerTransfer(ticker, did, from_did, to_did, value, data, operator_data));
-
+            <ApproveAllowlistEnabled>::insert(&ticker, enabled);
+            Self::deposit_event(RawEvent::ApproveAllowlistToggled(ticker, enabled));
             Ok(())
         }
 
-        /// approve token transfer from one DID to DID
-        /// once this is done, transfer_from can be called with corresponding values
+        /// Adds or removes a spender from the approve allowlist of a given token. Only called by
+        /// the token owner.
         ///
         /// # Arguments
-        /// * `_origin` Signing key of the token owner (i.e sender)
-        /// * `did` DID of the sender
-        /// * `spender_did` DID of the spender
-        /// * `value` Amount of the tokens approved
-        fn approve(_origin, did: IdentityId, ticker: Ticker, spender_did: IdentityId, value: T::Balance) -> DispatchResult {
-            let sender = ensure_signed(_origin)?;
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `spender_did` - the DID of the spender
+        /// * `allowed` - whether `spender_did` can be approved
+        pub fn set_approved_spender(origin, did: IdentityId, ticker: Ticker, spender_did: IdentityId, allowed: bool) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
             let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
-
             // Check that sender is allowed to act on behalf of `did`
             ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
             ticker.canonize();
-            ensure!(<BalanceOf<T>>::exists((ticker, did)), "Account does not own this token");
-            let allowance = Self::allowance((ticker, did, spender_did));
-            let updated_allowance = allowance.checked_add(&value).ok_or("overflow in calculating allowance")?;
-            <Allowance<T>>::insert((ticker, did, spender_did), updated_allowance);
-
-            Self::deposit_event(RawEvent::Approval(ticker, did, spender_did, value));
-
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            if allowed {
+                <ApprovedSpenders>::insert((ticker, spender_did), true);
+            } else {
+                <ApprovedSpenders>::remove((ticker, spender_did));
+            }
+            Self::deposit_event(RawEvent::ApprovedSpenderChanged(ticker, spender_did, allowed));
             Ok(())
         }
 
-        /// If sufficient allowance provided, transfer from a DID to another DID without token owner's signature.
+        /// Sets the maximum allowance and custody allowance that a holder can give to a single
+        /// spender of a given token. Only called by the token owner.
         ///
         /// # Arguments
-        /// * `_origin` Signing key of spender
-        /// * `did` DID of the spender
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `max_allowance` - the maximum allowance per spender. `None` removes the cap
+        pub fn set_max_allowance_per_spender(origin, did: IdentityId, ticker: Ticker, max_allowance: Option<T::Balance>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            match max_allowance {
+                Some(max_allowance) => <MaxAllowancePerSpender<T>>::insert(&ticker, max_allowance),
+                None => <MaxAllowancePerSpender<T>>::remove(&ticker),
+            }
+            Self::deposit_event(RawEvent::MaxAllowancePerSpenderChanged(ticker, max_allowance));
+            Ok(())
+        }
+
+        /// Enables or disables the aggregation of the transfer events of a given token. When
+        /// enabled, its transfers emit a single `AggregatedTransfers` event at the end of each
+        /// block instead of a `Transfer` event each. Only called by the token owner.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `enabled` - whether transfer events are aggregated
+        pub fn set_aggregate_transfer_events(origin, did: IdentityId, ticker: Ticker, enabled: bool) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            <AggregateTransferEvents>::insert(&ticker, enabled);
+            Self::deposit_event(RawEvent::AggregateTransferEventsToggled(ticker, enabled));
+            Ok(())
+        }
+
+        /// Sets the value of an attribute of a given token, replacing any previous value. Only
+        /// called by the token owner.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `key` - the attribute key, up to `MAX_ATTRIBUTE_KEY_LENGTH` bytes
+        /// * `value` - the attribute value, up to `MAX_ATTRIBUTE_VALUE_LENGTH` bytes
+        pub fn set_attribute(origin, did: IdentityId, ticker: Ticker, key: Vec<u8>, value: Vec<u8>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            ensure!(
+                key.len() <= MAX_ATTRIBUTE_KEY_LENGTH && value.len() <= MAX_ATTRIBUTE_VALUE_LENGTH,
+                Error::<T>::AttributeTooLong
+            );
+            <TokenAttributes>::insert((ticker, key.clone()), value.clone());
+            Self::deposit_event(RawEvent::AttributeSet(ticker, key, value));
+            Ok(())
+        }
+
+        /// Removes an attribute of a given token. Only called by the token owner.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `key` - the attribute key
+        pub fn remove_attribute(origin, did: IdentityId, ticker: Ticker, key: Vec<u8>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            let ticker_key = (ticker, key);
+            ensure!(<TokenAttributes>::exists(&ticker_key), Error::<T>::AttributeNotFound);
+            <TokenAttributes>::remove(&ticker_key);
+            Self::deposit_event(RawEvent::AttributeRemoved(ticker, ticker_key.1));
+            Ok(())
+        }
+
+        /// Sets the bitmask of paused operations of a given token. Only called by the token owner.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `flags` - a combination of `PAUSE_TRANSFER`, `PAUSE_ISSUE`, `PAUSE_REDEEM` and `PAUSE_APPROVE`
+        pub fn set_operation_pause_flags(origin, did: IdentityId, ticker: Ticker, flags: u8) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            <OperationPauseFlags>::insert(&ticker, flags);
+            Self::deposit_event(RawEvent::OperationPauseFlagsChanged(ticker, flags));
+            Ok(())
+        }
+
+        /// Sets whether issuing a given token requires the recipient to have a valid KYC.
+        /// Only called by the token owner.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `required` - whether a valid KYC of the recipient is required
+        pub fn set_require_kyc_on_issue(origin, did: IdentityId, ticker: Ticker, required: bool) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            <RequireKycOnIssue>::insert(&ticker, required);
+            Self::deposit_event(RawEvent::RequireKycOnIssueChanged(ticker, required));
+            Ok(())
+        }
+
+        /// Sets whether `transfer_from` of a given token requires the recipient to have a valid
+        /// KYC. Only called by the token owner.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `required` - whether a valid KYC of the recipient is required
+        pub fn set_require_kyc_on_transfer_from(origin, did: IdentityId, ticker: Ticker, required: bool) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            <RequireKycOnTransferFrom>::insert(&ticker, required);
+            Self::deposit_event(RawEvent::RequireKycOnTransferFromChanged(ticker, required));
+            Ok(())
+        }
+
+        /// Sets whether issuing an indivisible token rounds the issued amount down to a whole
+        /// unit instead of failing. Only called by the token owner.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `enabled` - whether issued amounts are rounded down
+        pub fn set_issuance_rounding(origin, did: IdentityId, ticker: Ticker, enabled: bool) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            <IssuanceRoundingEnabled>::insert(&ticker, enabled);
+            Self::deposit_event(RawEvent::IssuanceRoundingChanged(ticker, enabled));
+            Ok(())
+        }
+
+        /// Sets whether receivers of a given token are automatically assigned the custodian named
+        /// in their custodian claim issued by the token owner. Only called by the token owner.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `enabled` - whether custodians are assigned automatically
+        pub fn set_auto_custodian(origin, did: IdentityId, ticker: Ticker, enabled: bool) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            <AutoCustodian>::insert(&ticker, enabled);
+            Self::deposit_event(RawEvent::AutoCustodianChanged(ticker, enabled));
+            Ok(())
+        }
+
+        /// Sets the secondary classification of a given token. The primary `asset_type` of the
+        /// token is unchanged. Only called by the token owner.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `classification` - the secondary asset types of the token
+        pub fn set_asset_classification(origin, did: IdentityId, ticker: Ticker, classification: Vec<AssetType>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            <AssetClassification>::insert(&ticker, classification.clone());
+            Self::deposit_event(RawEvent::ClassificationUpdated(ticker, classification));
+            Ok(())
+        }
+
+        /// Raises the total supply of a given token that redemptions cannot go below. To protect
+        /// investors, the owner cannot lower the floor; only root can with `force_set_supply_floor`.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `floor` - the new supply floor, not lower than the current one
+        pub fn set_supply_floor(origin, did: IdentityId, ticker: Ticker, floor: T::Balance) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            ensure!(floor >= Self::supply_floor(&ticker), Error::<T>::SupplyFloorDecrease);
+            <SupplyFloor<T>>::insert(&ticker, floor);
+            Self::deposit_event(RawEvent::SupplyFloorChanged(ticker, floor));
+            Ok(())
+        }
+
+        /// Sets the total supply of a given token that redemptions cannot go below. Only called
+        /// by root.
+        ///
+        /// # Arguments
+        /// * `origin` - root origin
+        /// * `ticker` - the ticker of the token
+        /// * `floor` - the new supply floor
+        pub fn force_set_supply_floor(origin, ticker: Ticker, floor: T::Balance) -> DispatchResult {
+            ensure_root(origin)?;
+            ticker.canonize();
+            <SupplyFloor<T>>::insert(&ticker, floor);
+            Self::deposit_event(RawEvent::SupplyFloorChanged(ticker, floor));
+            Ok(())
+        }
+
+        /// Sets the lot size of a given token. Transferred, issued and redeemed amounts must be
+        /// multiples of the lot size, or of the lot size in whole units for indivisible tokens.
+        /// Only called by the token owner.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `lot_size` - the new lot size, which cannot be zero
+        pub fn set_lot_size(origin, did: IdentityId, ticker: Ticker, lot_size: T::Balance) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            ensure!(!lot_size.is_zero(), Error::<T>::InvalidLotSize);
+            <LotSize<T>>::insert(&ticker, lot_size);
+            Self::deposit_event(RawEvent::LotSizeChanged(ticker, lot_size));
+            Ok(())
+        }
+
+        /// Sets whether transfers of a given token to frozen DIDs are rejected. Only called by
+        /// the token owner.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `blocked` - whether transfers to frozen DIDs are rejected
+        pub fn set_block_transfers_to_frozen_dids(origin, did: IdentityId, ticker: Ticker, blocked: bool) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            <BlockTransfersToFrozenDids>::insert(&ticker, blocked);
+            Self::deposit_event(RawEvent::BlockTransfersToFrozenDidsChanged(ticker, blocked));
+            Ok(())
+        }
+
+        /// Sets the claims that senders of a given token must hold. For each claim key, one of
+        /// its acceptable issuers must have issued a non-expired claim to the sender. Only
+        /// called by the token owner.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `claims` - the (claim key, acceptable issuers) pairs. Empty removes the requirement
+        pub fn set_required_sender_claims(origin, did: IdentityId, ticker: Ticker, claims: Vec<(Vec<u8>, Vec<IdentityId>)>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            if claims.is_empty() {
+                <RequiredSenderClaims>::remove(&ticker);
+            } else {
+                <RequiredSenderClaims>::insert(&ticker, claims.clone());
+            }
+            Self::deposit_event(RawEvent::RequiredSenderClaimsChanged(ticker, claims));
+            Ok(())
+        }
+
+        /// Sets whether both the sender and the receiver of a transfer of a given token must be
+        /// in its transfer allowlist. The token owner is always allowlisted. Only called by the
+        /// token owner or its compliance officer.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner or compliance officer
+        /// * `did` - the DID of the token owner or compliance officer
+        /// * `ticker` - the ticker of the token
+        /// * `enforced` - whether the allowlist is enforced
+        pub fn set_allowlist_enforced(origin, did: IdentityId, ticker: Ticker, enforced: bool) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner_or_compliance_officer(&ticker, did), "user is not authorized");
+            <AllowlistEnforced>::insert(&ticker, enforced);
+            Self::deposit_event(RawEvent::AllowlistEnforcedChanged(ticker, enforced));
+            Ok(())
+        }
+
+        /// Adds `holders` to the transfer allowlist of a given token, or removes them from it.
+        /// Only called by the token owner or its compliance officer.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner or compliance officer
+        /// * `did` - the DID of the token owner or compliance officer
+        /// * `ticker` - the ticker of the token
+        /// * `holders` - the DIDs to add or remove
+        /// * `allowlisted` - whether `holders` are added (`true`) or removed (`false`)
+        pub fn set_allowlisted(origin, did: IdentityId, ticker: Ticker, holders: Vec<IdentityId>, allowlisted: bool) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner_or_compliance_officer(&ticker, did), "user is not authorized");
+            for holder in holders.iter() {
+                if allowlisted {
+                    <TransferAllowlist>::insert((ticker, *holder), true);
+                } else {
+                    <TransferAllowlist>::remove((ticker, *holder));
+                }
+            }
+            Self::deposit_event(RawEvent::TransferAllowlistChanged(ticker, holders, allowlisted));
+            Ok(())
+        }
+
+        /// Sets the compliance officer of a given token, who can manage its transfer allowlist
+        /// and freeze its holders, but cannot issue, redeem or move tokens. Only called by the
+        /// token owner.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `officer_did` - the DID of the compliance officer. `None` removes it
+        pub fn set_compliance_officer(origin, did: IdentityId, ticker: Ticker, officer_did: Option<IdentityId>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            match officer_did {
+                Some(officer_did) => <ComplianceOfficer>::insert(&ticker, officer_did),
+                None => <ComplianceOfficer>::remove(&ticker),
+            }
+            Self::deposit_event(RawEvent::ComplianceOfficerChanged(ticker, officer_did));
+            Ok(())
+        }
+
+        /// Limits the amount of a given token issued per period to a percentage of the total
+        /// supply at the start of the period. A new period starts now. Only called by the token
+        /// owner.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `max_percent` - the percentage of the total supply that can be issued per period
+        /// * `period` - the length of the period, or zero to remove the limit
+        pub fn set_issuance_limit(origin, did: IdentityId, ticker: Ticker, max_percent: Perbill, period: T::Moment) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            <MaxIssuancePercentPerPeriod>::insert(&ticker, max_percent);
+            <IssuanceLimitPeriod<T>>::insert(&ticker, period);
+            <CurrentIssuanceWindow<T>>::insert(&ticker, IssuanceWindow {
+                start: <pallet_timestamp::Module<T>>::get(),
+                supply_at_start: Self::token_details(&ticker).total_supply,
+                issued: 0.into(),
+            });
+            Self::deposit_event(RawEvent::IssuanceLimitChanged(ticker, max_percent, period));
+            Ok(())
+        }
+
+        /// Delays the time from which holders other than the owner can transfer a given token.
+        /// It can only be changed before the current trading start time has passed. Only called by
+        /// the token owner.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the token owner
+        /// * `ticker` - the ticker of the token
+        /// * `start` - the new trading start time
+        pub fn set_trading_start_time(origin, did: IdentityId, ticker: Ticker, start: T::Moment) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            ensure!(
+                <pallet_timestamp::Module<T>>::get() <= Self::trading_start_time(&ticker),
+                Error::<T>::TradingAlreadyStarted
+            );
+            <TradingStartTime<T>>::insert(&ticker, start);
+            Self::deposit_event(RawEvent::TradingStartTimeChanged(ticker, start));
+            Ok(())
+        }
+
+        /// Renames a given token.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the sender
+        /// * `ticker` - the ticker of the token
+        /// * `name` - the new name of the token
+        pub fn rename_token(origin, ticker: Ticker, name: Vec<u8>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            ticker.canonize();
+            ensure!(<Tokens<T>>::exists(&ticker), "token doesn't exist");
+            let token = <Tokens<T>>::get(&ticker);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(token.owner_did, &signer), "sender must be a signing key for the token owner DID");
+            if token.name != name {
+                Self::ensure_unique_token_name(&name)?;
+                Self::remove_token_name(&token.name, &ticker);
+                Self::add_token_name(&name, &ticker);
+            }
+            <Tokens<T>>::mutate(&ticker, |token| token.name = name.clone());
+            Self::deposit_event(RawEvent::TokenRenamed(ticker, name));
+            Ok(())
+        }
+
+        /// Transfer tokens from one DID to another DID as tokens are stored/managed on the DID level
+        ///
+        /// # Arguments
+        /// * `_origin` signing key of the sender
+        /// * `did` DID of the `from` token holder, from whom tokens needs to transferred
+        /// * `ticker` Ticker of the token
+        /// * `to_did` DID of the `to` token holder, to whom token needs to transferred
+        /// * `value` Value that needs to transferred. Transfers of zero tokens are rejected.
+        pub fn transfer(_origin, did: IdentityId, ticker: Ticker, to_did: IdentityId, value: T::Balance) -> DispatchResult {
+            ensure!(!value.is_zero(), Error::<T>::ZeroAmount);
+            let sender = ensure_signed(_origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(!Self::is_operation_paused(&ticker, PAUSE_TRANSFER), Error::<T>::OperationPaused);
+            // Check whether the custody allowance remain intact or not
+            Self::_check_custody_allowance(&ticker, did, value)?;
+            Self::ensure_valid_transfer(&ticker, Some(did), Some(to_did), value)?;
+
+            Self::_transfer(&ticker, did, to_did, value)
+        }
+
+        /// Transfer tokens of several tickers from one DID to other DIDs.
+        /// The signing key is checked only once and the batch is atomic: all legs are validated
+        /// before any balance is updated.
+        ///
+        /// # Arguments
+        /// * `origin` signing key of the sender
+        /// * `did` DID of the `from` token holder, from whom tokens needs to transferred
+        /// * `legs` Vector of (ticker, DID of the receiver, value) tuples
+        pub fn transfer_batch_same_sender(origin, did: IdentityId, legs: Vec<(Ticker, IdentityId, T::Balance)>) -> DispatchResult {
+            Self::ensure_batch_size(legs.len())?;
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+
+            // Total value transferred per ticker and per (ticker, receiver). Legs are validated
+            // against these cumulative totals so that the batch fails before any transfer is made.
+            let mut totals: Vec<(Ticker, T::Balance)> = Vec::new();
+            let mut received: Vec<(Ticker, IdentityId, T::Balance)> = Vec::new();
+            for (ticker, to_did, value) in &legs {
+                ticker.canonize();
+                ensure!(!Self::is_operation_paused(ticker, PAUSE_TRANSFER), Error::<T>::OperationPaused);
+                Self::ensure_transferable(ticker, did, *to_did, *value)?;
+                match totals.iter_mut().find(|(t, _)| *t == *ticker) {
+                    Some((_, total)) => {
+                        *total = total.checked_add(value).ok_or("overflow in calculating balance")?;
+                    },
+                    None => totals.push((*ticker, *value)),
+                }
+                match received.iter_mut().find(|(t, r, _)| *t == *ticker && *r == *to_did) {
+                    Some((_, _, total)) => {
+                        *total = total.checked_add(value).ok_or("overflow in calculating balance")?;
+                    },
+                    None => received.push((*ticker, *to_did, *value)),
+                }
+            }
+            for (ticker, total) in &totals {
+                ensure!(Self::balance_of((*ticker, did)) >= *total, "Not enough balance.");
+                // Check whether the custody allowance remain intact or not
+                Self::_check_custody_allowance(ticker, did, *total)?;
+            }
+            for (ticker, to_did, total) in &received {
+                Self::ensure_valid_transfer(ticker, Some(did), Some(*to_did), *total)?;
+                if *to_did != did {
+                    Self::ensure_received_auto_custody(ticker, *to_did, *total)?;
+                }
+            }
+
+            for (ticker, to_did, value) in legs {
+                Self::_transfer(&ticker, did, to_did, value)?;
+            }
+            Ok(())
+        }
+
+        /// Forces a transfer between two DIDs & This can only be called by security token owner.
+        /// This function doesn't validate any type of restriction beside a valid KYC check
+        ///
+        /// # Arguments
+        /// * `_origin` signing key of the token owner DID.
+        /// * `did` Token owner DID.
+        /// * `ticker` symbol of the token
+        /// * `from_did` DID of the token holder from whom balance token will be transferred.
+        /// * `to_did` DID of token holder to whom token balance will be transferred.
+        /// * `value` Amount of tokens.
+        /// * `data` Some off chain data to validate the restriction.
+        /// * `operator_data` It is a string which describes the reason of this control transfer call.
+        pub fn controller_transfer(_origin, did: IdentityId, ticker: Ticker, from_did: IdentityId, to_did: IdentityId, value: T::Balance, data: Vec<u8>, operator_data: Vec<u8>) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            let signer = Signatory::AccountKey( AccountKey::try_from(sender.encode())?);
+
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+
+            Self::_transfer(&ticker, from_did, to_did, value.clone())?;
+            Self::log_controller_action(&ticker, ControllerActionType::Transfer, from_did, Some(to_did), value, operator_data.clone());
+
+            Self::deposit_event(RawEvent::ControllerTransfer(ticker, did, from_did, to_did, value, data, operator_data));
+
+            Ok(())
+        }
+
+        /// approve token transfer from one DID to DID
+        /// once this is done, transfer_from can be called with corresponding values
+        /// The approved allowance does not expire. An expired allowance is replaced by `value`.
+        ///
+        /// # Arguments
+        /// * `_origin` Signing key of the token owner (i.e sender)
+        /// * `did` DID of the sender
+        /// * `spender_did` DID of the spender
+        /// * `value` Amount of the tokens approved
+        pub fn approve(_origin, did: IdentityId, ticker: Ticker, spender_did: IdentityId, value: T::Balance) -> DispatchResult {
+            let sender = ensure_signed(_origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(!Self::is_operation_paused(&ticker, PAUSE_APPROVE), Error::<T>::OperationPaused);
+            ensure!(
+                !Self::approve_allowlist_enabled(&ticker) || Self::approved_spenders((ticker, spender_did)),
+                Error::<T>::SpenderNotAllowlisted
+            );
+            ensure!(<BalanceOf<T>>::exists((ticker, did)), "Account does not own this token");
+            let ticker_did_spender = (ticker, did, spender_did);
+            let allowance = if Self::is_allowance_expired(&ticker_did_spender) {
+                Zero::zero()
+            } else {
+                Self::allowance(&ticker_did_spender)
+            };
+            let updated_allowance = allowance.checked_add(&value).ok_or("overflow in calculating allowance")?;
+            ensure!(Self::is_within_allowance_cap(&ticker, updated_allowance), Error::<T>::AllowanceCapExceeded);
+            Self::set_allowance(&ticker, did, spender_did, updated_allowance);
+            <AllowanceExpiry<T>>::remove(&ticker_did_spender);
+
+            Self::deposit_event(RawEvent::Approval(ticker, did, spender_did, value));
+
+            Ok(())
+        }
+
+        /// Approves `value` tokens like `approve` and makes the whole allowance of `spender_did`
+        /// expire at `expiry`. Expired allowances cannot be spent.
+        ///
+        /// # Arguments
+        /// * `origin` Signing key of the token owner (i.e sender)
+        /// * `did` DID of the sender
+        /// * `spender_did` DID of the spender
+        /// * `value` Amount of the tokens approved
+        /// * `expiry` Time after which the allowance can no longer be spent
+        pub fn approve_with_expiry(origin, did: IdentityId, ticker: Ticker, spender_did: IdentityId, value: T::Balance, expiry: T::Moment) -> DispatchResult {
+            ensure!(expiry > <pallet_timestamp::Module<T>>::get(), Error::<T>::AllowanceExpired);
+            Self::approve(origin, did, ticker, spender_did, value)?;
+            ticker.canonize();
+            <AllowanceExpiry<T>>::insert((ticker, did, spender_did), expiry);
+            Ok(())
+        }
+
+        /// Removes the expired allowances that `did` granted to `spenders`. Allowances of the
+        /// listed spenders that have not expired are kept.
+        ///
+        /// # Arguments
+        /// * `origin` Signing key of the DID that granted the allowances
+        /// * `did` DID that granted the allowances
+        /// * `ticker` Ticker of the token
+        /// * `spenders` DIDs of the spenders whose expired allowances are removed
+        pub fn prune_expired_allowances(origin, did: IdentityId, ticker: Ticker, spenders: Vec<IdentityId>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            Self::ensure_batch_size(spenders.len())?;
+            ticker.canonize();
+            for spender_did in spenders {
+                let ticker_did_spender = (ticker, did, spender_did);
+                if Self::is_allowance_expired(&ticker_did_spender) {
+                    Self::set_allowance(&ticker, did, spender_did, 0.into());
+                    <Allowance<T>>::remove(&ticker_did_spender);
+                    <AllowanceExpiry<T>>::remove(&ticker_did_spender);
+                    Self::deposit_event(RawEvent::Approval(ticker, did, spender_did, 0.into()));
+                }
+            }
+            Ok(())
+        }
+
+        /// If sufficient allowance provided, transfer from a DID to another DID without token owner's signature.
+        ///
+        /// # Arguments
+        /// * `_origin` Signing key of spender
+        /// * `did` DID of the spender
         /// * `_ticker` Ticker of the token
         /// * `from_did` DID from whom token is being transferred
         /// * `to_did` DID to whom token is being transferred
@@ -600,19 +1826,25 @@ decl_module! {
             ticker.canonize();
             let ticker_from_did_did = (ticker, from_did, did);
             ensure!(<Allowance<T>>::exists(&ticker_from_did_did), "Allowance does not exist");
+            ensure!(!Self::is_allowance_expired(&ticker_from_did_did), Error::<T>::AllowanceExpired);
             let allowance = Self::allowance(&ticker_from_did_did);
             ensure!(allowance >= value, "Not enough allowance");
+            ensure!(
+                !Self::require_kyc_on_transfer_from(&ticker)
+                    || <identity::Module<T>>::is_identity_has_valid_kyc(to_did, 0).0,
+                Error::<T>::RecipientKycInvalid
+            );
 
             // using checked_sub (safe math) to avoid overflow
             let updated_allowance = allowance.checked_sub(&value).ok_or("overflow in calculating allowance")?;
             // Check whether the custody allowance remain intact or not
             Self::_check_custody_allowance(&ticker, from_did, value)?;
 
-            ensure!(Self::_is_valid_transfer(&ticker, Some(from_did), Some(to_did), value)? == ERC1400_TRANSFER_SUCCESS, "Transfer restrictions failed");
+            Self::ensure_valid_transfer(&ticker, Some(from_did), Some(to_did), value)?;
             Self::_transfer(&ticker, from_did, to_did, value)?;
 
             // Change allowance afterwards
-            <Allowance<T>>::insert(&ticker_from_did_did, updated_allowance);
+            Self::set_allowance(&ticker, from_did, did, updated_allowance);
 
             Self::deposit_event(RawEvent::Approval(ticker, from_did, did, value));
             Ok(())
@@ -632,27 +1864,110 @@ decl_module! {
             ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
             ticker.canonize();
             ensure!(Self::is_owner(&ticker, did), "user is not authorized");
-            Self::_create_checkpoint(&ticker)
+            Self::_create_checkpoint(&ticker)?;
+            Ok(())
+        }
+
+        /// Function is used to issue(or mint) new tokens for the given DID
+        /// can only be executed by the token owner
+        ///
+        /// # Arguments
+        /// * `origin` Signing key of token owner
+        /// * `did` DID of the token owner
+        /// * `ticker` Ticker of the token
+        /// * `to_did` DID of the token holder to whom new tokens get issued.
+        /// * `value` Amount of tokens that get issued. Issuing zero tokens is rejected.
+        pub fn issue(origin, did: IdentityId, ticker: Ticker, to_did: IdentityId, value: T::Balance, _data: Vec<u8>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            ensure!(!Self::is_operation_paused(&ticker, PAUSE_ISSUE), Error::<T>::OperationPaused);
+            Self::_mint(&ticker, to_did, value)
+        }
+
+        /// Issues new tokens like `issue` and gives `custodian_did` a custody allowance of the
+        /// issued amount over them. Either both the issuance and the allowance are applied or none.
+        ///
+        /// # Arguments
+        /// * `origin` Signing key of the token owner
+        /// * `did` DID of the token owner
+        /// * `ticker` Ticker of the token
+        /// * `holder_did` DID of the holder receiving the tokens
+        /// * `custodian_did` DID of the custodian of the issued tokens
+        /// * `value` Amount of tokens to issue
+        pub fn issue_to_custody(
+            origin,
+            did: IdentityId,
+            ticker: Ticker,
+            holder_did: IdentityId,
+            custodian_did: IdentityId,
+            value: T::Balance
+        ) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            ensure!(!Self::is_operation_paused(&ticker, PAUSE_ISSUE), Error::<T>::OperationPaused);
+            // The automatic custodian would take a custody allowance over the same tokens.
+            ensure!(!Self::auto_custodian(&ticker), Error::<T>::AutoCustodianEnabled);
+
+            // Check the allowance before minting, as the issuance cannot be undone afterwards.
+            let value = Self::round_issuance(&ticker, value);
+            let holder_balance = Self::balance_of((ticker, holder_did))
+                .checked_add(&value)
+                .ok_or("overflow in calculating balance")?;
+            let (new_custody_allowance, new_allowance) =
+                Self::ensure_custody_allowance_increase(ticker, holder_did, custodian_did, value, holder_balance)?;
+
+            Self::_mint(&ticker, holder_did, value)?;
+            Self::set_custody_allowance(ticker, holder_did, custodian_did, new_custody_allowance, new_allowance);
+            Ok(())
         }
 
-        /// Function is used to issue(or mint) new tokens for the given DID
-        /// can only be executed by the token owner
+        /// Issues new tokens like `issue` to a recipient identified by a claim. As claims are not
+        /// indexed by value, the caller passes the DID of the recipient and it is only verified
+        /// to hold the claim.
         ///
         /// # Arguments
-        /// * `origin` Signing key of token owner
+        /// * `origin` Signing key of the token owner
         /// * `did` DID of the token owner
         /// * `ticker` Ticker of the token
-        /// * `to_did` DID of the token holder to whom new tokens get issued.
-        /// * `value` Amount of tokens that get issued
-        pub fn issue(origin, did: IdentityId, ticker: Ticker, to_did: IdentityId, value: T::Balance, _data: Vec<u8>) -> DispatchResult {
-            let sender = ensure_signed(origin)?;
+        /// * `to_did` DID of the recipient, which must hold the claim
+        /// * `claim_key` Key of the claim identifying the recipient
+        /// * `claim_issuer` DID of the issuer of the claim
+        /// * `claim_value` Value of the claim identifying the recipient
+        /// * `value` Amount of tokens to issue
+        pub fn issue_to_claim_holder(
+            origin,
+            did: IdentityId,
+            ticker: Ticker,
+            to_did: IdentityId,
+            claim_key: Vec<u8>,
+            claim_issuer: IdentityId,
+            claim_value: ClaimValue,
+            value: T::Balance
+        ) -> DispatchResult {
+            let sender = ensure_signed(origin.clone())?;
             let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
 
-            // Check that sender is allowed to act on behalf of `did`
+            // Check that sender is allowed to act on behalf of `did` before revealing any claim.
             ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
             ticker.canonize();
             ensure!(Self::is_owner(&ticker, did), "user is not authorized");
-            Self::_mint(&ticker, to_did, value)
+            ensure!(
+                <identity::Module<T>>::fetch_claim_value(to_did, claim_key.clone(), claim_issuer) == Some(claim_value),
+                Error::<T>::RecipientClaimNotHeld
+            );
+            Self::issue(origin, did, ticker, to_did, value, vec![])?;
+            <identity::Module<T>>::note_claims_in_grace_period(to_did, &[(claim_key, vec![claim_issuer])]);
+            Ok(())
         }
 
         /// Function is used issue(or mint) new tokens for the given DIDs
@@ -663,8 +1978,10 @@ decl_module! {
         /// * `did` DID of the token owner
         /// * `ticker` Ticker of the token
         /// * `investor_dids` Array of the DID of the token holders to whom new tokens get issued.
-        /// * `values` Array of the Amount of tokens that get issued
+        /// * `values` Array of the Amount of tokens that get issued. The whole batch is rejected
+        /// if any amount is zero.
         pub fn batch_issue(origin, did: IdentityId, ticker: Ticker, investor_dids: Vec<IdentityId>, values: Vec<T::Balance>) -> DispatchResult {
+            Self::ensure_batch_size(investor_dids.len())?;
             let sender = ensure_signed(origin)?;
             let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
 
@@ -674,6 +1991,12 @@ decl_module! {
             ensure!(investor_dids.len() == values.len(), "Investor/amount list length inconsistent");
             ticker.canonize();
             ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            Self::ensure_funding_round_open(&ticker)?;
+            let values: Vec<T::Balance> = values
+                .into_iter()
+                .map(|value| Self::round_issuance(&ticker, value))
+                .collect();
+            ensure!(values.iter().all(|value| !value.is_zero()), Error::<T>::ZeroIssuanceAmount);
 
             // A helper vec for calculated new investor balances
             let mut updated_balances = Vec::with_capacity(investor_dids.len());
@@ -693,6 +2016,10 @@ decl_module! {
                     .checked_add(&values[i])
                     .ok_or("overflow in calculating total supply")?;
                 ensure!(updated_total_supply <= MAX_SUPPLY.into(), "Total supply above the limit");
+                ensure!(
+                    Self::hard_max_supply(&ticker).map_or(true, |max| updated_total_supply <= max),
+                    Error::<T>::HardMaxSupplyExceeded
+                );
 
                 current_balances.push(Self::balance_of((ticker, investor_dids[i].clone())));
                 updated_balances.push(current_balances[i]
@@ -701,6 +2028,8 @@ decl_module! {
 
                 // verify transfer check
                 ensure!(Self::_is_valid_transfer(&ticker, None, Some(investor_dids[i]), values[i])? == ERC1400_TRANSFER_SUCCESS, "Transfer restrictions failed");
+                ensure!(Self::is_valid_issue_recipient(&ticker, investor_dids[i]), Error::<T>::RecipientKycInvalid);
+                ensure!(!Self::is_incoming_frozen(&ticker, investor_dids[i]), Error::<T>::ReceiverFrozen);
 
                 // New total supply must be valid
                 token.total_supply = updated_total_supply;
@@ -714,11 +2043,20 @@ decl_module! {
                     .checked_add(v)
                     .ok_or("current funding round total overflowed")?;
             }
+            let total_value = values
+                .iter()
+                .try_fold(T::Balance::zero(), |total, value| total.checked_add(value))
+                .ok_or("overflow in calculating total supply")?;
+            if let Some(window) = Self::issuance_window_after(&ticker, total_value)? {
+                <CurrentIssuanceWindow<T>>::insert(&ticker, window);
+            }
             <IssuedInFundingRound<T>>::insert(&ticker_round, issued_in_this_round);
             // Update investor balances and emit events quoting the updated total token balance issued.
             for i in 0..investor_dids.len() {
                 Self::_update_checkpoint(&ticker, investor_dids[i], current_balances[i]);
                 <BalanceOf<T>>::insert((ticker, investor_dids[i]), updated_balances[i]);
+                Self::_update_holder(&ticker, investor_dids[i]);
+                Self::note_transfer_claims(&ticker, None, Some(investor_dids[i]));
                  <statistics::Module<T>>::update_transfer_stats( &ticker, None, Some(updated_balances[i]), values[i]);
                 Self::deposit_event(RawEvent::Issued(
                     ticker,
@@ -739,50 +2077,55 @@ decl_module! {
         /// * `_origin` Signing key of the token holder who wants to redeem the tokens
         /// * `did` DID of the token holder
         /// * `ticker` Ticker of the token
-        /// * `value` Amount of the tokens needs to redeem
+        /// * `value` Amount of the tokens needs to redeem. Redeeming zero tokens is rejected.
         /// * `_data` An off chain data blob used to validate the redeem functionality.
         pub fn redeem(_origin, did: IdentityId, ticker: Ticker, value: T::Balance, _data: Vec<u8>) -> DispatchResult {
+            ensure!(!value.is_zero(), Error::<T>::ZeroAmount);
             let sender = ensure_signed(_origin)?;
             let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
 
             // Check that sender is allowed to act on behalf of `did`
             ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer), "sender must be a signing key for DID");
             ticker.canonize();
-            // Granularity check
-            ensure!(
-                Self::check_granularity(&ticker, value),
-                "Invalid granularity"
-                );
-            let ticker_did = (ticker, did);
-            ensure!(<BalanceOf<T>>::exists(&ticker_did), "Account does not own this token");
-            let burner_balance = Self::balance_of(&ticker_did);
-            ensure!(burner_balance >= value, "Not enough balance.");
-
-            // Reduce sender's balance
-            let updated_burner_balance = burner_balance
-                .checked_sub(&value)
-                .ok_or("overflow in calculating balance")?;
-            // Check whether the custody allowance remain intact or not
-            Self::_check_custody_allowance(&ticker, did, value)?;
-
-            // verify transfer check
-            ensure!(Self::_is_valid_transfer(&ticker, Some(did), None, value)? == ERC1400_TRANSFER_SUCCESS, "Transfer restrictions failed");
-
-            //Decrease total supply
-            let mut token = Self::token_details(&ticker);
-            token.total_supply = token.total_supply.checked_sub(&value).ok_or("overflow in calculating balance")?;
-
-            Self::_update_checkpoint(&ticker, did, burner_balance);
-
-            <BalanceOf<T>>::insert((ticker, did), updated_burner_balance);
-            <Tokens<T>>::insert(&ticker, token);
-            <statistics::Module<T>>::update_transfer_stats( &ticker, Some(updated_burner_balance), None, value);
-
+            Self::_burn(&ticker, did, value)
+        }
 
-            Self::deposit_event(RawEvent::Redeemed(ticker, did, value));
+        /// Redeems tokens like `redeem` and deducts them from the total issued in a funding round.
+        /// The funding round total never drops below zero. Only the token owner can redeem from a
+        /// funding round.
+        ///
+        /// # Arguments
+        /// * `origin` Signing key of the token owner who wants to redeem the tokens
+        /// * `did` DID of the token owner
+        /// * `ticker` Ticker of the token
+        /// * `value` Amount of the tokens needs to redeem
+        /// * `round` Name of the funding round the tokens were issued in
+        /// * `data` An off chain data blob used to validate the redeem functionality.
+        pub fn redeem_from_funding_round(origin, did: IdentityId, ticker: Ticker, value: T::Balance, round: Vec<u8>, data: Vec<u8>) -> DispatchResult {
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            Self::redeem(origin, did, ticker, value, data)?;
 
+            let ticker_round = (ticker, round);
+            let issued_in_round = Self::issued_in_funding_round(&ticker_round)
+                .checked_sub(&value)
+                .unwrap_or_else(|| 0.into());
+            <IssuedInFundingRound<T>>::insert(&ticker_round, issued_in_round);
             Ok(())
+        }
 
+        /// Redeems the whole balance of a token holder that is not allowed to its custodians, with
+        /// the same checks as `redeem`.
+        ///
+        /// # Arguments
+        /// * `origin` Signing key of the token holder who wants to redeem the tokens
+        /// * `did` DID of the token holder
+        /// * `ticker` Ticker of the token
+        /// * `data` An off chain data blob used to validate the redeem functionality.
+        pub fn redeem_all(origin, did: IdentityId, ticker: Ticker, data: Vec<u8>) -> DispatchResult {
+            ticker.canonize();
+            let free_balance = Self::available_balance(&ticker, did);
+            Self::redeem(origin, did, ticker, free_balance, data)
         }
 
         /// Used to redeem the security tokens by some other DID who has approval
@@ -818,6 +2161,7 @@ decl_module! {
 
             let ticker_from_did_did = (ticker, from_did, did);
             ensure!(<Allowance<T>>::exists(&ticker_from_did_did), "Allowance does not exist");
+            ensure!(!Self::is_allowance_expired(&ticker_from_did_did), Error::<T>::AllowanceExpired);
             let allowance = Self::allowance(&ticker_from_did_did);
             ensure!(allowance >= value, "Not enough allowance");
             // Check whether the custody allowance remain intact or not
@@ -829,11 +2173,13 @@ decl_module! {
             //Decrease total suply
             let mut token = Self::token_details(&ticker);
             token.total_supply = token.total_supply.checked_sub(&value).ok_or("overflow in calculating balance")?;
+            Self::ensure_supply_floor(&ticker, token.total_supply)?;
 
             Self::_update_checkpoint(&ticker, did, burner_balance);
 
-            <Allowance<T>>::insert(&ticker_from_did_did, updated_allowance);
+            Self::set_allowance(&ticker, from_did, did, updated_allowance);
             <BalanceOf<T>>::insert(&ticker_did, updated_burner_balance);
+            Self::_update_holder(&ticker, did);
             <Tokens<T>>::insert(&ticker, token);
             <statistics::Module<T>>::update_transfer_stats( &ticker, Some(updated_burner_balance), None, value);
 
@@ -879,18 +2225,70 @@ decl_module! {
             //Decrease total suply
             let mut token = Self::token_details(&ticker);
             token.total_supply = token.total_supply.checked_sub(&value).ok_or("overflow in calculating balance")?;
+            Self::ensure_supply_floor(&ticker, token.total_supply)?;
 
             Self::_update_checkpoint(&ticker, token_holder_did, burner_balance);
 
             <BalanceOf<T>>::insert(&ticker_token_holder_did, updated_burner_balance);
+            Self::_update_holder(&ticker, token_holder_did);
             <Tokens<T>>::insert(&ticker, token);
             <statistics::Module<T>>::update_transfer_stats( &ticker, Some(updated_burner_balance), None, value);
+            Self::log_controller_action(&ticker, ControllerActionType::Redeem, token_holder_did, None, value, operator_data.clone());
 
             Self::deposit_event(RawEvent::ControllerRedemption(ticker, did, token_holder_did, value, data, operator_data));
 
             Ok(())
         }
 
+        /// Proposes the redemption of `value` tokens of a holder. The tokens are only redeemed once
+        /// the holder approves the request with `approve_redemption` before it expires. A new
+        /// request replaces any pending request of the same holder.
+        ///
+        /// # Arguments
+        /// * `origin` Signing key of the token owner
+        /// * `ticker` Ticker of the token
+        /// * `holder_did` DID of the token holder whose tokens are to be redeemed
+        /// * `value` Amount of the tokens to redeem
+        pub fn request_redemption(origin, ticker: Ticker, holder_did: IdentityId, value: T::Balance) -> DispatchResult {
+            let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+            let did = Self::caller_did(&sender_key)?;
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &Signatory::AccountKey(sender_key)), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "user is not authorized");
+            ensure!(Self::check_granularity(&ticker, value), "Invalid granularity");
+            ensure!(Self::balance_of((ticker, holder_did)) >= value, "Not enough balance.");
+
+            let expiry = <pallet_timestamp::Module<T>>::get()
+                .checked_add(&T::Moment::from(REDEMPTION_REQUEST_LENGTH))
+                .ok_or(Error::<T>::RedemptionExpiryOverflow)?;
+            <PendingRedemptions<T>>::insert((ticker, holder_did), PendingRedemption { value, expiry });
+            Self::deposit_event(RawEvent::RedemptionRequested(ticker, holder_did, value, expiry));
+
+            Ok(())
+        }
+
+        /// Approves the pending redemption request of the token owner, redeeming the requested
+        /// tokens of the holder.
+        ///
+        /// # Arguments
+        /// * `origin` Signing key of the token holder
+        /// * `ticker` Ticker of the token
+        pub fn approve_redemption(origin, ticker: Ticker) -> DispatchResult {
+            let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+            let did = Self::caller_did(&sender_key)?;
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &Signatory::AccountKey(sender_key)), "sender must be a signing key for DID");
+            ticker.canonize();
+            let ticker_did = (ticker, did);
+            ensure!(<PendingRedemptions<T>>::exists(&ticker_did), Error::<T>::NoPendingRedemption);
+            let redemption = Self::pending_redemption(&ticker_did);
+            ensure!(<pallet_timestamp::Module<T>>::get() <= redemption.expiry, Error::<T>::RedemptionRequestExpired);
+
+            Self::_burn(&ticker, did, redemption.value)?;
+            <PendingRedemptions<T>>::remove(&ticker_did);
+
+            Ok(())
+        }
+
         /// Makes an indivisible token divisible. Only called by the token owner
         ///
         /// # Arguments
@@ -926,27 +2324,67 @@ decl_module! {
         /// * `data` Off chain data blob to validate the transfer.
         pub fn can_transfer(_origin, ticker: Ticker, from_did: IdentityId, to_did: IdentityId, value: T::Balance, data: Vec<u8>) {
             ticker.canonize();
-            let mut current_balance: T::Balance = Self::balance_of((ticker, from_did));
-            if current_balance < value {
-                current_balance = 0.into();
-            } else {
-                current_balance = current_balance - value;
-            }
-            if current_balance < Self::total_custody_allowance((ticker, from_did)) {
-                sp_runtime::print("Insufficient balance");
-                Self::deposit_event(RawEvent::CanTransfer(ticker, from_did, to_did, value, data, ERC1400_INSUFFICIENT_BALANCE as u32));
-            } else {
-                match Self::_is_valid_transfer(&ticker, Some(from_did), Some(to_did), value) {
-                    Ok(code) =>
-                    {
-                        Self::deposit_event(RawEvent::CanTransfer(ticker, from_did, to_did, value, data, code as u32));
-                    },
-                    Err(msg) => {
-                        // We emit a generic error with the event whenever there's an internal issue - i.e. captured
-                        // in a string error and not using the status codes
-                        sp_runtime::print(msg);
-                        Self::deposit_event(RawEvent::CanTransfer(ticker, from_did, to_did, value, data, ERC1400_TRANSFER_FAILURE as u32));
-                    }
+            let code = Self::can_transfer_status(&ticker, from_did, to_did, value);
+            Self::deposit_event(RawEvent::CanTransfer(ticker, from_did, to_did, value, data, code));
+        }
+
+        /// Checks whether a transaction with given parameters can take place or not, like
+        /// `can_transfer`, and also reports the balance of the sender that is not allowed to its
+        /// custodians.
+        ///
+        /// # Arguments
+        /// * `_origin` Signing Key of the caller
+        /// * `ticker` Ticker of the token
+        /// * `from_did` DID from whom tokens will be transferred
+        /// * `to_did` DID to whom tokens will be transferred
+        /// * `value` Amount of the tokens
+        /// * `data` Off chain data blob to validate the transfer.
+        pub fn can_transfer_detailed(_origin, ticker: Ticker, from_did: IdentityId, to_did: IdentityId, value: T::Balance, data: Vec<u8>) {
+            ticker.canonize();
+            let code = Self::can_transfer_status(&ticker, from_did, to_did, value);
+            let balance = Self::balance_of((ticker, from_did));
+            let available = Self::available_balance(&ticker, from_did);
+            Self::deposit_event(RawEvent::CanTransferDetailed(ticker, from_did, to_did, value, data, code, available, balance));
+        }
+
+        /// Checks whether the DID of the caller can transfer `value` tokens to `to_did` right
+        /// now, without transferring them, and emits the resulting status code.
+        ///
+        /// # Arguments
+        /// * `origin` Signing key of the sender
+        /// * `ticker` Ticker of the token
+        /// * `to_did` DID to whom tokens would be transferred
+        /// * `value` Amount of the tokens
+        pub fn check_my_transfer(origin, ticker: Ticker, to_did: IdentityId, value: T::Balance) -> DispatchResult {
+            let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+            let did = Self::caller_did(&sender_key)?;
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &Signatory::AccountKey(sender_key)), "sender must be a signing key for DID");
+            ticker.canonize();
+            let code = Self::transfer_preflight_status(&ticker, did, to_did, value);
+            Self::deposit_event(RawEvent::MyTransferCheck(ticker, to_did, value, code));
+            Ok(())
+        }
+
+        /// Checks whether a redemption with given parameters can take place or not
+        /// This function is state less function and used to validate the redemption before actual redeem call.
+        ///
+        /// # Arguments
+        /// * `_origin` Signing Key of the caller
+        /// * `ticker` Ticker of the token
+        /// * `from_did` DID whose tokens will be redeemed
+        /// * `value` Amount of the tokens
+        /// * `data` Off chain data blob to validate the redemption.
+        pub fn can_redeem(_origin, ticker: Ticker, from_did: IdentityId, value: T::Balance, data: Vec<u8>) {
+            ticker.canonize();
+            match Self::_is_valid_redeem(&ticker, from_did, value) {
+                Ok(code) => {
+                    Self::deposit_event(RawEvent::CanRedeem(ticker, from_did, value, data, code as u32));
+                },
+                Err(msg) => {
+                    // We emit a generic error with the event whenever there's an internal issue - i.e. captured
+                    // in a string error and not using the status codes
+                    sp_runtime::print(msg);
+                    Self::deposit_event(RawEvent::CanRedeem(ticker, from_did, value, data, ERC1400_TRANSFER_FAILURE as u32));
                 }
             }
         }
@@ -969,6 +2407,30 @@ decl_module! {
             Ok(())
         }
 
+        /// Transfers tokens like `transfer` and stores `memo` on-chain under the next transfer
+        /// memo sequence number of the ticker, so that it can be retrieved with `transfer_memo`.
+        ///
+        /// # Arguments
+        /// * `origin` Signing key of the sender
+        /// * `did` DID from whom tokens will be transferred
+        /// * `ticker` Ticker of the token
+        /// * `to_did` DID to whom tokens will be transferred
+        /// * `value` Amount of the tokens
+        /// * `memo` Memo of the transfer, up to `MAX_TRANSFER_MEMO_LENGTH` bytes
+        pub fn transfer_with_memo(origin, did: IdentityId, ticker: Ticker, to_did: IdentityId, value: T::Balance, memo: Vec<u8>) -> DispatchResult {
+            ensure!(memo.len() <= MAX_TRANSFER_MEMO_LENGTH, Error::<T>::TransferMemoTooLong);
+            ticker.canonize();
+            let memo_id = Self::transfer_memo_count(&ticker)
+                .checked_add(1)
+                .ok_or("transfer memo count overflowed")?;
+            Self::transfer(origin, did, ticker, to_did, value)?;
+
+            <TransferMemoCount>::insert(&ticker, memo_id);
+            <TransferMemos>::insert((ticker, memo_id), memo);
+            Self::deposit_event(RawEvent::TransferWithMemo(ticker, did, to_did, value, memo_id));
+            Ok(())
+        }
+
         /// An ERC1594 transfer_from with data
         /// This function can be used by the exchanges of other third parties to dynamically validate the transaction
         /// by passing the data blob
@@ -1016,9 +2478,9 @@ decl_module! {
 
             let ticker_did = <identity::Module<T>>::get_token_did(&ticker)?;
             let signer = Signatory::from(ticker_did);
-            documents.into_iter().for_each(|doc| {
-                <identity::Module<T>>::add_link(signer, LinkData::DocumentOwned(doc), None);
-            });
+            for doc in documents {
+                <identity::Module<T>>::add_link(signer, LinkData::DocumentOwned(doc), None)?;
+            }
 
             Ok(())
         }
@@ -1073,6 +2535,36 @@ decl_module! {
             Ok(())
         }
 
+        /// Updates a single document of the given token, keeping its id. Only be called by the token owner
+        ///
+        /// # Arguments
+        /// * `origin` Signing key of the token owner
+        /// * `did` DID of the token owner
+        /// * `ticker` Ticker of the token
+        /// * `doc_id` Id of the document link to update
+        /// * `document` New contents of the document
+        pub fn update_document(origin, did: IdentityId, ticker: Ticker, doc_id: u64, document: Document) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let sender_signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &sender_signer), "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "caller is not the owner of this asset");
+
+            let ticker_did = <identity::Module<T>>::get_token_did(&ticker)?;
+            let signer = Signatory::from(ticker_did);
+            ensure!(<identity::Links<T>>::exists((signer, doc_id)), Error::<T>::NotADocument);
+            match <identity::Module<T>>::links((signer, doc_id)).link_data {
+                LinkData::DocumentOwned(_) => {}
+                _ => return Err(Error::<T>::NotADocument.into()),
+            }
+            <identity::Module<T>>::update_link(signer, doc_id, LinkData::DocumentOwned(document));
+
+            Self::deposit_event(RawEvent::DocumentUpdated(ticker, doc_id));
+            Ok(())
+        }
+
         /// ERC-2258 Implementation
 
         /// Used to increase the allowance for a given custodian
@@ -1152,47 +2644,299 @@ decl_module! {
             Ok(())
         }
 
-        /// Used to transfer the tokens by the approved custodian
+        /// Used to transfer the tokens by the approved custodian
+        ///
+        /// # Arguments
+        /// * `origin` Signing key of the custodian
+        /// * `ticker` Ticker of the token
+        /// * `holder_did` DID of the token holder (i.e whom balance get reduced)
+        /// * `custodian_did` DID of the custodian (i.e who has the valid approved allowance)
+        /// * `receiver_did` DID of the receiver
+        /// * `value` Amount of tokens need to transfer
+        pub fn transfer_by_custodian(
+            origin,
+            ticker: Ticker,
+            holder_did: IdentityId,
+            custodian_did: IdentityId,
+            receiver_did: IdentityId,
+            value: T::Balance
+        ) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let sender_signer = Signatory::AccountKey( AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(
+                <identity::Module<T>>::is_signer_authorized(custodian_did, &sender_signer),
+                "sender must be a signing key for DID"
+            );
+            ticker.canonize();
+            // Check whether the custodian has enough allowance or not
+            ensure!(Self::custodian_allowance((ticker, holder_did, custodian_did)) >= value, "Insufficient allowance");
+            // Validate the transfer
+            Self::ensure_valid_transfer(&ticker, Some(holder_did), Some(receiver_did), value)?;
+            Self::_transfer_by_custodian(&ticker, custodian_did, holder_did, receiver_did, value)
+        }
+
+        /// Used by a custodian to make several transfers on behalf of the token holders in one go.
+        /// Either all transfers succeed or none of them is made.
+        ///
+        /// # Arguments
+        /// * `origin` Signing key of the custodian
+        /// * `ticker` Ticker of the token
+        /// * `custodian_did` DID of the custodian (i.e whom allowance provided)
+        /// * `transfers` Array of (holder DID, receiver DID, amount)
+        pub fn batch_transfer_by_custodian(
+            origin,
+            ticker: Ticker,
+            custodian_did: IdentityId,
+            transfers: Vec<(IdentityId, IdentityId, T::Balance)>
+        ) -> DispatchResult {
+            Self::ensure_batch_size(transfers.len())?;
+            let sender = ensure_signed(origin)?;
+            let sender_signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(
+                <identity::Module<T>>::is_signer_authorized(custodian_did, &sender_signer),
+                "sender must be a signing key for DID"
+            );
+            ticker.canonize();
+
+            // Total value transferred per holder, used to validate allowances across legs, and
+            // received per receiver, used to validate the transfer restrictions and automatic
+            // custody of the receiver against the cumulative amount.
+            let mut totals: Vec<(IdentityId, T::Balance)> = Vec::new();
+            let mut received: Vec<(IdentityId, T::Balance)> = Vec::new();
+            let mut senders: Vec<(IdentityId, IdentityId)> = Vec::new();
+            for (holder_did, receiver_did, value) in &transfers {
+                Self::ensure_transferable(&ticker, *holder_did, *receiver_did, *value)?;
+                if !senders.contains(&(*holder_did, *receiver_did)) {
+                    senders.push((*holder_did, *receiver_did));
+                }
+                match totals.iter_mut().find(|(did, _)| *did == *holder_did) {
+                    Some((_, total)) => {
+                        *total = total.checked_add(value).ok_or("overflow in calculating balance")?;
+                    },
+                    None => totals.push((*holder_did, *value)),
+                }
+                match received.iter_mut().find(|(did, _)| *did == *receiver_did) {
+                    Some((_, total)) => {
+                        *total = total.checked_add(value).ok_or("overflow in calculating balance")?;
+                    },
+                    None => received.push((*receiver_did, *value)),
+                }
+            }
+            for (holder_did, total) in &totals {
+                ensure!(Self::custodian_allowance((ticker, *holder_did, custodian_did)) >= *total, "Insufficient allowance");
+                ensure!(Self::balance_of((ticker, *holder_did)) >= *total, "Not enough balance.");
+            }
+            for (receiver_did, total) in &received {
+                for (holder_did, _) in senders.iter().filter(|(_, did)| did == receiver_did) {
+                    Self::ensure_valid_transfer(&ticker, Some(*holder_did), Some(*receiver_did), *total)?;
+                }
+                Self::ensure_received_auto_custody(&ticker, *receiver_did, *total)?;
+            }
+
+            for (holder_did, receiver_did, value) in transfers {
+                Self::_transfer_by_custodian(&ticker, custodian_did, holder_did, receiver_did, value)?;
+            }
+            Ok(())
+        }
+
+        /// Used to move tokens of a holder into the escrow of a custodian. The tokens are held by
+        /// the escrow DID of the custodian and only the custodian can release them.
+        /// The custodian is subject to the same limits as a custodian with an allowance, and the
+        /// deposit has to pass the transfer restrictions from the holder to the custodian.
+        ///
+        /// # Arguments
+        /// * `origin` Signing key of the token holder
+        /// * `ticker` Ticker of the token
+        /// * `holder_did` DID of the token holder
+        /// * `custodian_did` DID of the custodian of the escrow
+        /// * `value` Amount of tokens moved into escrow
+        pub fn transfer_to_escrow(
+            origin,
+            ticker: Ticker,
+            holder_did: IdentityId,
+            custodian_did: IdentityId,
+            value: T::Balance
+        ) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let sender_signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(
+                <identity::Module<T>>::is_signer_authorized(holder_did, &sender_signer),
+                "sender must be a signing key for DID"
+            );
+            ticker.canonize();
+            ensure!(!value.is_zero(), Error::<T>::ZeroAmount);
+            ensure!(!Self::is_operation_paused(&ticker, PAUSE_TRANSFER), Error::<T>::OperationPaused);
+            ensure!(holder_did != custodian_did, Error::<T>::SelfCustodyNotAllowed);
+            ensure!(<identity::DidRecords>::exists(custodian_did), "Invalid custodian DID");
+            let custodians = Self::escrow_custodians_of((ticker, holder_did));
+            ensure!(
+                custodians.contains(&custodian_did)
+                    || custodians.len() < T::MaxCustodiansPerHolder::get() as usize,
+                Error::<T>::TooManyCustodians
+            );
+            Self::_check_custody_allowance(&ticker, holder_did, value)?;
+            let escrow_key = (ticker, custodian_did, holder_did);
+            let new_escrow_balance = Self::escrow_balance(&escrow_key)
+                .checked_add(&value)
+                .ok_or("overflow in calculating escrow balance")?;
+            ensure!(
+                Self::is_within_allowance_cap(&ticker, new_escrow_balance),
+                Error::<T>::AllowanceCapExceeded
+            );
+            Self::ensure_valid_transfer(&ticker, Some(holder_did), Some(custodian_did), value)?;
+            Self::_transfer(&ticker, holder_did, Self::escrow_did(&ticker, custodian_did), value)?;
+            <EscrowBalance<T>>::insert(&escrow_key, new_escrow_balance);
+            <EscrowCustodiansOf>::mutate((ticker, holder_did), |custodians| {
+                if !custodians.contains(&custodian_did) {
+                    custodians.push(custodian_did);
+                }
+            });
+            Self::deposit_event(RawEvent::EscrowDeposited(ticker, custodian_did, holder_did, value));
+            Ok(())
+        }
+
+        /// Used by a custodian to release tokens that a holder moved into its escrow.
         ///
         /// # Arguments
         /// * `origin` Signing key of the custodian
         /// * `ticker` Ticker of the token
-        /// * `holder_did` DID of the token holder (i.e whom balance get reduced)
-        /// * `custodian_did` DID of the custodian (i.e who has the valid approved allowance)
-        /// * `receiver_did` DID of the receiver
-        /// * `value` Amount of tokens need to transfer
-        pub fn transfer_by_custodian(
+        /// * `holder_did` DID of the token holder who moved the tokens into escrow
+        /// * `to_did` DID of the receiver of the released tokens
+        /// * `value` Amount of tokens released
+        pub fn release_escrow(
             origin,
             ticker: Ticker,
             holder_did: IdentityId,
-            custodian_did: IdentityId,
-            receiver_did: IdentityId,
+            to_did: IdentityId,
             value: T::Balance
         ) -> DispatchResult {
-            let sender = ensure_signed(origin)?;
-            let sender_signer = Signatory::AccountKey( AccountKey::try_from(sender.encode())?);
-            // Check that sender is allowed to act on behalf of `did`
-            ensure!(
-                <identity::Module<T>>::is_signer_authorized(custodian_did, &sender_signer),
-                "sender must be a signing key for DID"
-            );
+            let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+            let custodian_did = Self::caller_did(&sender_key)?;
             ticker.canonize();
-            let mut custodian_allowance = Self::custodian_allowance((ticker, holder_did, custodian_did));
-            // Check whether the custodian has enough allowance or not
-            ensure!(custodian_allowance >= value, "Insufficient allowance");
-            // using checked_sub (safe math) to avoid underflow
-            custodian_allowance = custodian_allowance.checked_sub(&value).ok_or("underflow in calculating allowance")?;
-            // using checked_sub (safe math) to avoid underflow
-            let new_total_allowance = Self::total_custody_allowance((ticker, holder_did))
+            ensure!(!Self::is_operation_paused(&ticker, PAUSE_TRANSFER), Error::<T>::OperationPaused);
+            // The tokens are released on behalf of the holder, so its freeze applies.
+            ensure!(!Self::is_outgoing_frozen(&ticker, holder_did), Error::<T>::HolderFrozen);
+            let escrow_key = (ticker, custodian_did, holder_did);
+            let new_escrow_balance = Self::escrow_balance(&escrow_key)
                 .checked_sub(&value)
-                .ok_or("underflow in calculating the total allowance")?;
-            // Validate the transfer
-            ensure!(Self::_is_valid_transfer(&ticker, Some(holder_did), Some(receiver_did), value)? == ERC1400_TRANSFER_SUCCESS, "Transfer restrictions failed");
-            Self::_transfer(&ticker, holder_did, receiver_did, value)?;
-            // Update Storage of allowance
-            <CustodianAllowance<T>>::insert((ticker, custodian_did, holder_did), &custodian_allowance);
-            <TotalCustodyAllowance<T>>::insert((ticker, holder_did), new_total_allowance);
-            Self::deposit_event(RawEvent::CustodyTransfer(ticker, custodian_did, holder_did, receiver_did, value));
+                .ok_or(Error::<T>::InsufficientEscrowBalance)?;
+            // Validate the transfer as if it was made by the holder
+            Self::ensure_valid_transfer(&ticker, Some(holder_did), Some(to_did), value)?;
+            Self::_transfer(&ticker, Self::escrow_did(&ticker, custodian_did), to_did, value)?;
+            <EscrowBalance<T>>::insert(&escrow_key, new_escrow_balance);
+            if new_escrow_balance.is_zero() {
+                <EscrowCustodiansOf>::mutate((ticker, holder_did), |custodians| {
+                    custodians.retain(|did| *did != custodian_did)
+                });
+            }
+            Self::deposit_event(RawEvent::EscrowReleased(ticker, custodian_did, holder_did, to_did, value));
+            Ok(())
+        }
+
+        /// Recomputes the total custody allowance of a token holder from the allowances of its
+        /// custodians and rewrites it if it was out of sync. Only called by root or the token owner.
+        ///
+        /// # Arguments
+        /// * `origin` Root or signing key of the token owner
+        /// * `ticker` Ticker of the token
+        /// * `holder_did` DID of the token holder
+        pub fn repair_total_custody_allowance(origin, ticker: Ticker, holder_did: IdentityId) -> DispatchResult {
+            ticker.canonize();
+            if ensure_root(origin.clone()).is_err() {
+                let sender_key = AccountKey::try_from(ensure_signed(origin)?.encode())?;
+                let did = Self::caller_did(&sender_key)?;
+                ensure!(Self::is_owner(&ticker, did), Error::<T>::UnAuthorized);
+            }
+            let ticker_holder_did = (ticker, holder_did);
+            let mut total_allowance: T::Balance = 0.into();
+            for custodian_did in Self::custodians_of(&ticker_holder_did) {
+                total_allowance = total_allowance
+                    .checked_add(&Self::custodian_allowance((ticker, holder_did, custodian_did)))
+                    .ok_or("total custody allowance get overflowed")?;
+            }
+            let old_total_allowance = Self::total_custody_allowance(&ticker_holder_did);
+            if old_total_allowance != total_allowance {
+                <TotalCustodyAllowance<T>>::insert(&ticker_holder_did, total_allowance);
+                Self::deposit_event(RawEvent::TotalCustodyAllowanceRepaired(ticker, holder_did, old_total_allowance, total_allowance));
+            }
+            Ok(())
+        }
+
+        /// Adds the given custodians of a token holder to `CustodiansOf` if they have a custody
+        /// allowance and are not listed yet. It backfills allowances granted before the index
+        /// existed. Only called by root.
+        ///
+        /// # Arguments
+        /// * `origin` Root
+        /// * `ticker` Ticker of the token
+        /// * `holder_did` DID of the token holder
+        /// * `custodians` DIDs of the custodians to add
+        pub fn backfill_custodians_of(origin, ticker: Ticker, holder_did: IdentityId, custodians: Vec<IdentityId>) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::ensure_batch_size(custodians.len())?;
+            ticker.canonize();
+            <CustodiansOf>::mutate((ticker, holder_did), |listed| {
+                for custodian_did in custodians {
+                    if !listed.contains(&custodian_did)
+                        && !Self::custodian_allowance((ticker, holder_did, custodian_did)).is_zero()
+                    {
+                        listed.push(custodian_did);
+                    }
+                }
+            });
+            Ok(())
+        }
+
+        /// Adds the given escrow custodians of a token holder to `EscrowCustodiansOf` if they hold
+        /// tokens of the holder in escrow and are not listed yet. It backfills escrows funded
+        /// before the index existed. Only called by root.
+        ///
+        /// # Arguments
+        /// * `origin` Root
+        /// * `ticker` Ticker of the token
+        /// * `holder_did` DID of the token holder
+        /// * `custodians` DIDs of the escrow custodians to add
+        pub fn backfill_escrow_custodians_of(origin, ticker: Ticker, holder_did: IdentityId, custodians: Vec<IdentityId>) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::ensure_batch_size(custodians.len())?;
+            ticker.canonize();
+            <EscrowCustodiansOf>::mutate((ticker, holder_did), |listed| {
+                for custodian_did in custodians {
+                    if !listed.contains(&custodian_did)
+                        && !Self::escrow_balance((ticker, custodian_did, holder_did)).is_zero()
+                    {
+                        listed.push(custodian_did);
+                    }
+                }
+            });
+            Ok(())
+        }
+
+        /// Adds the given spenders of a token holder to `SpendersOf` if they have an allowance
+        /// and are not listed yet. It backfills allowances granted before the index existed.
+        /// Only called by root.
+        ///
+        /// # Arguments
+        /// * `origin` Root
+        /// * `ticker` Ticker of the token
+        /// * `owner_did` DID of the token holder who granted the allowances
+        /// * `spenders` DIDs of the spenders to add
+        pub fn backfill_spenders_of(origin, ticker: Ticker, owner_did: IdentityId, spenders: Vec<IdentityId>) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::ensure_batch_size(spenders.len())?;
+            ticker.canonize();
+            <SpendersOf>::mutate((ticker, owner_did), |listed| {
+                for spender_did in spenders {
+                    if !listed.contains(&spender_did)
+                        && !Self::allowance((ticker, owner_did, spender_did)).is_zero()
+                    {
+                        listed.push(spender_did);
+                    }
+                }
+            });
             Ok(())
         }
 
@@ -1216,6 +2960,60 @@ decl_module! {
             Ok(())
         }
 
+        /// Sets the time window in which tokens can be issued while a funding round is the
+        /// current one. Issuance fails outside the window.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner DID.
+        /// * `did` - the token owner DID.
+        /// * `ticker` - the ticker of the token.
+        /// * `name` - the name of the funding round.
+        /// * `start` - the first moment at which tokens can be issued.
+        /// * `end` - the last moment at which tokens can be issued.
+        pub fn set_funding_round_window(origin, did: IdentityId, ticker: Ticker, name: Vec<u8>, start: T::Moment, end: T::Moment) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer),
+                    "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "DID is not of the asset owner");
+            ensure!(start <= end, Error::<T>::InvalidFundingRoundWindow);
+            <FundingRoundWindow<T>>::insert((ticker, name.clone()), (start, end));
+            Self::deposit_event(RawEvent::FundingRoundWindowSet(ticker, name, start, end));
+            Ok(())
+        }
+
+        /// Adds the amount issued in funding round `from_round` to `into_round` and zeroes the
+        /// total of `from_round`, e.g. to fix issuances made under a mislabelled round.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner DID.
+        /// * `did` - the token owner DID.
+        /// * `ticker` - the ticker of the token.
+        /// * `from_round` - the funding round whose total is moved.
+        /// * `into_round` - the funding round that receives the total.
+        pub fn merge_funding_rounds(origin, did: IdentityId, ticker: Ticker, from_round: Vec<u8>, into_round: Vec<u8>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            // Check that sender is allowed to act on behalf of `did`
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &signer),
+                    "sender must be a signing key for DID");
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, did), "DID is not of the asset owner");
+            ensure!(from_round != into_round, Error::<T>::SameFundingRound);
+            let from_ticker_round = (ticker, from_round.clone());
+            let into_ticker_round = (ticker, into_round.clone());
+            let merged = Self::issued_in_funding_round(&from_ticker_round);
+            let into_total = Self::issued_in_funding_round(&into_ticker_round)
+                .checked_add(&merged)
+                .ok_or("overflow in calculating funding round total")?;
+            <IssuedInFundingRound<T>>::insert(&into_ticker_round, into_total);
+            <IssuedInFundingRound<T>>::remove(&from_ticker_round);
+            Self::deposit_event(RawEvent::FundingRoundsMerged(ticker, from_round, into_round, merged));
+            Ok(())
+        }
+
         /// Updates the asset identifiers. Can only be called by the token owner.
         ///
         /// # Arguments
@@ -1236,13 +3034,95 @@ decl_module! {
                     "sender must be a signing key for DID");
             ticker.canonize();
             ensure!(Self::is_owner(&ticker, did), "user is not authorized");
-            for (typ, val) in &identifiers {
-                <Identifiers>::insert((ticker, typ.clone()), val.clone());
-            }
+            ensure!(
+                identifiers.iter().all(|(typ, val)| Self::is_valid_identifier(typ, val)),
+                Error::<T>::InvalidIdentifier
+            );
+            Self::ensure_identifiers_within_limit(&ticker, identifiers.iter().map(|(typ, _)| typ))?;
+            Self::insert_identifiers(&ticker, &identifiers);
             Self::deposit_event(RawEvent::IdentifiersUpdated(ticker, identifiers));
             Ok(())
         }
 
+        /// Batch version of `update_identifiers`. Either all the updates are applied or none.
+        ///
+        /// # Arguments
+        /// * `origin` - the signing key of the token owner
+        /// * `did` - the DID of the owner of all the tokens
+        /// * `updates` - the asset identifiers to be updated for each ticker
+        pub fn batch_update_identifiers(
+            origin,
+            did: IdentityId,
+            updates: Vec<(Ticker, Vec<(IdentifierType, Vec<u8>)>)>
+        ) -> DispatchResult {
+            Self::ensure_batch_size(updates.len())?;
+            let sender = ensure_signed(origin)?;
+            let sender_signer = Signatory::AccountKey(AccountKey::try_from(sender.encode())?);
+            ensure!(<identity::Module<T>>::is_signer_authorized(did, &sender_signer),
+                    "sender must be a signing key for DID");
+            // Validate all the updates before applying any of them.
+            for (ticker, identifiers) in &updates {
+                ticker.canonize();
+                ensure!(Self::is_owner(ticker, did), "user is not authorized");
+                ensure!(
+                    identifiers.iter().all(|(typ, val)| Self::is_valid_identifier(typ, val)),
+                    Error::<T>::InvalidIdentifier
+                );
+                // Identifiers of a ticker listed more than once are counted together.
+                let types = updates
+                    .iter()
+                    .filter(|(other, _)| other == ticker)
+                    .flat_map(|(_, other_identifiers)| other_identifiers.iter().map(|(typ, _)| typ));
+                Self::ensure_identifiers_within_limit(ticker, types)?;
+            }
+            for (ticker, identifiers) in updates {
+                Self::insert_identifiers(&ticker, &identifiers);
+                Self::deposit_event(RawEvent::IdentifiersUpdated(ticker, identifiers));
+            }
+            Ok(())
+        }
+
+        /// Adds the given tokens to `TokenNames` under their current name if they are not listed
+        /// yet. It backfills tokens created before the index existed. Tokens that do not exist are
+        /// skipped. Only called by root.
+        ///
+        /// # Arguments
+        /// * `origin` Root
+        /// * `tickers` Tickers of the tokens to add
+        pub fn backfill_token_names(origin, tickers: Vec<Ticker>) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::ensure_batch_size(tickers.len())?;
+            for ticker in tickers {
+                ticker.canonize();
+                if <Tokens<T>>::exists(&ticker) {
+                    Self::add_token_name(&Self::token_details(&ticker).name, &ticker);
+                }
+            }
+            Ok(())
+        }
+
+        /// Adds the given identifier types of a token to `IdentifierTypesOf` if the token has an
+        /// identifier of that type and it is not listed yet. It backfills identifiers set before
+        /// the index existed. Only called by root.
+        ///
+        /// # Arguments
+        /// * `origin` Root
+        /// * `ticker` Ticker of the token
+        /// * `types` Identifier types to add
+        pub fn backfill_identifier_types(origin, ticker: Ticker, types: Vec<IdentifierType>) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::ensure_batch_size(types.len())?;
+            ticker.canonize();
+            <IdentifierTypesOf>::mutate(ticker, |listed| {
+                for typ in types {
+                    if !listed.contains(&typ) && <Identifiers>::exists((ticker, typ.clone())) {
+                        listed.push(typ);
+                    }
+                }
+            });
+            Ok(())
+        }
+
         /// Whitelisting the Smart-Extension address for a given ticker
         ///
         /// # Arguments
@@ -1252,16 +3132,7 @@ decl_module! {
         pub fn add_extension(origin, ticker: Ticker, extension_details: SmartExtension<T::AccountId>) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             let sender_key = AccountKey::try_from(sender.encode())?;
-            let my_did =  match <identity::Module<T>>::current_did() {
-                Some(x) => x,
-                None => {
-                    if let Some(did) = <identity::Module<T>>::get_identity(&sender_key) {
-                        did
-                    } else {
-                        return Err(Error::<T>::DIDNotFound.into());
-                    }
-                }
-            };
+            let my_did = Self::caller_did(&sender_key)?;
             ticker.canonize();
             ensure!(Self::is_owner(&ticker, my_did), Error::<T>::UnAuthorized);
 
@@ -1271,6 +3142,7 @@ decl_module! {
             <Extensions<T>>::mutate((ticker, &extension_details.extension_type), |ids| {
                 ids.push(extension_details.extension_id.clone())
             });
+            <ExtensionTickers<T>>::mutate(&extension_details.extension_id, |tickers| tickers.push(ticker));
             Self::deposit_event(RawEvent::ExtensionAdded(ticker, extension_details.extension_id, extension_details.extension_name, extension_details.extension_type));
             Ok(())
         }
@@ -1284,16 +3156,7 @@ decl_module! {
         pub fn archive_extension(origin, ticker: Ticker, extension_id: T::AccountId) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             let sender_key = AccountKey::try_from(sender.encode())?;
-            let my_did =  match <identity::Module<T>>::current_did() {
-                Some(x) => x,
-                None => {
-                    if let Some(did) = <identity::Module<T>>::get_identity(&sender_key) {
-                        did
-                    } else {
-                        return Err(Error::<T>::DIDNotFound.into());
-                    }
-                }
-            };
+            let my_did = Self::caller_did(&sender_key)?;
             ticker.canonize();
             ensure!(Self::is_owner(&ticker, my_did), Error::<T>::UnAuthorized);
             ensure!(<ExtensionDetails<T>>::exists((ticker, &extension_id)), "Smart extension not exists");
@@ -1313,16 +3176,7 @@ decl_module! {
         pub fn unarchive_extension(origin, ticker: Ticker, extension_id: T::AccountId) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             let sender_key = AccountKey::try_from(sender.encode())?;
-            let my_did =  match <identity::Module<T>>::current_did() {
-                Some(x) => x,
-                None => {
-                    if let Some(did) = <identity::Module<T>>::get_identity(&sender_key) {
-                        did
-                    } else {
-                        return Err(Error::<T>::DIDNotFound.into());
-                    }
-                }
-            };
+            let my_did = Self::caller_did(&sender_key)?;
             ticker.canonize();
             ensure!(Self::is_owner(&ticker, my_did), Error::<T>::UnAuthorized);
             ensure!(<ExtensionDetails<T>>::exists((ticker, &extension_id)), "Smart extension not exists");
@@ -1332,6 +3186,50 @@ decl_module! {
             Self::deposit_event(RawEvent::ExtensionUnArchived(ticker, extension_id));
             Ok(())
         }
+
+        /// Removes the extension, either active or archived, from the asset.
+        ///
+        /// # Arguments
+        /// * `origin` - Signatory who owns the ticker/asset.
+        /// * `ticker` - Ticker symbol of the asset.
+        /// * `extension_id` - AccountId of the extension that need to be removed
+        pub fn remove_extension(origin, ticker: Ticker, extension_id: T::AccountId) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let sender_key = AccountKey::try_from(sender.encode())?;
+            let my_did = Self::caller_did(&sender_key)?;
+            ticker.canonize();
+            ensure!(Self::is_owner(&ticker, my_did), Error::<T>::UnAuthorized);
+            ensure!(<ExtensionDetails<T>>::exists((ticker, &extension_id)), "Smart extension not exists");
+            let extension_type = <ExtensionDetails<T>>::take((ticker, &extension_id)).extension_type;
+            <Extensions<T>>::mutate((ticker, &extension_type), |ids| ids.retain(|id| *id != extension_id));
+            <ExtensionTickers<T>>::mutate(&extension_id, |tickers| tickers.retain(|t| *t != ticker));
+            Self::deposit_event(RawEvent::ExtensionRemoved(ticker, extension_id));
+            Ok(())
+        }
+
+        /// Adds `ticker` to the `ExtensionTickers` of the given smart extensions if they are
+        /// attached to it and it is not listed yet. It backfills extensions added before the
+        /// index existed. Only called by root.
+        ///
+        /// # Arguments
+        /// * `origin` Root
+        /// * `ticker` Ticker of the token
+        /// * `extension_ids` AccountIds of the smart extensions attached to the token
+        pub fn backfill_extension_tickers(origin, ticker: Ticker, extension_ids: Vec<T::AccountId>) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::ensure_batch_size(extension_ids.len())?;
+            ticker.canonize();
+            for extension_id in extension_ids {
+                if <ExtensionDetails<T>>::exists((ticker, &extension_id)) {
+                    <ExtensionTickers<T>>::mutate(&extension_id, |tickers| {
+                        if !tickers.contains(&ticker) {
+                            tickers.push(ticker);
+                        }
+                    });
+                }
+            }
+            Ok(())
+        }
     }
 }
 
@@ -1360,6 +3258,9 @@ decl_event! {
         /// event for when a forced redemption takes place
         /// ticker, controller DID, token holder DID, value, data, operator data
         ControllerRedemption(Ticker, IdentityId, IdentityId, Balance, Vec<u8>, Vec<u8>),
+        /// An event emitted when the token owner proposes the redemption of a holder's tokens.
+        /// Parameters: ticker, holder DID, value, expiry of the request.
+        RedemptionRequested(Ticker, IdentityId, Balance, Moment),
         /// Event for creation of the asset
         /// ticker, total supply, owner DID, divisibility, asset type
         IssuedToken(Ticker, Balance, IdentityId, bool, AssetType),
@@ -1374,10 +3275,23 @@ decl_event! {
         /// 0 - OK
         /// 1,2... - Error, meanings TBD
         CanTransfer(Ticker, IdentityId, IdentityId, Balance, Vec<u8>, u32),
+        /// An event emitted by `check_my_transfer`.
+        /// Parameters: ticker, receiver DID, value, ERC1066 status code.
+        MyTransferCheck(Ticker, IdentityId, Balance, u32),
+        /// can_transfer_detailed() output
+        /// ticker, from_did, to_did, value, data, ERC1066 status, balance of from_did not allowed
+        /// to custodians, balance of from_did
+        CanTransferDetailed(Ticker, IdentityId, IdentityId, Balance, Vec<u8>, u32, Balance, Balance),
+        /// can_redeem() output
+        /// ticker, from_did, value, data, ERC1066 status
+        CanRedeem(Ticker, IdentityId, Balance, Vec<u8>, u32),
         /// An additional event to Transfer; emitted when transfer_with_data is called; similar to
         /// Transfer with data added at the end.
         /// ticker, from DID, to DID, value, data
         TransferWithData(Ticker, IdentityId, IdentityId, Balance, Vec<u8>),
+        /// An additional event to Transfer; emitted when transfer_with_memo is called.
+        /// ticker, from DID, to DID, value, sequence number of the stored memo
+        TransferWithMemo(Ticker, IdentityId, IdentityId, Balance, u64),
         /// is_issuable() output
         /// ticker, return value (true if issuable)
         IsIssuable(Ticker, bool),
@@ -1387,9 +3301,18 @@ decl_event! {
         /// emit when tokens transferred by the custodian
         /// ticker, custodian did, holder/from did, to did, amount
         CustodyTransfer(Ticker, IdentityId, IdentityId, IdentityId, Balance),
+        /// emit when tokens are moved into the escrow of a custodian
+        /// ticker, custodian did, holder did, amount
+        EscrowDeposited(Ticker, IdentityId, IdentityId, Balance),
+        /// emit when the custodian releases tokens held in escrow
+        /// ticker, custodian did, holder did, to did, amount
+        EscrowReleased(Ticker, IdentityId, IdentityId, IdentityId, Balance),
         /// emit when allowance get increased
-        /// ticker, holder did, custodian did, oldAllowance, newAllowance
-        CustodyAllowanceChanged(Ticker, IdentityId, IdentityId, Balance, Balance),
+        /// ticker, holder did, custodian did, oldAllowance, newAllowance, nonce of the change
+        CustodyAllowanceChanged(Ticker, IdentityId, IdentityId, Balance, Balance, u32),
+        /// emit when the total custody allowance of a holder was out of sync and got rewritten
+        /// ticker, holder did, old total allowance, repaired total allowance
+        TotalCustodyAllowanceRepaired(Ticker, IdentityId, Balance, Balance),
         /// emit when ticker is registered
         /// ticker, ticker owner, expiry
         TickerRegistered(Ticker, IdentityId, Option<Moment>),
@@ -1399,6 +3322,9 @@ decl_event! {
         /// emit when token ownership is transferred
         /// ticker, from, to
         TokenOwnershipTransferred(Ticker, IdentityId, IdentityId),
+        /// A pending token ownership transfer was cancelled by the owner.
+        /// Parameters: ticker, DID of the cancelled recipient.
+        TokenOwnershipTransferCancelled(Ticker, IdentityId),
         /// emit when ticker is registered
         /// ticker, current owner, approved owner
         TickerTransferApproval(Ticker, IdentityId, IdentityId),
@@ -1411,12 +3337,121 @@ decl_event! {
         /// An event emitted when an asset is unfrozen.
         /// Parameter: ticker.
         Unfrozen(Ticker),
+        /// An event emitted when the paused operations of a token change.
+        /// Parameters: ticker, pause flags.
+        OperationPauseFlagsChanged(Ticker, u8),
+        /// An event emitted when the token owner freezes the transfers of a holder.
+        /// Parameters: ticker, holder DID, reason.
+        HolderFrozenByController(Ticker, IdentityId, Vec<u8>),
+        /// An event emitted when the token owner unfreezes the transfers of a holder.
+        /// Parameters: ticker, holder DID, reason.
+        HolderUnfrozenByController(Ticker, IdentityId, Vec<u8>),
+        /// An event emitted when the token owner freezes the transfers of a holder in a direction.
+        /// Parameters: ticker, holder DID, frozen direction.
+        HolderFreezeDirectionSet(Ticker, IdentityId, FreezeDirection),
+        /// An event emitted when the approve allowlist of a token is enabled or disabled.
+        /// Parameters: ticker, enabled.
+        ApproveAllowlistToggled(Ticker, bool),
+        /// An event emitted when a spender is added to or removed from the approve allowlist.
+        /// Parameters: ticker, spender DID, allowed.
+        ApprovedSpenderChanged(Ticker, IdentityId, bool),
+        /// An event emitted when the maximum allowance per spender of a token is changed.
+        /// Parameters: ticker, maximum allowance, or `None` if uncapped.
+        MaxAllowancePerSpenderChanged(Ticker, Option<Balance>),
+        /// An event emitted when the aggregation of transfer events of a token is toggled.
+        /// Parameters: ticker, enabled.
+        AggregateTransferEventsToggled(Ticker, bool),
+        /// An event emitted at the end of a block for the transfers of a token with aggregated
+        /// transfer events.
+        /// Parameters: ticker, number of transfers, total volume.
+        AggregatedTransfers(Ticker, u64, Balance),
+        /// An event emitted when an attribute of a token is set.
+        /// Parameters: ticker, key, value.
+        AttributeSet(Ticker, Vec<u8>, Vec<u8>),
+        /// An event emitted when an attribute of a token is removed.
+        /// Parameters: ticker, key.
+        AttributeRemoved(Ticker, Vec<u8>),
+        /// An event emitted when a checkpoint is created.
+        /// Parameters: ticker, checkpoint id, timestamp.
+        CheckpointCreated(Ticker, u64, Moment),
+        /// An event emitted when the KYC requirement on issuance of a token changes.
+        /// Parameters: ticker, whether a valid KYC of the recipient is required.
+        RequireKycOnIssueChanged(Ticker, bool),
+        /// An event emitted when the KYC requirement on `transfer_from` of a token changes.
+        /// Parameters: ticker, whether a valid KYC of the recipient is required.
+        RequireKycOnTransferFromChanged(Ticker, bool),
+        /// An event emitted when the issuance rounding of a token is enabled or disabled.
+        /// Parameters: ticker, enabled.
+        IssuanceRoundingChanged(Ticker, bool),
+        /// An event emitted when the automatic custodian assignment of a token is enabled or disabled.
+        /// Parameters: ticker, enabled.
+        AutoCustodianChanged(Ticker, bool),
+        /// An event emitted when the secondary classification of a token is updated.
+        /// Parameters: ticker, asset types.
+        ClassificationUpdated(Ticker, Vec<AssetType>),
+        /// An event emitted when the trading start time of a token is changed.
+        /// Parameters: ticker, trading start time.
+        TradingStartTimeChanged(Ticker, Moment),
+        /// An event emitted when the supply floor of a token is changed.
+        /// Parameters: ticker, total supply that redemptions cannot go below.
+        SupplyFloorChanged(Ticker, Balance),
+        /// An event emitted when the lot size of a token is changed.
+        /// Parameters: ticker, lot size.
+        LotSizeChanged(Ticker, Balance),
+        /// An event emitted when transfers of a token to frozen DIDs are blocked or allowed.
+        /// Parameters: ticker, whether transfers to frozen DIDs are rejected.
+        BlockTransfersToFrozenDidsChanged(Ticker, bool),
+        /// An event emitted when the claims required from senders of a token are changed.
+        /// Parameters: ticker, (claim key, acceptable issuers) pairs.
+        RequiredSenderClaimsChanged(Ticker, Vec<(Vec<u8>, Vec<IdentityId>)>),
+        /// An event emitted when the transfer allowlist of a token is enforced or lifted.
+        /// Parameters: ticker, whether the allowlist is enforced.
+        AllowlistEnforcedChanged(Ticker, bool),
+        /// An event emitted when DIDs are added to or removed from the allowlist of a token.
+        /// Parameters: ticker, DIDs, whether they were added.
+        TransferAllowlistChanged(Ticker, Vec<IdentityId>, bool),
+        /// An event emitted when the compliance officer of a token is changed.
+        /// Parameters: ticker, compliance officer DID.
+        ComplianceOfficerChanged(Ticker, Option<IdentityId>),
+        /// An event emitted when the issuance limit of a token is changed.
+        /// Parameters: ticker, percentage of the supply issuable per period, period length.
+        IssuanceLimitChanged(Ticker, Perbill, Moment),
+        /// An event emitted when the longest custom ticker registration is changed.
+        /// Parameters: longest registration length, or `None` if unlimited.
+        MaxTickerRegistrationLengthChanged(Option<Moment>),
+        /// An event emitted when the global KYC requirement of receivers is changed.
+        /// Parameters: whether a valid KYC of the receiver is required.
+        GlobalKycRequiredChanged(bool),
+        /// An event emitted when the freezing of receivers without a valid KYC is changed.
+        /// Parameters: whether receivers without a valid KYC are frozen.
+        AutoFreezeOnKycLossChanged(bool),
+        /// An event emitted when the uniqueness requirement of token names is changed.
+        /// Parameters: whether token names must be unique.
+        EnforceUniqueTokenNamesChanged(bool),
+        /// An event emitted when the maximum number of token creations per block is changed.
+        /// Parameters: maximum token creations per block, or `None` if unlimited.
+        MaxTokensPerBlockChanged(Option<u32>),
+        /// An event emitted when the asset types allowed for new tokens are changed.
+        /// Parameters: allowed asset types, or an empty list if any asset type is allowed.
+        AllowedAssetTypesChanged(Vec<AssetType>),
+        /// An event emitted when the burn DID is changed.
+        /// Parameters: burn DID, or `None` if transfers are never treated as redemptions.
+        BurnDidChanged(Option<IdentityId>),
+        /// An event emitted when a document of a token is updated.
+        /// Parameters: ticker, document id.
+        DocumentUpdated(Ticker, u64),
         /// An event emitted when a token is renamed.
         /// Parameters: ticker, new token name.
         TokenRenamed(Ticker, Vec<u8>),
         /// An event carrying the name of the current funding round of a ticker.
         /// Parameters: ticker, funding round name.
         FundingRound(Ticker, Vec<u8>),
+        /// An event emitted when the issuance window of a funding round is set.
+        /// Parameters: ticker, funding round name, start, end.
+        FundingRoundWindowSet(Ticker, Vec<u8>, Moment, Moment),
+        /// An event emitted when the issuance total of a funding round is merged into another.
+        /// Parameters: ticker, source funding round, target funding round, merged amount.
+        FundingRoundsMerged(Ticker, Vec<u8>, Vec<u8>, Balance),
         /// Emitted when extension is added successfully
         /// ticker, extension AccountId, extension name, type of smart Extension
         ExtensionAdded(Ticker, AccountId, Vec<u8>, SmartExtensionType),
@@ -1426,6 +3461,9 @@ decl_event! {
         /// Emitted when extension get archived
         /// ticker, AccountId
         ExtensionUnArchived(Ticker, AccountId),
+        /// Emitted when extension get removed
+        /// ticker, AccountId
+        ExtensionRemoved(Ticker, AccountId),
     }
 }
 
@@ -1444,7 +3482,95 @@ decl_error! {
         /// when extension already unarchived
         AlreadyUnArchived,
         /// when extension is already added
-        ExtensionAlreadyPresent
+        ExtensionAlreadyPresent,
+        /// An ISIN or CUSIP identifier is malformed
+        InvalidIdentifier,
+        /// The operation is paused for this token
+        OperationPaused,
+        /// The recipient of an issuance does not have a valid KYC
+        RecipientKycInvalid,
+        /// The transfers of the holder are frozen by the token owner
+        HolderFrozen,
+        /// The transfers to the receiver are frozen by the token owner
+        ReceiverFrozen,
+        /// There is no pending ownership transfer of the token to the given DID
+        NoPendingOwnershipTransfer,
+        /// The spender is not in the approve allowlist of the token
+        SpenderNotAllowlisted,
+        /// The batch exceeds the maximum batch size
+        BatchTooLarge,
+        /// The ticker is longer than the maximum ticker length
+        TickerTooLong,
+        /// The ticker is shorter than the minimum ticker length
+        TickerTooShort,
+        /// The requested ticker registration is longer than the maximum registration length
+        TickerRegistrationTooLong,
+        /// The custodian does not hold enough tokens of the holder in escrow
+        InsufficientEscrowBalance,
+        /// The number of checkpoints of the token cannot be increased any further
+        CheckpointOverflow,
+        /// Only the token owner can transfer before the trading start time
+        TradingNotStarted,
+        /// The trading start time has already passed
+        TradingAlreadyStarted,
+        /// The link is not a document of the token
+        NotADocument,
+        /// The recipient does not hold the claim that identifies it
+        RecipientClaimNotHeld,
+        /// The maximum number of tokens created in this block has been reached
+        TokenCreationRateLimited,
+        /// There is no pending redemption request of the token for the holder
+        NoPendingRedemption,
+        /// The redemption request has expired
+        RedemptionRequestExpired,
+        /// The expiry of the redemption request overflows
+        RedemptionExpiryOverflow,
+        /// An issuance amount is zero
+        ZeroIssuanceAmount,
+        /// A transfer or redemption amount is zero
+        ZeroAmount,
+        /// The asset type is not in the list of allowed asset types
+        AssetTypeNotAllowed,
+        /// The transfer memo is longer than the maximum memo length
+        TransferMemoTooLong,
+        /// The redemption would reduce the total supply below the supply floor of the token
+        SupplyFloorBreached,
+        /// Only root can lower the supply floor of a token
+        SupplyFloorDecrease,
+        /// The lot size of a token cannot be zero
+        InvalidLotSize,
+        /// The total supply would exceed the hard maximum supply of the token
+        HardMaxSupplyExceeded,
+        /// The DID of the recipient is frozen
+        RecipientDidFrozen,
+        /// The issuance would exceed the percentage of the supply issuable in the current period
+        IssuanceLimitExceeded,
+        /// The start of a funding round window is after its end
+        InvalidFundingRoundWindow,
+        /// The current funding round does not allow issuance at this time
+        FundingRoundClosed,
+        /// A funding round cannot be merged into itself
+        SameFundingRound,
+        /// The first checkpoint of a range is after its last one
+        InvalidCheckpointRange,
+        /// A token holder cannot be its own custodian
+        SelfCustodyNotAllowed,
+        /// The allowance exceeds the maximum allowance per spender of the token
+        AllowanceCapExceeded,
+        /// The holder already has the maximum number of custodians for the token
+        TooManyCustodians,
+        /// The token would have more identifiers than allowed
+        TooManyIdentifiers,
+        /// Another token already has the name
+        DuplicateTokenName,
+        /// The token assigns custodians automatically on issuance
+        AutoCustodianEnabled,
+        /// The allowance has expired
+        AllowanceExpired,
+        /// The key or value of the token attribute is too long
+        AttributeTooLong,
+        /// The token has no attribute with the given key
+        AttributeNotFound,
     }
 }
 
@@ -1505,6 +3631,15 @@ impl<T: Trait> AcceptTransfer for Module<T> {
 /// Public functions can be called from other modules e.g.: lock and unlock (being called from the tcr module)
 /// All functions in the impl module section are not part of public interface because they are not part of the Call enum
 impl<T: Trait> Module<T> {
+    /// Fails with `BatchTooLarge` if a batch of `len` items exceeds `T::MaxBatchSize`.
+    pub fn ensure_batch_size(len: usize) -> DispatchResult {
+        ensure!(
+            len <= T::MaxBatchSize::get() as usize,
+            Error::<T>::BatchTooLarge
+        );
+        Ok(())
+    }
+
     // Public immutables
     pub fn _is_owner(ticker: &Ticker, did: IdentityId) -> bool {
         let token = Self::token_details(ticker);
@@ -1575,43 +3710,258 @@ impl<T: Trait> Module<T> {
         return TickerRegistrationStatus::Available;
     }
 
+    /// Returns the owners of `ticker` and of its token, and the DID of the asset, or `None` if
+    /// the ticker is not registered.
+    pub fn ownership_info(ticker: &Ticker) -> Option<OwnershipInfo<T::Moment>> {
+        if !<Tickers<T>>::exists(ticker) {
+            return None;
+        }
+        let registration = Self::ticker_registration(ticker);
+        let (token_owner, asset_did) = if <Tokens<T>>::exists(ticker) {
+            (
+                Some(Self::token_details(ticker).owner_did),
+                <identity::Module<T>>::get_token_did(ticker).ok(),
+            )
+        } else {
+            (None, None)
+        };
+        Some(OwnershipInfo {
+            ticker_owner: registration.owner,
+            token_owner,
+            asset_did,
+            expiry: registration.expiry,
+        })
+    }
+
+    /// Returns the DID the caller acts for: the current identity if it is set, otherwise the
+    /// identity linked to `sender_key`.
+    pub fn caller_did(sender_key: &AccountKey) -> StdResult<IdentityId, DispatchError> {
+        match <identity::Module<T>>::current_did() {
+            Some(did) => Ok(did),
+            None => <identity::Module<T>>::get_identity(sender_key)
+                .ok_or_else(|| Error::<T>::DIDNotFound.into()),
+        }
+    }
+
+    /// Registers `ticker` to the DID of `sender` for `registration_length`, or forever if `None`.
+    fn register_ticker_with_length(
+        sender: T::AccountId,
+        ticker: Ticker,
+        registration_length: Option<T::Moment>,
+    ) -> DispatchResult {
+        let sender_key = AccountKey::try_from(sender.encode())?;
+        let signer = Signatory::AccountKey(sender_key.clone());
+        let to_did = Self::caller_did(&sender_key)?;
+
+        ticker.canonize();
+        ensure!(
+            <identity::Module<T>>::is_signer_authorized(to_did, &signer),
+            "sender must be a signing key for DID"
+        );
+
+        ensure!(!<Tokens<T>>::exists(&ticker), "token already created");
+
+        let ticker_config = Self::ticker_registration_config();
+
+        ensure!(
+            ticker.len() <= usize::try_from(ticker_config.max_ticker_length).unwrap_or_default(),
+            Error::<T>::TickerTooLong
+        );
+        ensure!(
+            ticker.len() >= usize::from(Self::min_ticker_length()),
+            Error::<T>::TickerTooShort
+        );
+
+        // Ensure that the ticker is not registered by someone else
+        ensure!(
+            Self::is_ticker_available_or_registered_to(&ticker, to_did)
+                != TickerRegistrationStatus::RegisteredByOther,
+            "ticker registered to someone else"
+        );
+
+        let now = <pallet_timestamp::Module<T>>::get();
+        let expiry = registration_length
+            .map(|length| {
+                now.checked_add(&length)
+                    .ok_or(Error::<T>::TickerRegistrationTooLong)
+            })
+            .transpose()?;
+
+        Self::_register_ticker(&ticker, sender, to_did, expiry)
+    }
+
     fn _register_ticker(
         ticker: &Ticker,
         sender: T::AccountId,
         to_did: IdentityId,
         expiry: Option<T::Moment>,
-    ) {
+    ) -> DispatchResult {
         // charge fee
-        Self::charge_ticker_registration_fee(ticker, sender.clone(), to_did);
+        Self::charge_ticker_registration_fee(ticker, sender, to_did);
+
+        Self::_set_ticker_owner(ticker, to_did, expiry)
+    }
+
+    /// Registers `ticker` to `to_did` until `expiry`, replacing any previous registration.
+    fn _set_ticker_owner(
+        ticker: &Ticker,
+        to_did: IdentityId,
+        expiry: Option<T::Moment>,
+    ) -> DispatchResult {
+        let link = <identity::Module<T>>::add_link(
+            Signatory::from(to_did),
+            LinkData::TickerOwned(*ticker),
+            expiry.clone(),
+        )?;
+
+        if <Tickers<T>>::exists(ticker) {
+            let ticker_details = <Tickers<T>>::get(ticker);
+            <identity::Module<T>>::remove_link(
+                Signatory::from(ticker_details.owner),
+                ticker_details.link_id,
+            );
+        }
+
+        let ticker_registration = TickerRegistration {
+            owner: to_did,
+            expiry: expiry.clone(),
+            link_id: link,
+        };
+
+        // Store ticker registration details
+        <Tickers<T>>::insert(ticker, ticker_registration);
+
+        Self::deposit_event(RawEvent::TickerRegistered(*ticker, to_did, expiry));
+        Ok(())
+    }
+
+    fn charge_ticker_registration_fee(_ticker: &Ticker, _sender: T::AccountId, _did: IdentityId) {
+        //TODO: Charge fee
+    }
+
+    /// Checks that a token can be created by `did` with the given details. It returns the
+    /// registration status of the ticker for `did`.
+    fn ensure_token_creatable(
+        did: IdentityId,
+        name: &[u8],
+        ticker: &Ticker,
+        total_supply: T::Balance,
+        divisible: bool,
+        asset_type: &AssetType,
+        identifiers: &[(IdentifierType, Vec<u8>)],
+        hard_max_supply: Option<T::Balance>,
+    ) -> StdResult<TickerRegistrationStatus, DispatchError> {
+        ensure!(!<Tokens<T>>::exists(ticker), "token already created");
+
+        let ticker_config = Self::ticker_registration_config();
+
+        ensure!(
+            ticker.len() <= usize::try_from(ticker_config.max_ticker_length).unwrap_or_default(),
+            Error::<T>::TickerTooLong
+        );
+        ensure!(
+            ticker.len() >= usize::from(Self::min_ticker_length()),
+            Error::<T>::TickerTooShort
+        );
+
+        let allowed_asset_types = Self::allowed_asset_types();
+        ensure!(
+            allowed_asset_types.is_empty() || allowed_asset_types.contains(asset_type),
+            Error::<T>::AssetTypeNotAllowed
+        );
+
+        // checking max size for name and ticker
+        // byte arrays (vecs) with no max size should be avoided
+        ensure!(name.len() <= 64, "token name cannot exceed 64 bytes");
+        Self::ensure_unique_token_name(name)?;
+
+        ensure!(
+            identifiers
+                .iter()
+                .all(|(typ, val)| Self::is_valid_identifier(typ, val)),
+            Error::<T>::InvalidIdentifier
+        );
+        Self::ensure_identifiers_within_limit(ticker, identifiers.iter().map(|(typ, _)| typ))?;
+
+        let is_ticker_available_or_registered_to =
+            Self::is_ticker_available_or_registered_to(ticker, did);
+
+        ensure!(
+            is_ticker_available_or_registered_to != TickerRegistrationStatus::RegisteredByOther,
+            "Ticker registered to someone else"
+        );
 
-        if <Tickers<T>>::exists(ticker) {
-            let ticker_details = <Tickers<T>>::get(ticker);
-            <identity::Module<T>>::remove_link(
-                Signatory::from(ticker_details.owner),
-                ticker_details.link_id,
+        if !divisible {
+            ensure!(
+                total_supply % ONE_UNIT.into() == 0.into(),
+                "Invalid Total supply"
             );
         }
 
-        let link = <identity::Module<T>>::add_link(
-            Signatory::from(to_did),
-            LinkData::TickerOwned(*ticker),
-            expiry.clone(),
+        ensure!(
+            total_supply <= MAX_SUPPLY.into(),
+            "Total supply above the limit"
+        );
+        ensure!(
+            hard_max_supply.map_or(true, |max| total_supply <= max),
+            Error::<T>::HardMaxSupplyExceeded
         );
+        Ok(is_ticker_available_or_registered_to)
+    }
 
-        let ticker_registration = TickerRegistration {
-            owner: to_did,
-            expiry: expiry.clone(),
+    /// Creates the `ticker` token owned by `did`, whose ticker is already registered to `did`,
+    /// and gives the whole `total_supply` to `did`. It expects the token to have been checked by
+    /// `ensure_token_creatable`.
+    fn _create_token(
+        did: IdentityId,
+        name: Vec<u8>,
+        ticker: Ticker,
+        total_supply: T::Balance,
+        divisible: bool,
+        asset_type: AssetType,
+        identifiers: Vec<(IdentifierType, Vec<u8>)>,
+        funding_round: Option<Vec<u8>>,
+        hard_max_supply: Option<T::Balance>,
+    ) -> DispatchResult {
+        <identity::Module<T>>::register_asset_did(&ticker)?;
+        let link = <identity::Module<T>>::add_link(
+            Signatory::from(did),
+            LinkData::TokenOwned(ticker),
+            None,
+        )?;
+
+        let token = SecurityToken {
+            name,
+            total_supply,
+            owner_did: did,
+            divisible,
+            asset_type: asset_type.clone(),
             link_id: link,
         };
-
-        // Store ticker registration details
-        <Tickers<T>>::insert(ticker, ticker_registration);
-
-        Self::deposit_event(RawEvent::TickerRegistered(*ticker, to_did, expiry));
-    }
-
-    fn charge_ticker_registration_fee(_ticker: &Ticker, _sender: T::AccountId, _did: IdentityId) {
-        //TODO: Charge fee
+        Self::add_token_name(&token.name, &ticker);
+        <Tokens<T>>::insert(&ticker, token);
+        if let Some(max) = hard_max_supply {
+            <HardMaxSupply<T>>::insert(&ticker, max);
+        }
+        <BalanceOf<T>>::insert((ticker, did), total_supply);
+        Self::_update_holder(&ticker, did);
+        let now = <pallet_timestamp::Module<T>>::get();
+        <TradingStartTime<T>>::insert(&ticker, now);
+        <TokenCreationTime<T>>::insert(&ticker, now);
+        Self::deposit_event(RawEvent::IssuedToken(
+            ticker,
+            total_supply,
+            did,
+            divisible,
+            asset_type,
+        ));
+        Self::insert_identifiers(&ticker, &identifiers);
+        // Add funding round name
+        if let Some(round) = funding_round {
+            <FundingRound>::insert(ticker, round);
+        }
+        Self::deposit_event(RawEvent::IdentifiersUpdated(ticker, identifiers));
+        Ok(())
     }
 
     /// Get the asset `id` balance of `who`.
@@ -1639,6 +3989,10 @@ impl<T: Trait> Module<T> {
 
         if <UserCheckpoints>::exists(&ticker_did) {
             let user_checkpoints = Self::user_checkpoints(&ticker_did);
+            if user_checkpoints.is_empty() {
+                // Defensive guard: there is no checkpoint data despite the entry existing.
+                return Self::balance_of(&ticker_did);
+            }
             if at > *user_checkpoints.last().unwrap_or(&0) {
                 // Using unwrap_or to be defensive.
                 // or part should never be triggered due to the check on 2 lines above
@@ -1660,10 +4014,57 @@ impl<T: Trait> Module<T> {
         return Self::balance_of(&ticker_did);
     }
 
+    /// Returns the absolute change of the balance of `did` in `ticker` from checkpoint
+    /// `from_checkpoint` to checkpoint `to_checkpoint`, and `true` if the balance did not
+    /// decrease. Fails with `InvalidCheckpointRange` if `from_checkpoint` is after
+    /// `to_checkpoint`.
+    pub fn balance_delta(
+        ticker: Ticker,
+        did: IdentityId,
+        from_checkpoint: u64,
+        to_checkpoint: u64,
+    ) -> StdResult<(T::Balance, bool), DispatchError> {
+        ensure!(
+            from_checkpoint <= to_checkpoint,
+            Error::<T>::InvalidCheckpointRange
+        );
+        let from_balance = Self::get_balance_at(ticker, did, from_checkpoint);
+        let to_balance = Self::get_balance_at(ticker, did, to_checkpoint);
+        Ok(if to_balance >= from_balance {
+            (to_balance - from_balance, true)
+        } else {
+            (from_balance - to_balance, false)
+        })
+    }
+
+    /// Returns the total supply of `ticker` at checkpoint `at`. If that checkpoint has no stored
+    /// total supply, it uses the nearest prior checkpoint. The current total supply is returned
+    /// for `at` values outside the range of existing checkpoints.
+    pub fn total_supply_at_safe(ticker: Ticker, at: u64) -> T::Balance {
+        ticker.canonize();
+        if !<TotalCheckpoints>::exists(ticker) ||
+            at == 0 || //checkpoints start from 1
+            at > Self::total_checkpoints_of(&ticker)
+        {
+            return Self::token_details(&ticker).total_supply;
+        }
+
+        (1..=at)
+            .rev()
+            .map(|checkpoint| (ticker, checkpoint))
+            .find(|ticker_checkpoint| <CheckpointTotalSupply<T>>::exists(ticker_checkpoint))
+            .map(|ticker_checkpoint| Self::total_supply_at(ticker_checkpoint))
+            .unwrap_or_else(|| Self::token_details(&ticker).total_supply)
+    }
+
     fn find_ceiling(arr: &Vec<u64>, key: u64) -> u64 {
         // This function assumes that key <= last element of the array,
         // the array consists of unique sorted elements,
         // array len > 0
+        if arr.is_empty() {
+            // Defensive guard: the checkpoint is its own ceiling when there is no data.
+            return key;
+        }
         let mut end = arr.len();
         let mut start = 0;
         let mut mid = (start + end) / 2;
@@ -1693,6 +4094,25 @@ impl<T: Trait> Module<T> {
         value: T::Balance,
     ) -> StdResult<u8, &'static str> {
         ensure!(!Self::frozen(ticker), "asset is frozen");
+        if let Some(to_did) = to_did {
+            if !Self::is_valid_global_kyc_receiver(ticker, to_did) {
+                return Ok(APP_RECEIVER_KYC_INVALID);
+            }
+        }
+        if let Some(from_did) = from_did {
+            let required_claims = Self::required_sender_claims(ticker);
+            if !<identity::Module<T>>::has_all_claims(from_did, &required_claims) {
+                return Ok(APP_SENDER_CLAIMS_MISSING);
+            }
+        }
+        if Self::allowlist_enforced(ticker)
+            && !from_did
+                .into_iter()
+                .chain(to_did)
+                .all(|did| Self::is_allowlisted(ticker, did))
+        {
+            return Ok(APP_NOT_ALLOWLISTED);
+        }
         let general_status_code =
             <general_tm::Module<T>>::verify_restriction(ticker, from_did, to_did, value)?;
         Ok(if general_status_code != ERC1400_TRANSFER_SUCCESS {
@@ -1702,9 +4122,478 @@ impl<T: Trait> Module<T> {
         })
     }
 
-    // the SimpleToken standard transfer function
-    // internal
-    fn _transfer(
+    /// Returns `true` if `did` is the owner or the compliance officer of `ticker`.
+    pub fn is_owner_or_compliance_officer(ticker: &Ticker, did: IdentityId) -> bool {
+        Self::_is_owner(ticker, did) || Self::compliance_officer(ticker) == Some(did)
+    }
+
+    /// Returns `true` if `did` is in the transfer allowlist of `ticker` or is its owner.
+    pub fn is_allowlisted(ticker: &Ticker, did: IdentityId) -> bool {
+        Self::_is_owner(ticker, did) || Self::transfer_allowlist((*ticker, did))
+    }
+
+    /// Fails if the transfer restrictions of `ticker` block a transfer of `value` tokens from
+    /// `from_did` to `to_did`. If `AutoFreezeOnKycLoss` is enabled and the transfer is blocked
+    /// because `to_did` has no valid KYC, `to_did` is frozen.
+    fn ensure_valid_transfer(
+        ticker: &Ticker,
+        from_did: Option<IdentityId>,
+        to_did: Option<IdentityId>,
+        value: T::Balance,
+    ) -> DispatchResult {
+        let code = Self::_is_valid_transfer(ticker, from_did, to_did, value)?;
+        if code == APP_RECEIVER_KYC_INVALID && Self::auto_freeze_on_kyc_loss() {
+            if let Some(to_did) = to_did {
+                <identity::Module<T>>::freeze_did(to_did);
+            }
+        }
+        ensure!(
+            code == ERC1400_TRANSFER_SUCCESS,
+            "Transfer restrictions failed"
+        );
+        Self::note_transfer_claims(ticker, from_did, to_did);
+        Ok(())
+    }
+
+    /// Notes the use of the claims that the transfer restrictions of `ticker` check on
+    /// `from_did` and `to_did`, so that the claims used within their grace period are reported.
+    fn note_transfer_claims(
+        ticker: &Ticker,
+        from_did: Option<IdentityId>,
+        to_did: Option<IdentityId>,
+    ) {
+        let (mut sender_claims, mut receiver_claims) =
+            <general_tm::Module<T>>::claim_requirements(ticker);
+        if let Some(from_did) = from_did {
+            sender_claims.extend(Self::required_sender_claims(ticker));
+            <identity::Module<T>>::note_claims_in_grace_period(from_did, &sender_claims);
+        }
+        if let Some(to_did) = to_did {
+            if Self::global_kyc_required() || Self::require_kyc_on_issue(ticker) {
+                receiver_claims.push(<identity::Module<T>>::kyc_claim_requirement());
+            }
+            <identity::Module<T>>::note_claims_in_grace_period(to_did, &receiver_claims);
+        }
+    }
+
+    /// Returns the ERC1066 status code of a transfer of `value` tokens from `from_did` to `to_did`.
+    fn can_transfer_status(
+        ticker: &Ticker,
+        from_did: IdentityId,
+        to_did: IdentityId,
+        value: T::Balance,
+    ) -> u32 {
+        let mut current_balance: T::Balance = Self::balance_of((*ticker, from_did));
+        if current_balance < value {
+            current_balance = 0.into();
+        } else {
+            current_balance = current_balance - value;
+        }
+        if current_balance < Self::total_custody_allowance((*ticker, from_did)) {
+            sp_runtime::print("Insufficient balance");
+            ERC1400_INSUFFICIENT_BALANCE as u32
+        } else {
+            match Self::_is_valid_transfer(ticker, Some(from_did), Some(to_did), value) {
+                Ok(code) => code as u32,
+                Err(msg) => {
+                    // We emit a generic error with the event whenever there's an internal issue - i.e. captured
+                    // in a string error and not using the status codes
+                    sp_runtime::print(msg);
+                    ERC1400_TRANSFER_FAILURE as u32
+                }
+            }
+        }
+    }
+
+    /// Returns the ERC1066 status code of a transfer of `value` tokens from `from_did` to
+    /// `to_did` made now. Unlike `can_transfer_status`, the sender is also limited to its
+    /// `spendable_balance`.
+    pub fn transfer_preflight_status(
+        ticker: &Ticker,
+        from_did: IdentityId,
+        to_did: IdentityId,
+        value: T::Balance,
+    ) -> u32 {
+        match Self::_is_valid_transfer(ticker, Some(from_did), Some(to_did), value) {
+            Ok(code) if code != ERC1400_TRANSFER_SUCCESS => code as u32,
+            Ok(_) if value > Self::spendable_balance(*ticker, from_did) => {
+                ERC1400_INSUFFICIENT_BALANCE as u32
+            }
+            Ok(_) => ERC1400_TRANSFER_SUCCESS as u32,
+            Err(msg) => {
+                sp_runtime::print(msg);
+                ERC1400_TRANSFER_FAILURE as u32
+            }
+        }
+    }
+
+    /// Returns the balance of `did` that is not allowed to its custodians.
+    pub fn available_balance(ticker: &Ticker, did: IdentityId) -> T::Balance {
+        let ticker_did = (*ticker, did);
+        Self::balance_of(&ticker_did).saturating_sub(Self::total_custody_allowance(&ticker_did))
+    }
+
+    /// Returns the sum of the allowances granted by `owner` and the allowance of each spender.
+    pub fn total_allowance_granted(
+        ticker: Ticker,
+        owner: IdentityId,
+    ) -> (T::Balance, Vec<(IdentityId, T::Balance)>) {
+        let allowances = Self::spenders_of((ticker, owner))
+            .into_iter()
+            .map(|spender| (spender, Self::allowance((ticker, owner, spender))))
+            .collect::<Vec<_>>();
+        let total = allowances
+            .iter()
+            .fold(Zero::zero(), |total: T::Balance, (_, allowance)| {
+                total.saturating_add(*allowance)
+            });
+        (total, allowances)
+    }
+
+    /// Sets the allowance granted by `owner` to `spender`, keeping `SpendersOf` in sync.
+    fn set_allowance(
+        ticker: &Ticker,
+        owner: IdentityId,
+        spender: IdentityId,
+        allowance: T::Balance,
+    ) {
+        <Allowance<T>>::insert((*ticker, owner, spender), allowance);
+        <SpendersOf>::mutate((*ticker, owner), |spenders| {
+            let position = spenders.iter().position(|s| *s == spender);
+            match position {
+                Some(index) if allowance.is_zero() => {
+                    spenders.remove(index);
+                }
+                None if !allowance.is_zero() => spenders.push(spender),
+                _ => {}
+            }
+        });
+    }
+
+    /// Returns whether the transfers from `did` are frozen by the token owner.
+    fn is_outgoing_frozen(ticker: &Ticker, did: IdentityId) -> bool {
+        Self::controller_frozen_holder((*ticker, did))
+            || match Self::holder_freeze_direction((*ticker, did)) {
+                Some(FreezeDirection::Both) | Some(FreezeDirection::Outgoing) => true,
+                _ => false,
+            }
+    }
+
+    /// Returns whether the transfers to `did` are frozen by the token owner.
+    fn is_incoming_frozen(ticker: &Ticker, did: IdentityId) -> bool {
+        match Self::holder_freeze_direction((*ticker, did)) {
+            Some(FreezeDirection::Both) | Some(FreezeDirection::Incoming) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the amount of `ticker` tokens of `did` that are encumbered, that is, the tokens
+    /// reserved for its custodians plus the tokens it moved into escrow. Escrowed tokens are no
+    /// longer part of the holder balance, so only the custody allowance reduces
+    /// `spendable_balance`.
+    pub fn total_locked(ticker: Ticker, did: IdentityId) -> T::Balance {
+        ticker.canonize();
+        Self::escrow_custodians_of((ticker, did)).into_iter().fold(
+            Self::total_custody_allowance((ticker, did)),
+            |locked, custodian_did| {
+                locked.saturating_add(Self::escrow_balance((ticker, custodian_did, did)))
+            },
+        )
+    }
+
+    /// Returns the amount of `ticker` tokens that `did` is able to move right now, that is, its
+    /// balance minus the tokens reserved for custodians. It is zero if the asset or the holder is
+    /// frozen.
+    pub fn spendable_balance(ticker: Ticker, did: IdentityId) -> T::Balance {
+        ticker.canonize();
+        if Self::frozen(&ticker) || Self::is_outgoing_frozen(&ticker, did) {
+            return 0.into();
+        }
+        Self::available_balance(&ticker, did)
+    }
+
+    /// Returns the balance of each `(ticker, did)` pair of `pairs`, in the same order.
+    pub fn balances_of(pairs: Vec<(Ticker, IdentityId)>) -> Vec<T::Balance> {
+        pairs
+            .into_iter()
+            .map(|(ticker, did)| {
+                ticker.canonize();
+                Self::balance_of((ticker, did))
+            })
+            .collect()
+    }
+
+    /// Returns up to `count` holders of `ticker` with their balances, starting at the `start`
+    /// position of the holders index, along with the total number of holders.
+    pub fn holders_paged(
+        ticker: Ticker,
+        start: u32,
+        count: u32,
+    ) -> (Vec<(IdentityId, T::Balance)>, u32) {
+        ticker.canonize();
+        let holders = Self::ticker_holders(&ticker);
+        let page = holders
+            .iter()
+            .skip(start as usize)
+            .take(count as usize)
+            .map(|holder| (*holder, Self::balance_of((ticker, *holder))))
+            .collect();
+        (page, holders.len() as u32)
+    }
+
+    /// Returns up to `count` logged controller actions of `ticker` with their sequence numbers,
+    /// starting at sequence number `start`, along with the total number of controller actions.
+    /// Only the latest `CONTROLLER_ACTION_LOG_LEN` actions are kept.
+    pub fn controller_actions_paged(
+        ticker: Ticker,
+        start: u64,
+        count: u32,
+    ) -> (Vec<(u64, ControllerAction<T::Balance, T::Moment>)>, u64) {
+        ticker.canonize();
+        let total = Self::controller_action_count(&ticker);
+        let oldest = total.saturating_sub(CONTROLLER_ACTION_LOG_LEN);
+        let page = (start.max(oldest)..total)
+            .take(count as usize)
+            .map(|seq| (seq, Self::controller_action((ticker, seq))))
+            .collect();
+        (page, total)
+    }
+
+    /// Records a controller action of `ticker` in `ControllerActionLog`, dropping the oldest
+    /// entry once the log holds `CONTROLLER_ACTION_LOG_LEN` actions.
+    fn log_controller_action(
+        ticker: &Ticker,
+        action_type: ControllerActionType,
+        from_did: IdentityId,
+        to_did: Option<IdentityId>,
+        value: T::Balance,
+        reason: Vec<u8>,
+    ) {
+        let seq = Self::controller_action_count(ticker);
+        if seq >= CONTROLLER_ACTION_LOG_LEN {
+            <ControllerActionLog<T>>::remove((*ticker, seq - CONTROLLER_ACTION_LOG_LEN));
+        }
+        let action = ControllerAction {
+            action_type,
+            from_did,
+            to_did,
+            value,
+            reason,
+            timestamp: <pallet_timestamp::Module<T>>::get(),
+        };
+        <ControllerActionLog<T>>::insert((*ticker, seq), action);
+        <ControllerActionCount>::insert(ticker, seq + 1);
+    }
+
+    /// Returns the non-zero balances of the holders of `ticker` at checkpoint `at`.
+    ///
+    /// The balance of every holder is read, so the size and cost of the result grow with the
+    /// number of holders. Use `balances_at_checkpoint_paged` for tokens with many holders.
+    pub fn balances_at_checkpoint(ticker: Ticker, at: u64) -> Vec<(IdentityId, T::Balance)> {
+        ticker.canonize();
+        let holders_count = Self::holders_at_checkpoint(&ticker, at).len() as u32;
+        Self::balances_at_checkpoint_paged(ticker, at, 0, holders_count).0
+    }
+
+    /// Returns the holders of `ticker` when checkpoint `at` was created. Checkpoints created
+    /// before holders were recorded fall back to the current holders.
+    fn holders_at_checkpoint(ticker: &Ticker, at: u64) -> Vec<IdentityId> {
+        Self::checkpoint_holders((*ticker, at)).unwrap_or_else(|| Self::ticker_holders(ticker))
+    }
+
+    /// Returns the non-zero balances at checkpoint `at` of up to `count` holders of `ticker`,
+    /// starting at the `start`-th holder, and the total number of holders.
+    pub fn balances_at_checkpoint_paged(
+        ticker: Ticker,
+        at: u64,
+        start: u32,
+        count: u32,
+    ) -> (Vec<(IdentityId, T::Balance)>, u32) {
+        ticker.canonize();
+        let holders = Self::holders_at_checkpoint(&ticker, at);
+        let page = holders
+            .iter()
+            .skip(start as usize)
+            .take(count as usize)
+            .map(|holder| (*holder, Self::get_balance_at(ticker, *holder, at)))
+            .filter(|(_, balance)| *balance != 0.into())
+            .collect();
+        (page, holders.len() as u32)
+    }
+
+    /// Returns the transfer restrictions of `ticker` from both transfer managers.
+    pub fn transfer_rules_summary(ticker: Ticker) -> RulesSummary {
+        ticker.canonize();
+        RulesSummary {
+            active_rules: <general_tm::Module<T>>::active_rules(&ticker),
+            max_percentage: <percentage_tm::Module<T>>::maximum_percentage_enabled_for_token(
+                &ticker,
+            ),
+            required_receiver_claims: <general_tm::Module<T>>::required_receiver_claims(&ticker),
+        }
+    }
+
+    /// Returns the owner-configurable settings of `ticker`, or `None` if no token was created
+    /// for it.
+    pub fn token_config(ticker: Ticker) -> Option<TokenConfig<T::Balance, T::Moment>> {
+        ticker.canonize();
+        if !<Tokens<T>>::exists(&ticker) {
+            return None;
+        }
+        Some(TokenConfig {
+            frozen: Self::frozen(&ticker),
+            operation_pause_flags: Self::operation_pause_flags(&ticker),
+            hard_max_supply: Self::hard_max_supply(&ticker),
+            supply_floor: Self::supply_floor(&ticker),
+            lot_size: Self::lot_size(&ticker),
+            funding_round: Self::funding_round(&ticker),
+            trading_start_time: Self::trading_start_time(&ticker),
+            require_kyc_on_issue: Self::require_kyc_on_issue(&ticker),
+            require_kyc_on_transfer_from: Self::require_kyc_on_transfer_from(&ticker),
+            issuance_rounding_enabled: Self::issuance_rounding_enabled(&ticker),
+            auto_custodian: Self::auto_custodian(&ticker),
+            block_transfers_to_frozen_dids: Self::block_transfers_to_frozen_dids(&ticker),
+            approve_allowlist_enabled: Self::approve_allowlist_enabled(&ticker),
+            max_allowance_per_spender: Self::max_allowance_per_spender(&ticker),
+            allowlist_enforced: Self::allowlist_enforced(&ticker),
+            compliance_officer: Self::compliance_officer(&ticker),
+            aggregate_transfer_events: Self::aggregate_transfer_events(&ticker),
+            required_sender_claims: Self::required_sender_claims(&ticker).len() as u32,
+            required_receiver_claims: <general_tm::Module<T>>::required_receiver_claims(&ticker)
+                .len() as u32,
+        })
+    }
+
+    /// Returns the tickers of the tokens owned by `did`, oldest first.
+    pub fn tickers_owned_by(did: IdentityId) -> Vec<Ticker> {
+        let signer = Signatory::from(did);
+        let mut tickers = Vec::new();
+        let mut link_id = <identity::Module<T>>::last_link(&signer);
+        while link_id != 0 {
+            let link = <identity::Module<T>>::links((signer, link_id));
+            if let LinkData::TokenOwned(ticker) = link.link_data {
+                tickers.push(ticker);
+            }
+            link_id = link.previous_link;
+        }
+        tickers.reverse();
+        tickers
+    }
+
+    /// Returns each token owned by `did` with its `frozen` flag and operation pause flags.
+    pub fn owned_tickers_state(did: IdentityId) -> Vec<(Ticker, bool, u8)> {
+        Self::tickers_owned_by(did)
+            .into_iter()
+            .map(|ticker| {
+                (
+                    ticker,
+                    Self::frozen(&ticker),
+                    Self::operation_pause_flags(&ticker),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the smart extensions of `ticker` as (type, extension id, archived) tuples.
+    /// Extensions are enumerated by type, so only the `TransferManager` and `Offerings` types
+    /// are included; `Custom` types cannot be enumerated.
+    pub fn all_extensions(ticker: Ticker) -> Vec<(SmartExtensionType, T::AccountId, bool)> {
+        ticker.canonize();
+        [
+            SmartExtensionType::TransferManager,
+            SmartExtensionType::Offerings,
+        ]
+        .iter()
+        .flat_map(|extension_type| {
+            Self::extensions((ticker, extension_type.clone()))
+                .into_iter()
+                .map(move |extension_id| {
+                    let is_archive =
+                        Self::extension_details((ticker, extension_id.clone())).is_archive;
+                    (extension_type.clone(), extension_id, is_archive)
+                })
+        })
+        .collect()
+    }
+
+    /// Returns the ERC1400 status code of redeeming `value` tokens of `from_did`.
+    /// It applies the same checks as `redeem`: redeem pause, granularity, balance, custody
+    /// allowance, supply floor and transfer restrictions with no destination.
+    pub fn _is_valid_redeem(
+        ticker: &Ticker,
+        from_did: IdentityId,
+        value: T::Balance,
+    ) -> StdResult<u8, &'static str> {
+        if Self::is_operation_paused(ticker, PAUSE_REDEEM) {
+            return Ok(ERC1400_TRANSFERS_HALTED);
+        }
+        if !Self::check_granularity(ticker, value) {
+            return Ok(APP_INVALID_GRANULARITY);
+        }
+        let balance = Self::balance_of((*ticker, from_did));
+        if balance < value {
+            return Ok(ERC1400_INSUFFICIENT_BALANCE);
+        }
+        if balance - value < Self::total_custody_allowance((*ticker, from_did)) {
+            return Ok(ERC1400_FUNDS_LOCKED);
+        }
+        if Self::token_details(ticker)
+            .total_supply
+            .saturating_sub(value)
+            < Self::supply_floor(ticker)
+        {
+            return Ok(APP_SUPPLY_FLOOR_BREACHED);
+        }
+        Self::_is_valid_transfer(ticker, Some(from_did), None, value)
+    }
+
+    /// Redeems `value` tokens of `from_did` and reduces the total supply of `ticker`.
+    /// It fails unless `_is_valid_redeem` succeeds, before any storage is written.
+    fn _burn(ticker: &Ticker, from_did: IdentityId, value: T::Balance) -> DispatchResult {
+        let ticker_did = (*ticker, from_did);
+        ensure!(
+            <BalanceOf<T>>::exists(&ticker_did),
+            "Account does not own this token"
+        );
+        match Self::_is_valid_redeem(ticker, from_did, value)? {
+            ERC1400_TRANSFER_SUCCESS => {}
+            ERC1400_TRANSFERS_HALTED => return Err(Error::<T>::OperationPaused.into()),
+            APP_INVALID_GRANULARITY => return Err("Invalid granularity".into()),
+            ERC1400_INSUFFICIENT_BALANCE => return Err("Not enough balance.".into()),
+            ERC1400_FUNDS_LOCKED => return Err("Insufficient balance for transfer".into()),
+            APP_SUPPLY_FLOOR_BREACHED => return Err(Error::<T>::SupplyFloorBreached.into()),
+            _ => return Err("Transfer restrictions failed".into()),
+        }
+        let burner_balance = Self::balance_of(&ticker_did);
+        let updated_burner_balance = burner_balance
+            .checked_sub(&value)
+            .ok_or("overflow in calculating balance")?;
+
+        //Decrease total supply
+        let mut token = Self::token_details(ticker);
+        token.total_supply = token
+            .total_supply
+            .checked_sub(&value)
+            .ok_or("overflow in calculating balance")?;
+
+        Self::_update_checkpoint(ticker, from_did, burner_balance);
+        <BalanceOf<T>>::insert(&ticker_did, updated_burner_balance);
+        Self::_update_holder(ticker, from_did);
+        <Tokens<T>>::insert(ticker, token);
+        <statistics::Module<T>>::update_transfer_stats(
+            ticker,
+            Some(updated_burner_balance),
+            None,
+            value,
+        );
+
+        Self::deposit_event(RawEvent::Redeemed(*ticker, from_did, value));
+        Ok(())
+    }
+
+    /// Checks everything `_transfer` requires from a transfer of `value` tokens from `from_did`
+    /// to `to_did` besides the balance of `from_did`, which batches check against their totals.
+    fn ensure_transferable(
         ticker: &Ticker,
         from_did: IdentityId,
         to_did: IdentityId,
@@ -1715,22 +4604,59 @@ impl<T: Trait> Module<T> {
             Self::check_granularity(ticker, value),
             "Invalid granularity"
         );
-        let ticker_from_did = (*ticker, from_did);
         ensure!(
-            <BalanceOf<T>>::exists(&ticker_from_did),
+            !Self::is_outgoing_frozen(ticker, from_did),
+            Error::<T>::HolderFrozen
+        );
+        ensure!(
+            !Self::is_incoming_frozen(ticker, to_did),
+            Error::<T>::ReceiverFrozen
+        );
+        ensure!(
+            !Self::block_transfers_to_frozen_dids(ticker)
+                || !<identity::Module<T>>::is_did_frozen(to_did),
+            Error::<T>::RecipientDidFrozen
+        );
+        ensure!(
+            Self::_is_owner(ticker, from_did)
+                || <pallet_timestamp::Module<T>>::get() >= Self::trading_start_time(ticker),
+            Error::<T>::TradingNotStarted
+        );
+        ensure!(
+            <BalanceOf<T>>::exists(&(*ticker, from_did)),
             "Account does not own this token"
         );
+        Ok(())
+    }
+
+    // the SimpleToken standard transfer function
+    // internal
+    fn _transfer(
+        ticker: &Ticker,
+        from_did: IdentityId,
+        to_did: IdentityId,
+        value: T::Balance,
+    ) -> DispatchResult {
+        Self::ensure_transferable(ticker, from_did, to_did, value)?;
+        if Self::burn_did() == Some(to_did) {
+            // Transfers to the burn DID redeem the tokens.
+            return Self::_burn(ticker, from_did, value);
+        }
+
+        let ticker_from_did = (*ticker, from_did);
         let sender_balance = Self::balance_of(&ticker_from_did);
         ensure!(sender_balance >= value, "Not enough balance.");
 
         let updated_from_balance = sender_balance
             .checked_sub(&value)
             .ok_or("overflow in calculating balance")?;
+
         let ticker_to_did = (*ticker, to_did);
         let receiver_balance = Self::balance_of(ticker_to_did);
         let updated_to_balance = receiver_balance
             .checked_add(&value)
             .ok_or("overflow in calculating balance")?;
+        let auto_custody = Self::ensure_auto_custody(ticker, to_did, value, updated_to_balance)?;
 
         Self::_update_checkpoint(ticker, from_did, sender_balance);
         Self::_update_checkpoint(ticker, to_did, receiver_balance);
@@ -1740,6 +4666,18 @@ impl<T: Trait> Module<T> {
         // increase receiver's balance
         <BalanceOf<T>>::insert(ticker_to_did, updated_to_balance);
 
+        Self::_update_holder(ticker, from_did);
+        Self::_update_holder(ticker, to_did);
+        if let Some((custodian_did, new_custody_allowance, new_allowance)) = auto_custody {
+            Self::set_custody_allowance(
+                *ticker,
+                to_did,
+                custodian_did,
+                new_custody_allowance,
+                new_allowance,
+            );
+        }
+
         // Update statistic info.
         <statistics::Module<T>>::update_transfer_stats(
             ticker,
@@ -1748,41 +4686,151 @@ impl<T: Trait> Module<T> {
             value,
         );
 
-        Self::deposit_event(RawEvent::Transfer(ticker.clone(), from_did, to_did, value));
+        if Self::aggregate_transfer_events(ticker) {
+            Self::aggregate_transfer(ticker, value);
+        } else {
+            Self::deposit_event(RawEvent::Transfer(ticker.clone(), from_did, to_did, value));
+        }
         Ok(())
     }
 
-    pub fn _create_checkpoint(ticker: &Ticker) -> DispatchResult {
-        if <TotalCheckpoints>::exists(ticker) {
-            let mut checkpoint_count = Self::total_checkpoints_of(ticker);
-            checkpoint_count = checkpoint_count
+    /// Adds a transfer of `value` to the aggregated transfers of `ticker` in the current block.
+    /// It runs once the transfer is applied, so the aggregates saturate instead of failing.
+    fn aggregate_transfer(ticker: &Ticker, value: T::Balance) {
+        let (count, total_volume) = Self::transfer_aggregates(ticker);
+        if count == 0 {
+            <TransferAggregateTickers>::mutate(|tickers| tickers.push(*ticker));
+        }
+        <TransferAggregates<T>>::insert(
+            ticker,
+            (count.saturating_add(1), total_volume.saturating_add(value)),
+        );
+    }
+
+    /// Creates a new checkpoint of `ticker` and returns its id.
+    pub fn _create_checkpoint(ticker: &Ticker) -> StdResult<u64, DispatchError> {
+        let checkpoint_count = if <TotalCheckpoints>::exists(ticker) {
+            Self::total_checkpoints_of(ticker)
                 .checked_add(1)
-                .ok_or("overflow in adding checkpoint")?;
-            <TotalCheckpoints>::insert(ticker, checkpoint_count);
-            <CheckpointTotalSupply<T>>::insert(
-                &(*ticker, checkpoint_count),
-                Self::token_details(ticker).total_supply,
-            );
+                .ok_or(Error::<T>::CheckpointOverflow)?
         } else {
-            <TotalCheckpoints>::insert(ticker, 1);
-            <CheckpointTotalSupply<T>>::insert(
-                &(*ticker, 1),
-                Self::token_details(ticker).total_supply,
+            1
+        };
+        <TotalCheckpoints>::insert(ticker, checkpoint_count);
+        <CheckpointTotalSupply<T>>::insert(
+            &(*ticker, checkpoint_count),
+            Self::token_details(ticker).total_supply,
+        );
+        <CheckpointHolders>::insert(&(*ticker, checkpoint_count), Self::ticker_holders(ticker));
+        Self::deposit_event(RawEvent::CheckpointCreated(
+            *ticker,
+            checkpoint_count,
+            <pallet_timestamp::Module<T>>::get(),
+        ));
+        Ok(checkpoint_count)
+    }
+
+    /// Sets the balance of `did` in `ticker` at genesis and adjusts the total supply to it. If
+    /// the token does not exist, it is created like `create_token`, as a divisible token owned
+    /// by `did`.
+    fn seed_genesis_balance(
+        ticker: Ticker,
+        did: IdentityId,
+        balance: T::Balance,
+    ) -> DispatchResult {
+        ticker.canonize();
+        ensure!(<identity::DidRecords>::exists(did), "DID does not exist");
+        if !<Tokens<T>>::exists(&ticker) {
+            let name = ticker.0[..ticker.len()].to_vec();
+            let asset_type = AssetType::default();
+            let is_ticker_available_or_registered_to = Self::ensure_token_creatable(
+                did,
+                &name,
+                &ticker,
+                0.into(),
+                true,
+                &asset_type,
+                &[],
+                None,
+            )?;
+            if is_ticker_available_or_registered_to == TickerRegistrationStatus::Available {
+                Self::_set_ticker_owner(&ticker, did, None)?;
+            }
+            Self::_create_token(
+                did,
+                name,
+                ticker,
+                0.into(),
+                true,
+                asset_type,
+                vec![],
+                None,
+                None,
+            )?;
+        }
+
+        let ticker_did = (ticker, did);
+        let old_balance = Self::balance_of(&ticker_did);
+        let token = Self::token_details(&ticker);
+        if !token.divisible {
+            ensure!(
+                balance % ONE_UNIT.into() == 0.into(),
+                "Invalid Total supply"
+            );
+        }
+        let total_supply = token
+            .total_supply
+            .checked_sub(&old_balance)
+            .and_then(|supply| supply.checked_add(&balance))
+            .ok_or("overflow in calculating total supply")?;
+        ensure!(
+            total_supply <= MAX_SUPPLY.into(),
+            "Total supply above the limit"
+        );
+
+        <Tokens<T>>::mutate(&ticker, |token| token.total_supply = total_supply);
+        <BalanceOf<T>>::insert(&ticker_did, balance);
+        Self::_update_holder(&ticker, did);
+        if old_balance == 0.into() {
+            <statistics::Module<T>>::update_transfer_stats(&ticker, None, Some(balance), balance);
+        } else if balance == 0.into() {
+            <statistics::Module<T>>::update_transfer_stats(
+                &ticker,
+                Some(balance),
+                None,
+                old_balance,
             );
         }
         Ok(())
     }
 
+    /// Adds `did` to the holders of `ticker` when it has a balance and removes it otherwise.
+    fn _update_holder(ticker: &Ticker, did: IdentityId) {
+        let has_balance = Self::balance_of((*ticker, did)) != 0.into();
+        <TickerHolders>::mutate(ticker, |holders| {
+            if !has_balance {
+                holders.retain(|holder| *holder != did);
+            } else if !holders.contains(&did) {
+                holders.push(did);
+            }
+        });
+    }
+
+    /// Returns `true` if a balance change of `did` in `ticker` would record its balance at the
+    /// latest checkpoint, i.e. a checkpoint exists and `did` has not transacted since.
+    pub fn transfer_touches_checkpoint(ticker: Ticker, did: IdentityId) -> bool {
+        ticker.canonize();
+        <TotalCheckpoints>::exists(ticker)
+            && !<CheckpointBalance<T>>::exists((ticker, did, Self::total_checkpoints_of(ticker)))
+    }
+
     fn _update_checkpoint(ticker: &Ticker, user_did: IdentityId, user_balance: T::Balance) {
-        if <TotalCheckpoints>::exists(ticker) {
+        if Self::transfer_touches_checkpoint(*ticker, user_did) {
             let checkpoint_count = Self::total_checkpoints_of(ticker);
-            let ticker_user_did_checkpont = (*ticker, user_did, checkpoint_count);
-            if !<CheckpointBalance<T>>::exists(&ticker_user_did_checkpont) {
-                <CheckpointBalance<T>>::insert(&ticker_user_did_checkpont, user_balance);
-                <UserCheckpoints>::mutate(&(*ticker, user_did), |user_checkpoints| {
-                    user_checkpoints.push(checkpoint_count);
-                });
-            }
+            <CheckpointBalance<T>>::insert((*ticker, user_did, checkpoint_count), user_balance);
+            <UserCheckpoints>::mutate(&(*ticker, user_did), |user_checkpoints| {
+                user_checkpoints.push(checkpoint_count);
+            });
         }
     }
 
@@ -1791,6 +4839,9 @@ impl<T: Trait> Module<T> {
     }
 
     pub fn _mint(ticker: &Ticker, to_did: IdentityId, value: T::Balance) -> DispatchResult {
+        let value = Self::round_issuance(ticker, value);
+        ensure!(!value.is_zero(), Error::<T>::ZeroIssuanceAmount);
+        Self::ensure_funding_round_open(ticker)?;
         // Granularity check
         ensure!(
             Self::check_granularity(ticker, value),
@@ -1808,6 +4859,14 @@ impl<T: Trait> Module<T> {
                 == ERC1400_TRANSFER_SUCCESS,
             "Transfer restrictions failed"
         );
+        ensure!(
+            Self::is_valid_issue_recipient(ticker, to_did),
+            Error::<T>::RecipientKycInvalid
+        );
+        ensure!(
+            !Self::is_incoming_frozen(ticker, to_did),
+            Error::<T>::ReceiverFrozen
+        );
 
         // Read the token details
         let mut token = Self::token_details(ticker);
@@ -1819,18 +4878,38 @@ impl<T: Trait> Module<T> {
             updated_total_supply <= MAX_SUPPLY.into(),
             "Total supply above the limit"
         );
+        ensure!(
+            Self::hard_max_supply(ticker).map_or(true, |max| updated_total_supply <= max),
+            Error::<T>::HardMaxSupplyExceeded
+        );
+        let issuance_window = Self::issuance_window_after(ticker, value)?;
+        let auto_custody = Self::ensure_auto_custody(ticker, to_did, value, updated_to_balance)?;
+        let round = Self::funding_round(ticker);
+        let ticker_round = (*ticker, round.clone());
+        let issued_in_this_round = Self::issued_in_funding_round(&ticker_round)
+            .checked_add(&value)
+            .ok_or("current funding round total overflowed")?;
         //Increase total suply
         token.total_supply = updated_total_supply;
 
         Self::_update_checkpoint(ticker, to_did, current_to_balance);
 
         <BalanceOf<T>>::insert(&ticker_to_did, updated_to_balance);
+        Self::_update_holder(ticker, to_did);
+        Self::note_transfer_claims(ticker, None, Some(to_did));
         <Tokens<T>>::insert(ticker, token);
-        let round = Self::funding_round(ticker);
-        let ticker_round = (*ticker, round.clone());
-        let issued_in_this_round = Self::issued_in_funding_round(&ticker_round)
-            .checked_add(&value)
-            .ok_or("current funding round total overflowed")?;
+        if let Some(window) = issuance_window {
+            <CurrentIssuanceWindow<T>>::insert(ticker, window);
+        }
+        if let Some((custodian_did, new_custody_allowance, new_allowance)) = auto_custody {
+            Self::set_custody_allowance(
+                *ticker,
+                to_did,
+                custodian_did,
+                new_custody_allowance,
+                new_allowance,
+            );
+        }
         <IssuedInFundingRound<T>>::insert(&ticker_round, issued_in_this_round);
         Self::deposit_event(RawEvent::Issued(
             *ticker,
@@ -1843,10 +4922,251 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    /// Ensures that no other token is named `name` if `EnforceUniqueTokenNames` is set.
+    fn ensure_unique_token_name(name: &[u8]) -> DispatchResult {
+        ensure!(
+            !Self::enforce_unique_token_names() || Self::token_names(name).is_empty(),
+            Error::<T>::DuplicateTokenName
+        );
+        Ok(())
+    }
+
+    /// Records that the token `ticker` is named `name` in `TokenNames`.
+    fn add_token_name(name: &[u8], ticker: &Ticker) {
+        <TokenNames>::mutate(name, |tickers| {
+            if !tickers.contains(ticker) {
+                tickers.push(*ticker);
+            }
+        });
+    }
+
+    /// Removes the token `ticker` from the tokens named `name` in `TokenNames`.
+    fn remove_token_name(name: &[u8], ticker: &Ticker) {
+        let mut tickers = Self::token_names(name);
+        tickers.retain(|t| t != ticker);
+        if tickers.is_empty() {
+            <TokenNames>::remove(name);
+        } else {
+            <TokenNames>::insert(name, tickers);
+        }
+    }
+
+    /// Ensures that `ticker` does not exceed `MaxIdentifiersPerToken` once identifiers of
+    /// `new_types` are set. Types that are already set are only overwritten and therefore
+    /// counted once.
+    fn ensure_identifiers_within_limit<'a>(
+        ticker: &Ticker,
+        new_types: impl Iterator<Item = &'a IdentifierType>,
+    ) -> DispatchResult {
+        let mut types = Self::identifier_types_of(ticker);
+        for typ in new_types {
+            if !types.contains(typ) {
+                types.push(typ.clone());
+            }
+        }
+        ensure!(
+            types.len() <= T::MaxIdentifiersPerToken::get() as usize,
+            Error::<T>::TooManyIdentifiers
+        );
+        Ok(())
+    }
+
+    /// Stores `identifiers` of `ticker` and keeps track of their types.
+    fn insert_identifiers(ticker: &Ticker, identifiers: &[(IdentifierType, Vec<u8>)]) {
+        for (typ, val) in identifiers {
+            <Identifiers>::insert((*ticker, typ.clone()), val.clone());
+            <IdentifierTypesOf>::mutate(ticker, |types| {
+                if !types.contains(typ) {
+                    types.push(typ.clone());
+                }
+            });
+        }
+    }
+
+    /// Checks the format and the check digit of well-known identifier types.
+    /// `Custom` identifiers are not validated.
+    pub fn is_valid_identifier(typ: &IdentifierType, value: &[u8]) -> bool {
+        match typ {
+            IdentifierType::Isin => Self::is_valid_isin(value),
+            IdentifierType::Cusip => Self::is_valid_cusip(value),
+            IdentifierType::Custom(_) => true,
+        }
+    }
+
+    /// An ISIN has 12 characters: a 2 letter country code, 9 alphanumeric characters and a
+    /// check digit computed with the Luhn algorithm over the expanded digits.
+    fn is_valid_isin(value: &[u8]) -> bool {
+        if value.len() != 12
+            || !value[..2].iter().all(u8::is_ascii_uppercase)
+            || !value[2..11]
+                .iter()
+                .all(|c| c.is_ascii_digit() || c.is_ascii_uppercase())
+            || !value[11].is_ascii_digit()
+        {
+            return false;
+        }
+        // Letters are expanded into two digits (A = 10, ..., Z = 35).
+        let mut digits = Vec::with_capacity(24);
+        for c in value {
+            if c.is_ascii_digit() {
+                digits.push(c - b'0');
+            } else {
+                let v = c - b'A' + 10;
+                digits.push(v / 10);
+                digits.push(v % 10);
+            }
+        }
+        let sum: u32 = digits
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, d)| {
+                let d = u32::from(*d);
+                if i % 2 == 1 {
+                    let doubled = d * 2;
+                    doubled / 10 + doubled % 10
+                } else {
+                    d
+                }
+            })
+            .sum();
+        sum % 10 == 0
+    }
+
+    /// A CUSIP has 9 characters: 8 alphanumeric (or `*`, `@`, `#`) characters and a check digit.
+    fn is_valid_cusip(value: &[u8]) -> bool {
+        if value.len() != 9 || !value[8].is_ascii_digit() {
+            return false;
+        }
+        let mut sum = 0u32;
+        for (i, c) in value[..8].iter().enumerate() {
+            let mut v = match c {
+                b'0'..=b'9' => u32::from(c - b'0'),
+                b'A'..=b'Z' => u32::from(c - b'A') + 10,
+                b'*' => 36,
+                b'@' => 37,
+                b'#' => 38,
+                _ => return false,
+            };
+            if i % 2 == 1 {
+                v *= 2;
+            }
+            sum += v / 10 + v % 10;
+        }
+        (10 - sum % 10) % 10 == u32::from(value[8] - b'0')
+    }
+
+    /// Checks if any of the `operation` bits is set in the pause flags of `ticker`.
+    pub fn is_operation_paused(ticker: &Ticker, operation: u8) -> bool {
+        Self::operation_pause_flags(ticker) & operation != 0
+    }
+
+    /// Whether `to_did` satisfies the global KYC requirement for `ticker`, i.e. the requirement
+    /// is disabled, `to_did` is exempted from it or it has a valid KYC.
+    pub fn is_valid_global_kyc_receiver(ticker: &Ticker, to_did: IdentityId) -> bool {
+        !Self::global_kyc_required()
+            || <exemption::Module<T>>::is_exempted(ticker, GLOBAL_KYC_TM, to_did)
+            || <identity::Module<T>>::is_identity_has_valid_kyc(to_did, 0).0
+    }
+
+    /// Whether `to_did` may receive newly issued tokens, i.e. it has a valid KYC or the token
+    /// does not require one on issuance.
+    pub fn is_valid_issue_recipient(ticker: &Ticker, to_did: IdentityId) -> bool {
+        !Self::require_kyc_on_issue(ticker)
+            || <identity::Module<T>>::is_identity_has_valid_kyc(to_did, 0).0
+    }
+
+    /// Returns the lot size of `ticker`, which is 1 unless set by the token owner.
+    pub fn lot_size(ticker: &Ticker) -> T::Balance {
+        let lot_size = <LotSize<T>>::get(ticker);
+        if lot_size.is_zero() {
+            1.into()
+        } else {
+            lot_size
+        }
+    }
+
+    /// Checks that `value` is a multiple of the lot size of `ticker`, counted in whole units if
+    /// the token is indivisible.
     fn check_granularity(ticker: &Ticker, value: T::Balance) -> bool {
         // Read the token details
         let token = Self::token_details(ticker);
-        token.divisible || value % ONE_UNIT.into() == 0.into()
+        let lot_size = Self::lot_size(ticker);
+        let granularity = if token.divisible {
+            lot_size
+        } else {
+            lot_size.saturating_mul(ONE_UNIT.into())
+        };
+        value % granularity == 0.into()
+    }
+
+    /// Fails with `SupplyFloorBreached` if `total_supply` is below the supply floor of `ticker`.
+    fn ensure_supply_floor(ticker: &Ticker, total_supply: T::Balance) -> DispatchResult {
+        ensure!(
+            total_supply >= Self::supply_floor(ticker),
+            Error::<T>::SupplyFloorBreached
+        );
+        Ok(())
+    }
+
+    /// Fails with `FundingRoundClosed` if the current funding round of `ticker` has a window and
+    /// now is outside of it.
+    fn ensure_funding_round_open(ticker: &Ticker) -> DispatchResult {
+        let ticker_round = (*ticker, Self::funding_round(ticker));
+        if <FundingRoundWindow<T>>::exists(&ticker_round) {
+            let (start, end) = Self::funding_round_window(&ticker_round);
+            let now = <pallet_timestamp::Module<T>>::get();
+            ensure!(start <= now && now <= end, Error::<T>::FundingRoundClosed);
+        }
+        Ok(())
+    }
+
+    /// Returns the issuance window of `ticker` after issuing `value`, or `None` if the issuance of
+    /// `ticker` is not limited. An elapsed period is restarted at the current total supply.
+    ///
+    /// # Failure
+    /// It fails with `IssuanceLimitExceeded` if the period issuance would exceed the limit.
+    fn issuance_window_after(
+        ticker: &Ticker,
+        value: T::Balance,
+    ) -> StdResult<Option<IssuanceWindow<T::Balance, T::Moment>>, DispatchError> {
+        let period = Self::issuance_limit_period(ticker);
+        if period.is_zero() {
+            return Ok(None);
+        }
+        let now = <pallet_timestamp::Module<T>>::get();
+        let mut window = Self::issuance_window(ticker);
+        if now >= window.start.saturating_add(period) {
+            window = IssuanceWindow {
+                start: now,
+                supply_at_start: Self::token_details(ticker).total_supply,
+                issued: 0.into(),
+            };
+        }
+        window.issued = window
+            .issued
+            .checked_add(&value)
+            .ok_or("overflow in calculating issued amount")?;
+        ensure!(
+            window.issued <= Self::max_issuance_percent_per_period(ticker) * window.supply_at_start,
+            Error::<T>::IssuanceLimitExceeded
+        );
+        Ok(Some(window))
+    }
+
+    /// Rounds an issued `value` down to a whole unit if `ticker` is indivisible and has issuance
+    /// rounding enabled. Otherwise `value` is returned unchanged.
+    fn round_issuance(ticker: &Ticker, value: T::Balance) -> T::Balance {
+        if Self::issuance_rounding_enabled(ticker) && !Self::token_details(ticker).divisible {
+            value - value % ONE_UNIT.into()
+        } else {
+            value
+        }
+    }
+
+    /// Returns the DID that holds the tokens in escrow of `custodian_did`.
+    pub fn escrow_did(ticker: &Ticker, custodian_did: IdentityId) -> IdentityId {
+        IdentityId::from(blake2_256(&(ESCROW, ticker, custodian_did).encode()))
     }
 
     fn _check_custody_allowance(
@@ -1864,18 +5184,89 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    /// Returns whether the (ticker, sender, spender) allowance has an expiry that has passed.
+    fn is_allowance_expired(ticker_did_spender: &(Ticker, IdentityId, IdentityId)) -> bool {
+        Self::allowance_expiry(ticker_did_spender).map_or(false, |expiry| {
+            <pallet_timestamp::Module<T>>::get() > expiry
+        })
+    }
+
+    /// Returns whether `allowance` is within the maximum allowance per spender of a ticker.
+    fn is_within_allowance_cap(ticker: &Ticker, allowance: T::Balance) -> bool {
+        Self::max_allowance_per_spender(ticker)
+            .map_or(true, |max_allowance| allowance <= max_allowance)
+    }
+
     fn _increase_custody_allowance(
         ticker: Ticker,
         holder_did: IdentityId,
         custodian_did: IdentityId,
         value: T::Balance,
     ) -> DispatchResult {
+        let holder_balance = Self::balance_of((ticker, holder_did));
+        let (new_custody_allowance, new_current_allowance) =
+            Self::ensure_custody_allowance_increase(
+                ticker,
+                holder_did,
+                custodian_did,
+                value,
+                holder_balance,
+            )?;
+        Self::set_custody_allowance(
+            ticker,
+            holder_did,
+            custodian_did,
+            new_custody_allowance,
+            new_current_allowance,
+        );
+        Ok(())
+    }
+
+    /// Stores the allowances returned by `ensure_custody_allowance_increase` for `custodian_did`
+    /// over the tokens of `holder_did`.
+    fn set_custody_allowance(
+        ticker: Ticker,
+        holder_did: IdentityId,
+        custodian_did: IdentityId,
+        new_custody_allowance: T::Balance,
+        new_current_allowance: T::Balance,
+    ) {
+        let old_allowance = Self::custodian_allowance((ticker, holder_did, custodian_did));
+        // Update Storage
+        <CustodianAllowance<T>>::insert(
+            (ticker, holder_did, custodian_did),
+            &new_current_allowance,
+        );
+        <TotalCustodyAllowance<T>>::insert((ticker, holder_did), new_custody_allowance);
+        Self::record_custody_allowance_change(
+            ticker,
+            holder_did,
+            custodian_did,
+            old_allowance,
+            new_current_allowance,
+        );
+    }
+
+    /// Checks that the custody allowance of `custodian_did` over the tokens of `holder_did` can be
+    /// increased by `value` when the holder has `holder_balance`. It returns the new total custody
+    /// allowance of the holder and the new allowance of the custodian.
+    fn ensure_custody_allowance_increase(
+        ticker: Ticker,
+        holder_did: IdentityId,
+        custodian_did: IdentityId,
+        value: T::Balance,
+        holder_balance: T::Balance,
+    ) -> StdResult<(T::Balance, T::Balance), DispatchError> {
+        ensure!(
+            holder_did != custodian_did,
+            Error::<T>::SelfCustodyNotAllowed
+        );
         let new_custody_allowance = Self::total_custody_allowance((ticker, holder_did))
             .checked_add(&value)
             .ok_or("total custody allowance get overflowed")?;
         // Ensure that balance of the token holder should greater than or equal to the total custody allowance + value
         ensure!(
-            Self::balance_of((ticker, holder_did)) >= new_custody_allowance,
+            holder_balance >= new_custody_allowance,
             "Insufficient balance of holder did"
         );
         // Ensure the valid DID
@@ -1884,24 +5275,160 @@ impl<T: Trait> Module<T> {
             "Invalid custodian DID"
         );
 
+        // Only new custodians count towards the limit.
+        let custodians = Self::custodians_of((ticker, holder_did));
+        ensure!(
+            custodians.contains(&custodian_did)
+                || custodians.len() < T::MaxCustodiansPerHolder::get() as usize,
+            Error::<T>::TooManyCustodians
+        );
+
         let old_allowance = Self::custodian_allowance((ticker, holder_did, custodian_did));
         let new_current_allowance = old_allowance
             .checked_add(&value)
             .ok_or("allowance get overflowed")?;
-        // Update Storage
-        <CustodianAllowance<T>>::insert(
-            (ticker, holder_did, custodian_did),
-            &new_current_allowance,
+        ensure!(
+            Self::is_within_allowance_cap(&ticker, new_current_allowance),
+            Error::<T>::AllowanceCapExceeded
         );
-        <TotalCustodyAllowance<T>>::insert((ticker, holder_did), new_custody_allowance);
+        Ok((new_custody_allowance, new_current_allowance))
+    }
+
+    /// Checks that `value` received by `holder_did` can be put under the custody of the custodian
+    /// named in the `CUSTODIAN_CLAIM_KEY` claim of the holder, if the token has `AutoCustodian`
+    /// enabled, once the holder has `holder_balance`. The claim must be issued by the token owner
+    /// and hold the custodian DID. Holders without a valid claim are left without custodian.
+    /// It returns the custodian and its allowances, to be stored with `set_custody_allowance`
+    /// once the tokens are received.
+    fn ensure_auto_custody(
+        ticker: &Ticker,
+        holder_did: IdentityId,
+        value: T::Balance,
+        holder_balance: T::Balance,
+    ) -> StdResult<Option<(IdentityId, T::Balance, T::Balance)>, DispatchError> {
+        if !Self::auto_custodian(ticker) {
+            return Ok(None);
+        }
+        let owner_did = Self::token_details(ticker).owner_did;
+        let custodian_did = <identity::Module<T>>::fetch_claim_value(
+            holder_did,
+            CUSTODIAN_CLAIM_KEY.to_vec(),
+            owner_did,
+        )
+        .and_then(|claim| IdentityId::try_from(claim.value.as_slice()).ok())
+        .filter(|custodian_did| {
+            *custodian_did != holder_did && <identity::DidRecords>::exists(custodian_did)
+        });
+        match custodian_did {
+            Some(custodian_did) => {
+                let (new_custody_allowance, new_allowance) =
+                    Self::ensure_custody_allowance_increase(
+                        *ticker,
+                        holder_did,
+                        custodian_did,
+                        value,
+                        holder_balance,
+                    )?;
+                Ok(Some((custodian_did, new_custody_allowance, new_allowance)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Checks, before a batch is applied, that `holder_did` can receive `total` tokens of
+    /// `ticker` in it without its automatic custody failing.
+    fn ensure_received_auto_custody(
+        ticker: &Ticker,
+        holder_did: IdentityId,
+        total: T::Balance,
+    ) -> DispatchResult {
+        let holder_balance = Self::balance_of((*ticker, holder_did))
+            .checked_add(&total)
+            .ok_or("overflow in calculating balance")?;
+        Self::ensure_auto_custody(ticker, holder_did, total, holder_balance)?;
+        Ok(())
+    }
+
+    /// Transfers `value` of `holder_did` to `receiver_did` and deducts it from the custody
+    /// allowance of `custodian_did`. The allowance and the transfer must be validated by the caller.
+    fn _transfer_by_custodian(
+        ticker: &Ticker,
+        custodian_did: IdentityId,
+        holder_did: IdentityId,
+        receiver_did: IdentityId,
+        value: T::Balance,
+    ) -> DispatchResult {
+        let old_allowance = Self::custodian_allowance((*ticker, holder_did, custodian_did));
+        // using checked_sub (safe math) to avoid underflow
+        let new_allowance = old_allowance
+            .checked_sub(&value)
+            .ok_or("underflow in calculating allowance")?;
+        // using checked_sub (safe math) to avoid underflow
+        let new_total_allowance = Self::total_custody_allowance((*ticker, holder_did))
+            .checked_sub(&value)
+            .ok_or("underflow in calculating the total allowance")?;
+        Self::_transfer(ticker, holder_did, receiver_did, value)?;
+        // Update Storage of allowance
+        <CustodianAllowance<T>>::insert((*ticker, holder_did, custodian_did), &new_allowance);
+        <TotalCustodyAllowance<T>>::insert((*ticker, holder_did), new_total_allowance);
+        Self::record_custody_allowance_change(
+            *ticker,
+            holder_did,
+            custodian_did,
+            old_allowance,
+            new_allowance,
+        );
+        Self::deposit_event(RawEvent::CustodyTransfer(
+            *ticker,
+            custodian_did,
+            holder_did,
+            receiver_did,
+            value,
+        ));
+        Ok(())
+    }
+
+    /// Increments the custody allowance nonce of the holder, stores the change in its history,
+    /// keeps `CustodiansOf` up to date and emits `CustodyAllowanceChanged`.
+    fn record_custody_allowance_change(
+        ticker: Ticker,
+        holder_did: IdentityId,
+        custodian_did: IdentityId,
+        old_allowance: T::Balance,
+        new_allowance: T::Balance,
+    ) {
+        let ticker_holder_did = (ticker, holder_did);
+        <CustodiansOf>::mutate(&ticker_holder_did, |custodians| {
+            let position = custodians.iter().position(|did| *did == custodian_did);
+            match position {
+                None if new_allowance != 0.into() => custodians.push(custodian_did),
+                Some(index) if new_allowance == 0.into() => {
+                    custodians.swap_remove(index);
+                }
+                _ => {}
+            }
+        });
+        let nonce = Self::custody_allowance_nonce(&ticker_holder_did).wrapping_add(1);
+        <CustodyAllowanceNonce>::insert(&ticker_holder_did, nonce);
+        <CustodyAllowanceHistory<T>>::mutate(&ticker_holder_did, |history| {
+            if history.len() >= CUSTODY_ALLOWANCE_HISTORY_LEN {
+                history.remove(0);
+            }
+            history.push(CustodyAllowanceChange {
+                nonce,
+                custodian_did,
+                old_allowance,
+                new_allowance,
+            });
+        });
         Self::deposit_event(RawEvent::CustodyAllowanceChanged(
             ticker,
             holder_did,
             custodian_did,
             old_allowance,
-            new_current_allowance,
+            new_allowance,
+            nonce,
         ));
-        Ok(())
     }
 
     /// Accept and process a ticker transfer
@@ -1939,7 +5466,7 @@ impl<T: Trait> Module<T> {
             Signatory::from(to_did),
             LinkData::TickerOwned(ticker),
             ticker_details.expiry,
-        );
+        )?;
 
         <Tickers<T>>::mutate(&ticker, |tr| {
             tr.owner = to_did;
@@ -1996,12 +5523,12 @@ impl<T: Trait> Module<T> {
             Signatory::from(to_did),
             LinkData::TickerOwned(ticker),
             None,
-        );
+        )?;
         let token_link = <identity::Module<T>>::add_link(
             Signatory::from(to_did),
             LinkData::TokenOwned(ticker),
             None,
-        );
+        )?;
 
         <Tickers<T>>::mutate(&ticker, |tr| {
             tr.owner = to_did;
@@ -129,8 +129,7 @@ decl_module! {
                 if count > 0 {
                     count
                 } else {
-                    <asset::Module<T>>::_create_checkpoint(&ticker)?;
-                    <asset::TotalCheckpoints>::get(&ticker)
+                    <asset::Module<T>>::_create_checkpoint(&ticker)?
                 }
             };
             // Check if checkpoint exists
@@ -579,9 +578,16 @@ mod tests {
         type Event = ();
     }
 
+    parameter_types! {
+        pub const MaxCustodiansPerHolder: u32 = 10;
+        pub const MaxIdentifiersPerToken: u32 = 10;
+    }
+
     impl asset::Trait for Test {
         type Event = ();
         type Currency = balances::Module<Test>;
+        type MaxCustodiansPerHolder = MaxCustodiansPerHolder;
+        type MaxIdentifiersPerToken = MaxIdentifiersPerToken;
     }
 
     impl AcceptTransfer for Test {
@@ -596,12 +602,19 @@ mod tests {
 
     impl statistics::Trait for Test {}
 
+    parameter_types! {
+        pub const MaxBatchSize: u32 = 10;
+    }
+
     impl identity::Trait for Test {
         type Event = ();
         type Proposal = Call<Test>;
         type AddSignerMultiSigTarget = Test;
         type KycServiceProviders = Test;
         type Balances = balances::Module<Test>;
+        type MaxBatchSize = MaxBatchSize;
+        type AuthorizationHandlers = ();
+        type ClaimFeeCollector = ();
     }
 
     impl GroupTrait for Test {
@@ -724,6 +737,9 @@ mod tests {
         identity::GenesisConfig::<Test> {
             owner: AccountKeyring::Alice.public().into(),
             did_creation_fee: 250,
+            claim_expiry_grace_period: 0,
+            claim_fee: 0,
+            identities: vec![],
         }
         .assimilate_storage(&mut t)
         .unwrap();
@@ -734,7 +750,9 @@ mod tests {
                 max_ticker_length: 12,
                 registration_length: Some(10000),
             },
+            min_ticker_length: 0,
             fee_collector: AccountKeyring::Dave.public().into(),
+            initial_balances: vec![],
         }
         .assimilate_storage(&mut t)
         .unwrap();
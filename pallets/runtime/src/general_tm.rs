@@ -42,6 +42,7 @@
 //! ### Public Functions
 //!
 //! - `verify_restriction` - Checks if a transfer is a valid transfer and returns the result
+//! - `required_receiver_claims` - Returns the claim keys that receivers are checked against
 
 use crate::{
     asset::{self, AssetTrait},
@@ -199,12 +200,59 @@ impl<T: Trait> Module<T> {
         T::Asset::is_owner(ticker, sender_did)
     }
 
+    /// Returns the distinct claim keys checked on receivers by the active rules of `ticker`.
+    pub fn required_receiver_claims(ticker: &Ticker) -> Vec<Vec<u8>> {
+        let mut keys = Self::active_rules(ticker)
+            .into_iter()
+            .flat_map(|asset_rule| asset_rule.receiver_rules)
+            .map(|rule| rule.key)
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+    /// Returns the claim keys and trusted issuers that the active rules of `ticker` check on
+    /// senders and on receivers respectively.
+    pub fn claim_requirements(
+        ticker: &Ticker,
+    ) -> (
+        Vec<(Vec<u8>, Vec<IdentityId>)>,
+        Vec<(Vec<u8>, Vec<IdentityId>)>,
+    ) {
+        let mut sender_claims = Vec::new();
+        let mut receiver_claims = Vec::new();
+        for asset_rule in Self::active_rules(ticker) {
+            sender_claims.extend(
+                asset_rule
+                    .sender_rules
+                    .into_iter()
+                    .map(|rule| (rule.key, rule.trusted_issuers)),
+            );
+            receiver_claims.extend(
+                asset_rule
+                    .receiver_rules
+                    .into_iter()
+                    .map(|rule| (rule.key, rule.trusted_issuers)),
+            );
+        }
+        (sender_claims, receiver_claims)
+    }
+
+    /// Fetches the claim value of `did` from the claims of the identity, falling back to the
+    /// claims that only apply to `ticker`.
     fn fetch_value(
         did: IdentityId,
+        ticker: &Ticker,
         key: Vec<u8>,
         trusted_issuers: Vec<IdentityId>,
     ) -> Option<ClaimValue> {
-        <identity::Module<T>>::fetch_claim_value_multiple_issuers(did, key, trusted_issuers)
+        <identity::Module<T>>::fetch_claim_value_multiple_issuers(
+            did,
+            key.clone(),
+            trusted_issuers.clone(),
+        )
+        .or_else(|| <identity::Module<T>>::fetch_ticker_claim(did, *ticker, key, trusted_issuers))
     }
 
     ///  Sender restriction verification
@@ -223,6 +271,7 @@ impl<T: Trait> Module<T> {
                 for sender_rule in active_rule.sender_rules {
                     let identity_value = Self::fetch_value(
                         from_did.clone(),
+                        ticker,
                         sender_rule.key,
                         sender_rule.trusted_issuers,
                     );
@@ -248,6 +297,7 @@ impl<T: Trait> Module<T> {
                 for receiver_rule in active_rule.receiver_rules {
                     let identity_value = Self::fetch_value(
                         to_did.clone(),
+                        ticker,
                         receiver_rule.key,
                         receiver_rule.trusted_issuers,
                     );
@@ -282,7 +332,9 @@ mod tests {
     use super::*;
     use chrono::prelude::*;
     use frame_support::traits::Currency;
-    use frame_support::{assert_ok, dispatch::DispatchResult, impl_outer_origin, parameter_types};
+    use frame_support::{
+        assert_err, assert_ok, dispatch::DispatchResult, impl_outer_origin, parameter_types,
+    };
     use frame_system::EnsureSignedBy;
     use sp_core::{crypto::key_types, H256};
     use sp_runtime::{
@@ -480,12 +532,19 @@ mod tests {
         type MembershipChanged = ();
     }
 
+    parameter_types! {
+        pub const MaxBatchSize: u32 = 10;
+    }
+
     impl identity::Trait for Test {
         type Event = ();
         type Proposal = Call<Test>;
         type AddSignerMultiSigTarget = Test;
         type KycServiceProviders = Test;
         type Balances = balances::Module<Test>;
+        type MaxBatchSize = MaxBatchSize;
+        type AuthorizationHandlers = ();
+        type ClaimFeeCollector = ();
     }
 
     impl AddSignerMultiSig for Test {
@@ -494,9 +553,16 @@ mod tests {
         }
     }
 
+    parameter_types! {
+        pub const MaxCustodiansPerHolder: u32 = 10;
+        pub const MaxIdentifiersPerToken: u32 = 10;
+    }
+
     impl asset::Trait for Test {
         type Event = ();
         type Currency = balances::Module<Test>;
+        type MaxCustodiansPerHolder = MaxCustodiansPerHolder;
+        type MaxIdentifiersPerToken = MaxIdentifiersPerToken;
     }
 
     impl statistics::Trait for Test {}
@@ -517,6 +583,7 @@ mod tests {
 
     type Identity = identity::Module<Test>;
     type GeneralTM = Module<Test>;
+    type PercentageTM = percentage_tm::Module<Test>;
     type Balances = balances::Module<Test>;
     type Asset = asset::Module<Test>;
 
@@ -528,6 +595,9 @@ mod tests {
         identity::GenesisConfig::<Test> {
             owner: AccountKeyring::Alice.public().into(),
             did_creation_fee: 250,
+            claim_expiry_grace_period: 0,
+            claim_fee: 0,
+            identities: vec![],
         }
         .assimilate_storage(&mut t)
         .unwrap();
@@ -538,7 +608,9 @@ mod tests {
                 max_ticker_length: 12,
                 registration_length: Some(10000),
             },
+            min_ticker_length: 0,
             fee_collector: AccountKeyring::Dave.public().into(),
+            initial_balances: vec![],
         }
         .assimilate_storage(&mut t)
         .unwrap();
@@ -788,4 +860,162 @@ mod tests {
             assert_eq!(asset_rules_new.len(), 0);
         });
     }
+
+    #[test]
+    fn transfer_rules_summary_aggregates_both_managers() {
+        identity_owned_by_alice().execute_with(|| {
+            let token_owner_acc = AccountId::from(AccountKeyring::Alice);
+            let (token_owner_signed, token_owner_did) = make_account(&token_owner_acc).unwrap();
+            let ticker = Ticker::from_slice(b"SUMMARY");
+            assert_ok!(Asset::create_token(
+                token_owner_signed.clone(),
+                token_owner_did,
+                b"SUMMARY".to_vec(),
+                ticker,
+                1_000_000,
+                true,
+                AssetType::default(),
+                vec![],
+                None
+            ));
+            assert_eq!(Asset::transfer_rules_summary(ticker), Default::default());
+
+            let rule = |key: &[u8]| RuleData {
+                key: key.to_vec(),
+                value: 1u8.encode(),
+                trusted_issuers: vec![token_owner_did],
+                operator: Operators::EqualTo,
+            };
+            let asset_rule_1 = AssetRule {
+                sender_rules: vec![rule(b"sender_key")],
+                receiver_rules: vec![rule(b"kyc"), rule(b"accredited")],
+            };
+            let asset_rule_2 = AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![rule(b"kyc")],
+            };
+            assert_ok!(GeneralTM::add_active_rule(
+                token_owner_signed.clone(),
+                token_owner_did,
+                ticker,
+                asset_rule_1.clone()
+            ));
+            assert_ok!(GeneralTM::add_active_rule(
+                token_owner_signed.clone(),
+                token_owner_did,
+                ticker,
+                asset_rule_2.clone()
+            ));
+            assert_ok!(PercentageTM::toggle_maximum_percentage_restriction(
+                token_owner_signed.clone(),
+                token_owner_did,
+                ticker,
+                15
+            ));
+
+            let summary = Asset::transfer_rules_summary(ticker);
+            assert_eq!(summary.active_rules, vec![asset_rule_1, asset_rule_2]);
+            assert_eq!(summary.max_percentage, 15);
+            assert_eq!(
+                summary.required_receiver_claims,
+                vec![b"accredited".to_vec(), b"kyc".to_vec()]
+            );
+        });
+    }
+
+    #[test]
+    fn ticker_scoped_claims_only_apply_to_their_ticker() {
+        identity_owned_by_alice().execute_with(|| {
+            let token_owner_acc = AccountId::from(AccountKeyring::Alice);
+            let (token_owner_signed, token_owner_did) = make_account(&token_owner_acc).unwrap();
+            let investor_acc = AccountId::from(AccountKeyring::Bob);
+            let (_, investor_did) = make_account(&investor_acc).unwrap();
+            <pallet_timestamp::Module<Test>>::set_timestamp(Utc::now().timestamp() as u64);
+
+            let approved_name = b"APPROVED";
+            let other_name = b"OTHER";
+            let approved_ticker = Ticker::from_slice(approved_name);
+            let other_ticker = Ticker::from_slice(other_name);
+            let asset_rule = AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![RuleData {
+                    key: b"approved".to_vec(),
+                    value: b"yes".to_vec(),
+                    trusted_issuers: vec![token_owner_did],
+                    operator: Operators::EqualTo,
+                }],
+            };
+            for (name, ticker) in &[
+                (approved_name.to_vec(), approved_ticker),
+                (other_name.to_vec(), other_ticker),
+            ] {
+                assert_ok!(Asset::create_token(
+                    token_owner_signed.clone(),
+                    token_owner_did,
+                    name.clone(),
+                    *ticker,
+                    1_000_000,
+                    true,
+                    AssetType::default(),
+                    vec![],
+                    None
+                ));
+                assert_ok!(GeneralTM::add_active_rule(
+                    token_owner_signed.clone(),
+                    token_owner_did,
+                    *ticker,
+                    asset_rule.clone()
+                ));
+            }
+
+            assert_ok!(Identity::add_ticker_claim(
+                token_owner_signed.clone(),
+                investor_did,
+                approved_ticker,
+                b"approved".to_vec(),
+                token_owner_did,
+                99999999999999999u64,
+                ClaimValue {
+                    data_type: DataTypes::VecU8,
+                    value: b"yes".to_vec(),
+                }
+            ));
+
+            assert_ok!(Asset::transfer(
+                token_owner_signed.clone(),
+                token_owner_did,
+                approved_ticker,
+                investor_did,
+                100
+            ));
+            assert_err!(
+                Asset::transfer(
+                    token_owner_signed.clone(),
+                    token_owner_did,
+                    other_ticker,
+                    investor_did,
+                    100
+                ),
+                "Transfer restrictions failed"
+            );
+
+            assert_ok!(Identity::revoke_ticker_claim(
+                token_owner_signed.clone(),
+                investor_did,
+                approved_ticker,
+                b"approved".to_vec(),
+                token_owner_did
+            ));
+            assert_err!(
+                Asset::transfer(
+                    token_owner_signed,
+                    token_owner_did,
+                    approved_ticker,
+                    investor_did,
+                    100
+                ),
+                "Transfer restrictions failed"
+            );
+        });
+    }
 }
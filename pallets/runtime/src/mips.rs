@@ -646,12 +646,19 @@ mod tests {
         type Identity = identity::Module<Test>;
     }
 
+    parameter_types! {
+        pub const MaxBatchSize: u32 = 10;
+    }
+
     impl identity::Trait for Test {
         type Event = ();
         type Proposal = Call;
         type AddSignerMultiSigTarget = Test;
         type KycServiceProviders = Test;
         type Balances = balances::Module<Test>;
+        type MaxBatchSize = MaxBatchSize;
+        type AuthorizationHandlers = ();
+        type ClaimFeeCollector = ();
     }
 
     impl group::GroupTrait for Test {
@@ -107,7 +107,7 @@ decl_module! {
                     signer,
                     AuthorizationData::AddMultiSigSigner,
                     None
-                );
+                )?;
             }
 
             <MultiSigSignsRequired<T>>::insert(&wallet_id, &sigs_required);
@@ -233,7 +233,7 @@ decl_module! {
                 signer,
                 AuthorizationData::AddMultiSigSigner,
                 None
-            );
+            )?;
             Self::deposit_event(RawEvent::MultiSigSignerAuthorized(sender, signer));
             Ok(())
         }
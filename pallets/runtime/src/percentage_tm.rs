@@ -64,7 +64,7 @@ decl_module! {
         fn deposit_event() = default;
 
         /// Set a maximum percentage that can be owned by a single investor
-        fn toggle_maximum_percentage_restriction(origin, did: IdentityId, ticker: Ticker, max_percentage: u16) -> DispatchResult  {
+        pub fn toggle_maximum_percentage_restriction(origin, did: IdentityId, ticker: Ticker, max_percentage: u16) -> DispatchResult  {
             let sender = Signatory::AccountKey(AccountKey::try_from(ensure_signed(origin)?.encode())?);
             // Check that sender is allowed to act on behalf of `did`
             ensure!(<identity::Module<T>>::is_signer_authorized(did, &sender), "sender must be a signing key for DID");
@@ -133,8 +133,8 @@ impl<T: Trait> Module<T> {
             }
             Ok(ERC1400_TRANSFER_SUCCESS)
         } else {
-            sp_runtime::print("to account is not active");
-            Ok(ERC1400_INVALID_RECEIVER)
+            // Redemptions have no receiver, so no holding can exceed the maximum percentage.
+            Ok(ERC1400_TRANSFER_SUCCESS)
         }
     }
 }
@@ -467,9 +467,16 @@ impl multisig::Trait for Runtime {
     type Event = Event;
 }
 
+parameter_types! {
+    pub const MaxCustodiansPerHolder: u32 = 50;
+    pub const MaxIdentifiersPerToken: u32 = 16;
+}
+
 impl asset::Trait for Runtime {
     type Event = Event;
     type Currency = Balances;
+    type MaxCustodiansPerHolder = MaxCustodiansPerHolder;
+    type MaxIdentifiersPerToken = MaxIdentifiersPerToken;
 }
 
 impl utils::Trait for Runtime {
@@ -505,12 +512,19 @@ impl percentage_tm::Trait for Runtime {
     type Event = Event;
 }
 
+parameter_types! {
+    pub const MaxBatchSize: u32 = 500;
+}
+
 impl identity::Trait for Runtime {
     type Event = Event;
     type Proposal = Call;
     type AddSignerMultiSigTarget = MultiSig;
     type KycServiceProviders = KycServiceProviders;
     type Balances = balances::Module<Runtime>;
+    type MaxBatchSize = MaxBatchSize;
+    type AuthorizationHandlers = ();
+    type ClaimFeeCollector = ();
 }
 
 impl contracts_wrapper::Trait for Runtime {}
@@ -578,9 +592,10 @@ construct_runtime!(
         Mips: mips::{Module, Call, Storage, Event<T>, Config<T>},
 
         //Polymesh
+        // Must be before asset, whose genesis uses the genesis identities.
+        Identity: identity::{Module, Call, Storage, Event<T>, Config<T>},
         Asset: asset::{Module, Call, Storage, Config<T>, Event<T>},
         Dividend: dividend::{Module, Call, Storage, Event<T>},
-        Identity: identity::{Module, Call, Storage, Event<T>, Config<T>},
         GeneralTM: general_tm::{Module, Call, Storage, Event},
         Voting: voting::{Module, Call, Storage, Event<T>},
         StoCapped: sto_capped::{Module, Call, Storage, Event<T>},
@@ -1,22 +1,38 @@
 use crate::{
-    asset::{self, AssetType, IdentifierType, SecurityToken, SignData},
-    general_tm,
+    asset::{
+        self, AssetType, ControllerAction, ControllerActionType, FreezeDirection, IdentifierType,
+        OwnershipInfo, SecurityToken, SignData, TokenConfig, MAX_ATTRIBUTE_KEY_LENGTH,
+        MAX_ATTRIBUTE_VALUE_LENGTH, MAX_TRANSFER_MEMO_LENGTH, REDEMPTION_REQUEST_LENGTH,
+    },
+    exemption, general_tm, statistics,
     test::{
-        storage::{make_account, TestStorage},
+        storage::{make_account, KycServiceProviders, TestStorage},
         ExtBuilder,
     },
 };
 
 use polymesh_primitives::{
-    AuthorizationData, Document, IdentityId, LinkData, Signatory, SmartExtension,
+    AccountKey, AuthorizationData, Document, IdentityId, LinkData, Signatory, SmartExtension,
     SmartExtensionType, Ticker,
 };
 use polymesh_runtime_balances as balances;
+use polymesh_runtime_common::{
+    constants::{
+        currency::{MAX_SUPPLY, ONE_UNIT},
+        APP_SUPPLY_FLOOR_BREACHED, CUSTODIAN_CLAIM_KEY, ERC1400_FUNDS_LOCKED,
+        ERC1400_INSUFFICIENT_BALANCE, ERC1400_TRANSFERS_HALTED, ERC1400_TRANSFER_SUCCESS,
+        KYC_EXPIRY_CLAIM_KEY,
+    },
+    traits::identity::{ClaimValue, DataTypes},
+};
 use polymesh_runtime_identity as identity;
 
 use codec::Encode;
 use frame_support::{assert_err, assert_noop, assert_ok, traits::Currency, StorageMap};
-use sp_runtime::AnySignature;
+use sp_runtime::{
+    traits::{OnFinalize, OnInitialize},
+    AnySignature, Perbill,
+};
 use test_client::AccountKeyring;
 
 use chrono::prelude::Utc;
@@ -28,6 +44,8 @@ type Balances = balances::Module<TestStorage>;
 type Asset = asset::Module<TestStorage>;
 type Timestamp = pallet_timestamp::Module<TestStorage>;
 type GeneralTM = general_tm::Module<TestStorage>;
+type Exemption = exemption::Module<TestStorage>;
+type Statistic = statistics::Module<TestStorage>;
 type AssetError = asset::Error<TestStorage>;
 
 type OffChainSignature = AnySignature;
@@ -770,7 +788,7 @@ fn register_ticker() {
                 owner_signed.clone(),
                 Ticker::from_slice(&[0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01])
             ),
-            "ticker length over the limit"
+            AssetError::TickerTooLong
         );
 
         let ticker = Ticker::from_slice(&[0x01, 0x01]);
@@ -817,19 +835,19 @@ fn transfer_ticker() {
         assert_eq!(Asset::is_ticker_available(&ticker), true);
         assert_ok!(Asset::register_ticker(owner_signed.clone(), ticker));
 
-        Identity::add_auth(
+        assert_ok!(Identity::add_auth(
             Signatory::from(owner_did),
             Signatory::from(alice_did),
             AuthorizationData::TransferTicker(ticker),
             None,
-        );
+        ));
 
-        Identity::add_auth(
+        assert_ok!(Identity::add_auth(
             Signatory::from(owner_did),
             Signatory::from(bob_did),
             AuthorizationData::TransferTicker(ticker),
             None,
-        );
+        ));
 
         assert_eq!(Asset::is_ticker_registry_valid(&ticker, owner_did), true);
         assert_eq!(Asset::is_ticker_registry_valid(&ticker, alice_did), false);
@@ -866,36 +884,36 @@ fn transfer_ticker() {
             "Illegal use of Authorization"
         );
 
-        Identity::add_auth(
+        assert_ok!(Identity::add_auth(
             Signatory::from(alice_did),
             Signatory::from(bob_did),
             AuthorizationData::TransferTicker(ticker),
             Some(now.timestamp() as u64 - 100),
-        );
+        ));
         auth_id = Identity::last_authorization(Signatory::from(bob_did));
         assert_err!(
             Asset::accept_ticker_transfer(bob_signed.clone(), auth_id),
             "Authorization expired"
         );
 
-        Identity::add_auth(
+        assert_ok!(Identity::add_auth(
             Signatory::from(alice_did),
             Signatory::from(bob_did),
             AuthorizationData::Custom(ticker),
             Some(now.timestamp() as u64 + 100),
-        );
+        ));
         auth_id = Identity::last_authorization(Signatory::from(bob_did));
         assert_err!(
             Asset::accept_ticker_transfer(bob_signed.clone(), auth_id),
             AssetError::NoTickerTransferAuth
         );
 
-        Identity::add_auth(
+        assert_ok!(Identity::add_auth(
             Signatory::from(alice_did),
             Signatory::from(bob_did),
             AuthorizationData::TransferTicker(ticker),
             Some(now.timestamp() as u64 + 100),
-        );
+        ));
         auth_id = Identity::last_authorization(Signatory::from(bob_did));
         assert_ok!(Asset::accept_ticker_transfer(bob_signed.clone(), auth_id));
 
@@ -930,19 +948,19 @@ fn transfer_token_ownership() {
             None
         ));
 
-        Identity::add_auth(
+        assert_ok!(Identity::add_auth(
             Signatory::from(owner_did),
             Signatory::from(alice_did),
             AuthorizationData::TransferTokenOwnership(ticker),
             None,
-        );
+        ));
 
-        Identity::add_auth(
+        assert_ok!(Identity::add_auth(
             Signatory::from(owner_did),
             Signatory::from(bob_did),
             AuthorizationData::TransferTokenOwnership(ticker),
             None,
-        );
+        ));
 
         assert_eq!(Asset::token_details(&ticker).owner_did, owner_did);
 
@@ -994,48 +1012,48 @@ fn transfer_token_ownership() {
             "Illegal use of Authorization"
         );
 
-        Identity::add_auth(
+        assert_ok!(Identity::add_auth(
             Signatory::from(alice_did),
             Signatory::from(bob_did),
             AuthorizationData::TransferTokenOwnership(ticker),
             Some(now.timestamp() as u64 - 100),
-        );
+        ));
         auth_id = Identity::last_authorization(Signatory::from(bob_did));
         assert_err!(
             Asset::accept_token_ownership_transfer(bob_signed.clone(), auth_id),
             "Authorization expired"
         );
 
-        Identity::add_auth(
+        assert_ok!(Identity::add_auth(
             Signatory::from(alice_did),
             Signatory::from(bob_did),
             AuthorizationData::Custom(ticker),
             Some(now.timestamp() as u64 + 100),
-        );
+        ));
         auth_id = Identity::last_authorization(Signatory::from(bob_did));
         assert_err!(
             Asset::accept_token_ownership_transfer(bob_signed.clone(), auth_id),
             AssetError::NotTickerOwnershipTransferAuth
         );
 
-        Identity::add_auth(
+        assert_ok!(Identity::add_auth(
             Signatory::from(alice_did),
             Signatory::from(bob_did),
             AuthorizationData::TransferTokenOwnership(Ticker::from_slice(&[0x50])),
             Some(now.timestamp() as u64 + 100),
-        );
+        ));
         auth_id = Identity::last_authorization(Signatory::from(bob_did));
         assert_err!(
             Asset::accept_token_ownership_transfer(bob_signed.clone(), auth_id),
             "Token does not exist"
         );
 
-        Identity::add_auth(
+        assert_ok!(Identity::add_auth(
             Signatory::from(alice_did),
             Signatory::from(bob_did),
             AuthorizationData::TransferTokenOwnership(ticker),
             Some(now.timestamp() as u64 + 100),
-        );
+        ));
         auth_id = Identity::last_authorization(Signatory::from(bob_did));
         assert_ok!(Asset::accept_token_ownership_transfer(
             bob_signed.clone(),
@@ -1063,7 +1081,7 @@ fn update_identifiers() {
         assert!(!<identity::DidRecords>::exists(
             Identity::get_token_did(&ticker).unwrap()
         ));
-        let identifier_value1 = b"ABC123";
+        let identifier_value1 = b"037833100";
         let identifiers = vec![(IdentifierType::Cusip, identifier_value1.to_vec())];
         assert_ok!(Asset::create_token(
             owner_signed.clone(),
@@ -1084,9 +1102,9 @@ fn update_identifiers() {
             Asset::identifiers((ticker, IdentifierType::Cusip)),
             identifier_value1.to_vec()
         );
-        let identifier_value2 = b"XYZ555";
+        let identifier_value2 = b"US0378331005";
         let updated_identifiers = vec![
-            (IdentifierType::Cusip, Default::default()),
+            (IdentifierType::Cusip, b"38259P508".to_vec()),
             (IdentifierType::Isin, identifier_value2.to_vec()),
         ];
         assert_ok!(Asset::update_identifiers(
@@ -1098,6 +1116,36 @@ fn update_identifiers() {
         for (typ, val) in updated_identifiers {
             assert_eq!(Asset::identifiers((ticker, typ)), val);
         }
+
+        // Malformed ISIN (wrong check digit) is rejected.
+        assert_err!(
+            Asset::update_identifiers(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                vec![(IdentifierType::Isin, b"US0378331004".to_vec())],
+            ),
+            AssetError::InvalidIdentifier
+        );
+        assert_eq!(
+            Asset::identifiers((ticker, IdentifierType::Isin)),
+            identifier_value2.to_vec()
+        );
+
+        // Custom identifiers are not validated.
+        let custom_identifiers = vec![(
+            IdentifierType::Custom(b"internal".to_vec()),
+            b"XYZ555".to_vec(),
+        )];
+        assert_ok!(Asset::update_identifiers(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            custom_identifiers.clone(),
+        ));
+        for (typ, val) in custom_identifiers {
+            assert_eq!(Asset::identifiers((ticker, typ)), val);
+        }
     });
 }
 
@@ -1779,12 +1827,12 @@ fn freeze_unfreeze_asset() {
             "asset must not already be frozen"
         );
         // Attempt to transfer token ownership.
-        Identity::add_auth(
+        assert_ok!(Identity::add_auth(
             Signatory::from(alice_did),
             Signatory::from(bob_did),
             AuthorizationData::TransferTokenOwnership(ticker),
             None,
-        );
+        ));
         let auth_id = Identity::last_authorization(Signatory::from(bob_did));
         // Attempt to mint tokens.
         assert_err!(
@@ -2186,3 +2234,6157 @@ fn freeze_unfreeze_asset() {
  *        }
  *    }
  */
+
+#[test]
+fn percentage_tm_allows_redemptions() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"PCTREDM");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"PCTREDM".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(
+            crate::percentage_tm::Module::<TestStorage>::toggle_maximum_percentage_restriction(
+                owner_signed,
+                owner_did,
+                ticker,
+                10
+            )
+        );
+
+        // Receivers are still limited to the maximum percentage.
+        assert_eq!(
+            crate::percentage_tm::Module::<TestStorage>::verify_restriction(
+                &ticker,
+                Some(owner_did),
+                Some(alice_did),
+                200_000
+            ),
+            Ok(polymesh_runtime_common::constants::APP_FUNDS_LIMIT_REACHED)
+        );
+        // Redemptions have no receiver and always pass.
+        assert_eq!(
+            crate::percentage_tm::Module::<TestStorage>::verify_restriction(
+                &ticker,
+                Some(owner_did),
+                None,
+                200_000
+            ),
+            Ok(ERC1400_TRANSFER_SUCCESS)
+        );
+    });
+}
+
+#[test]
+fn can_redeem_status_codes() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, custodian_did) = make_account(AccountKeyring::Eve.public()).unwrap();
+
+        let now = Utc::now();
+        Timestamp::set_timestamp(now.timestamp() as u64);
+
+        let ticker = Ticker::from_slice(b"REDEEM");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"REDEEM".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        // Allow all transfers
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+
+        // Insufficient balance.
+        assert_eq!(
+            Asset::_is_valid_redeem(&ticker, owner_did, 2_000_000),
+            Ok(ERC1400_INSUFFICIENT_BALANCE)
+        );
+        // Clean redemption.
+        assert_eq!(
+            Asset::_is_valid_redeem(&ticker, owner_did, 500_000),
+            Ok(ERC1400_TRANSFER_SUCCESS)
+        );
+        assert_ok!(Asset::can_redeem(
+            owner_signed.clone(),
+            ticker,
+            owner_did,
+            500_000,
+            vec![]
+        ));
+
+        // Custody allowance blocks part of the balance.
+        assert_ok!(Asset::increase_custody_allowance(
+            owner_signed.clone(),
+            ticker,
+            owner_did,
+            custodian_did,
+            600_000
+        ));
+        assert_eq!(
+            Asset::_is_valid_redeem(&ticker, owner_did, 500_000),
+            Ok(ERC1400_FUNDS_LOCKED)
+        );
+        assert_eq!(
+            Asset::_is_valid_redeem(&ticker, owner_did, 400_000),
+            Ok(ERC1400_TRANSFER_SUCCESS)
+        );
+
+        // The supply floor and the redeem pause are reported as `redeem` enforces them.
+        assert_ok!(Asset::set_supply_floor(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            700_000
+        ));
+        assert_eq!(
+            Asset::_is_valid_redeem(&ticker, owner_did, 400_000),
+            Ok(APP_SUPPLY_FLOOR_BREACHED)
+        );
+        assert_err!(
+            Asset::redeem(owner_signed.clone(), owner_did, ticker, 400_000, vec![]),
+            AssetError::SupplyFloorBreached
+        );
+        assert_ok!(Asset::set_operation_pause_flags(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            asset::PAUSE_REDEEM
+        ));
+        assert_eq!(
+            Asset::_is_valid_redeem(&ticker, owner_did, 300_000),
+            Ok(ERC1400_TRANSFERS_HALTED)
+        );
+        assert_err!(
+            Asset::redeem(owner_signed, owner_did, ticker, 300_000, vec![]),
+            AssetError::OperationPaused
+        );
+    });
+}
+
+#[test]
+fn pause_redemptions_only() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, investor_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let now = Utc::now();
+        Timestamp::set_timestamp(now.timestamp() as u64);
+
+        let ticker = Ticker::from_slice(b"PAUSE");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"PAUSE".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        // Allow all transfers
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+
+        assert_ok!(Asset::set_operation_pause_flags(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            asset::PAUSE_REDEEM
+        ));
+        assert_eq!(Asset::operation_pause_flags(ticker), asset::PAUSE_REDEEM);
+
+        assert_err!(
+            Asset::redeem(owner_signed.clone(), owner_did, ticker, 1_000, vec![]),
+            AssetError::OperationPaused
+        );
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            investor_did,
+            1_000
+        ));
+
+        // Resume all operations.
+        assert_ok!(Asset::set_operation_pause_flags(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            0
+        ));
+        assert_ok!(Asset::redeem(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            1_000,
+            vec![]
+        ));
+    });
+}
+
+#[test]
+fn transfer_batch_same_sender_is_atomic() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, investor_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let now = Utc::now();
+        Timestamp::set_timestamp(now.timestamp() as u64);
+
+        let tickers = vec![Ticker::from_slice(b"ALPHA"), Ticker::from_slice(b"BETA")];
+        for ticker in &tickers {
+            assert_ok!(Asset::create_token(
+                owner_signed.clone(),
+                owner_did,
+                b"TOKEN".to_vec(),
+                *ticker,
+                1_000_000,
+                true,
+                AssetType::default(),
+                vec![],
+                None
+            ));
+            // Allow all transfers
+            assert_ok!(GeneralTM::add_active_rule(
+                owner_signed.clone(),
+                owner_did,
+                *ticker,
+                general_tm::AssetRule {
+                    sender_rules: vec![],
+                    receiver_rules: vec![],
+                }
+            ));
+        }
+
+        // The second leg exceeds the balance, so nothing is transferred.
+        assert_err!(
+            Asset::transfer_batch_same_sender(
+                owner_signed.clone(),
+                owner_did,
+                vec![
+                    (tickers[0], investor_did, 1_000),
+                    (tickers[1], investor_did, 2_000_000),
+                ]
+            ),
+            "Not enough balance."
+        );
+        assert_eq!(Asset::balance_of((tickers[0], owner_did)), 1_000_000);
+        assert_eq!(Asset::balance_of((tickers[0], investor_did)), 0);
+
+        assert_ok!(Asset::transfer_batch_same_sender(
+            owner_signed.clone(),
+            owner_did,
+            vec![
+                (tickers[0], investor_did, 1_000),
+                (tickers[1], investor_did, 2_000),
+            ]
+        ));
+        assert_eq!(Asset::balance_of((tickers[0], investor_did)), 1_000);
+        assert_eq!(Asset::balance_of((tickers[1], investor_did)), 2_000);
+
+        // Each leg is within the 10% holding limit, but together they exceed it.
+        assert_ok!(
+            crate::percentage_tm::Module::<TestStorage>::toggle_maximum_percentage_restriction(
+                owner_signed.clone(),
+                owner_did,
+                tickers[0],
+                10
+            )
+        );
+        assert_err!(
+            Asset::transfer_batch_same_sender(
+                owner_signed.clone(),
+                owner_did,
+                vec![
+                    (tickers[0], investor_did, 60_000),
+                    (tickers[0], investor_did, 60_000),
+                ]
+            ),
+            "Transfer restrictions failed"
+        );
+        assert_eq!(Asset::balance_of((tickers[0], investor_did)), 1_000);
+    });
+}
+
+#[test]
+fn spendable_balance_accounts_for_custody_and_freeze() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, custodian_did) = make_account(AccountKeyring::Eve.public()).unwrap();
+
+        let now = Utc::now();
+        Timestamp::set_timestamp(now.timestamp() as u64);
+
+        let ticker = Ticker::from_slice(b"SPEND");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"SPEND".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_eq!(Asset::spendable_balance(ticker, owner_did), 1_000_000);
+
+        assert_ok!(Asset::increase_custody_allowance(
+            owner_signed.clone(),
+            ticker,
+            owner_did,
+            custodian_did,
+            300_000
+        ));
+        assert_eq!(Asset::spendable_balance(ticker, owner_did), 700_000);
+
+        assert_ok!(Asset::freeze(owner_signed.clone(), ticker));
+        assert_eq!(Asset::spendable_balance(ticker, owner_did), 0);
+    });
+}
+
+#[test]
+fn issue_requires_recipient_kyc_when_enabled() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (provider_signed, provider_did) =
+            make_account(AccountKeyring::Charlie.public()).unwrap();
+        let (_, kyc_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, no_kyc_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let now = Utc::now().timestamp() as u64;
+        Timestamp::set_timestamp(now);
+
+        // Charlie is a trusted KYC provider and vouches for Alice only.
+        assert_ok!(KycServiceProviders::add_member(
+            owner_signed.clone(),
+            provider_did
+        ));
+        assert_ok!(Identity::add_claim(
+            provider_signed.clone(),
+            kyc_did,
+            KYC_EXPIRY_CLAIM_KEY.to_vec(),
+            provider_did,
+            now + 10_000,
+            ClaimValue {
+                data_type: DataTypes::U64,
+                value: (now + 10_000).to_be_bytes().to_vec(),
+            }
+        ));
+
+        let ticker = Ticker::from_slice(b"KYCISSUE");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"KYCISSUE".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        // Without the flag, anyone can receive newly issued tokens.
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            no_kyc_did,
+            100,
+            vec![]
+        ));
+
+        assert_ok!(Asset::set_require_kyc_on_issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            true
+        ));
+        assert!(Asset::require_kyc_on_issue(ticker));
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            kyc_did,
+            100,
+            vec![]
+        ));
+        assert_err!(
+            Asset::issue(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                no_kyc_did,
+                100,
+                vec![]
+            ),
+            AssetError::RecipientKycInvalid
+        );
+        assert_err!(
+            Asset::batch_issue(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                vec![kyc_did, no_kyc_did],
+                vec![100, 100]
+            ),
+            AssetError::RecipientKycInvalid
+        );
+        assert_eq!(Asset::balance_of((ticker, kyc_did)), 100);
+        assert_eq!(Asset::balance_of((ticker, no_kyc_did)), 100);
+
+        assert_ok!(Asset::set_require_kyc_on_issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            false
+        ));
+        assert_ok!(Asset::batch_issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            vec![kyc_did, no_kyc_did],
+            vec![100, 100]
+        ));
+        assert_eq!(Asset::balance_of((ticker, no_kyc_did)), 200);
+    });
+}
+
+#[test]
+fn redeem_from_funding_round_reduces_round_total() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (investor_signed, investor_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let now = Utc::now();
+        Timestamp::set_timestamp(now.timestamp() as u64);
+
+        let ticker = Ticker::from_slice(b"ROUND");
+        let round = b"round1".to_vec();
+        let ticker_round = (ticker, round.clone());
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"ROUND".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            Some(round.clone())
+        ));
+
+        // Allow all transfers
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            owner_did,
+            1_000,
+            vec![]
+        ));
+        assert_eq!(Asset::issued_in_funding_round(&ticker_round), 1_000);
+
+        // Holders other than the owner cannot reduce the round total.
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            investor_did,
+            100
+        ));
+        assert_err!(
+            Asset::redeem_from_funding_round(
+                investor_signed.clone(),
+                investor_did,
+                ticker,
+                100,
+                round.clone(),
+                vec![]
+            ),
+            "user is not authorized"
+        );
+        assert_eq!(Asset::issued_in_funding_round(&ticker_round), 1_000);
+        assert_eq!(Asset::balance_of((ticker, investor_did)), 100);
+
+        assert_ok!(Asset::redeem_from_funding_round(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            400,
+            round.clone(),
+            vec![]
+        ));
+        assert_eq!(Asset::issued_in_funding_round(&ticker_round), 600);
+        assert_eq!(Asset::balance_of((ticker, owner_did)), 1_000_500);
+
+        // The round total floors at zero.
+        assert_ok!(Asset::redeem_from_funding_round(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            1_000,
+            round.clone(),
+            vec![]
+        ));
+        assert_eq!(Asset::issued_in_funding_round(&ticker_round), 0);
+        assert_eq!(Asset::balance_of((ticker, owner_did)), 999_500);
+    });
+}
+
+#[test]
+fn holders_paged_tracks_holders() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+        let (_, charlie_did) = make_account(AccountKeyring::Charlie.public()).unwrap();
+
+        let now = Utc::now();
+        Timestamp::set_timestamp(now.timestamp() as u64);
+
+        let ticker = Ticker::from_slice(b"HOLDERS");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"HOLDERS".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_eq!(
+            Asset::holders_paged(ticker, 0, 10),
+            (vec![(owner_did, 1_000_000)], 1)
+        );
+
+        // Allow all transfers
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100
+        ));
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            bob_did,
+            200
+        ));
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            charlie_did,
+            300,
+            vec![]
+        ));
+
+        assert_eq!(
+            Asset::holders_paged(ticker, 0, 2),
+            (vec![(owner_did, 999_700), (alice_did, 100)], 4)
+        );
+        assert_eq!(
+            Asset::holders_paged(ticker, 2, 2),
+            (vec![(bob_did, 200), (charlie_did, 300)], 4)
+        );
+        assert_eq!(Asset::holders_paged(ticker, 4, 2), (vec![], 4));
+
+        // Alice fully divests and is dropped from the holders.
+        assert_ok!(Asset::transfer(
+            alice_signed,
+            alice_did,
+            ticker,
+            bob_did,
+            100
+        ));
+        assert_eq!(
+            Asset::holders_paged(ticker, 0, 10),
+            (
+                vec![(owner_did, 999_700), (bob_did, 300), (charlie_did, 300)],
+                3
+            )
+        );
+    });
+}
+
+#[test]
+fn controller_freeze_holder_blocks_outgoing_transfers() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let now = Utc::now();
+        Timestamp::set_timestamp(now.timestamp() as u64);
+
+        let ticker = Ticker::from_slice(b"HFREEZE");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"HFREEZE".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        // Allow all transfers
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            500
+        ));
+
+        // Only the token owner can freeze a holder.
+        assert_err!(
+            Asset::controller_freeze_holder(
+                alice_signed.clone(),
+                alice_did,
+                ticker,
+                alice_did,
+                b"reason".to_vec()
+            ),
+            "user is not authorized"
+        );
+        assert_ok!(Asset::controller_freeze_holder(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            b"investigation".to_vec()
+        ));
+        assert!(Asset::controller_frozen_holder((ticker, alice_did)));
+        assert_eq!(Asset::spendable_balance(ticker, alice_did), 0);
+
+        // Alice can't send tokens but can still receive them.
+        assert_err!(
+            Asset::transfer(alice_signed.clone(), alice_did, ticker, bob_did, 100),
+            AssetError::HolderFrozen
+        );
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100
+        ));
+
+        assert_ok!(Asset::controller_unfreeze_holder(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            b"cleared".to_vec()
+        ));
+        assert!(!Asset::controller_frozen_holder((ticker, alice_did)));
+        assert_ok!(Asset::transfer(
+            alice_signed,
+            alice_did,
+            ticker,
+            bob_did,
+            100
+        ));
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 500);
+        assert_eq!(Asset::balance_of((ticker, bob_did)), 100);
+    });
+}
+
+#[test]
+fn cancel_token_ownership_transfer() {
+    ExtBuilder::default().build().execute_with(|| {
+        let now = Utc::now();
+        Timestamp::set_timestamp(now.timestamp() as u64);
+
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"CANCEL");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"CANCEL".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        assert_err!(
+            Asset::cancel_token_ownership_transfer(owner_signed.clone(), ticker, alice_did),
+            AssetError::NoPendingOwnershipTransfer
+        );
+
+        assert_ok!(Identity::add_auth(
+            Signatory::from(owner_did),
+            Signatory::from(alice_did),
+            AuthorizationData::TransferTokenOwnership(ticker),
+            None,
+        ));
+        let auth_id = Identity::last_authorization(Signatory::from(alice_did));
+
+        // Only the token owner can cancel the transfer.
+        assert_err!(
+            Asset::cancel_token_ownership_transfer(alice_signed.clone(), ticker, alice_did),
+            "sender must be a signing key for the token owner DID"
+        );
+        assert_ok!(Asset::cancel_token_ownership_transfer(
+            owner_signed.clone(),
+            ticker,
+            alice_did
+        ));
+        assert_eq!(Identity::last_authorization(Signatory::from(alice_did)), 0);
+
+        assert_err!(
+            Asset::accept_token_ownership_transfer(alice_signed, auth_id),
+            "Authorization does not exist"
+        );
+        assert_eq!(Asset::token_details(&ticker).owner_did, owner_did);
+    });
+}
+
+#[test]
+fn total_supply_at_safe_falls_back() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+
+        let now = Utc::now();
+        Timestamp::set_timestamp(now.timestamp() as u64);
+
+        let ticker = Ticker::from_slice(b"SUPPLY");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"SUPPLY".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        // No checkpoints yet.
+        assert_eq!(Asset::total_supply_at_safe(ticker, 1), 1_000_000);
+
+        // Allow all transfers
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+
+        assert_ok!(Asset::create_checkpoint(
+            owner_signed.clone(),
+            owner_did,
+            ticker
+        ));
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            owner_did,
+            1_000,
+            vec![]
+        ));
+        assert_ok!(Asset::create_checkpoint(
+            owner_signed.clone(),
+            owner_did,
+            ticker
+        ));
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            owner_did,
+            1_000,
+            vec![]
+        ));
+
+        // Exact checkpoints.
+        assert_eq!(Asset::total_supply_at_safe(ticker, 1), 1_000_000);
+        assert_eq!(Asset::total_supply_at_safe(ticker, 2), 1_001_000);
+        // Beyond the last checkpoint and zero use the current supply.
+        assert_eq!(Asset::total_supply_at_safe(ticker, 3), 1_002_000);
+        assert_eq!(Asset::total_supply_at_safe(ticker, 0), 1_002_000);
+
+        // A missing checkpoint entry falls back to the nearest prior checkpoint.
+        <asset::CheckpointTotalSupply<TestStorage>>::remove((ticker, 2));
+        assert_eq!(Asset::total_supply_at_safe(ticker, 2), 1_000_000);
+    });
+}
+
+#[test]
+fn approve_allowlist_restricts_spenders() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"ALLOW");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"ALLOW".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        assert_ok!(Asset::set_approve_allowlist_enabled(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            true
+        ));
+        assert_ok!(Asset::set_approved_spender(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            true
+        ));
+
+        assert_ok!(Asset::approve(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100
+        ));
+        assert_eq!(Asset::allowance((ticker, owner_did, alice_did)), 100);
+        assert_err!(
+            Asset::approve(owner_signed.clone(), owner_did, ticker, bob_did, 100),
+            AssetError::SpenderNotAllowlisted
+        );
+
+        // Any spender can be approved once the allowlist is disabled.
+        assert_ok!(Asset::set_approve_allowlist_enabled(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            false
+        ));
+        assert_ok!(Asset::approve(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            bob_did,
+            100
+        ));
+        assert_eq!(Asset::allowance((ticker, owner_did, bob_did)), 100);
+    });
+}
+
+#[test]
+fn create_checkpoint_returns_incremented_id() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"CHECK");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"CHECK".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        assert_ok!(Asset::create_checkpoint(
+            owner_signed.clone(),
+            owner_did,
+            ticker
+        ));
+        assert_eq!(Asset::total_checkpoints_of(&ticker), 1);
+
+        assert_eq!(Asset::_create_checkpoint(&ticker), Ok(2));
+        assert_eq!(Asset::total_checkpoints_of(&ticker), 2);
+        assert_eq!(Asset::total_supply_at((ticker, 2)), 1_000_000);
+    });
+}
+
+#[test]
+fn batch_extrinsics_enforce_max_batch_size() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"BATCH");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"BATCH".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        // The test runtime accepts batches of up to 10 items.
+        assert_err!(
+            Asset::batch_issue(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                vec![alice_did; 11],
+                vec![1; 11]
+            ),
+            AssetError::BatchTooLarge
+        );
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 0);
+        assert_ok!(Asset::batch_issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            vec![alice_did; 10],
+            vec![1; 10]
+        ));
+
+        assert_err!(
+            Asset::transfer_batch_same_sender(
+                owner_signed.clone(),
+                owner_did,
+                vec![(ticker, alice_did, 1); 11]
+            ),
+            AssetError::BatchTooLarge
+        );
+    });
+}
+
+#[test]
+fn ticker_to_asset_did_matches_token_did() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"ASSETDID");
+        assert_eq!(Identity::ticker_to_asset_did(ticker), None);
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"ASSETDID".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        let token_did = Identity::get_token_did(&ticker).unwrap();
+        assert_eq!(Identity::ticker_to_asset_did(ticker), Some(token_did));
+
+        // Tickers without a stored DID still resolve to the computed one.
+        let legacy_ticker = Ticker::from_slice(b"LEGACY");
+        assert!(Identity::get_token_did(&legacy_ticker).is_ok());
+        assert_eq!(Identity::ticker_to_asset_did(legacy_ticker), None);
+    });
+}
+
+#[test]
+fn issuance_rounding_of_indivisible_tokens() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"ROUND");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"ROUND".to_vec(),
+            ticker,
+            1_000 * ONE_UNIT,
+            false,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        // By default fractional amounts of an indivisible token cannot be issued.
+        assert_err!(
+            Asset::issue(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                alice_did,
+                ONE_UNIT + ONE_UNIT / 2,
+                vec![]
+            ),
+            "Invalid granularity"
+        );
+
+        assert_ok!(Asset::set_issuance_rounding(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            true
+        ));
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            ONE_UNIT + ONE_UNIT / 2,
+            vec![]
+        ));
+        assert_eq!(Asset::balance_of((ticker, alice_did)), ONE_UNIT);
+        assert_ok!(Asset::batch_issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            vec![alice_did],
+            vec![2 * ONE_UNIT + 1]
+        ));
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 3 * ONE_UNIT);
+        assert_eq!(Asset::token_details(ticker).total_supply, 1_003 * ONE_UNIT);
+    });
+}
+
+#[test]
+fn set_asset_classification_keeps_primary_type() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"CLASS");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"CLASS".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::Equity,
+            vec![],
+            None
+        ));
+        assert!(Asset::asset_classification(ticker).is_empty());
+
+        let classification = vec![
+            AssetType::StructuredProduct,
+            AssetType::Custom(b"convertible".to_vec()),
+        ];
+        assert_err!(
+            Asset::set_asset_classification(
+                alice_signed.clone(),
+                alice_did,
+                ticker,
+                classification.clone()
+            ),
+            "user is not authorized"
+        );
+        assert_ok!(Asset::set_asset_classification(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            classification.clone()
+        ));
+        assert_eq!(Asset::asset_classification(ticker), classification);
+        assert_eq!(Asset::token_details(ticker).asset_type, AssetType::Equity);
+    });
+}
+
+#[test]
+fn min_ticker_length_is_enforced() {
+    ExtBuilder::default()
+        .min_ticker_length(3)
+        .build()
+        .execute_with(|| {
+            let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+            let create = |ticker: &[u8]| {
+                Asset::create_token(
+                    owner_signed.clone(),
+                    owner_did,
+                    ticker.to_vec(),
+                    Ticker::from_slice(ticker),
+                    1_000_000,
+                    true,
+                    AssetType::default(),
+                    vec![],
+                    None,
+                )
+            };
+
+            assert_err!(
+                Asset::register_ticker(owner_signed.clone(), Ticker::from_slice(b"AB")),
+                AssetError::TickerTooShort
+            );
+            assert_ok!(Asset::register_ticker(
+                owner_signed.clone(),
+                Ticker::from_slice(b"ABC")
+            ));
+            assert_ok!(Asset::register_ticker(
+                owner_signed.clone(),
+                Ticker::from_slice(b"ABCD")
+            ));
+
+            assert_err!(create(b"XY"), AssetError::TickerTooShort);
+            assert_ok!(create(b"XYZ"));
+            assert_ok!(create(b"WXYZ"));
+        });
+}
+
+#[test]
+fn escrow_is_released_only_by_custodian() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (custodian_signed, custodian_did) =
+            make_account(AccountKeyring::Charlie.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"ESCROW");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"ESCROW".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![]
+            }
+        ));
+
+        // The holder cannot be its own custodian and the custodian must exist.
+        assert_err!(
+            Asset::transfer_to_escrow(owner_signed.clone(), ticker, owner_did, owner_did, 1_000),
+            AssetError::SelfCustodyNotAllowed
+        );
+        assert_err!(
+            Asset::transfer_to_escrow(
+                owner_signed.clone(),
+                ticker,
+                owner_did,
+                IdentityId::from(999),
+                1_000
+            ),
+            "Invalid custodian DID"
+        );
+        // Only the holder can move its tokens into escrow.
+        assert_err!(
+            Asset::transfer_to_escrow(
+                alice_signed.clone(),
+                ticker,
+                owner_did,
+                custodian_did,
+                1_000
+            ),
+            "sender must be a signing key for DID"
+        );
+
+        assert_ok!(Asset::transfer_to_escrow(
+            owner_signed.clone(),
+            ticker,
+            owner_did,
+            custodian_did,
+            1_000
+        ));
+        let escrow_did = Asset::escrow_did(&ticker, custodian_did);
+        assert_eq!(Asset::balance_of((ticker, owner_did)), 999_000);
+        assert_eq!(Asset::balance_of((ticker, escrow_did)), 1_000);
+        assert_eq!(
+            Asset::escrow_balance((ticker, custodian_did, owner_did)),
+            1_000
+        );
+
+        // Only the custodian can release the escrow.
+        assert_err!(
+            Asset::release_escrow(alice_signed.clone(), ticker, owner_did, alice_did, 100),
+            AssetError::InsufficientEscrowBalance
+        );
+        assert_err!(
+            Asset::release_escrow(custodian_signed.clone(), ticker, owner_did, bob_did, 1_001),
+            AssetError::InsufficientEscrowBalance
+        );
+
+        // Releases are transfers of the holder, so they are paused and frozen like them.
+        assert_ok!(Asset::set_operation_pause_flags(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            asset::PAUSE_TRANSFER
+        ));
+        assert_err!(
+            Asset::release_escrow(custodian_signed.clone(), ticker, owner_did, bob_did, 400),
+            AssetError::OperationPaused
+        );
+        assert_ok!(Asset::set_operation_pause_flags(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            0
+        ));
+        assert_ok!(Asset::controller_freeze_holder_directional(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            owner_did,
+            FreezeDirection::Outgoing
+        ));
+        assert_err!(
+            Asset::release_escrow(custodian_signed.clone(), ticker, owner_did, bob_did, 400),
+            AssetError::HolderFrozen
+        );
+        assert_ok!(Asset::controller_unfreeze_holder(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            owner_did,
+            vec![]
+        ));
+        assert_ok!(Asset::release_escrow(
+            custodian_signed.clone(),
+            ticker,
+            owner_did,
+            bob_did,
+            400
+        ));
+        assert_eq!(Asset::balance_of((ticker, bob_did)), 400);
+        assert_eq!(Asset::balance_of((ticker, escrow_did)), 600);
+        assert_eq!(
+            Asset::escrow_balance((ticker, custodian_did, owner_did)),
+            600
+        );
+    });
+}
+
+#[test]
+fn get_balance_at_handles_empty_user_checkpoints() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"EMPTYCP");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"EMPTYCP".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(Asset::create_checkpoint(
+            owner_signed.clone(),
+            owner_did,
+            ticker
+        ));
+
+        // An existing but empty entry falls back to the current balance.
+        <asset::UserCheckpoints>::insert((ticker, owner_did), Vec::<u64>::new());
+        assert_eq!(Asset::get_balance_at(ticker, owner_did, 1), 1_000_000);
+    });
+}
+
+#[test]
+fn custody_allowance_history_keeps_latest_changes() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, custodian_did) = make_account(AccountKeyring::Charlie.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"CUSTODY");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"CUSTODY".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        let changes = asset::CUSTODY_ALLOWANCE_HISTORY_LEN as u32 + 2;
+        for _ in 0..changes {
+            assert_ok!(Asset::increase_custody_allowance(
+                owner_signed.clone(),
+                ticker,
+                owner_did,
+                custodian_did,
+                100
+            ));
+        }
+        assert_eq!(Asset::custody_allowance_nonce((ticker, owner_did)), changes);
+
+        let history = Asset::custody_allowance_history((ticker, owner_did));
+        assert_eq!(history.len(), asset::CUSTODY_ALLOWANCE_HISTORY_LEN);
+        assert_eq!(history.first().unwrap().nonce, 3);
+        assert_eq!(
+            history.last().unwrap(),
+            &asset::CustodyAllowanceChange {
+                nonce: changes,
+                custodian_did,
+                old_allowance: 100 * (changes as u128 - 1),
+                new_allowance: 100 * changes as u128,
+            }
+        );
+    });
+}
+
+#[test]
+fn batch_transfer_by_custodian_is_atomic() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (custodian_signed, custodian_did) =
+            make_account(AccountKeyring::Charlie.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (bob_signed, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+        let (_, eve_did) = make_account(AccountKeyring::Eve.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"OMNIBUS");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"OMNIBUS".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![]
+            }
+        ));
+        for (holder_signed, holder_did) in &[(alice_signed, alice_did), (bob_signed, bob_did)] {
+            assert_ok!(Asset::transfer(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                *holder_did,
+                1_000
+            ));
+            assert_ok!(Asset::increase_custody_allowance(
+                holder_signed.clone(),
+                ticker,
+                *holder_did,
+                custodian_did,
+                500
+            ));
+        }
+
+        // Bob's leg exceeds the allowance, so no transfer is made.
+        assert_err!(
+            Asset::batch_transfer_by_custodian(
+                custodian_signed.clone(),
+                ticker,
+                custodian_did,
+                vec![(alice_did, eve_did, 300), (bob_did, eve_did, 501)]
+            ),
+            "Insufficient allowance"
+        );
+        assert_err!(
+            Asset::batch_transfer_by_custodian(
+                custodian_signed.clone(),
+                ticker,
+                custodian_did,
+                vec![(alice_did, eve_did, 300), (alice_did, bob_did, 300)]
+            ),
+            "Insufficient allowance"
+        );
+        // A receiver that cannot receive tokens fails the batch before any transfer.
+        assert_ok!(Asset::controller_freeze_holder_directional(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            owner_did,
+            FreezeDirection::Incoming
+        ));
+        assert_err!(
+            Asset::batch_transfer_by_custodian(
+                custodian_signed.clone(),
+                ticker,
+                custodian_did,
+                vec![(alice_did, eve_did, 300), (bob_did, owner_did, 300)]
+            ),
+            AssetError::ReceiverFrozen
+        );
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 1_000);
+        assert_eq!(Asset::balance_of((ticker, bob_did)), 1_000);
+        assert_eq!(Asset::balance_of((ticker, eve_did)), 0);
+
+        assert_ok!(Asset::batch_transfer_by_custodian(
+            custodian_signed.clone(),
+            ticker,
+            custodian_did,
+            vec![(alice_did, eve_did, 300), (bob_did, eve_did, 500)]
+        ));
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 700);
+        assert_eq!(Asset::balance_of((ticker, bob_did)), 500);
+        assert_eq!(Asset::balance_of((ticker, eve_did)), 800);
+        assert_eq!(
+            Asset::custodian_allowance((ticker, alice_did, custodian_did)),
+            200
+        );
+        assert_eq!(
+            Asset::custodian_allowance((ticker, bob_did, custodian_did)),
+            0
+        );
+        assert_eq!(Asset::total_custody_allowance((ticker, bob_did)), 0);
+    });
+}
+
+#[test]
+fn batch_transfer_by_custodian_limits_the_total_received() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (custodian_signed, custodian_did) =
+            make_account(AccountKeyring::Charlie.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, eve_did) = make_account(AccountKeyring::Eve.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"OMNICAP");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"OMNICAP".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![]
+            }
+        ));
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            150_000
+        ));
+        assert_ok!(Asset::increase_custody_allowance(
+            alice_signed,
+            ticker,
+            alice_did,
+            custodian_did,
+            150_000
+        ));
+
+        // Each leg is within the 10% holding limit, but together they exceed it.
+        assert_ok!(
+            crate::percentage_tm::Module::<TestStorage>::toggle_maximum_percentage_restriction(
+                owner_signed,
+                owner_did,
+                ticker,
+                10
+            )
+        );
+        assert_err!(
+            Asset::batch_transfer_by_custodian(
+                custodian_signed.clone(),
+                ticker,
+                custodian_did,
+                vec![(alice_did, eve_did, 60_000), (alice_did, eve_did, 60_000)]
+            ),
+            "Transfer restrictions failed"
+        );
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 150_000);
+        assert_eq!(Asset::balance_of((ticker, eve_did)), 0);
+
+        assert_ok!(Asset::batch_transfer_by_custodian(
+            custodian_signed,
+            ticker,
+            custodian_did,
+            vec![(alice_did, eve_did, 40_000), (alice_did, eve_did, 40_000)]
+        ));
+        assert_eq!(Asset::balance_of((ticker, eve_did)), 80_000);
+    });
+}
+
+#[test]
+fn transfers_are_blocked_before_trading_start_time() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        Timestamp::set_timestamp(1_000);
+        let ticker = Ticker::from_slice(b"LOCKUP");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"LOCKUP".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_eq!(Asset::trading_start_time(ticker), 1_000);
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![]
+            }
+        ));
+        assert_ok!(Asset::set_trading_start_time(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            2_000
+        ));
+
+        // The owner can transfer and issue before the start time.
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100
+        ));
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100,
+            vec![]
+        ));
+        assert_err!(
+            Asset::transfer(alice_signed.clone(), alice_did, ticker, bob_did, 50),
+            AssetError::TradingNotStarted
+        );
+
+        Timestamp::set_timestamp(2_000);
+        assert_ok!(Asset::transfer(
+            alice_signed.clone(),
+            alice_did,
+            ticker,
+            bob_did,
+            50
+        ));
+        assert_eq!(Asset::balance_of((ticker, bob_did)), 50);
+
+        // The start time cannot be changed once it has passed.
+        Timestamp::set_timestamp(2_001);
+        assert_err!(
+            Asset::set_trading_start_time(owner_signed.clone(), owner_did, ticker, 3_000),
+            AssetError::TradingAlreadyStarted
+        );
+    });
+}
+
+#[test]
+fn owned_tickers_state_reports_frozen_and_paused_tokens() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, other_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let now = Utc::now();
+        Timestamp::set_timestamp(now.timestamp() as u64);
+
+        let frozen_ticker = Ticker::from_slice(b"FROZEN");
+        let paused_ticker = Ticker::from_slice(b"PAUSED");
+        for (name, ticker) in &[(b"FROZEN", frozen_ticker), (b"PAUSED", paused_ticker)] {
+            assert_ok!(Asset::create_token(
+                owner_signed.clone(),
+                owner_did,
+                name.to_vec(),
+                *ticker,
+                1_000_000,
+                true,
+                AssetType::default(),
+                vec![],
+                None
+            ));
+        }
+        assert_ok!(Asset::freeze(owner_signed.clone(), frozen_ticker));
+        assert_ok!(Asset::set_operation_pause_flags(
+            owner_signed.clone(),
+            owner_did,
+            paused_ticker,
+            asset::PAUSE_TRANSFER | asset::PAUSE_ISSUE
+        ));
+
+        assert_eq!(
+            Asset::tickers_owned_by(owner_did),
+            vec![frozen_ticker, paused_ticker]
+        );
+        assert_eq!(
+            Asset::owned_tickers_state(owner_did),
+            vec![
+                (frozen_ticker, true, 0),
+                (
+                    paused_ticker,
+                    false,
+                    asset::PAUSE_TRANSFER | asset::PAUSE_ISSUE
+                ),
+            ]
+        );
+        assert_eq!(Asset::owned_tickers_state(other_did), vec![]);
+    });
+}
+
+#[test]
+fn auto_custodian_is_assigned_from_claim() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+        let (_, custodian_did) = make_account(AccountKeyring::Charlie.public()).unwrap();
+
+        let now = Utc::now().timestamp() as u64;
+        Timestamp::set_timestamp(now);
+
+        let ticker = Ticker::from_slice(b"AUTOCUST");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"AUTOCUST".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        // Allow all transfers
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+        assert_ok!(Asset::set_auto_custodian(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            true
+        ));
+        assert!(Asset::auto_custodian(ticker));
+
+        // Alice is the only holder with a claim naming her custodian.
+        assert_ok!(Identity::add_claim(
+            owner_signed.clone(),
+            alice_did,
+            CUSTODIAN_CLAIM_KEY.to_vec(),
+            owner_did,
+            now + 10_000,
+            ClaimValue {
+                data_type: DataTypes::VecU8,
+                value: custodian_did.encode(),
+            }
+        ));
+
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100
+        ));
+        assert_eq!(
+            Asset::custodian_allowance((ticker, alice_did, custodian_did)),
+            100
+        );
+        assert_eq!(Asset::total_custody_allowance((ticker, alice_did)), 100);
+
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            50,
+            vec![]
+        ));
+        assert_eq!(
+            Asset::custodian_allowance((ticker, alice_did, custodian_did)),
+            150
+        );
+
+        // A custody that cannot be assigned fails the transfer before any balance is moved.
+        assert_ok!(Asset::set_max_allowance_per_spender(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            Some(200)
+        ));
+        assert_err!(
+            Asset::transfer(owner_signed.clone(), owner_did, ticker, alice_did, 100),
+            AssetError::AllowanceCapExceeded
+        );
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 150);
+        assert_eq!(Asset::total_custody_allowance((ticker, alice_did)), 150);
+
+        // Batches check the custody of every receiver before the first leg is applied.
+        assert_err!(
+            Asset::transfer_batch_same_sender(
+                owner_signed.clone(),
+                owner_did,
+                vec![
+                    (ticker, bob_did, 10),
+                    (ticker, alice_did, 30),
+                    (ticker, alice_did, 30)
+                ]
+            ),
+            AssetError::AllowanceCapExceeded
+        );
+        assert_eq!(Asset::balance_of((ticker, bob_did)), 0);
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 150);
+
+        // Without a claim the custody of the receiver is left unset.
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            bob_did,
+            100
+        ));
+        assert_eq!(Asset::balance_of((ticker, bob_did)), 100);
+        assert_eq!(Asset::total_custody_allowance((ticker, bob_did)), 0);
+    });
+}
+
+#[test]
+fn repair_total_custody_allowance_rewrites_desynced_total() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (holder_signed, holder_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (bob_signed, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+        let (_, charlie_did) = make_account(AccountKeyring::Charlie.public()).unwrap();
+
+        let now = Utc::now();
+        Timestamp::set_timestamp(now.timestamp() as u64);
+
+        let ticker = Ticker::from_slice(b"REPAIR");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"REPAIR".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        // Allow all transfers
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            holder_did,
+            1_000
+        ));
+        assert_ok!(Asset::increase_custody_allowance(
+            holder_signed.clone(),
+            ticker,
+            holder_did,
+            bob_did,
+            100
+        ));
+        assert_ok!(Asset::increase_custody_allowance(
+            holder_signed.clone(),
+            ticker,
+            holder_did,
+            charlie_did,
+            50
+        ));
+        assert_eq!(
+            Asset::custodians_of((ticker, holder_did)),
+            vec![bob_did, charlie_did]
+        );
+
+        // Custodians whose allowance is spent are dropped from the index.
+        let bob_signed_again = bob_signed.clone();
+        assert_ok!(Asset::transfer_by_custodian(
+            bob_signed, ticker, holder_did, bob_did, owner_did, 100
+        ));
+        assert_eq!(
+            Asset::custodians_of((ticker, holder_did)),
+            vec![charlie_did]
+        );
+        assert_eq!(Asset::total_custody_allowance((ticker, holder_did)), 50);
+
+        <asset::TotalCustodyAllowance<TestStorage>>::insert((ticker, holder_did), 999);
+        assert_err!(
+            Asset::repair_total_custody_allowance(holder_signed, ticker, holder_did),
+            AssetError::UnAuthorized
+        );
+        assert_eq!(Asset::total_custody_allowance((ticker, holder_did)), 999);
+
+        assert_ok!(Asset::repair_total_custody_allowance(
+            owner_signed,
+            ticker,
+            holder_did
+        ));
+        assert_eq!(Asset::total_custody_allowance((ticker, holder_did)), 50);
+
+        <asset::TotalCustodyAllowance<TestStorage>>::insert((ticker, holder_did), 0);
+        assert_ok!(Asset::repair_total_custody_allowance(
+            frame_system::RawOrigin::Root.into(),
+            ticker,
+            holder_did
+        ));
+        assert_eq!(Asset::total_custody_allowance((ticker, holder_did)), 50);
+
+        // Allowances that predate the index are backfilled by root, skipping spent ones.
+        <asset::CustodiansOf>::remove((ticker, holder_did));
+        assert!(Asset::backfill_custodians_of(
+            bob_signed_again,
+            ticker,
+            holder_did,
+            vec![charlie_did]
+        )
+        .is_err());
+        assert!(Asset::custodians_of((ticker, holder_did)).is_empty());
+        for _ in 0..2 {
+            assert_ok!(Asset::backfill_custodians_of(
+                frame_system::RawOrigin::Root.into(),
+                ticker,
+                holder_did,
+                vec![bob_did, charlie_did]
+            ));
+            assert_eq!(
+                Asset::custodians_of((ticker, holder_did)),
+                vec![charlie_did]
+            );
+        }
+    });
+}
+
+#[test]
+fn register_ticker_for_custom_duration_within_cap() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, _) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let now = Utc::now().timestamp() as u64;
+        Timestamp::set_timestamp(now);
+
+        assert!(
+            Asset::set_max_ticker_registration_length(owner_signed.clone(), Some(5_000)).is_err()
+        );
+        assert_ok!(Asset::set_max_ticker_registration_length(
+            frame_system::RawOrigin::Root.into(),
+            Some(5_000)
+        ));
+        assert_eq!(Asset::max_ticker_registration_length(), Some(5_000));
+
+        let ticker = Ticker::from_slice(b"CUSTOM");
+        assert_ok!(Asset::register_ticker_for(
+            owner_signed.clone(),
+            ticker,
+            3_000
+        ));
+        assert_eq!(Asset::ticker_registration(ticker).expiry, Some(now + 3_000));
+
+        // The default registration length is not limited by the cap.
+        let default_ticker = Ticker::from_slice(b"DEFAULT");
+        assert_ok!(Asset::register_ticker(owner_signed, default_ticker));
+        assert_eq!(
+            Asset::ticker_registration(default_ticker).expiry,
+            Some(now + 10_000)
+        );
+    });
+}
+
+#[test]
+fn register_ticker_for_duration_over_cap_fails() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, _) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let now = Utc::now().timestamp() as u64;
+        Timestamp::set_timestamp(now);
+
+        assert_ok!(Asset::set_max_ticker_registration_length(
+            frame_system::RawOrigin::Root.into(),
+            Some(5_000)
+        ));
+        let ticker = Ticker::from_slice(b"TOOLONG");
+        assert_err!(
+            Asset::register_ticker_for(owner_signed.clone(), ticker, 5_001),
+            AssetError::TickerRegistrationTooLong
+        );
+        assert!(!<asset::Tickers<TestStorage>>::exists(ticker));
+
+        // Without a cap any duration can be requested, as long as the expiry does not overflow.
+        assert_ok!(Asset::set_max_ticker_registration_length(
+            frame_system::RawOrigin::Root.into(),
+            None
+        ));
+        assert_err!(
+            Asset::register_ticker_for(owner_signed.clone(), ticker, u64::max_value()),
+            AssetError::TickerRegistrationTooLong
+        );
+        assert!(!<asset::Tickers<TestStorage>>::exists(ticker));
+        assert_ok!(Asset::register_ticker_for(owner_signed, ticker, 50_000));
+        assert_eq!(
+            Asset::ticker_registration(ticker).expiry,
+            Some(now + 50_000)
+        );
+    });
+}
+
+#[test]
+fn global_kyc_requirement_blocks_transfers_to_non_kyc_receivers() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (provider_signed, provider_did) =
+            make_account(AccountKeyring::Charlie.public()).unwrap();
+        let (_, kyc_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, no_kyc_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let now = Utc::now().timestamp() as u64;
+        Timestamp::set_timestamp(now);
+
+        // Charlie is a trusted KYC provider and vouches for Alice only.
+        assert_ok!(KycServiceProviders::add_member(
+            owner_signed.clone(),
+            provider_did
+        ));
+        assert_ok!(Identity::add_claim(
+            provider_signed,
+            kyc_did,
+            KYC_EXPIRY_CLAIM_KEY.to_vec(),
+            provider_did,
+            now + 10_000,
+            ClaimValue {
+                data_type: DataTypes::U64,
+                value: (now + 10_000).to_be_bytes().to_vec(),
+            }
+        ));
+
+        let ticker = Ticker::from_slice(b"GLOBALKYC");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"GLOBALKYC".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        // Allow all transfers
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+
+        // Without the flag, anyone can receive tokens.
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            no_kyc_did,
+            100
+        ));
+
+        assert!(Asset::set_global_kyc_required(owner_signed.clone(), true).is_err());
+        assert_ok!(Asset::set_global_kyc_required(
+            frame_system::RawOrigin::Root.into(),
+            true
+        ));
+        assert!(Asset::global_kyc_required());
+
+        assert!(!Asset::is_valid_global_kyc_receiver(&ticker, no_kyc_did));
+        assert!(Asset::is_valid_global_kyc_receiver(&ticker, kyc_did));
+        assert_err!(
+            Asset::transfer(owner_signed.clone(), owner_did, ticker, no_kyc_did, 100),
+            "Transfer restrictions failed"
+        );
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            kyc_did,
+            100
+        ));
+
+        // Receivers exempted for the token are still allowed.
+        assert_ok!(Exemption::modify_exemption_list(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            asset::GLOBAL_KYC_TM,
+            no_kyc_did,
+            true
+        ));
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            no_kyc_did,
+            100
+        ));
+        assert_eq!(Asset::balance_of((ticker, no_kyc_did)), 200);
+
+        assert_ok!(Asset::set_global_kyc_required(
+            frame_system::RawOrigin::Root.into(),
+            false
+        ));
+        assert!(!Asset::global_kyc_required());
+    });
+}
+
+#[test]
+fn batch_update_identifiers_is_atomic() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (other_signed, other_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let owned_ticker = Ticker::from_slice(b"OWNED");
+        let other_ticker = Ticker::from_slice(b"OTHER");
+        for (signed, did, ticker) in &[
+            (owner_signed.clone(), owner_did, owned_ticker),
+            (other_signed, other_did, other_ticker),
+        ] {
+            assert_ok!(Asset::create_token(
+                signed.clone(),
+                *did,
+                b"TOKEN".to_vec(),
+                *ticker,
+                1_000_000,
+                true,
+                AssetType::default(),
+                vec![],
+                None
+            ));
+        }
+        let isin = b"US0378331005".to_vec();
+        let cusip = b"037833100".to_vec();
+
+        // A ticker not owned by the DID rejects the whole batch.
+        assert_err!(
+            Asset::batch_update_identifiers(
+                owner_signed.clone(),
+                owner_did,
+                vec![
+                    (owned_ticker, vec![(IdentifierType::Isin, isin.clone())]),
+                    (other_ticker, vec![(IdentifierType::Cusip, cusip.clone())]),
+                ]
+            ),
+            "user is not authorized"
+        );
+        assert_eq!(
+            Asset::identifiers((owned_ticker, IdentifierType::Isin)),
+            vec![]
+        );
+
+        // So does an invalid identifier.
+        assert_err!(
+            Asset::batch_update_identifiers(
+                owner_signed.clone(),
+                owner_did,
+                vec![(
+                    owned_ticker,
+                    vec![
+                        (IdentifierType::Isin, isin.clone()),
+                        (IdentifierType::Isin, b"US0378331004".to_vec())
+                    ]
+                )]
+            ),
+            AssetError::InvalidIdentifier
+        );
+        assert_eq!(
+            Asset::identifiers((owned_ticker, IdentifierType::Isin)),
+            vec![]
+        );
+
+        assert_ok!(Asset::batch_update_identifiers(
+            owner_signed,
+            owner_did,
+            vec![(
+                owned_ticker,
+                vec![
+                    (IdentifierType::Isin, isin.clone()),
+                    (IdentifierType::Cusip, cusip.clone())
+                ]
+            )]
+        ));
+        assert_eq!(
+            Asset::identifiers((owned_ticker, IdentifierType::Isin)),
+            isin
+        );
+        assert_eq!(
+            Asset::identifiers((owned_ticker, IdentifierType::Cusip)),
+            cusip
+        );
+    });
+}
+
+#[test]
+fn redemption_requires_holder_approval() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (holder_signed, holder_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+        let (other_signed, _) = make_account(AccountKeyring::Charlie.public()).unwrap();
+
+        let now = Utc::now().timestamp() as u64;
+        Timestamp::set_timestamp(now);
+
+        let ticker = Ticker::from_slice(b"BUYBACK");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"BUYBACK".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            holder_did,
+            1_000
+        ));
+
+        // Only the owner can request a redemption.
+        assert_err!(
+            Asset::request_redemption(holder_signed.clone(), ticker, holder_did, 100),
+            "user is not authorized"
+        );
+        assert_ok!(Asset::request_redemption(
+            owner_signed.clone(),
+            ticker,
+            holder_did,
+            400
+        ));
+        assert_eq!(Asset::balance_of((ticker, holder_did)), 1_000);
+
+        // A non-holder has no request to approve.
+        assert_err!(
+            Asset::approve_redemption(other_signed, ticker),
+            AssetError::NoPendingRedemption
+        );
+
+        // Approvals go through the same checks as `redeem`, so they respect the redeem pause.
+        assert_ok!(Asset::set_operation_pause_flags(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            asset::PAUSE_REDEEM
+        ));
+        assert_err!(
+            Asset::approve_redemption(holder_signed.clone(), ticker),
+            AssetError::OperationPaused
+        );
+        assert!(<asset::PendingRedemptions<TestStorage>>::exists((
+            ticker, holder_did
+        )));
+        assert_ok!(Asset::set_operation_pause_flags(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            0
+        ));
+
+        assert_ok!(Asset::approve_redemption(holder_signed.clone(), ticker));
+        assert_eq!(Asset::balance_of((ticker, holder_did)), 600);
+        assert_eq!(Asset::token_details(ticker).total_supply, 999_600);
+        assert!(!<asset::PendingRedemptions<TestStorage>>::exists((
+            ticker, holder_did
+        )));
+        assert_err!(
+            Asset::approve_redemption(holder_signed.clone(), ticker),
+            AssetError::NoPendingRedemption
+        );
+
+        // A request whose expiry overflows is rejected.
+        Timestamp::set_timestamp(u64::max_value());
+        assert_err!(
+            Asset::request_redemption(owner_signed.clone(), ticker, holder_did, 100),
+            AssetError::RedemptionExpiryOverflow
+        );
+        Timestamp::set_timestamp(now);
+
+        // An expired request cannot be approved.
+        assert_ok!(Asset::request_redemption(
+            owner_signed,
+            ticker,
+            holder_did,
+            100
+        ));
+        Timestamp::set_timestamp(now + u64::from(REDEMPTION_REQUEST_LENGTH) + 1);
+        assert_err!(
+            Asset::approve_redemption(holder_signed, ticker),
+            AssetError::RedemptionRequestExpired
+        );
+        assert_eq!(Asset::balance_of((ticker, holder_did)), 600);
+    });
+}
+
+#[test]
+fn token_creations_are_rate_limited_per_block() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let create = |name: &[u8]| {
+            Asset::create_token(
+                owner_signed.clone(),
+                owner_did,
+                name.to_vec(),
+                Ticker::from_slice(name),
+                1_000_000,
+                true,
+                AssetType::default(),
+                vec![],
+                None,
+            )
+        };
+
+        assert!(Asset::set_max_tokens_per_block(owner_signed.clone(), Some(2)).is_err());
+        assert_ok!(Asset::set_max_tokens_per_block(
+            frame_system::RawOrigin::Root.into(),
+            Some(2)
+        ));
+
+        assert_ok!(create(b"RATE1"));
+        assert_ok!(create(b"RATE2"));
+        assert_err!(create(b"RATE3"), AssetError::TokenCreationRateLimited);
+        assert_eq!(Asset::tokens_created_this_block(), 2);
+
+        // The count is reset in the next block.
+        <Asset as OnInitialize<u64>>::on_initialize(2);
+        assert_eq!(Asset::tokens_created_this_block(), 0);
+        assert_ok!(create(b"RATE3"));
+    });
+}
+
+#[test]
+fn issue_to_claim_holder_requires_the_claim() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (issuer_signed, issuer_did) = make_account(AccountKeyring::Charlie.public()).unwrap();
+        let (_, holder_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (other_signed, other_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let now = Utc::now().timestamp() as u64;
+        Timestamp::set_timestamp(now);
+
+        let ticker = Ticker::from_slice(b"AIRDROP");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"AIRDROP".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+
+        let claim_key = b"email_hash".to_vec();
+        let claim_value = ClaimValue {
+            data_type: DataTypes::VecU8,
+            value: b"hash_of_holder".to_vec(),
+        };
+        assert_ok!(Identity::add_claim(
+            issuer_signed,
+            holder_did,
+            claim_key.clone(),
+            issuer_did,
+            now + 10_000,
+            claim_value.clone()
+        ));
+
+        // Callers that are not the token owner learn nothing about the claim.
+        assert_err!(
+            Asset::issue_to_claim_holder(
+                other_signed,
+                other_did,
+                ticker,
+                holder_did,
+                claim_key.clone(),
+                issuer_did,
+                claim_value.clone(),
+                100
+            ),
+            "user is not authorized"
+        );
+
+        // Neither a DID without the claim nor a different claim value match.
+        assert_err!(
+            Asset::issue_to_claim_holder(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                other_did,
+                claim_key.clone(),
+                issuer_did,
+                claim_value.clone(),
+                100
+            ),
+            AssetError::RecipientClaimNotHeld
+        );
+        assert_err!(
+            Asset::issue_to_claim_holder(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                holder_did,
+                claim_key.clone(),
+                issuer_did,
+                ClaimValue {
+                    data_type: DataTypes::VecU8,
+                    value: b"hash_of_other".to_vec(),
+                },
+                100
+            ),
+            AssetError::RecipientClaimNotHeld
+        );
+        assert_eq!(Asset::balance_of((ticker, holder_did)), 0);
+        assert_eq!(Asset::balance_of((ticker, other_did)), 0);
+
+        assert_ok!(Asset::issue_to_claim_holder(
+            owner_signed,
+            owner_did,
+            ticker,
+            holder_did,
+            claim_key,
+            issuer_did,
+            claim_value,
+            100
+        ));
+        assert_eq!(Asset::balance_of((ticker, holder_did)), 100);
+    });
+}
+
+#[test]
+fn max_ticker_length_is_enforced() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let create = |ticker: &[u8]| {
+            Asset::create_token(
+                owner_signed.clone(),
+                owner_did,
+                ticker.to_vec(),
+                Ticker::from_slice(ticker),
+                1_000_000,
+                true,
+                AssetType::default(),
+                vec![],
+                None,
+            )
+        };
+
+        assert_err!(
+            Asset::register_ticker(owner_signed.clone(), Ticker::from_slice(b"TOOLONGTK")),
+            AssetError::TickerTooLong
+        );
+        assert_err!(
+            Asset::register_ticker_for(owner_signed.clone(), Ticker::from_slice(b"TOOLONGTK"), 100),
+            AssetError::TickerTooLong
+        );
+        assert_err!(create(b"TOOLONGTK"), AssetError::TickerTooLong);
+
+        assert_ok!(Asset::register_ticker(
+            owner_signed.clone(),
+            Ticker::from_slice(b"LONGTICK")
+        ));
+        assert_ok!(create(b"MAXTICKR"));
+    });
+}
+
+#[test]
+fn balances_at_checkpoint_matches_get_balance_at() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let now = Utc::now();
+        Timestamp::set_timestamp(now.timestamp() as u64);
+
+        let ticker = Ticker::from_slice(b"SNAPSHOT");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"SNAPSHOT".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100
+        ));
+        assert_ok!(Asset::create_checkpoint(
+            owner_signed.clone(),
+            owner_did,
+            ticker
+        ));
+
+        // Balances change after the checkpoint, Bob only becomes a holder afterwards and Alice
+        // sells all her tokens.
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            50
+        ));
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            bob_did,
+            200
+        ));
+        assert_ok!(Asset::transfer(
+            alice_signed,
+            alice_did,
+            ticker,
+            bob_did,
+            150
+        ));
+        assert!(!Asset::ticker_holders(ticker).contains(&alice_did));
+
+        let snapshot = Asset::balances_at_checkpoint(ticker, 1);
+        assert_eq!(snapshot, vec![(owner_did, 999_900), (alice_did, 100)]);
+        for (did, balance) in &snapshot {
+            assert_eq!(Asset::get_balance_at(ticker, *did, 1), *balance);
+        }
+        assert_eq!(Asset::get_balance_at(ticker, bob_did, 1), 0);
+
+        assert_eq!(
+            Asset::balances_at_checkpoint_paged(ticker, 1, 1, 2),
+            (vec![(alice_did, 100)], 2)
+        );
+    });
+}
+
+#[test]
+fn transfers_to_burn_did_are_redemptions() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let burn_did = IdentityId::from(999);
+
+        let now = Utc::now();
+        Timestamp::set_timestamp(now.timestamp() as u64);
+
+        let ticker = Ticker::from_slice(b"BURN");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"BURN".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+
+        assert!(Asset::set_burn_did(owner_signed.clone(), Some(burn_did)).is_err());
+        assert_ok!(Asset::set_burn_did(
+            frame_system::RawOrigin::Root.into(),
+            Some(burn_did)
+        ));
+
+        // Transfers to the burn DID are redemptions, so they respect the redeem pause.
+        assert_ok!(Asset::set_operation_pause_flags(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            asset::PAUSE_REDEEM
+        ));
+        assert_err!(
+            Asset::transfer(owner_signed.clone(), owner_did, ticker, burn_did, 300),
+            AssetError::OperationPaused
+        );
+        assert_ok!(Asset::set_operation_pause_flags(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            0
+        ));
+
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            burn_did,
+            300
+        ));
+        assert_eq!(Asset::balance_of((ticker, owner_did)), 999_700);
+        assert_eq!(Asset::balance_of((ticker, burn_did)), 0);
+        assert_eq!(Asset::token_details(ticker).total_supply, 999_700);
+
+        // Transfers to other DIDs are unaffected.
+        assert_ok!(Asset::transfer(
+            owner_signed,
+            owner_did,
+            ticker,
+            alice_did,
+            100
+        ));
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 100);
+        assert_eq!(Asset::token_details(ticker).total_supply, 999_700);
+    });
+}
+
+#[test]
+fn update_single_document() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let ticker = Ticker::from_slice(b"DOCS");
+        let ticker_did = Identity::get_token_did(&ticker).unwrap();
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"DOCS".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        let document = |name: &[u8]| Document {
+            name: name.to_vec(),
+            uri: b"www.a.com".to_vec(),
+            hash: b"0x1".to_vec(),
+        };
+        assert_ok!(Asset::add_documents(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            vec![document(b"A")]
+        ));
+        let doc_id = Identity::last_link(Signatory::from(ticker_did));
+
+        assert_ok!(Asset::update_document(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            doc_id,
+            document(b"B")
+        ));
+        let doc = Identity::links((Signatory::from(ticker_did), doc_id));
+        assert_eq!(doc.link_data, LinkData::DocumentOwned(document(b"B")));
+        assert_eq!(Identity::last_link(Signatory::from(ticker_did)), doc_id);
+
+        // Links that are not documents cannot be updated.
+        let other_link_id = Identity::add_link(
+            Signatory::from(ticker_did),
+            LinkData::TokenOwned(ticker),
+            None,
+        )
+        .unwrap();
+        assert_err!(
+            Asset::update_document(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                other_link_id,
+                document(b"C")
+            ),
+            AssetError::NotADocument
+        );
+        assert_eq!(
+            Identity::links((Signatory::from(ticker_did), other_link_id)).link_data,
+            LinkData::TokenOwned(ticker)
+        );
+        assert_err!(
+            Asset::update_document(owner_signed, owner_did, ticker, 12345, document(b"C")),
+            AssetError::NotADocument
+        );
+    });
+}
+
+#[test]
+fn all_extensions_lists_every_type() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let ticker = Ticker::from_slice(b"EXTS");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"EXTS".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert!(Asset::all_extensions(ticker).is_empty());
+
+        let tm_id = AccountKeyring::Bob.public();
+        let sto_id = AccountKeyring::Charlie.public();
+        for (extension_type, extension_id) in vec![
+            (SmartExtensionType::TransferManager, tm_id.clone()),
+            (SmartExtensionType::Offerings, sto_id.clone()),
+        ] {
+            assert_ok!(Asset::add_extension(
+                owner_signed.clone(),
+                ticker,
+                SmartExtension {
+                    extension_type,
+                    extension_name: b"EXT".to_vec(),
+                    extension_id,
+                    is_archive: false,
+                }
+            ));
+        }
+        assert_ok!(Asset::archive_extension(
+            owner_signed.clone(),
+            ticker,
+            sto_id.clone()
+        ));
+
+        assert_eq!(
+            Asset::all_extensions(ticker),
+            vec![
+                (SmartExtensionType::TransferManager, tm_id, false),
+                (SmartExtensionType::Offerings, sto_id, true),
+            ]
+        );
+    });
+}
+
+#[test]
+fn zero_amounts_are_rejected() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"ZERO");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"ZERO".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        let asset_rule = general_tm::AssetRule {
+            sender_rules: vec![],
+            receiver_rules: vec![],
+        };
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            asset_rule
+        ));
+
+        // A single zero entry rejects the whole batch.
+        assert_err!(
+            Asset::batch_issue(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                vec![alice_did, bob_did],
+                vec![100, 0]
+            ),
+            AssetError::ZeroIssuanceAmount
+        );
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 0);
+        assert_eq!(Asset::balance_of((ticker, bob_did)), 0);
+        assert_eq!(Asset::token_details(ticker).total_supply, 1_000);
+
+        assert_err!(
+            Asset::issue(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                alice_did,
+                0,
+                vec![]
+            ),
+            AssetError::ZeroIssuanceAmount
+        );
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100,
+            vec![]
+        ));
+        assert_err!(
+            Asset::transfer(alice_signed.clone(), alice_did, ticker, bob_did, 0),
+            AssetError::ZeroAmount
+        );
+        assert_err!(
+            Asset::redeem(alice_signed.clone(), alice_did, ticker, 0, vec![]),
+            AssetError::ZeroAmount
+        );
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 100);
+        assert_eq!(Asset::token_details(ticker).total_supply, 1_100);
+    });
+}
+
+#[test]
+fn create_token_enforces_allowed_asset_types() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let create = |name: &[u8], asset_type: AssetType| {
+            Asset::create_token(
+                owner_signed.clone(),
+                owner_did,
+                name.to_vec(),
+                Ticker::from_slice(name),
+                1_000_000,
+                true,
+                asset_type,
+                vec![],
+                None,
+            )
+        };
+
+        // An empty list allows any asset type.
+        assert_ok!(create(b"ANY", AssetType::Custom(b"anything".to_vec())));
+
+        let allowed = vec![AssetType::Equity, AssetType::Debt];
+        assert!(Asset::set_allowed_asset_types(owner_signed.clone(), allowed.clone()).is_err());
+        assert_ok!(Asset::set_allowed_asset_types(
+            frame_system::RawOrigin::Root.into(),
+            allowed.clone()
+        ));
+        assert_eq!(Asset::allowed_asset_types(), allowed);
+
+        assert_ok!(create(b"EQUITY", AssetType::Equity));
+        assert_err!(
+            create(b"CUSTOM", AssetType::Custom(b"exotic".to_vec())),
+            AssetError::AssetTypeNotAllowed
+        );
+        assert!(!Asset::is_ticker_registry_valid(
+            &Ticker::from_slice(b"CUSTOM"),
+            owner_did
+        ));
+    });
+}
+
+#[test]
+fn transfer_with_memo_stores_the_memo() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"MEMO");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"MEMO".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+
+        assert_err!(
+            Asset::transfer_with_memo(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                alice_did,
+                100,
+                vec![0; MAX_TRANSFER_MEMO_LENGTH + 1]
+            ),
+            AssetError::TransferMemoTooLong
+        );
+        assert_eq!(Asset::transfer_memo_count(ticker), 0);
+
+        assert_ok!(Asset::transfer_with_memo(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100,
+            b"invoice 1".to_vec()
+        ));
+        assert_ok!(Asset::transfer_with_memo(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            50,
+            b"invoice 2".to_vec()
+        ));
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 150);
+        assert_eq!(Asset::transfer_memo_count(ticker), 2);
+        assert_eq!(Asset::transfer_memo((ticker, 1)), b"invoice 1".to_vec());
+        assert_eq!(Asset::transfer_memo((ticker, 2)), b"invoice 2".to_vec());
+    });
+}
+
+#[test]
+fn redemptions_cannot_go_below_supply_floor() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"FLOOR");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"FLOOR".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+
+        assert_ok!(Asset::set_supply_floor(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            600
+        ));
+        // The owner cannot lower the floor.
+        assert_err!(
+            Asset::set_supply_floor(owner_signed.clone(), owner_did, ticker, 500),
+            AssetError::SupplyFloorDecrease
+        );
+        assert_eq!(Asset::supply_floor(ticker), 600);
+
+        // Redeeming down to the floor is allowed, but not below it.
+        assert_ok!(Asset::redeem(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            400,
+            vec![]
+        ));
+        assert_eq!(Asset::token_details(ticker).total_supply, 600);
+        assert_err!(
+            Asset::redeem(owner_signed.clone(), owner_did, ticker, 1, vec![]),
+            AssetError::SupplyFloorBreached
+        );
+        assert_err!(
+            Asset::controller_redeem(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                owner_did,
+                1,
+                vec![],
+                vec![]
+            ),
+            AssetError::SupplyFloorBreached
+        );
+        assert_eq!(Asset::balance_of((ticker, owner_did)), 600);
+
+        // Only root can lower the floor.
+        assert!(Asset::force_set_supply_floor(owner_signed.clone(), ticker, 0).is_err());
+        assert_ok!(Asset::force_set_supply_floor(
+            frame_system::RawOrigin::Root.into(),
+            ticker,
+            500
+        ));
+        assert_ok!(Asset::redeem(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            100,
+            vec![]
+        ));
+        assert_eq!(Asset::token_details(ticker).total_supply, 500);
+    });
+}
+
+#[test]
+fn extension_tickers_reverses_extensions() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let extension_id = AccountKeyring::Bob.public();
+        assert!(Asset::extension_tickers(&extension_id).is_empty());
+
+        let tickers = vec![Ticker::from_slice(b"EXTA"), Ticker::from_slice(b"EXTB")];
+        for ticker in &tickers {
+            assert_ok!(Asset::create_token(
+                owner_signed.clone(),
+                owner_did,
+                b"EXT".to_vec(),
+                *ticker,
+                1_000_000,
+                true,
+                AssetType::default(),
+                vec![],
+                None
+            ));
+            assert_ok!(Asset::add_extension(
+                owner_signed.clone(),
+                *ticker,
+                SmartExtension {
+                    extension_type: SmartExtensionType::TransferManager,
+                    extension_name: b"EXT".to_vec(),
+                    extension_id: extension_id.clone(),
+                    is_archive: false,
+                }
+            ));
+        }
+
+        assert_eq!(Asset::extension_tickers(&extension_id), tickers);
+        assert!(Asset::extension_tickers(AccountKeyring::Charlie.public()).is_empty());
+
+        // Extensions attached before the index existed are added by root.
+        <asset::ExtensionTickers<TestStorage>>::remove(&extension_id);
+        assert!(Asset::backfill_extension_tickers(
+            owner_signed,
+            tickers[1],
+            vec![extension_id.clone()]
+        )
+        .is_err());
+        assert_ok!(Asset::backfill_extension_tickers(
+            frame_system::RawOrigin::Root.into(),
+            tickers[1],
+            vec![
+                extension_id.clone(),
+                extension_id.clone(),
+                AccountKeyring::Charlie.public()
+            ]
+        ));
+        assert_eq!(Asset::extension_tickers(&extension_id), vec![tickers[1]]);
+        assert!(Asset::extension_tickers(AccountKeyring::Charlie.public()).is_empty());
+    });
+}
+
+#[test]
+fn remove_extension_deletes_active_and_archived_extensions() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, _) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let ticker = Ticker::from_slice(b"REMEXT");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"REMEXT".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        let active_id = AccountKeyring::Bob.public();
+        let archived_id = AccountKeyring::Charlie.public();
+        for extension_id in vec![active_id.clone(), archived_id.clone()] {
+            assert_ok!(Asset::add_extension(
+                owner_signed.clone(),
+                ticker,
+                SmartExtension {
+                    extension_type: SmartExtensionType::TransferManager,
+                    extension_name: b"EXT".to_vec(),
+                    extension_id,
+                    is_archive: false,
+                }
+            ));
+        }
+        assert_ok!(Asset::archive_extension(
+            owner_signed.clone(),
+            ticker,
+            archived_id.clone()
+        ));
+
+        assert_err!(
+            Asset::remove_extension(alice_signed, ticker, active_id.clone()),
+            AssetError::UnAuthorized
+        );
+
+        for extension_id in vec![active_id.clone(), archived_id.clone()] {
+            assert_ok!(Asset::remove_extension(
+                owner_signed.clone(),
+                ticker,
+                extension_id.clone()
+            ));
+            assert!(!<asset::ExtensionDetails<TestStorage>>::exists((
+                ticker,
+                &extension_id
+            )));
+            assert!(Asset::extension_tickers(&extension_id).is_empty());
+        }
+        assert!(Asset::extensions((ticker, SmartExtensionType::TransferManager)).is_empty());
+
+        // A removed extension cannot be removed again.
+        assert_err!(
+            Asset::remove_extension(owner_signed.clone(), ticker, active_id),
+            "Smart extension not exists"
+        );
+    });
+}
+
+#[test]
+fn transfers_must_be_multiples_of_lot_size() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"LOTS");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"LOTS".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+        assert_eq!(Asset::lot_size(&ticker), 1);
+
+        assert_err!(
+            Asset::set_lot_size(owner_signed.clone(), owner_did, ticker, 0),
+            AssetError::InvalidLotSize
+        );
+        assert_ok!(Asset::set_lot_size(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            100
+        ));
+        assert_eq!(Asset::lot_size(&ticker), 100);
+
+        assert_err!(
+            Asset::transfer(owner_signed.clone(), owner_did, ticker, alice_did, 150),
+            "Invalid granularity"
+        );
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            200
+        ));
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 200);
+    });
+}
+
+#[test]
+fn total_allowance_granted_tracks_spenders() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+        let (_, charlie_did) = make_account(AccountKeyring::Charlie.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"ALLOW");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"ALLOW".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+        assert_eq!(
+            Asset::total_allowance_granted(ticker, owner_did),
+            (0, vec![])
+        );
+
+        assert_ok!(Asset::approve(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100
+        ));
+        assert_ok!(Asset::approve(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            bob_did,
+            50
+        ));
+        assert_eq!(
+            Asset::total_allowance_granted(ticker, owner_did),
+            (150, vec![(alice_did, 100), (bob_did, 50)])
+        );
+
+        // Draining the allowance of a spender removes it from the breakdown.
+        assert_ok!(Asset::transfer_from(
+            alice_signed,
+            alice_did,
+            ticker,
+            owner_did,
+            charlie_did,
+            100
+        ));
+        assert_eq!(
+            Asset::total_allowance_granted(ticker, owner_did),
+            (50, vec![(bob_did, 50)])
+        );
+        assert_eq!(Asset::spenders_of((ticker, owner_did)), vec![bob_did]);
+
+        // Allowances granted before the index existed are added by root.
+        <asset::SpendersOf>::remove((ticker, owner_did));
+        assert_eq!(
+            Asset::total_allowance_granted(ticker, owner_did),
+            (0, vec![])
+        );
+        assert!(
+            Asset::backfill_spenders_of(owner_signed, ticker, owner_did, vec![bob_did]).is_err()
+        );
+        assert_ok!(Asset::backfill_spenders_of(
+            frame_system::RawOrigin::Root.into(),
+            ticker,
+            owner_did,
+            vec![alice_did, bob_did, bob_did]
+        ));
+        assert_eq!(
+            Asset::total_allowance_granted(ticker, owner_did),
+            (50, vec![(bob_did, 50)])
+        );
+    });
+}
+
+#[test]
+fn hard_max_supply_caps_issuance() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"HARDMAX");
+        let create = |total_supply| {
+            Asset::create_token_with_hard_max_supply(
+                owner_signed.clone(),
+                owner_did,
+                b"HARDMAX".to_vec(),
+                ticker,
+                total_supply,
+                true,
+                AssetType::default(),
+                vec![],
+                None,
+                Some(1_500),
+            )
+        };
+        assert_err!(create(2_000), AssetError::HardMaxSupplyExceeded);
+        assert_ok!(create(1_000));
+        assert_eq!(Asset::hard_max_supply(ticker), Some(1_500));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+
+        // Minting up to the hard maximum is allowed, but not beyond it.
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            400,
+            vec![]
+        ));
+        assert_err!(
+            Asset::issue(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                alice_did,
+                101,
+                vec![]
+            ),
+            AssetError::HardMaxSupplyExceeded
+        );
+        assert_err!(
+            Asset::batch_issue(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                vec![alice_did, owner_did],
+                vec![50, 51]
+            ),
+            AssetError::HardMaxSupplyExceeded
+        );
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100,
+            vec![]
+        ));
+        assert_eq!(Asset::token_details(ticker).total_supply, 1_500);
+
+        // The maximum survives other changes of the token, and there is no extrinsic to raise it.
+        assert_ok!(Asset::rename_token(
+            owner_signed.clone(),
+            ticker,
+            b"RENAMED".to_vec()
+        ));
+        assert_ok!(Asset::redeem(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            500,
+            vec![]
+        ));
+        assert_eq!(Asset::hard_max_supply(ticker), Some(1_500));
+
+        // A plain token has no hard maximum.
+        let plain = Ticker::from_slice(b"PLAIN");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"PLAIN".to_vec(),
+            plain,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_eq!(Asset::hard_max_supply(plain), None);
+    });
+}
+
+#[test]
+fn redeem_all_keeps_custody_allowance() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (bob_signed, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+        let (_, custodian_did) = make_account(AccountKeyring::Charlie.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"ALL");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"ALL".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        let asset_rule = general_tm::AssetRule {
+            sender_rules: vec![],
+            receiver_rules: vec![],
+        };
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            asset_rule
+        ));
+        assert_ok!(Asset::batch_issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            vec![alice_did, bob_did],
+            vec![300, 200]
+        ));
+
+        // Alice only redeems the portion not allowed to her custodian.
+        assert_ok!(Asset::increase_custody_allowance(
+            alice_signed.clone(),
+            ticker,
+            alice_did,
+            custodian_did,
+            100
+        ));
+        assert_ok!(Asset::redeem_all(
+            alice_signed.clone(),
+            alice_did,
+            ticker,
+            vec![]
+        ));
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 100);
+        assert_eq!(Asset::total_custody_allowance((ticker, alice_did)), 100);
+
+        // Nothing is left to redeem.
+        assert_err!(
+            Asset::redeem_all(alice_signed, alice_did, ticker, vec![]),
+            AssetError::ZeroAmount
+        );
+
+        // Bob has no custodian and redeems everything.
+        assert_ok!(Asset::redeem_all(bob_signed, bob_did, ticker, vec![]));
+        assert_eq!(Asset::balance_of((ticker, bob_did)), 0);
+        assert_eq!(Asset::token_details(ticker).total_supply, 1_100);
+    });
+}
+
+#[test]
+fn transfers_to_frozen_dids_can_be_blocked() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"FROZEN");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"FROZEN".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        let asset_rule = general_tm::AssetRule {
+            sender_rules: vec![],
+            receiver_rules: vec![],
+        };
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            asset_rule
+        ));
+        assert_ok!(Identity::freeze_signing_keys(
+            alice_signed.clone(),
+            alice_did
+        ));
+
+        // Frozen DIDs receive tokens unless the owner blocks it.
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100
+        ));
+        assert_err!(
+            Asset::set_block_transfers_to_frozen_dids(
+                alice_signed.clone(),
+                alice_did,
+                ticker,
+                true
+            ),
+            "user is not authorized"
+        );
+        assert_ok!(Asset::set_block_transfers_to_frozen_dids(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            true
+        ));
+        assert_err!(
+            Asset::transfer(owner_signed.clone(), owner_did, ticker, alice_did, 100),
+            AssetError::RecipientDidFrozen
+        );
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            bob_did,
+            100
+        ));
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 100);
+        assert_eq!(Asset::balance_of((ticker, bob_did)), 100);
+
+        // Unfreezing the DID lets it receive tokens again.
+        assert_ok!(Identity::unfreeze_signing_keys(
+            alice_signed.clone(),
+            alice_did
+        ));
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100
+        ));
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 200);
+
+        // Lifting the block allows transfers to frozen DIDs again.
+        assert_ok!(Asset::set_block_transfers_to_frozen_dids(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            false
+        ));
+        assert_ok!(Identity::freeze_signing_keys(alice_signed, alice_did));
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100
+        ));
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 300);
+    });
+}
+
+#[test]
+fn issuance_is_limited_per_period() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"LIMIT");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"LIMIT".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        let asset_rule = general_tm::AssetRule {
+            sender_rules: vec![],
+            receiver_rules: vec![],
+        };
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            asset_rule
+        ));
+
+        // Up to 10% of the supply can be issued every 100 time units.
+        Timestamp::set_timestamp(1_000);
+        assert_ok!(Asset::set_issuance_limit(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            Perbill::from_percent(10),
+            100
+        ));
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            60,
+            vec![]
+        ));
+        assert_err!(
+            Asset::batch_issue(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                vec![alice_did, owner_did],
+                vec![30, 20]
+            ),
+            AssetError::IssuanceLimitExceeded
+        );
+        assert_ok!(Asset::batch_issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            vec![alice_did, owner_did],
+            vec![30, 10]
+        ));
+        assert_err!(
+            Asset::issue(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                alice_did,
+                1,
+                vec![]
+            ),
+            AssetError::IssuanceLimitExceeded
+        );
+        assert_eq!(Asset::token_details(ticker).total_supply, 1_100);
+        assert_eq!(Asset::issuance_window(ticker).issued, 100);
+
+        // The next period allows 10% of the supply at its start.
+        Timestamp::set_timestamp(1_100);
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            110,
+            vec![]
+        ));
+        assert_err!(
+            Asset::issue(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                alice_did,
+                1,
+                vec![]
+            ),
+            AssetError::IssuanceLimitExceeded
+        );
+
+        // A zero period removes the limit.
+        assert_ok!(Asset::set_issuance_limit(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            Perbill::from_percent(10),
+            0
+        ));
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            1_000,
+            vec![]
+        ));
+        assert_eq!(Asset::token_details(ticker).total_supply, 2_210);
+    });
+}
+
+#[test]
+fn available_balance_excludes_custody_allowance() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, custodian_did) = make_account(AccountKeyring::Charlie.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"AVAIL");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"AVAIL".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_eq!(Asset::available_balance(&ticker, owner_did), 1_000);
+
+        assert_ok!(Asset::increase_custody_allowance(
+            owner_signed.clone(),
+            ticker,
+            owner_did,
+            custodian_did,
+            300
+        ));
+        assert_eq!(Asset::available_balance(&ticker, owner_did), 700);
+        assert_eq!(
+            Asset::available_balance(&ticker, owner_did),
+            Asset::balance_of((ticker, owner_did))
+                - Asset::total_custody_allowance((ticker, owner_did))
+        );
+        assert_ok!(Asset::can_transfer_detailed(
+            alice_signed,
+            ticker,
+            owner_did,
+            alice_did,
+            800,
+            vec![]
+        ));
+
+        // A holder without any balance has nothing available.
+        assert_eq!(Asset::available_balance(&ticker, alice_did), 0);
+    });
+}
+
+#[test]
+fn balances_of_matches_balance_of() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"BATCH");
+        let other = Ticker::from_slice(b"OTHER");
+        for (name, supply) in [(ticker, 1_000u128), (other, 500u128)].iter() {
+            assert_ok!(Asset::create_token(
+                owner_signed.clone(),
+                owner_did,
+                b"BATCH".to_vec(),
+                *name,
+                *supply,
+                true,
+                AssetType::default(),
+                vec![],
+                None
+            ));
+        }
+        let asset_rule = general_tm::AssetRule {
+            sender_rules: vec![],
+            receiver_rules: vec![],
+        };
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            asset_rule
+        ));
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            300
+        ));
+
+        let pairs = vec![
+            (ticker, owner_did),
+            (other, alice_did),
+            (ticker, alice_did),
+            (other, owner_did),
+            (Ticker::from_slice(b"NONE"), owner_did),
+        ];
+        let expected = pairs
+            .iter()
+            .map(|pair| Asset::balance_of(pair))
+            .collect::<Vec<_>>();
+        assert_eq!(Asset::balances_of(pairs), expected);
+        assert_eq!(expected, vec![700, 0, 300, 500, 0]);
+        assert!(Asset::balances_of(vec![]).is_empty());
+    });
+}
+
+#[test]
+fn receivers_without_kyc_can_be_auto_frozen() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"AUTOFRZ");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"AUTOFRZ".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+        assert_ok!(Asset::set_global_kyc_required(
+            frame_system::RawOrigin::Root.into(),
+            true
+        ));
+
+        // Without the flag, a blocked transfer leaves the receiver unfrozen.
+        assert_err!(
+            Asset::transfer(owner_signed.clone(), owner_did, ticker, alice_did, 100),
+            "Transfer restrictions failed"
+        );
+        assert!(!Identity::is_did_frozen(alice_did));
+
+        assert!(Asset::set_auto_freeze_on_kyc_loss(owner_signed.clone(), true).is_err());
+        assert_ok!(Asset::set_auto_freeze_on_kyc_loss(
+            frame_system::RawOrigin::Root.into(),
+            true
+        ));
+        assert_err!(
+            Asset::transfer(owner_signed.clone(), owner_did, ticker, bob_did, 100),
+            "Transfer restrictions failed"
+        );
+        assert!(Identity::is_did_frozen(bob_did));
+        assert!(!Identity::is_did_frozen(alice_did));
+        assert_eq!(Asset::balance_of((ticker, bob_did)), 0);
+    });
+}
+
+#[test]
+fn issuance_is_limited_to_funding_round_window() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"WINDOW");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"WINDOW".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            Some(b"Seed".to_vec())
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+
+        assert_err!(
+            Asset::set_funding_round_window(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                b"Seed".to_vec(),
+                200,
+                100
+            ),
+            AssetError::InvalidFundingRoundWindow
+        );
+        assert_ok!(Asset::set_funding_round_window(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            b"Seed".to_vec(),
+            100,
+            200
+        ));
+        assert_eq!(
+            Asset::funding_round_window((ticker, b"Seed".to_vec())),
+            (100, 200)
+        );
+
+        // Before the window.
+        Timestamp::set_timestamp(99);
+        assert_err!(
+            Asset::issue(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                alice_did,
+                100,
+                vec![]
+            ),
+            AssetError::FundingRoundClosed
+        );
+        assert_err!(
+            Asset::batch_issue(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                vec![alice_did],
+                vec![100]
+            ),
+            AssetError::FundingRoundClosed
+        );
+
+        // Inside the window.
+        Timestamp::set_timestamp(150);
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100,
+            vec![]
+        ));
+        assert_ok!(Asset::batch_issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            vec![alice_did],
+            vec![100]
+        ));
+
+        // After the window.
+        Timestamp::set_timestamp(201);
+        assert_err!(
+            Asset::issue(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                alice_did,
+                100,
+                vec![]
+            ),
+            AssetError::FundingRoundClosed
+        );
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 200);
+
+        // A round without a window is always open.
+        assert_ok!(Asset::set_funding_round(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            b"Series A".to_vec()
+        ));
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100,
+            vec![]
+        ));
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 300);
+    });
+}
+
+#[test]
+fn controller_actions_are_logged() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"AUDIT");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"AUDIT".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            500
+        ));
+        assert_eq!(Asset::controller_actions_paged(ticker, 0, 10), (vec![], 0));
+
+        Timestamp::set_timestamp(10);
+        assert_ok!(Asset::controller_transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            bob_did,
+            200,
+            vec![],
+            b"court order".to_vec()
+        ));
+        Timestamp::set_timestamp(20);
+        assert_ok!(Asset::controller_redeem(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            bob_did,
+            50,
+            vec![],
+            b"lost keys".to_vec()
+        ));
+
+        let transfer = ControllerAction {
+            action_type: ControllerActionType::Transfer,
+            from_did: alice_did,
+            to_did: Some(bob_did),
+            value: 200,
+            reason: b"court order".to_vec(),
+            timestamp: 10,
+        };
+        let redemption = ControllerAction {
+            action_type: ControllerActionType::Redeem,
+            from_did: bob_did,
+            to_did: None,
+            value: 50,
+            reason: b"lost keys".to_vec(),
+            timestamp: 20,
+        };
+        assert_eq!(Asset::controller_action_count(ticker), 2);
+        assert_eq!(
+            Asset::controller_actions_paged(ticker, 0, 10),
+            (vec![(0, transfer.clone()), (1, redemption.clone())], 2)
+        );
+        assert_eq!(
+            Asset::controller_actions_paged(ticker, 1, 10),
+            (vec![(1, redemption)], 2)
+        );
+        assert_eq!(
+            Asset::controller_actions_paged(ticker, 0, 1),
+            (vec![(0, transfer)], 2)
+        );
+
+        // Failed controller actions are not logged.
+        assert!(Asset::controller_redeem(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            bob_did,
+            1_000,
+            vec![],
+            vec![]
+        )
+        .is_err());
+        assert_eq!(Asset::controller_action_count(ticker), 2);
+    });
+}
+
+#[test]
+fn merge_funding_rounds_combines_totals() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (other_signed, other_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"MERGE");
+        let round1 = b"round1".to_vec();
+        let round2 = b"round2".to_vec();
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"MERGE".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            Some(round1.clone())
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            owner_did,
+            1_000,
+            vec![]
+        ));
+        assert_ok!(Asset::set_funding_round(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            round2.clone()
+        ));
+        assert_ok!(Asset::issue(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            owner_did,
+            500,
+            vec![]
+        ));
+
+        assert_err!(
+            Asset::merge_funding_rounds(
+                other_signed,
+                other_did,
+                ticker,
+                round1.clone(),
+                round2.clone()
+            ),
+            "DID is not of the asset owner"
+        );
+        assert_err!(
+            Asset::merge_funding_rounds(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                round1.clone(),
+                round1.clone()
+            ),
+            AssetError::SameFundingRound
+        );
+
+        assert_ok!(Asset::merge_funding_rounds(
+            owner_signed,
+            owner_did,
+            ticker,
+            round1.clone(),
+            round2.clone()
+        ));
+        assert_eq!(Asset::issued_in_funding_round((ticker, round1)), 0);
+        assert_eq!(Asset::issued_in_funding_round((ticker, round2)), 1_500);
+    });
+}
+
+#[test]
+fn senders_must_hold_required_claims() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (bob_signed, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+        let (issuer_signed, issuer_did) = make_account(AccountKeyring::Charlie.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"LICENSE");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"LICENSE".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+        for did in &[alice_did, bob_did] {
+            assert_ok!(Asset::transfer(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                *did,
+                100
+            ));
+        }
+
+        let claims = vec![(b"seller_license".to_vec(), vec![issuer_did])];
+        assert_err!(
+            Asset::set_required_sender_claims(
+                alice_signed.clone(),
+                alice_did,
+                ticker,
+                claims.clone()
+            ),
+            "user is not authorized"
+        );
+        assert_ok!(Asset::set_required_sender_claims(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            claims.clone()
+        ));
+        assert_eq!(Asset::required_sender_claims(ticker), claims);
+
+        assert_ok!(Identity::add_claim(
+            issuer_signed,
+            alice_did,
+            b"seller_license".to_vec(),
+            issuer_did,
+            100,
+            ClaimValue {
+                data_type: DataTypes::VecU8,
+                value: b"licensed".to_vec(),
+            }
+        ));
+
+        // Bob holds no seller license.
+        assert_err!(
+            Asset::transfer(bob_signed.clone(), bob_did, ticker, alice_did, 10),
+            "Transfer restrictions failed"
+        );
+        assert_ok!(Asset::transfer(
+            alice_signed,
+            alice_did,
+            ticker,
+            bob_did,
+            10
+        ));
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 90);
+        assert_eq!(Asset::balance_of((ticker, bob_did)), 110);
+
+        // Clearing the requirement lets Bob sell again.
+        assert_ok!(Asset::set_required_sender_claims(
+            owner_signed,
+            owner_did,
+            ticker,
+            vec![]
+        ));
+        assert!(!<asset::RequiredSenderClaims>::exists(ticker));
+        assert_ok!(Asset::transfer(bob_signed, bob_did, ticker, alice_did, 10));
+    });
+}
+
+#[test]
+fn balance_delta_between_checkpoints() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"DELTA");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"DELTA".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+
+        // Alice holds 100 at checkpoint 1, 150 at checkpoint 2 and 30 at checkpoint 3.
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100
+        ));
+        assert_ok!(Asset::create_checkpoint(
+            owner_signed.clone(),
+            owner_did,
+            ticker
+        ));
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            50
+        ));
+        assert_ok!(Asset::create_checkpoint(
+            owner_signed.clone(),
+            owner_did,
+            ticker
+        ));
+        assert_ok!(Asset::transfer(
+            alice_signed,
+            alice_did,
+            ticker,
+            owner_did,
+            120
+        ));
+        assert_ok!(Asset::create_checkpoint(
+            owner_signed.clone(),
+            owner_did,
+            ticker
+        ));
+
+        assert_eq!(
+            Asset::balance_delta(ticker, alice_did, 1, 2),
+            Ok((50, true))
+        );
+        assert_eq!(
+            Asset::balance_delta(ticker, alice_did, 2, 3),
+            Ok((120, false))
+        );
+        assert_eq!(
+            Asset::balance_delta(ticker, alice_did, 1, 3),
+            Ok((70, false))
+        );
+        assert_eq!(Asset::balance_delta(ticker, alice_did, 2, 2), Ok((0, true)));
+        assert_eq!(
+            Asset::balance_delta(ticker, alice_did, 3, 1),
+            Err(AssetError::InvalidCheckpointRange.into())
+        );
+    });
+}
+
+#[test]
+fn holders_cannot_be_their_own_custodian() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, custodian_did) = make_account(AccountKeyring::Charlie.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"SELF");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"SELF".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        assert_err!(
+            Asset::increase_custody_allowance(
+                owner_signed.clone(),
+                ticker,
+                owner_did,
+                owner_did,
+                300
+            ),
+            AssetError::SelfCustodyNotAllowed
+        );
+        assert_eq!(Asset::total_custody_allowance((ticker, owner_did)), 0);
+
+        assert_ok!(Asset::increase_custody_allowance(
+            owner_signed,
+            ticker,
+            owner_did,
+            custodian_did,
+            300
+        ));
+        assert_eq!(
+            Asset::custodian_allowance((ticker, owner_did, custodian_did)),
+            300
+        );
+        assert_eq!(Asset::total_custody_allowance((ticker, owner_did)), 300);
+    });
+}
+
+#[test]
+fn genesis_seeds_token_balances() {
+    let ticker = Ticker::from_slice(b"GENESIS");
+    let alice_did = IdentityId::from(1u128);
+    let bob_did = IdentityId::from(2u128);
+    ExtBuilder::default()
+        .identities(vec![
+            (alice_did, AccountKeyring::Alice.public()),
+            (bob_did, AccountKeyring::Bob.public()),
+        ])
+        .initial_balances(vec![
+            (ticker, alice_did, 1_000),
+            (ticker, bob_did, 500),
+            (ticker, alice_did, 700),
+        ])
+        .build()
+        .execute_with(|| {
+            assert_eq!(Asset::balance_of((ticker, alice_did)), 700);
+            assert_eq!(Asset::balance_of((ticker, bob_did)), 500);
+
+            // The token is created with the first holder as owner, like `create_token` does.
+            let token = Asset::token_details(ticker);
+            assert_eq!(token.total_supply, 1_200);
+            assert_eq!(token.owner_did, alice_did);
+            assert_eq!(token.name, b"GENESIS".to_vec());
+            assert!(Asset::_is_owner(&ticker, alice_did));
+            assert!(!Asset::is_ticker_available(&ticker));
+            assert_eq!(
+                Asset::holders_paged(ticker, 0, 10),
+                (vec![(alice_did, 700), (bob_did, 500)], 2)
+            );
+            assert!(Identity::ticker_to_asset_did(ticker).is_some());
+            assert!(<asset::TradingStartTime<TestStorage>>::exists(ticker));
+            assert!(<asset::TokenCreationTime<TestStorage>>::exists(ticker));
+            assert_eq!(Statistic::investor_count_per_asset(&ticker), 2);
+
+            let alice = Signatory::from(alice_did);
+            let ticker_link = Identity::links((alice, Asset::ticker_registration(ticker).link_id));
+            assert_eq!(ticker_link.link_data, LinkData::TickerOwned(ticker));
+            let token_link = Identity::links((alice, token.link_id));
+            assert_eq!(token_link.link_data, LinkData::TokenOwned(ticker));
+        });
+}
+
+#[test]
+#[should_panic(expected = "Invalid initial balance")]
+fn genesis_balances_need_existing_identities() {
+    let ticker = Ticker::from_slice(b"GENESIS");
+    ExtBuilder::default()
+        .initial_balances(vec![(ticker, IdentityId::from(1u128), 1_000)])
+        .build();
+}
+
+#[test]
+#[should_panic(expected = "Invalid initial balance")]
+fn genesis_balances_respect_max_supply() {
+    let ticker = Ticker::from_slice(b"GENESIS");
+    let alice_did = IdentityId::from(1u128);
+    ExtBuilder::default()
+        .identities(vec![(alice_did, AccountKeyring::Alice.public())])
+        .initial_balances(vec![(ticker, alice_did, MAX_SUPPLY + 1)])
+        .build();
+}
+
+#[test]
+fn transfer_touches_checkpoint_until_holder_transacts() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"TOUCH");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"TOUCH".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+
+        // No checkpoint yet.
+        assert!(!Asset::transfer_touches_checkpoint(ticker, owner_did));
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100
+        ));
+
+        assert_ok!(Asset::create_checkpoint(
+            owner_signed.clone(),
+            owner_did,
+            ticker
+        ));
+        assert!(Asset::transfer_touches_checkpoint(ticker, owner_did));
+        assert!(Asset::transfer_touches_checkpoint(ticker, alice_did));
+
+        // Both balances are recorded by the first transfer after the checkpoint.
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100
+        ));
+        assert!(!Asset::transfer_touches_checkpoint(ticker, owner_did));
+        assert!(!Asset::transfer_touches_checkpoint(ticker, alice_did));
+
+        // A new checkpoint is touched again.
+        assert_ok!(Asset::create_checkpoint(owner_signed, owner_did, ticker));
+        assert!(Asset::transfer_touches_checkpoint(ticker, owner_did));
+    });
+}
+
+#[test]
+fn enforced_allowlist_requires_both_parties() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+        let (_, charlie_did) = make_account(AccountKeyring::Charlie.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"ALLOW");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"ALLOW".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+
+        assert_err!(
+            Asset::set_allowlist_enforced(alice_signed.clone(), alice_did, ticker, true),
+            "user is not authorized"
+        );
+        assert_ok!(Asset::set_allowlist_enforced(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            true
+        ));
+        assert_ok!(Asset::set_allowlisted(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            vec![alice_did, bob_did],
+            true
+        ));
+
+        // The owner is implicitly allowlisted.
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100
+        ));
+        assert_ok!(Asset::transfer(
+            alice_signed.clone(),
+            alice_did,
+            ticker,
+            bob_did,
+            10
+        ));
+        assert_err!(
+            Asset::transfer(alice_signed.clone(), alice_did, ticker, charlie_did, 10),
+            "Transfer restrictions failed"
+        );
+
+        // A removed DID can no longer send.
+        assert_ok!(Asset::set_allowlisted(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            vec![alice_did],
+            false
+        ));
+        assert!(!Asset::is_allowlisted(&ticker, alice_did));
+        assert_err!(
+            Asset::transfer(alice_signed.clone(), alice_did, ticker, bob_did, 10),
+            "Transfer restrictions failed"
+        );
+
+        // Lifting the allowlist allows any transfer.
+        assert_ok!(Asset::set_allowlist_enforced(
+            owner_signed,
+            owner_did,
+            ticker,
+            false
+        ));
+        assert_ok!(Asset::transfer(
+            alice_signed,
+            alice_did,
+            ticker,
+            charlie_did,
+            10
+        ));
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 80);
+        assert_eq!(Asset::balance_of((ticker, bob_did)), 10);
+        assert_eq!(Asset::balance_of((ticker, charlie_did)), 10);
+    });
+}
+
+#[test]
+fn compliance_officer_manages_allowlist_but_cannot_issue() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (officer_signed, officer_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"OFFICER");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"OFFICER".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        // Without the role, the officer cannot manage the allowlist.
+        assert_err!(
+            Asset::set_allowlisted(
+                officer_signed.clone(),
+                officer_did,
+                ticker,
+                vec![bob_did],
+                true
+            ),
+            "user is not authorized"
+        );
+        assert_err!(
+            Asset::set_compliance_officer(
+                officer_signed.clone(),
+                officer_did,
+                ticker,
+                Some(officer_did)
+            ),
+            "user is not authorized"
+        );
+        assert_ok!(Asset::set_compliance_officer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            Some(officer_did)
+        ));
+        assert_eq!(Asset::compliance_officer(ticker), Some(officer_did));
+
+        assert_ok!(Asset::set_allowlisted(
+            officer_signed.clone(),
+            officer_did,
+            ticker,
+            vec![bob_did],
+            true
+        ));
+        assert!(Asset::is_allowlisted(&ticker, bob_did));
+        assert_ok!(Asset::controller_freeze_holder(
+            officer_signed.clone(),
+            officer_did,
+            ticker,
+            bob_did,
+            vec![]
+        ));
+        assert!(Asset::controller_frozen_holder((ticker, bob_did)));
+
+        // The officer cannot issue tokens nor appoint another officer.
+        assert_err!(
+            Asset::issue(
+                officer_signed.clone(),
+                officer_did,
+                ticker,
+                bob_did,
+                100,
+                vec![]
+            ),
+            "user is not authorized"
+        );
+        assert_err!(
+            Asset::set_compliance_officer(
+                officer_signed.clone(),
+                officer_did,
+                ticker,
+                Some(bob_did)
+            ),
+            "user is not authorized"
+        );
+        assert_eq!(Asset::token_details(ticker).total_supply, 1_000);
+
+        // Removing the officer revokes its powers.
+        assert_ok!(Asset::set_compliance_officer(
+            owner_signed,
+            owner_did,
+            ticker,
+            None
+        ));
+        assert_err!(
+            Asset::controller_unfreeze_holder(officer_signed, officer_did, ticker, bob_did, vec![]),
+            "user is not authorized"
+        );
+    });
+}
+
+#[test]
+fn token_creation_time_is_recorded() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"AGE");
+        Timestamp::set_timestamp(12_345);
+        assert_ok!(Asset::create_token(
+            owner_signed,
+            owner_did,
+            b"AGE".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        Timestamp::set_timestamp(20_000);
+        assert_eq!(Asset::token_creation_time(ticker), 12_345);
+        assert_eq!(Asset::token_creation_time(Ticker::from_slice(b"NONE")), 0);
+    });
+}
+
+#[test]
+fn max_allowance_per_spender_caps_approvals() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, custodian_did) = make_account(AccountKeyring::Charlie.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"CAP");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"CAP".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        assert_ok!(Asset::set_max_allowance_per_spender(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            Some(200)
+        ));
+        assert_eq!(Asset::max_allowance_per_spender(ticker), Some(200));
+
+        assert_ok!(Asset::approve(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            150
+        ));
+        assert_ok!(Asset::approve(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            50
+        ));
+        assert_eq!(Asset::allowance((ticker, owner_did, alice_did)), 200);
+        assert_err!(
+            Asset::approve(owner_signed.clone(), owner_did, ticker, alice_did, 1),
+            AssetError::AllowanceCapExceeded
+        );
+        assert_eq!(Asset::allowance((ticker, owner_did, alice_did)), 200);
+
+        assert_err!(
+            Asset::increase_custody_allowance(
+                owner_signed.clone(),
+                ticker,
+                owner_did,
+                custodian_did,
+                201
+            ),
+            AssetError::AllowanceCapExceeded
+        );
+        assert_eq!(Asset::total_custody_allowance((ticker, owner_did)), 0);
+
+        // Removing the cap allows any allowance again.
+        assert_ok!(Asset::set_max_allowance_per_spender(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            None
+        ));
+        assert_ok!(Asset::approve(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            300
+        ));
+        assert_eq!(Asset::allowance((ticker, owner_did, alice_did)), 500);
+    });
+}
+
+#[test]
+fn transfer_events_are_aggregated_per_block_when_enabled() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+
+        let now = Utc::now();
+        Timestamp::set_timestamp(now.timestamp() as u64);
+
+        let ticker = Ticker::from_slice(b"AGG");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"AGG".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+
+        // Transfers emit individual events by default.
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100
+        ));
+        assert_eq!(Asset::transfer_aggregates(ticker), (0, 0));
+        assert!(Asset::transfer_aggregate_tickers().is_empty());
+
+        assert_ok!(Asset::set_aggregate_transfer_events(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            true
+        ));
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100
+        ));
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            250
+        ));
+        assert_eq!(Asset::transfer_aggregates(ticker), (2, 350));
+        assert_eq!(Asset::transfer_aggregate_tickers(), vec![ticker]);
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 450);
+
+        // The aggregate is emitted and cleared at the end of the block.
+        <Asset as OnFinalize<u64>>::on_finalize(1);
+        assert_eq!(Asset::transfer_aggregates(ticker), (0, 0));
+        assert!(Asset::transfer_aggregate_tickers().is_empty());
+
+        // The aggregated volume saturates instead of failing an applied transfer.
+        <asset::TransferAggregates<TestStorage>>::insert(ticker, (1, u128::max_value()));
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            50
+        ));
+        assert_eq!(Asset::transfer_aggregates(ticker), (2, u128::max_value()));
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 500);
+    });
+}
+
+#[test]
+fn ownership_info_combines_ticker_and_token_owners() {
+    ExtBuilder::default().build().execute_with(|| {
+        Timestamp::set_timestamp(1_000);
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+
+        let registered = Ticker::from_slice(b"REG");
+        let created = Ticker::from_slice(b"TOKEN");
+        assert_eq!(Asset::ownership_info(&registered), None);
+
+        assert_ok!(Asset::register_ticker(owner_signed.clone(), registered));
+        assert_eq!(
+            Asset::ownership_info(&registered),
+            Some(OwnershipInfo {
+                ticker_owner: owner_did,
+                token_owner: None,
+                asset_did: None,
+                expiry: Some(11_000),
+            })
+        );
+
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"TOKEN".to_vec(),
+            created,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_eq!(
+            Asset::ownership_info(&created),
+            Some(OwnershipInfo {
+                ticker_owner: owner_did,
+                token_owner: Some(owner_did),
+                asset_did: Some(Identity::get_token_did(&created).unwrap()),
+                expiry: None,
+            })
+        );
+    });
+}
+
+#[test]
+fn custodians_per_holder_are_capped() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"CUSTCAP");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"CUSTCAP".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        // The test runtime allows up to 4 custodians per holder.
+        let custodians = [
+            AccountKeyring::Alice,
+            AccountKeyring::Bob,
+            AccountKeyring::Charlie,
+            AccountKeyring::Eve,
+        ]
+        .iter()
+        .map(|acc| make_account(acc.public()).unwrap().1)
+        .collect::<Vec<_>>();
+        for custodian_did in custodians.iter() {
+            assert_ok!(Asset::increase_custody_allowance(
+                owner_signed.clone(),
+                ticker,
+                owner_did,
+                *custodian_did,
+                100
+            ));
+        }
+        assert_eq!(Asset::custodians_of((ticker, owner_did)), custodians);
+
+        let (_, ferdie_did) = make_account(AccountKeyring::Ferdie.public()).unwrap();
+        assert_err!(
+            Asset::increase_custody_allowance(
+                owner_signed.clone(),
+                ticker,
+                owner_did,
+                ferdie_did,
+                100
+            ),
+            AssetError::TooManyCustodians
+        );
+
+        // Existing custodians can still be increased.
+        assert_ok!(Asset::increase_custody_allowance(
+            owner_signed,
+            ticker,
+            owner_did,
+            custodians[0],
+            100
+        ));
+        assert_eq!(
+            Asset::custodian_allowance((ticker, owner_did, custodians[0])),
+            200
+        );
+        assert_eq!(Asset::total_custody_allowance((ticker, owner_did)), 500);
+    });
+}
+
+#[test]
+fn batch_fails_before_any_leg_when_an_auto_custodian_exceeds_the_cap() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+
+        let now = Utc::now().timestamp() as u64;
+        Timestamp::set_timestamp(now);
+
+        let ticker = Ticker::from_slice(b"CUSTCAP");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"CUSTCAP".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+        assert_ok!(Asset::transfer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100
+        ));
+
+        // Alice already has the 4 custodians allowed by the test runtime.
+        let custodians = [
+            AccountKeyring::Bob,
+            AccountKeyring::Charlie,
+            AccountKeyring::Eve,
+            AccountKeyring::Ferdie,
+        ]
+        .iter()
+        .map(|acc| make_account(acc.public()).unwrap().1)
+        .collect::<Vec<_>>();
+        for custodian_did in custodians.iter() {
+            assert_ok!(Asset::increase_custody_allowance(
+                alice_signed.clone(),
+                ticker,
+                alice_did,
+                *custodian_did,
+                10
+            ));
+        }
+
+        // Her claim names a new custodian, which cannot be assigned.
+        assert_ok!(Asset::set_auto_custodian(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            true
+        ));
+        assert_ok!(Identity::add_claim(
+            owner_signed.clone(),
+            alice_did,
+            CUSTODIAN_CLAIM_KEY.to_vec(),
+            owner_did,
+            now + 10_000,
+            ClaimValue {
+                data_type: DataTypes::VecU8,
+                value: owner_did.encode(),
+            }
+        ));
+
+        let bob_did = custodians[0];
+        assert_err!(
+            Asset::transfer_batch_same_sender(
+                owner_signed.clone(),
+                owner_did,
+                vec![(ticker, bob_did, 10), (ticker, alice_did, 10)]
+            ),
+            AssetError::TooManyCustodians
+        );
+        assert_eq!(Asset::balance_of((ticker, bob_did)), 0);
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 100);
+        assert_eq!(Asset::custodians_of((ticker, alice_did)), custodians);
+    });
+}
+
+#[test]
+fn prune_expired_allowances_keeps_unexpired_ones() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (bob_signed, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        Timestamp::set_timestamp(10);
+        let ticker = Ticker::from_slice(b"PRUNE");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"PRUNE".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+
+        assert_err!(
+            Asset::approve_with_expiry(owner_signed.clone(), owner_did, ticker, alice_did, 100, 10),
+            AssetError::AllowanceExpired
+        );
+        assert_ok!(Asset::approve_with_expiry(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100,
+            100
+        ));
+        assert_ok!(Asset::approve_with_expiry(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            bob_did,
+            100,
+            1_000
+        ));
+
+        // Alice's allowance expires and can no longer be spent.
+        Timestamp::set_timestamp(500);
+        assert_err!(
+            Asset::transfer_from(alice_signed, alice_did, ticker, owner_did, alice_did, 50),
+            AssetError::AllowanceExpired
+        );
+
+        // Only the DID that granted the allowances can prune them.
+        assert_err!(
+            Asset::prune_expired_allowances(bob_signed, owner_did, ticker, vec![alice_did]),
+            "sender must be a signing key for DID"
+        );
+        assert_ok!(Asset::prune_expired_allowances(
+            owner_signed,
+            owner_did,
+            ticker,
+            vec![alice_did, bob_did]
+        ));
+        assert_eq!(Asset::allowance((ticker, owner_did, alice_did)), 0);
+        assert_eq!(
+            Asset::allowance_expiry((ticker, owner_did, alice_did)),
+            None
+        );
+        assert_eq!(Asset::allowance((ticker, owner_did, bob_did)), 100);
+        assert_eq!(
+            Asset::allowance_expiry((ticker, owner_did, bob_did)),
+            Some(1_000)
+        );
+        assert_eq!(Asset::spenders_of((ticker, owner_did)), vec![bob_did]);
+    });
+}
+
+#[test]
+fn approve_replaces_an_expired_allowance() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+
+        Timestamp::set_timestamp(10);
+        let ticker = Ticker::from_slice(b"REAPPROVE");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"REAPPROVE".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(Asset::approve_with_expiry(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            100,
+            100
+        ));
+
+        Timestamp::set_timestamp(500);
+        assert_err!(
+            Asset::transfer_from(
+                alice_signed.clone(),
+                alice_did,
+                ticker,
+                owner_did,
+                alice_did,
+                50
+            ),
+            AssetError::AllowanceExpired
+        );
+
+        // A fresh approval replaces the expired allowance instead of adding to it.
+        assert_ok!(Asset::approve(
+            owner_signed,
+            owner_did,
+            ticker,
+            alice_did,
+            70
+        ));
+        assert_eq!(Asset::allowance((ticker, owner_did, alice_did)), 70);
+        assert_eq!(
+            Asset::allowance_expiry((ticker, owner_did, alice_did)),
+            None
+        );
+        assert_ok!(Asset::transfer_from(
+            alice_signed,
+            alice_did,
+            ticker,
+            owner_did,
+            alice_did,
+            50
+        ));
+        assert_eq!(Asset::allowance((ticker, owner_did, alice_did)), 20);
+    });
+}
+
+#[test]
+fn token_config_reflects_owner_settings() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, officer_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"CONFIG");
+        assert_eq!(Asset::token_config(ticker), None);
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"CONFIG".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_eq!(
+            Asset::token_config(ticker),
+            Some(TokenConfig {
+                lot_size: 1,
+                ..Default::default()
+            })
+        );
+
+        assert_ok!(Asset::freeze(owner_signed.clone(), ticker));
+        assert_ok!(Asset::set_operation_pause_flags(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            1
+        ));
+        assert_ok!(Asset::set_lot_size(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            10
+        ));
+        assert_ok!(Asset::set_funding_round(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            b"seed".to_vec()
+        ));
+        assert_ok!(Asset::set_max_allowance_per_spender(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            Some(500)
+        ));
+        assert_ok!(Asset::set_compliance_officer(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            Some(officer_did)
+        ));
+        assert_ok!(Asset::set_required_sender_claims(
+            owner_signed,
+            owner_did,
+            ticker,
+            vec![(b"ACCREDITED".to_vec(), vec![officer_did])]
+        ));
+
+        assert_eq!(
+            Asset::token_config(ticker),
+            Some(TokenConfig {
+                frozen: true,
+                operation_pause_flags: 1,
+                lot_size: 10,
+                funding_round: b"seed".to_vec(),
+                max_allowance_per_spender: Some(500),
+                compliance_officer: Some(officer_did),
+                required_sender_claims: 1,
+                ..Default::default()
+            })
+        );
+    });
+}
+
+#[test]
+fn token_attributes_can_be_set_and_removed() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"ATTR");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"ATTR".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        let key = b"sector".to_vec();
+
+        assert_err!(
+            Asset::set_attribute(
+                alice_signed,
+                alice_did,
+                ticker,
+                key.clone(),
+                b"tech".to_vec()
+            ),
+            "user is not authorized"
+        );
+        assert_ok!(Asset::set_attribute(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            key.clone(),
+            b"tech".to_vec()
+        ));
+        assert_eq!(
+            Asset::get_attribute((ticker, key.clone())),
+            b"tech".to_vec()
+        );
+
+        assert_ok!(Asset::set_attribute(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            key.clone(),
+            b"energy".to_vec()
+        ));
+        assert_eq!(
+            Asset::get_attribute((ticker, key.clone())),
+            b"energy".to_vec()
+        );
+
+        // Over-long keys and values are rejected.
+        assert_err!(
+            Asset::set_attribute(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                vec![b'k'; MAX_ATTRIBUTE_KEY_LENGTH + 1],
+                b"value".to_vec()
+            ),
+            AssetError::AttributeTooLong
+        );
+        assert_err!(
+            Asset::set_attribute(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                key.clone(),
+                vec![b'v'; MAX_ATTRIBUTE_VALUE_LENGTH + 1]
+            ),
+            AssetError::AttributeTooLong
+        );
+        assert_eq!(
+            Asset::get_attribute((ticker, key.clone())),
+            b"energy".to_vec()
+        );
+
+        assert_ok!(Asset::remove_attribute(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            key.clone()
+        ));
+        assert_eq!(
+            Asset::get_attribute((ticker, key.clone())),
+            Vec::<u8>::new()
+        );
+        assert_err!(
+            Asset::remove_attribute(owner_signed, owner_did, ticker, key),
+            AssetError::AttributeNotFound
+        );
+    });
+}
+
+#[test]
+fn check_my_transfer_reports_custody_blocked_transfers() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, custodian_did) = make_account(AccountKeyring::Charlie.public()).unwrap();
+
+        let now = Utc::now();
+        Timestamp::set_timestamp(now.timestamp() as u64);
+
+        let ticker = Ticker::from_slice(b"PREFLY");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"PREFLY".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+        assert_ok!(Asset::increase_custody_allowance(
+            owner_signed.clone(),
+            ticker,
+            owner_did,
+            custodian_did,
+            300
+        ));
+
+        assert_eq!(
+            Asset::transfer_preflight_status(&ticker, owner_did, alice_did, 700),
+            ERC1400_TRANSFER_SUCCESS as u32
+        );
+        assert_eq!(
+            Asset::transfer_preflight_status(&ticker, owner_did, alice_did, 800),
+            ERC1400_INSUFFICIENT_BALANCE as u32
+        );
+
+        // The check does not move any tokens.
+        assert_ok!(Asset::check_my_transfer(
+            owner_signed,
+            ticker,
+            alice_did,
+            700
+        ));
+        assert_eq!(Asset::balance_of((ticker, owner_did)), 1_000);
+        assert_eq!(Asset::balance_of((ticker, alice_did)), 0);
+
+        // The caller needs a DID.
+        assert_err!(
+            Asset::check_my_transfer(
+                frame_system::RawOrigin::Signed(AccountKeyring::Ferdie.public()).into(),
+                ticker,
+                alice_did,
+                700
+            ),
+            AssetError::DIDNotFound
+        );
+    });
+}
+
+#[test]
+fn transfer_from_requires_recipient_kyc_when_enabled() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (provider_signed, provider_did) =
+            make_account(AccountKeyring::Charlie.public()).unwrap();
+        let (_, kyc_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, no_kyc_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+        let (spender_signed, spender_did) = make_account(AccountKeyring::Eve.public()).unwrap();
+
+        let now = Utc::now().timestamp() as u64;
+        Timestamp::set_timestamp(now);
+
+        // Charlie is a trusted KYC provider and vouches for Alice only.
+        assert_ok!(KycServiceProviders::add_member(
+            owner_signed.clone(),
+            provider_did
+        ));
+        assert_ok!(Identity::add_claim(
+            provider_signed.clone(),
+            kyc_did,
+            KYC_EXPIRY_CLAIM_KEY.to_vec(),
+            provider_did,
+            now + 10_000,
+            ClaimValue {
+                data_type: DataTypes::U64,
+                value: (now + 10_000).to_be_bytes().to_vec(),
+            }
+        ));
+
+        let ticker = Ticker::from_slice(b"KYCFROM");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"KYCFROM".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+        assert_ok!(Asset::approve(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            spender_did,
+            300
+        ));
+
+        assert_ok!(Asset::set_require_kyc_on_transfer_from(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            true
+        ));
+        assert!(Asset::require_kyc_on_transfer_from(ticker));
+        assert_err!(
+            Asset::transfer_from(
+                spender_signed.clone(),
+                spender_did,
+                ticker,
+                owner_did,
+                no_kyc_did,
+                100
+            ),
+            AssetError::RecipientKycInvalid
+        );
+        assert_ok!(Asset::transfer_from(
+            spender_signed.clone(),
+            spender_did,
+            ticker,
+            owner_did,
+            kyc_did,
+            100
+        ));
+
+        // Without the flag, anyone can receive tokens through `transfer_from`.
+        assert_ok!(Asset::set_require_kyc_on_transfer_from(
+            owner_signed,
+            owner_did,
+            ticker,
+            false
+        ));
+        assert_ok!(Asset::transfer_from(
+            spender_signed,
+            spender_did,
+            ticker,
+            owner_did,
+            no_kyc_did,
+            100
+        ));
+        assert_eq!(Asset::balance_of((ticker, kyc_did)), 100);
+        assert_eq!(Asset::balance_of((ticker, no_kyc_did)), 100);
+        assert_eq!(Asset::allowance((ticker, owner_did, spender_did)), 100);
+    });
+}
+
+#[test]
+fn caller_did_resolves_current_or_linked_identity() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (_, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+        let alice_key = AccountKey::from(AccountKeyring::Alice.public().0);
+        let ferdie_key = AccountKey::from(AccountKeyring::Ferdie.public().0);
+
+        // The current identity takes precedence over the identity linked to the key.
+        Identity::set_current_did(Some(bob_did));
+        assert_eq!(Asset::caller_did(&alice_key), Ok(bob_did));
+        assert_eq!(Asset::caller_did(&ferdie_key), Ok(bob_did));
+
+        // Without a current identity, the key's own identity is used.
+        Identity::set_current_did(None);
+        assert_eq!(Asset::caller_did(&alice_key), Ok(alice_did));
+        assert_err!(Asset::caller_did(&ferdie_key), AssetError::DIDNotFound);
+    });
+}
+
+#[test]
+fn identifiers_are_limited_per_token() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let custom = |name: &[u8]| (IdentifierType::Custom(name.to_vec()), b"value".to_vec());
+
+        // The test runtime allows 3 identifiers per token.
+        let ticker = Ticker::from_slice(b"IDCAP");
+        let too_many = vec![custom(b"a"), custom(b"b"), custom(b"c"), custom(b"d")];
+        assert_err!(
+            Asset::create_token(
+                owner_signed.clone(),
+                owner_did,
+                b"IDCAP".to_vec(),
+                ticker,
+                1_000_000,
+                true,
+                AssetType::default(),
+                too_many,
+                None
+            ),
+            AssetError::TooManyIdentifiers
+        );
+
+        let at_cap = vec![custom(b"a"), custom(b"b"), custom(b"c")];
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"IDCAP".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            at_cap,
+            None
+        ));
+        assert_eq!(Asset::identifier_types_of(ticker).len(), 3);
+
+        // Overwriting existing identifiers does not count towards the limit.
+        assert_ok!(Asset::update_identifiers(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            vec![(IdentifierType::Custom(b"a".to_vec()), b"new".to_vec())],
+        ));
+        assert_eq!(
+            Asset::identifiers((ticker, IdentifierType::Custom(b"a".to_vec()))),
+            b"new".to_vec()
+        );
+
+        // A new identifier type would exceed the limit.
+        assert_err!(
+            Asset::update_identifiers(owner_signed.clone(), owner_did, ticker, vec![custom(b"d")]),
+            AssetError::TooManyIdentifiers
+        );
+        assert!(Asset::identifiers((ticker, IdentifierType::Custom(b"d".to_vec()))).is_empty());
+
+        // Identifiers of the same ticker are counted together across a batch.
+        let batch_ticker = Ticker::from_slice(b"IDBATCH");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"IDBATCH".to_vec(),
+            batch_ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_err!(
+            Asset::batch_update_identifiers(
+                owner_signed.clone(),
+                owner_did,
+                vec![
+                    (batch_ticker, vec![custom(b"a"), custom(b"b")]),
+                    (batch_ticker, vec![custom(b"b"), custom(b"c"), custom(b"d")]),
+                ]
+            ),
+            AssetError::TooManyIdentifiers
+        );
+        assert!(Asset::identifier_types_of(batch_ticker).is_empty());
+        assert_ok!(Asset::batch_update_identifiers(
+            owner_signed.clone(),
+            owner_did,
+            vec![
+                (batch_ticker, vec![custom(b"a"), custom(b"b")]),
+                (batch_ticker, vec![custom(b"b"), custom(b"c")]),
+            ]
+        ));
+        assert_eq!(Asset::identifier_types_of(batch_ticker).len(), 3);
+
+        // Identifiers set before the index existed are added by root.
+        <asset::IdentifierTypesOf>::remove(ticker);
+        let types = vec![
+            IdentifierType::Custom(b"a".to_vec()),
+            IdentifierType::Custom(b"b".to_vec()),
+            IdentifierType::Custom(b"d".to_vec()),
+        ];
+        assert!(Asset::backfill_identifier_types(owner_signed, ticker, types.clone()).is_err());
+        assert_ok!(Asset::backfill_identifier_types(
+            frame_system::RawOrigin::Root.into(),
+            ticker,
+            types.clone()
+        ));
+        assert_ok!(Asset::backfill_identifier_types(
+            frame_system::RawOrigin::Root.into(),
+            ticker,
+            types
+        ));
+        assert_eq!(
+            Asset::identifier_types_of(ticker),
+            vec![
+                IdentifierType::Custom(b"a".to_vec()),
+                IdentifierType::Custom(b"b".to_vec())
+            ]
+        );
+    });
+}
+
+#[test]
+fn issue_to_custody_mints_under_custody() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, holder_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (_, custodian_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"CUSTODY");
+        assert_ok!(Asset::create_token_with_hard_max_supply(
+            owner_signed.clone(),
+            owner_did,
+            b"CUSTODY".to_vec(),
+            ticker,
+            1_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None,
+            Some(1_500),
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+
+        assert_ok!(Asset::issue_to_custody(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            holder_did,
+            custodian_did,
+            400
+        ));
+        assert_eq!(Asset::balance_of((ticker, holder_did)), 400);
+        assert_eq!(
+            Asset::custodian_allowance((ticker, holder_did, custodian_did)),
+            400
+        );
+        assert_eq!(Asset::total_custody_allowance((ticker, holder_did)), 400);
+
+        // Issuance restrictions still apply and nothing is changed when they fail.
+        assert_err!(
+            Asset::issue_to_custody(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                holder_did,
+                custodian_did,
+                101
+            ),
+            AssetError::HardMaxSupplyExceeded
+        );
+        assert_eq!(Asset::balance_of((ticker, holder_did)), 400);
+        assert_eq!(
+            Asset::custodian_allowance((ticker, holder_did, custodian_did)),
+            400
+        );
+
+        // An invalid custody allowance aborts the issuance.
+        assert_err!(
+            Asset::issue_to_custody(owner_signed, owner_did, ticker, holder_did, holder_did, 50),
+            AssetError::SelfCustodyNotAllowed
+        );
+        assert_eq!(Asset::balance_of((ticker, holder_did)), 400);
+        assert_eq!(Asset::token_details(ticker).total_supply, 1_400);
+    });
+}
+
+#[test]
+fn directional_holder_freeze() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (alice_signed, alice_did) = make_account(AccountKeyring::Alice.public()).unwrap();
+        let (bob_signed, bob_did) = make_account(AccountKeyring::Bob.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"DIRFRZ");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"DIRFRZ".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![],
+            }
+        ));
+        for did in &[alice_did, bob_did] {
+            assert_ok!(Asset::transfer(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                *did,
+                500
+            ));
+        }
+
+        // Only the token owner can freeze a holder.
+        assert_err!(
+            Asset::controller_freeze_holder_directional(
+                alice_signed.clone(),
+                alice_did,
+                ticker,
+                bob_did,
+                FreezeDirection::Both
+            ),
+            "user is not authorized"
+        );
+
+        // An incoming freeze blocks receipts but allows sends.
+        assert_ok!(Asset::controller_freeze_holder_directional(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            FreezeDirection::Incoming
+        ));
+        assert_eq!(
+            Asset::holder_freeze_direction((ticker, alice_did)),
+            Some(FreezeDirection::Incoming)
+        );
+        assert_err!(
+            Asset::transfer(bob_signed.clone(), bob_did, ticker, alice_did, 100),
+            AssetError::ReceiverFrozen
+        );
+        // Issuance cannot bypass it either.
+        assert_err!(
+            Asset::issue(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                alice_did,
+                100,
+                vec![]
+            ),
+            AssetError::ReceiverFrozen
+        );
+        assert_err!(
+            Asset::batch_issue(
+                owner_signed.clone(),
+                owner_did,
+                ticker,
+                vec![bob_did, alice_did],
+                vec![100, 100]
+            ),
+            AssetError::ReceiverFrozen
+        );
+        assert_eq!(Asset::balance_of((ticker, bob_did)), 500);
+        assert_ok!(Asset::transfer(
+            alice_signed.clone(),
+            alice_did,
+            ticker,
+            bob_did,
+            100
+        ));
+
+        // An outgoing freeze blocks sends but allows receipts.
+        assert_ok!(Asset::controller_freeze_holder_directional(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            alice_did,
+            FreezeDirection::Outgoing
+        ));
+        assert_err!(
+            Asset::transfer(alice_signed.clone(), alice_did, ticker, bob_did, 100),
+            AssetError::HolderFrozen
+        );
+        assert_eq!(Asset::spendable_balance(ticker, alice_did), 0);
+        assert_ok!(Asset::transfer(
+            bob_signed.clone(),
+            bob_did,
+            ticker,
+            alice_did,
+            100
+        ));
+
+        // Unfreezing lifts the directional freeze.
+        assert_ok!(Asset::controller_unfreeze_holder(
+            owner_signed,
+            owner_did,
+            ticker,
+            alice_did,
+            b"cleared".to_vec()
+        ));
+        assert_eq!(Asset::holder_freeze_direction((ticker, alice_did)), None);
+        assert_ok!(Asset::transfer(
+            alice_signed,
+            alice_did,
+            ticker,
+            bob_did,
+            100
+        ));
+    });
+}
+
+#[test]
+fn total_locked_sums_custody_and_escrow() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let (_, custodian_did) = make_account(AccountKeyring::Charlie.public()).unwrap();
+        let (escrow_custodian_signed, escrow_custodian_did) =
+            make_account(AccountKeyring::Alice.public()).unwrap();
+
+        let ticker = Ticker::from_slice(b"LOCKED");
+        assert_ok!(Asset::create_token(
+            owner_signed.clone(),
+            owner_did,
+            b"LOCKED".to_vec(),
+            ticker,
+            1_000_000,
+            true,
+            AssetType::default(),
+            vec![],
+            None
+        ));
+        assert_ok!(GeneralTM::add_active_rule(
+            owner_signed.clone(),
+            owner_did,
+            ticker,
+            general_tm::AssetRule {
+                sender_rules: vec![],
+                receiver_rules: vec![]
+            }
+        ));
+        assert_eq!(Asset::total_locked(ticker, owner_did), 0);
+
+        assert_ok!(Asset::increase_custody_allowance(
+            owner_signed.clone(),
+            ticker,
+            owner_did,
+            custodian_did,
+            2_000
+        ));
+        assert_ok!(Asset::transfer_to_escrow(
+            owner_signed.clone(),
+            ticker,
+            owner_did,
+            escrow_custodian_did,
+            1_000
+        ));
+        assert_eq!(Asset::total_locked(ticker, owner_did), 3_000);
+        // Escrowed tokens left the holder balance, so only the custody allowance is deducted.
+        assert_eq!(Asset::spendable_balance(ticker, owner_did), 997_000);
+
+        // Escrows funded before the index existed are added by root.
+        <asset::EscrowCustodiansOf>::remove((ticker, owner_did));
+        assert_eq!(Asset::total_locked(ticker, owner_did), 2_000);
+        assert!(Asset::backfill_escrow_custodians_of(
+            owner_signed,
+            ticker,
+            owner_did,
+            vec![escrow_custodian_did]
+        )
+        .is_err());
+        assert_ok!(Asset::backfill_escrow_custodians_of(
+            frame_system::RawOrigin::Root.into(),
+            ticker,
+            owner_did,
+            vec![custodian_did, escrow_custodian_did, escrow_custodian_did]
+        ));
+        assert_eq!(
+            Asset::escrow_custodians_of((ticker, owner_did)),
+            vec![escrow_custodian_did]
+        );
+        assert_eq!(Asset::total_locked(ticker, owner_did), 3_000);
+
+        // Released escrow no longer counts.
+        assert_ok!(Asset::release_escrow(
+            escrow_custodian_signed,
+            ticker,
+            owner_did,
+            owner_did,
+            1_000
+        ));
+        assert!(Asset::escrow_custodians_of((ticker, owner_did)).is_empty());
+        assert_eq!(Asset::total_locked(ticker, owner_did), 2_000);
+    });
+}
+
+#[test]
+fn unique_token_names_can_be_enforced() {
+    ExtBuilder::default().build().execute_with(|| {
+        let (owner_signed, owner_did) = make_account(AccountKeyring::Dave.public()).unwrap();
+        let create = |ticker: &[u8], name: &[u8]| {
+            Asset::create_token(
+                owner_signed.clone(),
+                owner_did,
+                name.to_vec(),
+                Ticker::from_slice(ticker),
+                1_000_000,
+                true,
+                AssetType::default(),
+                vec![],
+                None,
+            )
+        };
+
+        // Names can be shared unless enforcement is enabled.
+        assert_ok!(create(b"FIRST", b"Shared"));
+        assert_ok!(create(b"SECOND", b"Shared"));
+        assert_eq!(
+            Asset::token_names(b"Shared".to_vec()),
+            vec![Ticker::from_slice(b"FIRST"), Ticker::from_slice(b"SECOND")]
+        );
+
+        assert!(Asset::set_enforce_unique_token_names(owner_signed.clone(), true).is_err());
+        assert_ok!(Asset::set_enforce_unique_token_names(
+            frame_system::RawOrigin::Root.into(),
+            true
+        ));
+        assert_err!(create(b"THIRD", b"Shared"), AssetError::DuplicateTokenName);
+        assert_ok!(create(b"THIRD", b"Third"));
+
+        // Renaming into a collision is rejected, keeping the current name is not.
+        let third = Ticker::from_slice(b"THIRD");
+        assert_err!(
+            Asset::rename_token(owner_signed.clone(), third, b"Shared".to_vec()),
+            AssetError::DuplicateTokenName
+        );
+        assert_ok!(Asset::rename_token(
+            owner_signed.clone(),
+            third,
+            b"Third".to_vec()
+        ));
+
+        // Renaming frees the previous name.
+        assert_ok!(Asset::rename_token(
+            owner_signed.clone(),
+            Ticker::from_slice(b"FIRST"),
+            b"First".to_vec()
+        ));
+        assert_ok!(Asset::rename_token(
+            owner_signed.clone(),
+            Ticker::from_slice(b"SECOND"),
+            b"Second".to_vec()
+        ));
+        assert!(Asset::token_names(b"Shared".to_vec()).is_empty());
+        assert_ok!(Asset::rename_token(
+            owner_signed.clone(),
+            third,
+            b"Shared".to_vec()
+        ));
+        assert_eq!(Asset::token_details(third).name, b"Shared".to_vec());
+
+        // Tokens created before the index existed do not block their name until root adds them.
+        <asset::TokenNames>::remove(b"Shared".to_vec());
+        assert_ok!(create(b"FOURTH", b"Shared"));
+        assert!(Asset::backfill_token_names(owner_signed.clone(), vec![third]).is_err());
+        for _ in 0..2 {
+            assert_ok!(Asset::backfill_token_names(
+                frame_system::RawOrigin::Root.into(),
+                vec![third, Ticker::from_slice(b"MISSING")]
+            ));
+        }
+        assert_eq!(
+            Asset::token_names(b"Shared".to_vec()),
+            vec![Ticker::from_slice(b"FOURTH"), third]
+        );
+        assert_err!(create(b"FIFTH", b"Shared"), AssetError::DuplicateTokenName);
+    });
+}
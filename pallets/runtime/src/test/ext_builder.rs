@@ -3,6 +3,7 @@ use crate::{
     test::TestStorage,
 };
 
+use polymesh_primitives::{IdentityId, Ticker};
 use polymesh_runtime_balances as balances;
 use polymesh_runtime_identity as identity;
 
@@ -22,6 +23,11 @@ pub struct ExtBuilder {
     creation_fee: u128,
     monied: bool,
     vesting: bool,
+    claim_expiry_grace_period: u64,
+    min_ticker_length: u8,
+    claim_fee: u128,
+    initial_balances: Vec<(Ticker, IdentityId, u128)>,
+    identities: Vec<(IdentityId, Public)>,
 }
 
 thread_local! {
@@ -60,6 +66,31 @@ impl ExtBuilder {
         self
     }
 
+    pub fn claim_expiry_grace_period(mut self, claim_expiry_grace_period: u64) -> Self {
+        self.claim_expiry_grace_period = claim_expiry_grace_period;
+        self
+    }
+
+    pub fn claim_fee(mut self, claim_fee: u128) -> Self {
+        self.claim_fee = claim_fee;
+        self
+    }
+
+    pub fn min_ticker_length(mut self, min_ticker_length: u8) -> Self {
+        self.min_ticker_length = min_ticker_length;
+        self
+    }
+
+    pub fn initial_balances(mut self, initial_balances: Vec<(Ticker, IdentityId, u128)>) -> Self {
+        self.initial_balances = initial_balances;
+        self
+    }
+
+    pub fn identities(mut self, identities: Vec<(IdentityId, Public)>) -> Self {
+        self.identities = identities;
+        self
+    }
+
     pub fn set_associated_consts(&self) {
         EXISTENTIAL_DEPOSIT.with(|v| *v.borrow_mut() = self.existential_deposit);
         TRANSFER_FEE.with(|v| *v.borrow_mut() = self.transfer_fee);
@@ -115,6 +146,9 @@ impl ExtBuilder {
         identity::GenesisConfig::<TestStorage> {
             owner: AccountKeyring::Alice.public().into(),
             did_creation_fee: 250,
+            claim_expiry_grace_period: self.claim_expiry_grace_period,
+            claim_fee: self.claim_fee,
+            identities: self.identities,
         }
         .assimilate_storage(&mut storage)
         .unwrap();
@@ -135,7 +169,9 @@ impl ExtBuilder {
                 max_ticker_length: 8,
                 registration_length: Some(10000),
             },
+            min_ticker_length: self.min_ticker_length,
             fee_collector: AccountKeyring::Dave.public().into(),
+            initial_balances: self.initial_balances,
         }
         .assimilate_storage(&mut storage)
         .unwrap();
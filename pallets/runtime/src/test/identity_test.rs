@@ -1,21 +1,27 @@
 use crate::test::{
-    storage::{register_keyring_account, TestStorage},
+    storage::{
+        register_keyring_account, register_keyring_account_with_balance, Call, KycServiceProviders,
+        TestStorage,
+    },
     ExtBuilder,
 };
 
 use polymesh_primitives::{
-    AccountKey, AuthorizationData, LinkData, Permission, Signatory, SignatoryType, SigningItem,
-    Ticker,
+    AccountKey, AuthorizationData, AuthorizationType, IdentityId, LinkData, LinkDataTag,
+    Permission, Signatory, SignatoryType, SigningItem, Ticker,
 };
 use polymesh_runtime_balances as balances;
+use polymesh_runtime_common::constants::did::{
+    DUAL_UNFREEZE_WINDOW, MAX_ALLOWED_CALLS, MAX_CALL_ID_LEN, RECOVERY_TIMELOCK,
+};
 use polymesh_runtime_common::traits::identity::{
-    Claim, ClaimMetaData, ClaimRecord, ClaimValue, DataTypes, SigningItemWithAuth,
-    TargetIdAuthorization,
+    Claim, ClaimMetaData, ClaimRecord, ClaimValue, DataTypes, KeyLinkConsistency, LinkedKeyInfo,
+    SigningItemWithAuth, TargetIdAuthorization,
 };
 use polymesh_runtime_identity::{self as identity, Error};
 
 use codec::Encode;
-use frame_support::{assert_err, assert_ok, traits::Currency};
+use frame_support::{assert_err, assert_ok, traits::Currency, StorageMap, StorageValue};
 
 use rand::Rng;
 use sp_core::H512;
@@ -266,6 +272,90 @@ fn only_master_key_can_add_signing_key_permissions_with_externalities() {
     ));
 }
 
+#[test]
+fn rotate_signing_key_keeps_permissions() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(&rotate_signing_key_keeps_permissions_with_externalities);
+}
+
+fn rotate_signing_key_keeps_permissions_with_externalities() {
+    let bob_key = AccountKey::from(AccountKeyring::Bob.public().0);
+    let eve_key = AccountKey::from(AccountKeyring::Eve.public().0);
+    let charlie_key = AccountKey::from(AccountKeyring::Charlie.public().0);
+    let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+    let _charlie_did = register_keyring_account(AccountKeyring::Charlie).unwrap();
+    let alice = Origin::signed(AccountKeyring::Alice.public());
+    let bob = Origin::signed(AccountKeyring::Bob.public());
+
+    assert_ok!(Identity::add_signing_items(
+        alice.clone(),
+        alice_did,
+        vec![SigningItem::from(bob_key)]
+    ));
+    assert_ok!(Identity::authorize_join_to_identity(bob.clone(), alice_did));
+    assert_ok!(Identity::set_permission_to_signer(
+        alice.clone(),
+        alice_did,
+        Signatory::AccountKey(bob_key),
+        vec![Permission::Operator]
+    ));
+
+    // Only the master key can rotate signing keys.
+    assert_err!(
+        Identity::rotate_signing_key(bob, alice_did, Signatory::AccountKey(bob_key), eve_key),
+        "Only master key of an identity is able to execute this operation"
+    );
+
+    // Charlie's key is already the master key of another identity.
+    assert_err!(
+        Identity::rotate_signing_key(
+            alice.clone(),
+            alice_did,
+            Signatory::AccountKey(bob_key),
+            charlie_key
+        ),
+        Error::<TestStorage>::AlreadyLinked
+    );
+
+    // Only existing signing items can be rotated.
+    assert_err!(
+        Identity::rotate_signing_key(
+            alice.clone(),
+            alice_did,
+            Signatory::AccountKey(eve_key),
+            eve_key
+        ),
+        Error::<TestStorage>::InvalidSender
+    );
+
+    assert_ok!(Identity::rotate_signing_key(
+        alice,
+        alice_did,
+        Signatory::AccountKey(bob_key),
+        eve_key
+    ));
+
+    let signing_items = Identity::did_records(alice_did).signing_items;
+    assert_eq!(
+        signing_items,
+        vec![SigningItem::new(
+            Signatory::AccountKey(eve_key),
+            vec![Permission::Operator]
+        )]
+    );
+    assert_eq!(Identity::get_identity(&bob_key), None);
+    assert_eq!(Identity::get_identity(&eve_key), Some(alice_did));
+    assert!(!Identity::is_signer_authorized(
+        alice_did,
+        &Signatory::AccountKey(bob_key)
+    ));
+    assert!(Identity::is_signer_authorized(
+        alice_did,
+        &Signatory::AccountKey(eve_key)
+    ));
+}
+
 #[test]
 fn add_signing_keys_with_specific_type() {
     ExtBuilder::default()
@@ -358,7 +448,7 @@ fn freeze_signing_keys_with_externalities() {
     // Freeze signing keys: bob & charlie.
     assert_err!(
         Identity::freeze_signing_keys(bob.clone(), alice_did),
-        "Only master key of an identity is able to execute this operation"
+        "Only master key or an operator key of an identity is able to execute this operation"
     );
     assert_ok!(Identity::freeze_signing_keys(alice.clone(), alice_did));
 
@@ -793,6 +883,158 @@ fn one_step_join_id_with_ext() {
     );
 }
 
+#[test]
+fn verify_signing_item_auth_checks_signature_and_revocation() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_id = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let bob_id = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+
+        let authorization = TargetIdAuthorization {
+            target_id: alice_id,
+            nonce: Identity::offchain_authorization_nonce(alice_id),
+            expires_at: 100u64,
+        };
+        let auth_encoded = authorization.encode();
+        let signed_by = |acc: AccountKeyring| SigningItemWithAuth {
+            signing_item: SigningItem::from(bob_id),
+            auth_signature: H512::from(acc.sign(&auth_encoded)),
+        };
+
+        assert_ok!(Identity::verify_signing_item_auth(
+            alice_id,
+            &signed_by(AccountKeyring::Bob),
+            &authorization
+        ));
+        assert_err!(
+            Identity::verify_signing_item_auth(
+                alice_id,
+                &signed_by(AccountKeyring::Charlie),
+                &authorization
+            ),
+            "Invalid Authorization signature"
+        );
+
+        assert_ok!(Identity::revoke_offchain_authorization(
+            bob,
+            Signatory::Identity(bob_id),
+            authorization.clone()
+        ));
+        assert_err!(
+            Identity::verify_signing_item_auth(
+                alice_id,
+                &signed_by(AccountKeyring::Bob),
+                &authorization
+            ),
+            "Authorization has been explicitly revoked"
+        );
+    });
+}
+
+#[test]
+fn validate_signing_items_auth_reports_every_invalid_item() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_id = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let bob_id = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let charlie_id = register_keyring_account(AccountKeyring::Charlie).unwrap();
+        let dave_id = register_keyring_account(AccountKeyring::Dave).unwrap();
+
+        let expires_at = 100u64;
+        let nonce = Identity::offchain_authorization_nonce(alice_id);
+        let auth_encoded = TargetIdAuthorization {
+            target_id: alice_id,
+            nonce,
+            expires_at,
+        }
+        .encode();
+        let signed_by = |did: IdentityId, acc: AccountKeyring| SigningItemWithAuth {
+            signing_item: SigningItem::from(did),
+            auth_signature: H512::from(acc.sign(&auth_encoded)),
+        };
+
+        let items = vec![
+            signed_by(bob_id, AccountKeyring::Bob),
+            signed_by(charlie_id, AccountKeyring::Dave),
+            signed_by(dave_id, AccountKeyring::Dave),
+            signed_by(bob_id, AccountKeyring::Alice),
+        ];
+        assert_eq!(
+            Identity::validate_signing_items_auth(alice_id, expires_at, &items),
+            vec![
+                (1, "Invalid Authorization signature"),
+                (3, "Invalid Authorization signature"),
+            ]
+        );
+        assert_eq!(
+            Identity::validate_signing_items_auth(alice_id, expires_at, &items[..1]),
+            vec![]
+        );
+        // Validation does not change the state.
+        assert_eq!(Identity::offchain_authorization_nonce(alice_id), nonce);
+
+        Timestamp::set_timestamp(expires_at);
+        assert_eq!(
+            Identity::validate_signing_items_auth(alice_id, expires_at, &items[..1]),
+            vec![(0, "Offchain authorization has expired")]
+        );
+    });
+}
+
+#[test]
+fn batch_revoke_offchain_authorization_is_all_or_nothing() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_id = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let bob_id = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let bob_key = Signatory::from(AccountKey::from(AccountKeyring::Bob.public().0));
+        let charlie_key = Signatory::from(AccountKey::from(AccountKeyring::Charlie.public().0));
+
+        let auth_with_nonce = |nonce| TargetIdAuthorization {
+            target_id: alice_id,
+            nonce,
+            expires_at: 100u64,
+        };
+
+        // Revoking an authorization of another key rejects the whole batch.
+        assert_err!(
+            Identity::batch_revoke_offchain_authorization(
+                bob.clone(),
+                vec![
+                    (Signatory::Identity(bob_id), auth_with_nonce(1)),
+                    (charlie_key, auth_with_nonce(2)),
+                ]
+            ),
+            "This key is not allowed to revoke this off-chain authorization"
+        );
+        assert_eq!(
+            Identity::is_offchain_authorization_revoked((
+                Signatory::Identity(bob_id),
+                auth_with_nonce(1)
+            )),
+            false
+        );
+
+        assert_ok!(Identity::batch_revoke_offchain_authorization(
+            bob,
+            vec![
+                (Signatory::Identity(bob_id), auth_with_nonce(1)),
+                (bob_key, auth_with_nonce(2)),
+            ]
+        ));
+        assert_eq!(
+            Identity::is_offchain_authorization_revoked((
+                Signatory::Identity(bob_id),
+                auth_with_nonce(1)
+            )),
+            true
+        );
+        assert_eq!(
+            Identity::is_offchain_authorization_revoked((bob_key, auth_with_nonce(2))),
+            true
+        );
+    });
+}
+
 #[test]
 fn adding_authorizations() {
     ExtBuilder::default().build().execute_with(|| {
@@ -935,13 +1177,29 @@ fn adding_links() {
         let ticker51 = Ticker::from_slice(&[0x51]);
         let mut link_ids_bob = Vec::new();
         link_ids_bob.push(0); // signifies that there are no more links left
-        Identity::add_link(bob_did, LinkData::TickerOwned(ticker50), None);
+        assert_ok!(Identity::add_link(
+            bob_did,
+            LinkData::TickerOwned(ticker50),
+            None
+        ));
         link_ids_bob.push(Identity::last_link(bob_did));
-        Identity::add_link(bob_did, LinkData::TickerOwned(ticker51), None);
+        assert_ok!(Identity::add_link(
+            bob_did,
+            LinkData::TickerOwned(ticker51),
+            None
+        ));
         link_ids_bob.push(Identity::last_link(bob_did));
-        Identity::add_link(bob_did, LinkData::TickerOwned(ticker50), Some(100));
+        assert_ok!(Identity::add_link(
+            bob_did,
+            LinkData::TickerOwned(ticker50),
+            Some(100)
+        ));
         link_ids_bob.push(Identity::last_link(bob_did));
-        Identity::add_link(bob_did, LinkData::TickerOwned(ticker50), Some(100));
+        assert_ok!(Identity::add_link(
+            bob_did,
+            LinkData::TickerOwned(ticker50),
+            Some(100)
+        ));
         link_ids_bob.push(Identity::last_link(bob_did));
         link_ids_bob.push(0); // signifies that there are no more links left
         for i in 1..(link_ids_bob.len() - 1) {
@@ -979,7 +1237,11 @@ fn removing_links() {
         let mut link_ids_bob = Vec::new();
         link_ids_bob.push(0); // signifies that there are no more links left
         for _ in 0..10 {
-            Identity::add_link(bob_did, LinkData::TickerOwned(ticker50), None);
+            assert_ok!(Identity::add_link(
+                bob_did,
+                LinkData::TickerOwned(ticker50),
+                None
+            ));
             link_ids_bob.push(Identity::last_link(bob_did));
         }
         link_ids_bob.push(0); // signifies that there are no more links left
@@ -1056,3 +1318,2849 @@ fn changing_master_key() {
         );
     });
 }
+
+#[test]
+fn numeric_claims_threshold() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let issuer_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let issuer = Origin::signed(AccountKeyring::Bob.public());
+        let other_issuer_did = register_keyring_account(AccountKeyring::Charlie).unwrap();
+        let claim_key = b"accredited_score".to_vec();
+
+        Timestamp::set_timestamp(10);
+        assert_ok!(Identity::add_numeric_claim(
+            issuer.clone(),
+            alice_did,
+            claim_key.clone(),
+            issuer_did,
+            100,
+            800
+        ));
+
+        // Threshold met by a listed issuer.
+        assert!(Identity::numeric_claim_at_least(
+            alice_did,
+            claim_key.clone(),
+            vec![other_issuer_did, issuer_did],
+            800
+        ));
+        // Threshold not met.
+        assert!(!Identity::numeric_claim_at_least(
+            alice_did,
+            claim_key.clone(),
+            vec![issuer_did],
+            801
+        ));
+        // Issuer not listed.
+        assert!(!Identity::numeric_claim_at_least(
+            alice_did,
+            claim_key.clone(),
+            vec![other_issuer_did],
+            1
+        ));
+
+        // Expired claims are ignored.
+        Timestamp::set_timestamp(100);
+        assert!(!Identity::numeric_claim_at_least(
+            alice_did,
+            claim_key.clone(),
+            vec![issuer_did],
+            800
+        ));
+
+        assert_ok!(Identity::revoke_numeric_claim(
+            issuer.clone(),
+            alice_did,
+            claim_key.clone(),
+            issuer_did
+        ));
+        assert_err!(
+            Identity::revoke_numeric_claim(issuer, alice_did, claim_key, issuer_did),
+            Error::<TestStorage>::NumericClaimNotFound
+        );
+    });
+}
+
+#[test]
+fn forwarded_call_nonce() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+
+        // Alice's identity becomes a signing item of Bob's identity.
+        assert_ok!(Identity::add_signing_items(
+            bob,
+            bob_did,
+            vec![SigningItem::from(alice_did)]
+        ));
+        assert_ok!(Identity::authorize_join_to_identity(alice.clone(), bob_did));
+
+        Identity::set_current_did(Some(alice_did));
+        let call = Box::new(Call::Identity(identity::Call::get_my_did()));
+        assert_ok!(Identity::forwarded_call(
+            alice.clone(),
+            bob_did,
+            0,
+            call.clone()
+        ));
+        assert_eq!(Identity::forwarded_call_nonce(alice_did), 1);
+
+        // A stale nonce is rejected.
+        Identity::set_current_did(Some(alice_did));
+        assert_err!(
+            Identity::forwarded_call(alice.clone(), bob_did, 0, call.clone()),
+            Error::<TestStorage>::InvalidForwardedCallNonce
+        );
+        assert_ok!(Identity::forwarded_call(alice, bob_did, 1, call));
+        assert_eq!(Identity::forwarded_call_nonce(alice_did), 2);
+    });
+}
+
+#[test]
+fn key_group_membership_changes() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let dave_key = AccountKey::from(AccountKeyring::Dave.public().0);
+        let dave_signing_item = SigningItem {
+            signer: Signatory::AccountKey(dave_key),
+            signer_type: SignatoryType::Relayer,
+            permissions: vec![],
+        };
+        let expires_at = 100u64;
+
+        // Dave's relayer key joins Alice's and Bob's identities.
+        for (origin, did) in [(alice.clone(), alice_did), (bob.clone(), bob_did)].iter() {
+            let authorization = TargetIdAuthorization {
+                target_id: *did,
+                nonce: Identity::offchain_authorization_nonce(*did),
+                expires_at,
+            };
+            let auth_signature = H512::from(AccountKeyring::Dave.sign(&authorization.encode()));
+            assert_ok!(Identity::add_signing_items_with_authorization(
+                origin.clone(),
+                *did,
+                expires_at,
+                vec![SigningItemWithAuth {
+                    signing_item: dave_signing_item.clone(),
+                    auth_signature,
+                }]
+            ));
+        }
+        let mut group = vec![alice_did, bob_did];
+        group.sort();
+        assert_eq!(
+            Identity::key_to_identity_ids(dave_key),
+            Some(LinkedKeyInfo::Group(group))
+        );
+
+        // Bob removes Dave's key.
+        assert_ok!(Identity::remove_signing_items(
+            bob,
+            bob_did,
+            vec![Signatory::AccountKey(dave_key)]
+        ));
+        assert_eq!(
+            Identity::key_to_identity_ids(dave_key),
+            Some(LinkedKeyInfo::Group(vec![alice_did]))
+        );
+    });
+}
+
+#[test]
+fn claim_issuers_of_ignores_expired_claims() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let bob_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let charlie_did = register_keyring_account(AccountKeyring::Charlie).unwrap();
+        let charlie = Origin::signed(AccountKeyring::Charlie.public());
+        let claim_value = ClaimValue {
+            data_type: DataTypes::VecU8,
+            value: b"value".to_vec(),
+        };
+
+        for claim_key in &[b"key1".to_vec(), b"key2".to_vec()] {
+            assert_ok!(Identity::add_claim(
+                bob.clone(),
+                alice_did,
+                claim_key.clone(),
+                bob_did,
+                100,
+                claim_value.clone()
+            ));
+        }
+        assert_ok!(Identity::add_claim(
+            charlie,
+            alice_did,
+            b"key1".to_vec(),
+            charlie_did,
+            10,
+            claim_value
+        ));
+
+        Timestamp::set_timestamp(50);
+        assert_eq!(Identity::claim_issuers_of(alice_did), vec![bob_did]);
+    });
+}
+
+#[test]
+fn batch_add_authorization_rejects_unknown_variants() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_did = Signatory::from(register_keyring_account(AccountKeyring::Bob).unwrap());
+        let charlie_key = AccountKey::from(AccountKeyring::Charlie.public().0);
+        let charlie_signer = Signatory::from(charlie_key);
+        let ticker50 = Ticker::from_slice(&[0x50]);
+
+        // A single unknown authorization rejects the whole batch.
+        assert_err!(
+            Identity::batch_add_authorization(
+                alice.clone(),
+                vec![
+                    (bob_did, AuthorizationData::TransferTicker(ticker50), None),
+                    (
+                        charlie_signer,
+                        AuthorizationData::JoinIdentity(alice_did),
+                        None
+                    ),
+                    (bob_did, AuthorizationData::Custom(ticker50), None),
+                ]
+            ),
+            Error::<TestStorage>::UnknownAuthorization
+        );
+        assert_eq!(Identity::last_authorization(bob_did), 0);
+        assert_eq!(Identity::last_authorization(charlie_signer), 0);
+
+        assert_ok!(Identity::batch_add_authorization(
+            alice,
+            vec![
+                (bob_did, AuthorizationData::TransferTicker(ticker50), None),
+                (
+                    charlie_signer,
+                    AuthorizationData::JoinIdentity(alice_did),
+                    None
+                ),
+            ]
+        ));
+        assert_ne!(Identity::last_authorization(bob_did), 0);
+
+        // Charlie's key accepts to join Alice's identity.
+        let join_auth_id = Identity::last_authorization(charlie_signer);
+        assert_ok!(Identity::accept_authorization(
+            Origin::signed(AccountKeyring::Charlie.public()),
+            join_auth_id
+        ));
+        assert_eq!(Identity::get_identity(&charlie_key), Some(alice_did));
+        assert!(Identity::is_signer_authorized(alice_did, &charlie_signer));
+        assert_eq!(Identity::last_authorization(charlie_signer), 0);
+    });
+}
+
+#[test]
+fn set_kyc_expiry_is_decoded_by_kyc_check() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let charlie_did = register_keyring_account(AccountKeyring::Charlie).unwrap();
+        let charlie = Origin::signed(AccountKeyring::Charlie.public());
+        assert_ok!(KycServiceProviders::add_member(
+            Origin::signed(AccountKeyring::Dave.public()),
+            charlie_did
+        ));
+
+        let now = 1_000_000u64;
+        Timestamp::set_timestamp(now);
+        assert_eq!(
+            Identity::is_identity_has_valid_kyc(alice_did, 0),
+            (false, None)
+        );
+
+        assert_ok!(Identity::set_kyc_expiry(
+            charlie.clone(),
+            charlie_did,
+            alice_did,
+            now + 100
+        ));
+        assert_eq!(
+            Identity::is_identity_has_valid_kyc(alice_did, 0),
+            (true, Some(charlie_did))
+        );
+        assert_eq!(
+            Identity::is_identity_has_valid_kyc(alice_did, 100),
+            (false, None)
+        );
+
+        // Only the issuer's signing keys can set the expiry.
+        assert_err!(
+            Identity::set_kyc_expiry(
+                Origin::signed(AccountKeyring::Alice.public()),
+                charlie_did,
+                alice_did,
+                now + 1_000
+            ),
+            "Sender must hold a claim issuer's signing key"
+        );
+
+        assert_ok!(Identity::set_kyc_expiry(
+            charlie,
+            charlie_did,
+            alice_did,
+            now
+        ));
+        assert_eq!(
+            Identity::is_identity_has_valid_kyc(alice_did, 0),
+            (false, None)
+        );
+    });
+}
+
+#[test]
+fn expired_claims_are_valid_within_grace_period() {
+    ExtBuilder::default()
+        .claim_expiry_grace_period(100)
+        .build()
+        .execute_with(|| {
+            let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+            let issuer_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+            let issuer = Origin::signed(AccountKeyring::Bob.public());
+            let claim_key = b"key".to_vec();
+            let claim_value = ClaimValue {
+                data_type: DataTypes::VecU8,
+                value: b"value".to_vec(),
+            };
+            let claim_meta_data = ClaimMetaData {
+                claim_key: claim_key.clone(),
+                claim_issuer: issuer_did,
+            };
+
+            Timestamp::set_timestamp(500);
+            assert_ok!(Identity::add_claim(
+                issuer.clone(),
+                alice_did,
+                claim_key.clone(),
+                issuer_did,
+                1_000,
+                claim_value.clone()
+            ));
+            let ticker = Ticker::from_slice(b"GRACE");
+            assert_ok!(Identity::add_ticker_claim(
+                issuer,
+                alice_did,
+                ticker,
+                claim_key.clone(),
+                issuer_did,
+                1_000,
+                claim_value.clone()
+            ));
+
+            // Just inside the grace window.
+            Timestamp::set_timestamp(1_099);
+            assert_eq!(
+                Identity::fetch_claim_value(alice_did, claim_key.clone(), issuer_did),
+                Some(claim_value.clone())
+            );
+            // Reading the claim does not use up the grace notice, only a call relying on it does.
+            assert_eq!(
+                Identity::claim_grace_notified((alice_did, claim_meta_data.clone())),
+                None
+            );
+            Identity::note_claims_in_grace_period(
+                alice_did,
+                &[(claim_key.clone(), vec![issuer_did])],
+            );
+            assert_eq!(
+                Identity::claim_grace_notified((alice_did, claim_meta_data)),
+                Some(1_000)
+            );
+
+            // Required claims and ticker claims are checked the same way.
+            let requirements = [(claim_key.clone(), vec![issuer_did])];
+            assert!(Identity::has_all_claims(alice_did, &requirements));
+            assert_eq!(
+                Identity::fetch_ticker_claim(
+                    alice_did,
+                    ticker,
+                    claim_key.clone(),
+                    vec![issuer_did]
+                ),
+                Some(claim_value.clone())
+            );
+
+            // Just outside the grace window.
+            Timestamp::set_timestamp(1_100);
+            assert_eq!(
+                Identity::fetch_claim_value(alice_did, claim_key.clone(), issuer_did),
+                None
+            );
+            assert!(!Identity::has_all_claims(alice_did, &requirements));
+            assert_eq!(
+                Identity::fetch_ticker_claim(alice_did, ticker, claim_key, vec![issuer_did]),
+                None
+            );
+        });
+}
+
+#[test]
+fn multi_purpose_nonce_does_not_overflow() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_key = Signatory::from(AccountKey::from(AccountKeyring::Bob.public().0));
+
+        // Registering a DID fails instead of wrapping the nonce around.
+        <identity::MultiPurposeNonce>::put(u64::max_value() - 3);
+        Balances::make_free_balance_be(&AccountKeyring::Charlie.public(), 1_000_000);
+        assert_err!(
+            Identity::register_did(Origin::signed(AccountKeyring::Charlie.public()), vec![]),
+            Error::<TestStorage>::NonceOverflow
+        );
+        assert_eq!(Identity::multi_purpose_nonce(), u64::max_value() - 3);
+
+        // Authorizations and links use the last nonce and fail afterwards.
+        <identity::MultiPurposeNonce>::put(u64::max_value() - 1);
+        assert_ok!(Identity::add_authorization(
+            alice.clone(),
+            bob_key,
+            AuthorizationData::AddMultiSigSigner,
+            None
+        ));
+        assert_eq!(Identity::last_authorization(bob_key), u64::max_value());
+        assert_err!(
+            Identity::add_authorization(
+                alice.clone(),
+                bob_key,
+                AuthorizationData::AddMultiSigSigner,
+                None
+            ),
+            Error::<TestStorage>::NonceOverflow
+        );
+        assert_eq!(Identity::last_authorization(bob_key), u64::max_value());
+        assert_err!(
+            Identity::add_link(Signatory::from(alice_did), LinkData::NoData, None),
+            Error::<TestStorage>::NonceOverflow
+        );
+        assert_eq!(Identity::multi_purpose_nonce(), u64::max_value());
+    });
+}
+
+#[test]
+fn signing_item_permissions_cache_is_consistent() {
+    ExtBuilder::default().build().execute_with(|| {
+        let bob_key = Signatory::from(AccountKey::from(AccountKeyring::Bob.public().0));
+        let charlie_key = Signatory::from(AccountKey::from(AccountKeyring::Charlie.public().0));
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+
+        assert_ok!(Identity::add_signing_items(
+            alice.clone(),
+            alice_did,
+            vec![
+                SigningItem::new(bob_key, vec![Permission::Operator]),
+                SigningItem::new(charlie_key, vec![])
+            ]
+        ));
+        // Pending signing items have no permissions yet.
+        assert_eq!(Identity::signing_item_permissions(alice_did, bob_key), None);
+
+        assert_ok!(Identity::authorize_join_to_identity(
+            Origin::signed(AccountKeyring::Bob.public()),
+            alice_did
+        ));
+        assert_ok!(Identity::authorize_join_to_identity(
+            Origin::signed(AccountKeyring::Charlie.public()),
+            alice_did
+        ));
+        assert_eq!(
+            Identity::cached_signing_item_permissions((alice_did, bob_key)),
+            Some(vec![Permission::Operator])
+        );
+        assert_eq!(
+            Identity::signing_item_permissions(alice_did, charlie_key),
+            Some(vec![])
+        );
+
+        assert_ok!(Identity::set_permission_to_signer(
+            alice.clone(),
+            alice_did,
+            bob_key,
+            vec![Permission::Admin, Permission::SpendFunds]
+        ));
+        assert_eq!(
+            Identity::cached_signing_item_permissions((alice_did, bob_key)),
+            Some(vec![Permission::Admin, Permission::SpendFunds])
+        );
+
+        assert_ok!(Identity::remove_signing_items(
+            alice,
+            alice_did,
+            vec![bob_key]
+        ));
+        assert_eq!(
+            Identity::cached_signing_item_permissions((alice_did, bob_key)),
+            None
+        );
+        assert_eq!(Identity::signing_item_permissions(alice_did, bob_key), None);
+        assert_eq!(
+            Identity::signing_item_permissions(alice_did, charlie_key),
+            Some(vec![])
+        );
+
+        // The cache matches the authoritative record.
+        for si in Identity::did_records(alice_did).signing_items {
+            assert_eq!(
+                Identity::cached_signing_item_permissions((alice_did, si.signer)),
+                Some(si.permissions)
+            );
+        }
+    });
+}
+
+#[test]
+fn batch_extrinsics_enforce_max_batch_size() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+
+        // The test runtime accepts batches of up to 10 items.
+        let claim = ClaimRecord {
+            did: bob_did,
+            claim_key: b"key".to_vec(),
+            expiry: 100u64,
+            claim_value: ClaimValue {
+                data_type: DataTypes::VecU8,
+                value: b"value".to_vec(),
+            },
+        };
+        assert_err!(
+            Identity::add_claims_batch(alice.clone(), alice_did, vec![claim.clone(); 11]),
+            Error::<TestStorage>::BatchTooLarge
+        );
+        assert_ok!(Identity::add_claims_batch(
+            alice.clone(),
+            alice_did,
+            vec![claim; 10]
+        ));
+
+        let auth = (
+            Signatory::from(bob_did),
+            AuthorizationData::TransferTicker(Ticker::from_slice(b"TICKER")),
+            None,
+        );
+        assert_err!(
+            Identity::batch_add_authorization(alice.clone(), vec![auth.clone(); 11]),
+            Error::<TestStorage>::BatchTooLarge
+        );
+        assert_eq!(Identity::last_authorization(Signatory::from(bob_did)), 0);
+        assert_ok!(Identity::batch_add_authorization(
+            alice.clone(),
+            vec![auth; 10]
+        ));
+
+        assert_err!(
+            Identity::batch_remove_authorization(
+                alice.clone(),
+                vec![(Signatory::from(bob_did), 1); 11]
+            ),
+            Error::<TestStorage>::BatchTooLarge
+        );
+        assert_err!(
+            Identity::batch_accept_authorization(
+                Origin::signed(AccountKeyring::Bob.public()),
+                vec![1; 11]
+            ),
+            Error::<TestStorage>::BatchTooLarge
+        );
+        assert_ok!(Identity::batch_accept_authorization(
+            Origin::signed(AccountKeyring::Bob.public()),
+            (1..=10).collect()
+        ));
+    });
+}
+
+#[test]
+fn claims_issued_count_tracks_issued_claims() {
+    ExtBuilder::default().build().execute_with(|| {
+        let issuer_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let issuer = Origin::signed(AccountKeyring::Alice.public());
+        let bob_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let charlie_did = register_keyring_account(AccountKeyring::Charlie).unwrap();
+        let claim_value = ClaimValue {
+            data_type: DataTypes::VecU8,
+            value: b"value".to_vec(),
+        };
+
+        assert_ok!(Identity::add_claim(
+            issuer.clone(),
+            bob_did,
+            b"key".to_vec(),
+            issuer_did,
+            100u64,
+            claim_value.clone()
+        ));
+        assert_eq!(Identity::claims_issued_count(issuer_did), 1);
+
+        // Editing an existing claim does not count as a new one.
+        assert_ok!(Identity::add_claim(
+            issuer.clone(),
+            bob_did,
+            b"key".to_vec(),
+            issuer_did,
+            200u64,
+            claim_value.clone()
+        ));
+        assert_eq!(Identity::claims_issued_count(issuer_did), 1);
+
+        let claim_record = |did, claim_key: &[u8]| ClaimRecord {
+            did,
+            claim_key: claim_key.to_vec(),
+            expiry: 100u64,
+            claim_value: claim_value.clone(),
+        };
+        assert_ok!(Identity::add_claims_batch(
+            issuer.clone(),
+            issuer_did,
+            vec![
+                claim_record(bob_did, b"key"),
+                claim_record(bob_did, b"other_key"),
+                claim_record(charlie_did, b"key"),
+                claim_record(charlie_did, b"key"),
+            ]
+        ));
+        assert_eq!(Identity::claims_issued_count(issuer_did), 3);
+
+        assert_ok!(Identity::revoke_claim(
+            issuer.clone(),
+            bob_did,
+            b"key".to_vec(),
+            issuer_did
+        ));
+        assert_eq!(Identity::claims_issued_count(issuer_did), 2);
+        // Revoking a missing claim leaves the count unchanged.
+        assert_ok!(Identity::revoke_claim(
+            issuer.clone(),
+            bob_did,
+            b"key".to_vec(),
+            issuer_did
+        ));
+        assert_eq!(Identity::claims_issued_count(issuer_did), 2);
+    });
+}
+
+#[test]
+fn dual_unfreeze_requires_both_keys() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let bob_key = AccountKey::from(AccountKeyring::Bob.public().0);
+
+        assert_err!(
+            Identity::set_require_dual_unfreeze(
+                alice.clone(),
+                alice_did,
+                Some(AccountKey::from(AccountKeyring::Alice.public().0))
+            ),
+            Error::<TestStorage>::InvalidRecoveryKey
+        );
+        assert_ok!(Identity::set_require_dual_unfreeze(
+            alice.clone(),
+            alice_did,
+            Some(bob_key)
+        ));
+        // No confirmation is recorded while the identity is not frozen.
+        assert_err!(
+            Identity::unfreeze_signing_keys(alice.clone(), alice_did),
+            Error::<TestStorage>::IdentityNotFrozen
+        );
+        assert_eq!(Identity::pending_unfreeze(alice_did), None);
+        assert_ok!(Identity::freeze_signing_keys(alice.clone(), alice_did));
+
+        // The master key alone cannot drop the dual unfreeze while frozen.
+        assert_err!(
+            Identity::set_require_dual_unfreeze(alice.clone(), alice_did, None),
+            Error::<TestStorage>::IdentityFrozen
+        );
+        assert_eq!(Identity::dual_unfreeze_key(alice_did), Some(bob_key));
+
+        // A single confirmation leaves the identity frozen, even if repeated.
+        Timestamp::set_timestamp(1_000);
+        assert_ok!(Identity::unfreeze_signing_keys(alice.clone(), alice_did));
+        assert_ok!(Identity::unfreeze_signing_keys(alice.clone(), alice_did));
+        assert!(Identity::is_did_frozen(alice_did));
+
+        // The second confirmation has to be given within the window.
+        Timestamp::set_timestamp(1_000 + DUAL_UNFREEZE_WINDOW + 1);
+        assert_ok!(Identity::unfreeze_signing_keys(bob.clone(), alice_did));
+        assert!(Identity::is_did_frozen(alice_did));
+
+        assert_ok!(Identity::unfreeze_signing_keys(alice.clone(), alice_did));
+        assert!(!Identity::is_did_frozen(alice_did));
+        assert_eq!(Identity::pending_unfreeze(alice_did), None);
+
+        // Without dual unfreeze, the master key alone can unfreeze.
+        assert_ok!(Identity::set_require_dual_unfreeze(
+            alice.clone(),
+            alice_did,
+            None
+        ));
+        assert_ok!(Identity::freeze_signing_keys(alice.clone(), alice_did));
+        assert_ok!(Identity::unfreeze_signing_keys(alice.clone(), alice_did));
+        assert!(!Identity::is_did_frozen(alice_did));
+    });
+}
+
+#[test]
+fn query_kyc_status_of_any_did() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let charlie_did = register_keyring_account(AccountKeyring::Charlie).unwrap();
+        let charlie = Origin::signed(AccountKeyring::Charlie.public());
+        assert_ok!(KycServiceProviders::add_member(
+            Origin::signed(AccountKeyring::Dave.public()),
+            charlie_did
+        ));
+
+        let now = 1_000_000u64;
+        Timestamp::set_timestamp(now);
+        assert_ok!(Identity::set_kyc_expiry(
+            charlie.clone(),
+            charlie_did,
+            alice_did,
+            now + 100
+        ));
+
+        // Callers without an identity can query the KYC status of others.
+        assert_ok!(Identity::query_kyc_status(bob.clone(), alice_did, 0));
+        assert_ok!(Identity::query_kyc_status(bob.clone(), charlie_did, 0));
+        assert_err!(
+            Identity::query_kyc_status(bob.clone(), IdentityId::from(42), 0),
+            "DID must already exist"
+        );
+    });
+}
+
+#[test]
+fn register_did_twice_fails_with_typed_error() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let alice_key = AccountKey::from(AccountKeyring::Alice.public().0);
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+
+        assert_err!(
+            Identity::register_did(alice.clone(), vec![]),
+            Error::<TestStorage>::MasterKeyAlreadyLinked
+        );
+        // The existing identity is left untouched.
+        assert_eq!(Identity::get_identity(&alice_key), Some(alice_did));
+    });
+}
+
+#[test]
+fn operator_key_can_freeze_but_not_remove_signing_keys() {
+    ExtBuilder::default().build().execute_with(|| {
+        let bob_key = AccountKey::from(AccountKeyring::Bob.public().0);
+        let charlie_key = AccountKey::from(AccountKeyring::Charlie.public().0);
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let charlie = Origin::signed(AccountKeyring::Charlie.public());
+
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        assert_ok!(Identity::add_signing_items(
+            alice.clone(),
+            alice_did,
+            vec![
+                SigningItem::new(Signatory::AccountKey(bob_key), vec![Permission::Operator]),
+                SigningItem::from(charlie_key),
+            ]
+        ));
+        assert_ok!(Identity::authorize_join_to_identity(bob.clone(), alice_did));
+        assert_ok!(Identity::authorize_join_to_identity(
+            charlie.clone(),
+            alice_did
+        ));
+
+        // Only the master key can remove signing keys.
+        assert_err!(
+            Identity::remove_signing_items(
+                bob.clone(),
+                alice_did,
+                vec![Signatory::AccountKey(charlie_key)]
+            ),
+            "Only master key of an identity is able to execute this operation"
+        );
+
+        // A signing key without `Operator` permission cannot freeze.
+        assert_err!(
+            Identity::freeze_signing_keys(charlie.clone(), alice_did),
+            "Only master key or an operator key of an identity is able to execute this operation"
+        );
+        assert_ok!(Identity::freeze_signing_keys(bob.clone(), alice_did));
+        assert!(Identity::is_did_frozen(alice_did));
+
+        // The operator key is frozen as well, so only the master key can unfreeze.
+        assert_err!(
+            Identity::unfreeze_signing_keys(bob.clone(), alice_did),
+            "Only master key of an identity is able to execute this operation"
+        );
+        assert_ok!(Identity::unfreeze_signing_keys(alice.clone(), alice_did));
+        assert!(!Identity::is_did_frozen(alice_did));
+    });
+}
+
+#[test]
+fn authorizations_to_unknown_dids_are_rejected() {
+    ExtBuilder::default().build().execute_with(|| {
+        let _alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_did = Signatory::from(register_keyring_account(AccountKeyring::Bob).unwrap());
+        let unknown_did = Signatory::from(IdentityId::from(42));
+        let ticker50 = Ticker::from_slice(&[0x50]);
+
+        assert_ok!(Identity::add_authorization(
+            alice.clone(),
+            bob_did,
+            AuthorizationData::TransferTicker(ticker50),
+            None,
+        ));
+        assert_ne!(Identity::last_authorization(bob_did), 0);
+
+        assert_err!(
+            Identity::add_authorization(
+                alice.clone(),
+                unknown_did,
+                AuthorizationData::TransferTicker(ticker50),
+                None,
+            ),
+            Error::<TestStorage>::NoDIDFound
+        );
+        assert_err!(
+            Identity::add_authorization_as_key(
+                alice.clone(),
+                unknown_did,
+                AuthorizationData::TransferTicker(ticker50),
+                None,
+            ),
+            Error::<TestStorage>::NoDIDFound
+        );
+        assert_eq!(Identity::last_authorization(unknown_did), 0);
+
+        // A single unknown target rejects the whole batch.
+        assert_err!(
+            Identity::batch_add_authorization(
+                alice,
+                vec![
+                    (bob_did, AuthorizationData::TransferTicker(ticker50), None),
+                    (
+                        unknown_did,
+                        AuthorizationData::TransferTicker(ticker50),
+                        None
+                    ),
+                ]
+            ),
+            Error::<TestStorage>::NoDIDFound
+        );
+        assert_eq!(Identity::last_authorization(unknown_did), 0);
+    });
+}
+
+#[test]
+fn runtime_authorization_handlers_accept_new_kinds() {
+    ExtBuilder::default().build().execute_with(|| {
+        let _alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_did = Signatory::from(register_keyring_account(AccountKeyring::Bob).unwrap());
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let mock_auth = AuthorizationData::Custom(Ticker::from_slice(b"MOCKAUTH"));
+        let unknown_auth = AuthorizationData::Custom(Ticker::from_slice(b"UNKNOWN"));
+
+        assert!(Identity::is_acceptable_authorization(&mock_auth));
+        assert!(!Identity::is_acceptable_authorization(&unknown_auth));
+
+        // The mock handler of the test runtime accepts its authorization.
+        assert_ok!(Identity::add_authorization(
+            alice.clone(),
+            bob_did,
+            mock_auth.clone(),
+            None
+        ));
+        let mock_auth_id = Identity::last_authorization(bob_did);
+        assert_ok!(Identity::accept_authorization(bob.clone(), mock_auth_id));
+        assert!(!<identity::Authorizations<TestStorage>>::exists((
+            bob_did,
+            mock_auth_id
+        )));
+
+        // Authorizations without a handler are still rejected.
+        assert_ok!(Identity::add_authorization(
+            alice.clone(),
+            bob_did,
+            unknown_auth,
+            None
+        ));
+        let unknown_auth_id = Identity::last_authorization(bob_did);
+        assert_err!(
+            Identity::accept_authorization(bob.clone(), unknown_auth_id),
+            Error::<TestStorage>::UnknownAuthorization
+        );
+
+        // Built-in handlers keep working alongside the runtime ones.
+        assert_ok!(Identity::batch_add_authorization(
+            alice,
+            vec![
+                (bob_did, mock_auth, None),
+                (
+                    bob_did,
+                    AuthorizationData::TransferTicker(Ticker::from_slice(b"TICKER")),
+                    None
+                ),
+            ]
+        ));
+        let ticker_auth_id = Identity::last_authorization(bob_did);
+        assert_ok!(Identity::accept_authorization(bob, ticker_auth_id));
+    });
+}
+
+#[test]
+fn accepting_each_authorization_type() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_did = Signatory::from(register_keyring_account(AccountKeyring::Bob).unwrap());
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let charlie_key = Signatory::from(AccountKey::from(AccountKeyring::Charlie.public().0));
+        let charlie = Origin::signed(AccountKeyring::Charlie.public());
+        let ticker = Ticker::from_slice(b"TICKER");
+
+        let accepted = vec![
+            (
+                bob_did,
+                bob.clone(),
+                AuthorizationData::TransferTicker(ticker),
+                AuthorizationType::TransferTicker,
+            ),
+            (
+                bob_did,
+                bob.clone(),
+                AuthorizationData::TransferTokenOwnership(ticker),
+                AuthorizationType::TransferTokenOwnership,
+            ),
+            (
+                bob_did,
+                bob,
+                AuthorizationData::Custom(Ticker::from_slice(b"MOCKAUTH")),
+                AuthorizationType::Custom,
+            ),
+            (
+                charlie_key,
+                charlie,
+                AuthorizationData::JoinIdentity(alice_did),
+                AuthorizationType::JoinIdentity,
+            ),
+        ];
+        for (target, target_origin, authorization_data, authorization_type) in accepted {
+            // `AuthorizationAccepted` carries the type of the accepted authorization.
+            assert_eq!(
+                AuthorizationType::from(&authorization_data),
+                authorization_type
+            );
+            assert_ok!(Identity::add_authorization(
+                alice.clone(),
+                target,
+                authorization_data,
+                None
+            ));
+            let auth_id = Identity::last_authorization(target);
+            assert_ok!(Identity::accept_authorization(target_origin, auth_id));
+        }
+        assert_eq!(
+            AuthorizationType::from(&AuthorizationData::AddMultiSigSigner),
+            AuthorizationType::AddMultiSigSigner
+        );
+        assert!(Identity::is_signer_authorized(alice_did, &charlie_key));
+    });
+}
+
+#[test]
+fn pending_joins_lists_pre_authorized_identities() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let charlie_key = AccountKey::from(AccountKeyring::Charlie.public().0);
+        let charlie_signer = Signatory::from(charlie_key);
+
+        assert_eq!(Identity::pending_joins(charlie_signer), vec![]);
+
+        assert_ok!(Identity::add_signing_items(
+            alice,
+            alice_did,
+            vec![SigningItem::from(charlie_key)]
+        ));
+        assert_ok!(Identity::add_signing_items(
+            bob,
+            bob_did,
+            vec![SigningItem::from(charlie_key)]
+        ));
+        assert_eq!(
+            Identity::pending_joins(charlie_signer),
+            vec![alice_did, bob_did]
+        );
+
+        // Joining an identity removes it from the pending list.
+        assert_ok!(Identity::authorize_join_to_identity(
+            Origin::signed(AccountKeyring::Charlie.public()),
+            bob_did
+        ));
+        assert_eq!(Identity::pending_joins(charlie_signer), vec![alice_did]);
+    });
+}
+
+#[test]
+fn expired_pre_authorizations_are_cleaned_up() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let charlie = Origin::signed(AccountKeyring::Charlie.public());
+        let charlie_key = AccountKey::from(AccountKeyring::Charlie.public().0);
+        let charlie_signer = Signatory::from(charlie_key);
+
+        Timestamp::set_timestamp(10);
+        assert_ok!(Identity::add_signing_items_with_expiry(
+            alice,
+            alice_did,
+            vec![SigningItem::from(charlie_key)],
+            Some(100)
+        ));
+        assert_ok!(Identity::add_signing_items_with_expiry(
+            bob,
+            bob_did,
+            vec![SigningItem::from(charlie_key)],
+            None
+        ));
+
+        // Nothing is removed before the expiry.
+        assert_ok!(Identity::cleanup_expired_pre_auths(
+            charlie.clone(),
+            charlie_signer,
+            10
+        ));
+        assert_eq!(
+            Identity::pending_joins(charlie_signer),
+            vec![alice_did, bob_did]
+        );
+
+        // An expired pre-authorization cannot be accepted, and it is removed by the cleanup.
+        Timestamp::set_timestamp(100);
+        assert_err!(
+            Identity::authorize_join_to_identity(charlie.clone(), alice_did),
+            Error::<TestStorage>::Unauthorized
+        );
+        assert_ok!(Identity::cleanup_expired_pre_auths(
+            charlie.clone(),
+            charlie_signer,
+            10
+        ));
+        assert_eq!(Identity::pending_joins(charlie_signer), vec![bob_did]);
+        assert!(Identity::pre_authorized_join_did_expiry((charlie_signer, alice_did)).is_none());
+
+        // The pre-authorization without expiry is still valid.
+        assert_ok!(Identity::authorize_join_to_identity(charlie, bob_did));
+        assert_eq!(Identity::pending_joins(charlie_signer), vec![]);
+    });
+}
+
+#[test]
+fn freeze_single_signing_key() {
+    ExtBuilder::default().build().execute_with(|| {
+        let bob_key = Signatory::from(AccountKey::from(AccountKeyring::Bob.public().0));
+        let charlie_key = Signatory::from(AccountKey::from(AccountKeyring::Charlie.public().0));
+        let dave_key = Signatory::from(AccountKey::from(AccountKeyring::Dave.public().0));
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let charlie = Origin::signed(AccountKeyring::Charlie.public());
+
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        assert_ok!(Identity::add_signing_items(
+            alice.clone(),
+            alice_did,
+            vec![
+                SigningItem::new(bob_key, vec![Permission::Operator]),
+                SigningItem::new(charlie_key, vec![Permission::Operator]),
+            ]
+        ));
+        assert_ok!(Identity::authorize_join_to_identity(bob.clone(), alice_did));
+        assert_ok!(Identity::authorize_join_to_identity(charlie, alice_did));
+
+        // Only the master key can freeze a single key, and only one of its signing keys.
+        assert_err!(
+            Identity::freeze_signing_key(bob.clone(), alice_did, charlie_key),
+            "Only master key of an identity is able to execute this operation"
+        );
+        assert_err!(
+            Identity::freeze_signing_key(alice.clone(), alice_did, dave_key),
+            Error::<TestStorage>::InvalidSender
+        );
+
+        assert_ok!(Identity::freeze_signing_key(
+            alice.clone(),
+            alice_did,
+            charlie_key
+        ));
+        assert!(!Identity::is_signer_authorized(alice_did, &charlie_key));
+        assert!(!Identity::is_did_frozen(alice_did));
+
+        // Bob is still usable.
+        assert!(Identity::is_signer_authorized(alice_did, &bob_key));
+        assert_ok!(Identity::freeze_signing_keys(bob, alice_did));
+        assert_ok!(Identity::unfreeze_signing_keys(alice.clone(), alice_did));
+
+        // Unfreezing the whole identity does not unfreeze the individually frozen key.
+        assert!(!Identity::is_signer_authorized(alice_did, &charlie_key));
+        assert_ok!(Identity::unfreeze_signing_key(
+            alice.clone(),
+            alice_did,
+            charlie_key
+        ));
+        assert!(Identity::is_signer_authorized(alice_did, &charlie_key));
+    });
+}
+
+#[test]
+fn accept_authorizations_by_type_only_accepts_matching_type() {
+    ExtBuilder::default().build().execute_with(|| {
+        let _alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_did = Signatory::from(register_keyring_account(AccountKeyring::Bob).unwrap());
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+
+        let mut ticker_auth_ids = Vec::new();
+        let mut other_auth_ids = Vec::new();
+        for i in 0..3u8 {
+            assert_ok!(Identity::add_authorization(
+                alice.clone(),
+                bob_did,
+                AuthorizationData::TransferTicker(Ticker::from_slice(&[0x50 + i])),
+                None
+            ));
+            ticker_auth_ids.push(Identity::last_authorization(bob_did));
+            assert_ok!(Identity::add_authorization(
+                alice.clone(),
+                bob_did,
+                AuthorizationData::TransferTokenOwnership(Ticker::from_slice(&[0x50 + i])),
+                None
+            ));
+            other_auth_ids.push(Identity::last_authorization(bob_did));
+        }
+
+        assert_err!(
+            Identity::accept_authorizations_by_type(
+                bob.clone(),
+                AuthorizationType::TransferTicker,
+                11
+            ),
+            Error::<TestStorage>::BatchTooLarge
+        );
+
+        // The limit applies to all inspected authorizations, most recent first.
+        assert_ok!(Identity::accept_authorizations_by_type(
+            bob.clone(),
+            AuthorizationType::TransferTicker,
+            4
+        ));
+        let exists =
+            |auth_id: &u64| <identity::Authorizations<TestStorage>>::exists((bob_did, *auth_id));
+        assert!(exists(&ticker_auth_ids[0]));
+        assert!(!exists(&ticker_auth_ids[1]));
+        assert!(!exists(&ticker_auth_ids[2]));
+        assert!(other_auth_ids.iter().all(exists));
+
+        assert_ok!(Identity::accept_authorizations_by_type(
+            bob,
+            AuthorizationType::TransferTicker,
+            10
+        ));
+        assert!(ticker_auth_ids.iter().all(|auth_id| !exists(auth_id)));
+        assert!(other_auth_ids.iter().all(exists));
+    });
+}
+
+#[test]
+fn signer_to_did_resolves_identities_and_keys() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice_key = AccountKey::from(AccountKeyring::Alice.public().0);
+        let unlinked_key = AccountKey::from(AccountKeyring::Dave.public().0);
+
+        assert_eq!(
+            Identity::signer_to_did(&Signatory::from(alice_did)),
+            Some(alice_did)
+        );
+        assert_eq!(
+            Identity::signer_to_did(&Signatory::from(IdentityId::from(999))),
+            None
+        );
+        assert_eq!(
+            Identity::signer_to_did(&Signatory::from(alice_key)),
+            Some(alice_did)
+        );
+        assert_eq!(
+            Identity::signer_to_did(&Signatory::from(unlinked_key)),
+            None
+        );
+    });
+}
+
+#[test]
+fn master_key_of_existing_and_missing_dids() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice_key = AccountKey::from(AccountKeyring::Alice.public().0);
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let missing_did = IdentityId::from(999);
+
+        assert_eq!(Identity::master_key(alice_did), Some(alice_key));
+        assert_ok!(Identity::emit_master_key(bob.clone(), alice_did));
+
+        assert_eq!(Identity::master_key(missing_did), None);
+        assert_err!(
+            Identity::emit_master_key(bob, missing_did),
+            Error::<TestStorage>::NoDIDFound
+        );
+    });
+}
+
+#[test]
+fn is_any_master_key_distinguishes_master_keys() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let alice_key = AccountKey::from(AccountKeyring::Alice.public().0);
+        let bob_key = AccountKey::from(AccountKeyring::Bob.public().0);
+        let charlie_key = AccountKey::from(AccountKeyring::Charlie.public().0);
+
+        assert_ok!(Identity::add_signing_items(
+            alice,
+            alice_did,
+            vec![SigningItem::from(bob_key)]
+        ));
+        assert_ok!(Identity::authorize_join_to_identity(
+            Origin::signed(AccountKeyring::Bob.public()),
+            alice_did
+        ));
+
+        assert!(Identity::is_any_master_key(&alice_key));
+        assert!(!Identity::is_any_master_key(&bob_key));
+        assert!(!Identity::is_any_master_key(&charlie_key));
+        assert_eq!(Identity::master_key_to_did(alice_key), Some(alice_did));
+
+        // Identities created before the index existed are added by root.
+        <identity::MasterKeyToDid>::remove(alice_key);
+        assert!(!Identity::is_any_master_key(&alice_key));
+        assert!(Identity::backfill_master_key_to_did(
+            Origin::signed(AccountKeyring::Bob.public()),
+            vec![alice_did]
+        )
+        .is_err());
+        assert_ok!(Identity::backfill_master_key_to_did(
+            frame_system::RawOrigin::Root.into(),
+            vec![alice_did, IdentityId::from(999)]
+        ));
+        assert_eq!(Identity::master_key_to_did(alice_key), Some(alice_did));
+        assert!(!Identity::is_any_master_key(&AccountKey::default()));
+    });
+}
+
+#[test]
+fn register_did_returns_the_new_did() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice = AccountKeyring::Alice.public();
+        let alice_key = AccountKey::from(alice.0);
+
+        let did = Identity::_register_did(alice.clone(), vec![]).unwrap();
+        assert_eq!(Identity::get_identity(&alice_key), Some(did));
+        assert_eq!(Identity::master_key(did), Some(alice_key));
+
+        // A second registration of the same master key fails without a DID.
+        assert_err!(
+            Identity::_register_did(alice, vec![]),
+            Error::<TestStorage>::MasterKeyAlreadyLinked
+        );
+    });
+}
+
+#[test]
+fn are_signers_authorized_checks_several_pairs() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let alice_key = Signatory::from(AccountKey::from(AccountKeyring::Alice.public().0));
+        let bob_key = Signatory::from(AccountKey::from(AccountKeyring::Bob.public().0));
+        let charlie_key = Signatory::from(AccountKey::from(AccountKeyring::Charlie.public().0));
+
+        assert_ok!(Identity::add_signing_items(
+            alice,
+            alice_did,
+            vec![SigningItem::from(AccountKey::from(
+                AccountKeyring::Charlie.public().0
+            ))]
+        ));
+        assert_ok!(Identity::authorize_join_to_identity(
+            Origin::signed(AccountKeyring::Charlie.public()),
+            alice_did
+        ));
+
+        let checks = vec![
+            (alice_did, alice_key),
+            (alice_did, charlie_key),
+            (alice_did, bob_key),
+            (bob_did, bob_key),
+            (bob_did, charlie_key),
+            (alice_did, charlie_key),
+        ];
+        let expected = checks
+            .iter()
+            .map(|(did, signer)| Identity::is_signer_authorized(*did, signer))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, vec![true, true, false, true, false, true]);
+        assert_eq!(Identity::are_signers_authorized(checks), expected);
+        assert!(Identity::are_signers_authorized(vec![]).is_empty());
+    });
+}
+
+#[test]
+fn set_module_owner_by_owner_or_root() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice = AccountKeyring::Alice.public();
+        let bob = AccountKeyring::Bob.public();
+        let charlie = AccountKeyring::Charlie.public();
+        assert_eq!(Identity::owner(), alice);
+
+        assert_err!(
+            Identity::set_module_owner(Origin::signed(charlie), charlie),
+            Error::<TestStorage>::Unauthorized
+        );
+        assert_ok!(Identity::set_module_owner(Origin::signed(alice), bob));
+        assert_eq!(Identity::owner(), bob);
+
+        // The previous owner has no control anymore.
+        assert_err!(
+            Identity::set_module_owner(Origin::signed(alice), alice),
+            Error::<TestStorage>::Unauthorized
+        );
+
+        assert_ok!(Identity::set_module_owner(
+            frame_system::RawOrigin::Root.into(),
+            charlie
+        ));
+        assert_eq!(Identity::owner(), charlie);
+    });
+}
+
+#[test]
+fn actionable_authorizations_skip_expired_and_unknown() {
+    ExtBuilder::default().build().execute_with(|| {
+        let _alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_did = Signatory::from(register_keyring_account(AccountKeyring::Bob).unwrap());
+        Timestamp::set_timestamp(100);
+
+        let valid_data = AuthorizationData::TransferTicker(Ticker::from_slice(b"VALID"));
+        let custom_data = AuthorizationData::Custom(Ticker::from_slice(b"MOCKAUTH"));
+        let add = |data: AuthorizationData, expiry: Option<u64>| {
+            assert_ok!(Identity::add_authorization(
+                alice.clone(),
+                bob_did,
+                data,
+                expiry
+            ));
+            Identity::last_authorization(bob_did)
+        };
+
+        let valid_id = add(valid_data.clone(), Some(1_000));
+        let _expired_id = add(
+            AuthorizationData::TransferTicker(Ticker::from_slice(b"EXPIRED")),
+            Some(200),
+        );
+        let _unknown_id = add(
+            AuthorizationData::Custom(Ticker::from_slice(b"UNKNOWN")),
+            None,
+        );
+        let custom_id = add(custom_data.clone(), None);
+
+        Timestamp::set_timestamp(500);
+        assert_eq!(
+            Identity::actionable_authorizations(bob_did),
+            vec![(custom_id, custom_data), (valid_id, valid_data)]
+        );
+    });
+}
+
+#[test]
+fn claim_fee_is_charged_to_the_issuer() {
+    ExtBuilder::default()
+        .claim_fee(100)
+        .build()
+        .execute_with(|| {
+            let subject_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+            let issuer_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+            let issuer = AccountKeyring::Bob.public();
+            // After paying the DID creation fee, Charlie cannot afford a claim.
+            let poor_issuer_did =
+                register_keyring_account_with_balance(AccountKeyring::Charlie, 300).unwrap();
+            let poor_issuer = AccountKeyring::Charlie.public();
+            let claim_value = ClaimValue {
+                data_type: DataTypes::VecU8,
+                value: b"value".to_vec(),
+            };
+            let claim_meta_data = |claim_key: &[u8], claim_issuer| ClaimMetaData {
+                claim_key: claim_key.to_vec(),
+                claim_issuer,
+            };
+
+            let balance = Balances::free_balance(&issuer);
+            assert_ok!(Identity::add_claim(
+                Origin::signed(issuer.clone()),
+                subject_did,
+                b"key".to_vec(),
+                issuer_did,
+                100u64,
+                claim_value.clone()
+            ));
+            assert_eq!(Balances::free_balance(&issuer), balance - 100);
+
+            // Batches are charged per claim.
+            let claim_record = |claim_key: &[u8]| ClaimRecord {
+                did: subject_did,
+                claim_key: claim_key.to_vec(),
+                expiry: 100u64,
+                claim_value: claim_value.clone(),
+            };
+            assert_ok!(Identity::add_claims_batch(
+                Origin::signed(issuer.clone()),
+                issuer_did,
+                vec![claim_record(b"key1"), claim_record(b"key2")]
+            ));
+            assert_eq!(Balances::free_balance(&issuer), balance - 300);
+
+            // Ticker scoped, numeric and KYC expiry claims are charged as well.
+            assert_ok!(Identity::add_ticker_claim(
+                Origin::signed(issuer.clone()),
+                subject_did,
+                Ticker::from_slice(b"FEE"),
+                b"key".to_vec(),
+                issuer_did,
+                100u64,
+                claim_value.clone()
+            ));
+            assert_ok!(Identity::add_numeric_claim(
+                Origin::signed(issuer.clone()),
+                subject_did,
+                b"key".to_vec(),
+                issuer_did,
+                100u64,
+                1
+            ));
+            assert_ok!(Identity::set_kyc_expiry(
+                Origin::signed(issuer.clone()),
+                issuer_did,
+                subject_did,
+                100u64
+            ));
+            assert_eq!(Balances::free_balance(&issuer), balance - 600);
+            assert!(Identity::add_ticker_claim(
+                Origin::signed(poor_issuer.clone()),
+                subject_did,
+                Ticker::from_slice(b"FEE"),
+                b"key".to_vec(),
+                poor_issuer_did,
+                100u64,
+                claim_value.clone()
+            )
+            .is_err());
+
+            assert!(Identity::add_claim(
+                Origin::signed(poor_issuer.clone()),
+                subject_did,
+                b"key".to_vec(),
+                poor_issuer_did,
+                100u64,
+                claim_value.clone()
+            )
+            .is_err());
+            assert!(Identity::add_claims_batch(
+                Origin::signed(poor_issuer),
+                poor_issuer_did,
+                vec![claim_record(b"key1")]
+            )
+            .is_err());
+            assert!(!<identity::Claims<TestStorage>>::exists((
+                subject_did,
+                claim_meta_data(b"key", poor_issuer_did)
+            )));
+            assert!(!<identity::Claims<TestStorage>>::exists((
+                subject_did,
+                claim_meta_data(b"key1", poor_issuer_did)
+            )));
+        });
+}
+
+#[test]
+fn reassign_authorizations_to_new_issuer() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let charlie = Signatory::from(register_keyring_account(AccountKeyring::Charlie).unwrap());
+        let charlie_signed = Origin::signed(AccountKeyring::Charlie.public());
+
+        let mut auth_ids = Vec::new();
+        for i in 0..3u8 {
+            assert_ok!(Identity::add_authorization(
+                alice.clone(),
+                charlie,
+                AuthorizationData::TransferTicker(Ticker::from_slice(&[0x50 + i])),
+                None
+            ));
+            auth_ids.push(Identity::last_authorization(charlie));
+        }
+        assert_eq!(
+            Identity::authorizations_by_issuer(Signatory::from(alice_did)),
+            auth_ids.iter().map(|&id| (charlie, id)).collect::<Vec<_>>()
+        );
+
+        // The new issuer has to consent with an authorization given to the old one.
+        let consent = |origin: Origin| {
+            assert_ok!(Identity::add_authorization(
+                origin,
+                Signatory::from(alice_did),
+                AuthorizationData::ReassignAuthorizations,
+                None
+            ));
+            Identity::last_authorization(Signatory::from(alice_did))
+        };
+        let charlie_consent = consent(charlie_signed.clone());
+        assert!(Identity::reassign_authorizations(
+            alice.clone(),
+            alice_did,
+            bob_did,
+            charlie_consent,
+            2
+        )
+        .is_err());
+        let bob_consent = consent(bob.clone());
+
+        // Only the master key of the issuer can reassign.
+        assert!(
+            Identity::reassign_authorizations(bob, alice_did, bob_did, bob_consent, 2).is_err()
+        );
+        assert_ok!(Identity::reassign_authorizations(
+            alice.clone(),
+            alice_did,
+            bob_did,
+            bob_consent,
+            2
+        ));
+        // The consent is consumed.
+        assert!(!<identity::Authorizations<TestStorage>>::exists((
+            Signatory::from(alice_did),
+            bob_consent
+        )));
+        assert!(Identity::reassign_authorizations(
+            alice.clone(),
+            alice_did,
+            bob_did,
+            bob_consent,
+            2
+        )
+        .is_err());
+        assert_eq!(
+            Identity::authorizations_by_issuer(Signatory::from(alice_did)),
+            vec![(charlie, auth_ids[0])]
+        );
+        assert_eq!(
+            Identity::authorizations_by_issuer(Signatory::from(bob_did)),
+            vec![(charlie, auth_ids[1]), (charlie, auth_ids[2])]
+        );
+        assert_eq!(
+            Identity::authorizations((charlie, auth_ids[2])).authorized_by,
+            Signatory::from(bob_did)
+        );
+
+        // Consuming the reassigned authorizations now validates against the new issuer.
+        assert!(Identity::consume_auth(Signatory::from(alice_did), charlie, auth_ids[2]).is_err());
+        assert_ok!(Identity::consume_auth(
+            Signatory::from(bob_did),
+            charlie,
+            auth_ids[2]
+        ));
+        assert_ok!(Identity::consume_auth(
+            Signatory::from(alice_did),
+            charlie,
+            auth_ids[0]
+        ));
+        assert_eq!(
+            Identity::authorizations_by_issuer(Signatory::from(bob_did)),
+            vec![(charlie, auth_ids[1])]
+        );
+        assert!(Identity::authorizations_by_issuer(Signatory::from(alice_did)).is_empty());
+
+        // Authorizations added before the index existed are indexed by root.
+        let unindexed = (charlie, auth_ids[1]);
+        <identity::AuthorizationsByIssuer>::remove(unindexed);
+        <identity::LastIssuedAuthorization>::remove(Signatory::from(bob_did));
+        assert!(Identity::authorizations_by_issuer(Signatory::from(bob_did)).is_empty());
+        assert!(Identity::backfill_authorizations_by_issuer(
+            Origin::signed(AccountKeyring::Bob.public()),
+            vec![unindexed]
+        )
+        .is_err());
+        assert_ok!(Identity::backfill_authorizations_by_issuer(
+            frame_system::RawOrigin::Root.into(),
+            vec![unindexed, unindexed, (charlie, auth_ids[2])]
+        ));
+        assert_eq!(
+            Identity::authorizations_by_issuer(Signatory::from(bob_did)),
+            vec![unindexed]
+        );
+    });
+}
+
+#[test]
+fn repair_dangling_key_links() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let bob_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let alice_key = AccountKey::from(AccountKeyring::Alice.public().0);
+        let dave_key = AccountKey::from(AccountKeyring::Dave.public().0);
+        let eve_key = AccountKey::from(AccountKeyring::Eve.public().0);
+
+        // Links created by the module are consistent.
+        assert_eq!(
+            Identity::verify_key_links(&alice_key),
+            KeyLinkConsistency {
+                linked_dids: vec![alice_did],
+                dangling_dids: vec![],
+            }
+        );
+        assert!(Identity::verify_key_links(&dave_key).is_consistent());
+
+        // Alice's identity lists Dave's key, but Bob's does not.
+        let dave_signing_item =
+            SigningItem::new(Signatory::AccountKey(dave_key), vec![Permission::Operator]);
+        let mut did_record = Identity::did_records(alice_did);
+        did_record.signing_items.push(dave_signing_item);
+        <identity::DidRecords>::insert(alice_did, did_record);
+        let mut group = vec![alice_did, bob_did];
+        group.sort();
+        <identity::KeyToIdentityIds>::insert(dave_key, LinkedKeyInfo::Group(group));
+        assert_eq!(
+            Identity::verify_key_links(&dave_key),
+            KeyLinkConsistency {
+                linked_dids: vec![alice_did],
+                dangling_dids: vec![bob_did],
+            }
+        );
+
+        // Eve's key is linked to an identity that does not list it.
+        <identity::KeyToIdentityIds>::insert(eve_key, LinkedKeyInfo::Unique(alice_did));
+        assert!(!Identity::verify_key_links(&eve_key).is_consistent());
+
+        // Only root repairs the links.
+        assert!(Identity::repair_key_link(
+            Origin::signed(AccountKeyring::Alice.public()),
+            dave_key
+        )
+        .is_err());
+        assert_ok!(Identity::repair_key_link(
+            frame_system::RawOrigin::Root.into(),
+            dave_key
+        ));
+        assert_eq!(
+            Identity::key_to_identity_ids(dave_key),
+            Some(LinkedKeyInfo::Group(vec![alice_did]))
+        );
+        assert!(Identity::verify_key_links(&dave_key).is_consistent());
+
+        assert_ok!(Identity::repair_key_link(
+            frame_system::RawOrigin::Root.into(),
+            eve_key
+        ));
+        assert_eq!(Identity::key_to_identity_ids(eve_key), None);
+        assert_eq!(Identity::get_identity(&alice_key), Some(alice_did));
+    });
+}
+
+#[test]
+fn signing_item_restricted_to_calls() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let alice_key = Signatory::AccountKey(AccountKey::from(AccountKeyring::Alice.public().0));
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let bob_key = Signatory::AccountKey(AccountKey::from(AccountKeyring::Bob.public().0));
+
+        assert_ok!(Identity::add_signing_items(
+            alice.clone(),
+            alice_did,
+            vec![SigningItem::new(bob_key, vec![Permission::Operator])]
+        ));
+        assert_ok!(Identity::authorize_join_to_identity(bob.clone(), alice_did));
+        // Calls are identified by a prefix of their encoding, e.g. module and call indexes.
+        let transfer = vec![4u8, 0];
+        let transfer_call = vec![4u8, 0, 7, 7];
+        let other_call = vec![4u8, 1, 7, 7];
+        assert!(Identity::is_signer_authorized_for_call(
+            alice_did,
+            &bob_key,
+            &other_call
+        ));
+
+        // Only the master key restricts signing keys.
+        assert_err!(
+            Identity::set_signing_item_allowed_calls(
+                bob.clone(),
+                alice_did,
+                bob_key,
+                Some(vec![transfer.clone()])
+            ),
+            "Only master key of an identity is able to execute this operation"
+        );
+        // The list of calls is bounded and identifiers cannot be empty.
+        assert_err!(
+            Identity::set_signing_item_allowed_calls(
+                alice.clone(),
+                alice_did,
+                bob_key,
+                Some((0..=MAX_ALLOWED_CALLS as u8).map(|i| vec![i]).collect())
+            ),
+            Error::<TestStorage>::TooManyAllowedCalls
+        );
+        assert_err!(
+            Identity::set_signing_item_allowed_calls(
+                alice.clone(),
+                alice_did,
+                bob_key,
+                Some(vec![vec![]])
+            ),
+            Error::<TestStorage>::InvalidCallId
+        );
+        assert_err!(
+            Identity::set_signing_item_allowed_calls(
+                alice.clone(),
+                alice_did,
+                bob_key,
+                Some(vec![vec![0u8; MAX_CALL_ID_LEN + 1]])
+            ),
+            Error::<TestStorage>::InvalidCallId
+        );
+
+        assert_ok!(Identity::set_signing_item_allowed_calls(
+            alice.clone(),
+            alice_did,
+            bob_key,
+            Some(vec![transfer.clone(), transfer.clone()])
+        ));
+        assert_eq!(
+            Identity::signing_item_allowed_calls((alice_did, bob_key)),
+            Some(vec![transfer.clone()])
+        );
+        assert!(Identity::is_signer_authorized_for_call(
+            alice_did,
+            &bob_key,
+            &transfer_call
+        ));
+        assert!(!Identity::is_signer_authorized_for_call(
+            alice_did,
+            &bob_key,
+            &other_call
+        ));
+        assert!(Identity::is_signer_authorized_for_call(
+            alice_did,
+            &alice_key,
+            &other_call
+        ));
+
+        // Lifting the restriction makes the key usable for any call.
+        assert_ok!(Identity::set_signing_item_allowed_calls(
+            alice.clone(),
+            alice_did,
+            bob_key,
+            None
+        ));
+        assert_eq!(
+            Identity::signing_item_allowed_calls((alice_did, bob_key)),
+            None
+        );
+        assert!(Identity::is_signer_authorized_for_call(
+            alice_did,
+            &bob_key,
+            &other_call
+        ));
+
+        // Removing the key drops its restriction.
+        assert_ok!(Identity::set_signing_item_allowed_calls(
+            alice.clone(),
+            alice_did,
+            bob_key,
+            Some(vec![transfer])
+        ));
+        assert_ok!(Identity::remove_signing_items(
+            alice.clone(),
+            alice_did,
+            vec![bob_key]
+        ));
+        assert_eq!(
+            Identity::signing_item_allowed_calls((alice_did, bob_key)),
+            None
+        );
+
+        // Unknown signers cannot be restricted.
+        let charlie_key =
+            Signatory::AccountKey(AccountKey::from(AccountKeyring::Charlie.public().0));
+        assert_err!(
+            Identity::set_signing_item_allowed_calls(alice, alice_did, charlie_key, None),
+            Error::<TestStorage>::InvalidSender
+        );
+    });
+}
+
+#[test]
+fn get_links_of_type_filters_links() {
+    ExtBuilder::default().build().execute_with(|| {
+        let bob_did = Signatory::from(register_keyring_account(AccountKeyring::Bob).unwrap());
+        let ticker50 = Ticker::from_slice(&[0x50]);
+        let ticker51 = Ticker::from_slice(&[0x51]);
+        let ticker52 = Ticker::from_slice(&[0x52]);
+
+        let ticker50_link =
+            Identity::add_link(bob_did, LinkData::TickerOwned(ticker50), None).unwrap();
+        assert_ok!(Identity::add_link(
+            bob_did,
+            LinkData::TokenOwned(ticker50),
+            None
+        ));
+        let expiring_link =
+            Identity::add_link(bob_did, LinkData::TickerOwned(ticker51), Some(100)).unwrap();
+        assert_ok!(Identity::add_link(bob_did, LinkData::NoData, None));
+        let ticker52_link =
+            Identity::add_link(bob_did, LinkData::TickerOwned(ticker52), None).unwrap();
+
+        let link_ids = |include_expired| {
+            Identity::get_links_of_type(bob_did, LinkDataTag::TickerOwned, include_expired)
+                .into_iter()
+                .map(|(link_id, link)| {
+                    assert_eq!(LinkDataTag::from(&link.link_data), LinkDataTag::TickerOwned);
+                    link_id
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            link_ids(false),
+            vec![ticker52_link, expiring_link, ticker50_link]
+        );
+
+        // Once expired, the link is only returned on demand.
+        Timestamp::set_timestamp(100);
+        assert_eq!(link_ids(false), vec![ticker52_link, ticker50_link]);
+        assert_eq!(
+            link_ids(true),
+            vec![ticker52_link, expiring_link, ticker50_link]
+        );
+
+        assert_eq!(
+            Identity::get_links_of_type(bob_did, LinkDataTag::TokenOwned, false)
+                .into_iter()
+                .map(|(_, link)| link.link_data)
+                .collect::<Vec<_>>(),
+            vec![LinkData::TokenOwned(ticker50)]
+        );
+        assert!(Identity::get_links_of_type(bob_did, LinkDataTag::DocumentOwned, true).is_empty());
+    });
+}
+
+#[test]
+fn recovery_key_replaces_master_key() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let alice_key = AccountKey::from(AccountKeyring::Alice.public().0);
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let bob_key = AccountKey::from(AccountKeyring::Bob.public().0);
+
+        // Only the master key sets a recovery key other than itself.
+        assert_err!(
+            Identity::set_recovery_key(bob.clone(), alice_did, Some(bob_key)),
+            "Only master key of an identity is able to execute this operation"
+        );
+        assert_err!(
+            Identity::set_recovery_key(alice.clone(), alice_did, Some(alice_key)),
+            Error::<TestStorage>::InvalidRecoveryKey
+        );
+        assert_err!(
+            Identity::initiate_recovery(bob.clone(), alice_did),
+            Error::<TestStorage>::Unauthorized
+        );
+        assert_ok!(Identity::set_recovery_key(
+            alice.clone(),
+            alice_did,
+            Some(bob_key)
+        ));
+        assert_eq!(Identity::recovery_key(alice_did), Some(bob_key));
+
+        // Completion before the timelock is rejected.
+        Timestamp::set_timestamp(1_000);
+        assert_err!(
+            Identity::complete_recovery(bob.clone(), alice_did),
+            Error::<TestStorage>::NoPendingRecovery
+        );
+        assert_ok!(Identity::initiate_recovery(bob.clone(), alice_did));
+        assert_err!(
+            Identity::initiate_recovery(bob.clone(), alice_did),
+            Error::<TestStorage>::RecoveryAlreadyInitiated
+        );
+        Timestamp::set_timestamp(1_000 + RECOVERY_TIMELOCK - 1);
+        assert_err!(
+            Identity::complete_recovery(bob.clone(), alice_did),
+            Error::<TestStorage>::RecoveryTimelockNotElapsed
+        );
+        assert_err!(
+            Identity::complete_recovery(alice.clone(), alice_did),
+            Error::<TestStorage>::Unauthorized
+        );
+
+        Timestamp::set_timestamp(1_000 + RECOVERY_TIMELOCK);
+        assert_ok!(Identity::complete_recovery(bob.clone(), alice_did));
+        assert_eq!(Identity::did_records(alice_did).master_key, bob_key);
+        assert_eq!(Identity::get_identity(&bob_key), Some(alice_did));
+        assert_eq!(Identity::get_identity(&alice_key), None);
+        assert_eq!(Identity::recovery_key(alice_did), None);
+        assert_eq!(Identity::pending_recovery(alice_did), None);
+
+        // The old master key lost its rights.
+        assert_err!(
+            Identity::set_recovery_key(alice, alice_did, Some(alice_key)),
+            "Only master key of an identity is able to execute this operation"
+        );
+    });
+}
+
+#[test]
+fn master_key_cancels_recovery() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let alice_key = AccountKey::from(AccountKeyring::Alice.public().0);
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let bob_key = AccountKey::from(AccountKeyring::Bob.public().0);
+
+        assert_ok!(Identity::set_recovery_key(
+            alice.clone(),
+            alice_did,
+            Some(bob_key)
+        ));
+        assert_err!(
+            Identity::cancel_recovery(alice.clone(), alice_did),
+            Error::<TestStorage>::NoPendingRecovery
+        );
+        assert_ok!(Identity::initiate_recovery(bob.clone(), alice_did));
+
+        assert_err!(
+            Identity::cancel_recovery(bob.clone(), alice_did),
+            "Only master key of an identity is able to execute this operation"
+        );
+        assert_ok!(Identity::cancel_recovery(alice.clone(), alice_did));
+        assert_eq!(Identity::pending_recovery(alice_did), None);
+
+        Timestamp::set_timestamp(RECOVERY_TIMELOCK);
+        assert_err!(
+            Identity::complete_recovery(bob.clone(), alice_did),
+            Error::<TestStorage>::NoPendingRecovery
+        );
+        assert_eq!(Identity::did_records(alice_did).master_key, alice_key);
+
+        // The recovery key can start a new recovery.
+        assert_ok!(Identity::initiate_recovery(bob, alice_did));
+    });
+}
+
+#[test]
+fn fetch_all_claim_values_skips_expired_claims() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let bob_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let charlie_did = register_keyring_account(AccountKeyring::Charlie).unwrap();
+        let charlie = Origin::signed(AccountKeyring::Charlie.public());
+        let bob_value = ClaimValue {
+            data_type: DataTypes::VecU8,
+            value: b"bob".to_vec(),
+        };
+        let charlie_value = ClaimValue {
+            data_type: DataTypes::VecU8,
+            value: b"charlie".to_vec(),
+        };
+
+        assert_ok!(Identity::add_claim(
+            bob.clone(),
+            alice_did,
+            b"key".to_vec(),
+            bob_did,
+            100,
+            bob_value.clone()
+        ));
+        assert_ok!(Identity::add_claim(
+            bob,
+            alice_did,
+            b"other".to_vec(),
+            bob_did,
+            100,
+            bob_value.clone()
+        ));
+        assert_ok!(Identity::add_claim(
+            charlie,
+            alice_did,
+            b"key".to_vec(),
+            charlie_did,
+            10,
+            charlie_value.clone()
+        ));
+
+        assert_eq!(
+            Identity::fetch_all_claim_values(alice_did, b"key".to_vec()),
+            vec![
+                (bob_did, bob_value.clone(), 100),
+                (charlie_did, charlie_value, 10)
+            ]
+        );
+
+        Timestamp::set_timestamp(50);
+        assert_eq!(
+            Identity::fetch_all_claim_values(alice_did, b"key".to_vec()),
+            vec![(bob_did, bob_value, 100)]
+        );
+        assert!(Identity::fetch_all_claim_values(alice_did, b"missing".to_vec()).is_empty());
+    });
+}
+
+#[test]
+fn register_did_is_rate_limited_per_account() {
+    ExtBuilder::default().build().execute_with(|| {
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let bob_key = AccountKey::from(AccountKeyring::Bob.public().0);
+
+        assert!(Identity::set_did_registration_cooldown(bob.clone(), 10).is_err());
+        assert_ok!(Identity::set_did_registration_cooldown(
+            frame_system::RawOrigin::Root.into(),
+            10
+        ));
+
+        // The first attempt fails for lack of funds but still starts the cooldown.
+        System::set_block_number(1);
+        assert!(Identity::register_did(bob.clone(), vec![]).is_err());
+        assert_eq!(
+            Identity::last_did_registration(AccountKeyring::Bob.public()),
+            1
+        );
+
+        Balances::make_free_balance_be(&AccountKeyring::Bob.public(), 1_000);
+        System::set_block_number(10);
+        assert_err!(
+            Identity::register_did(bob.clone(), vec![]),
+            Error::<TestStorage>::RegistrationRateLimited
+        );
+        assert_eq!(Identity::get_identity(&bob_key), None);
+
+        System::set_block_number(11);
+        assert_ok!(Identity::register_did(bob, vec![]));
+        assert!(Identity::get_identity(&bob_key).is_some());
+
+        // Other accounts are not affected.
+        Balances::make_free_balance_be(&AccountKeyring::Charlie.public(), 1_000);
+        assert_ok!(Identity::register_did(
+            Origin::signed(AccountKeyring::Charlie.public()),
+            vec![]
+        ));
+    });
+}
+
+#[test]
+fn authorization_issuer_reports_validity() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = Signatory::from(register_keyring_account(AccountKeyring::Alice).unwrap());
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_did = Signatory::from(register_keyring_account(AccountKeyring::Bob).unwrap());
+        let data = AuthorizationData::TransferTicker(Ticker::from_slice(b"TICKER"));
+
+        assert_ok!(Identity::add_authorization(
+            alice.clone(),
+            bob_did,
+            data.clone(),
+            None
+        ));
+        let valid_id = Identity::last_authorization(bob_did);
+        assert_ok!(Identity::add_authorization(alice, bob_did, data, Some(100)));
+        let expiring_id = Identity::last_authorization(bob_did);
+
+        assert_eq!(
+            Identity::authorization_issuer(bob_did, expiring_id),
+            Some((alice_did, true))
+        );
+
+        Timestamp::set_timestamp(100);
+        assert_eq!(
+            Identity::authorization_issuer(bob_did, valid_id),
+            Some((alice_did, true))
+        );
+        assert_eq!(
+            Identity::authorization_issuer(bob_did, expiring_id),
+            Some((alice_did, false))
+        );
+        assert_eq!(
+            Identity::authorization_issuer(bob_did, expiring_id + 1),
+            None
+        );
+        assert_eq!(Identity::authorization_issuer(alice_did, valid_id), None);
+    });
+}
+
+#[test]
+fn has_all_claims_checks_every_requirement() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let bob_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let charlie_did = register_keyring_account(AccountKeyring::Charlie).unwrap();
+        let charlie = Origin::signed(AccountKeyring::Charlie.public());
+        let claim_value = ClaimValue {
+            data_type: DataTypes::VecU8,
+            value: b"value".to_vec(),
+        };
+
+        assert_ok!(Identity::add_claim(
+            bob,
+            alice_did,
+            b"accredited".to_vec(),
+            bob_did,
+            100,
+            claim_value.clone()
+        ));
+        assert_ok!(Identity::add_claim(
+            charlie,
+            alice_did,
+            b"resident".to_vec(),
+            charlie_did,
+            10,
+            claim_value
+        ));
+
+        let requirements = vec![
+            (b"accredited".to_vec(), vec![charlie_did, bob_did]),
+            (b"resident".to_vec(), vec![charlie_did]),
+        ];
+        assert!(Identity::has_all_claims(alice_did, &[]));
+        assert!(Identity::has_all_claims(alice_did, &requirements));
+
+        // One requirement is not met by any acceptable issuer.
+        let mut missing = requirements.clone();
+        missing.push((b"accredited".to_vec(), vec![charlie_did]));
+        assert!(!Identity::has_all_claims(alice_did, &missing));
+
+        // The resident claim expires.
+        Timestamp::set_timestamp(50);
+        assert!(!Identity::has_all_claims(alice_did, &requirements));
+        assert!(Identity::has_all_claims(alice_did, &requirements[..1]));
+    });
+}
+
+#[test]
+fn emergency_freeze_key_can_freeze_and_unfreeze() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let bob_key = AccountKey::from(AccountKeyring::Bob.public().0);
+        let charlie = Origin::signed(AccountKeyring::Charlie.public());
+        let charlie_key = AccountKey::from(AccountKeyring::Charlie.public().0);
+
+        assert_ok!(Identity::add_signing_items(
+            alice.clone(),
+            alice_did,
+            vec![
+                SigningItem::new(
+                    Signatory::AccountKey(bob_key),
+                    vec![Permission::EmergencyFreeze]
+                ),
+                SigningItem::new(Signatory::AccountKey(charlie_key), vec![Permission::Admin]),
+            ]
+        ));
+        assert_ok!(Identity::authorize_join_to_identity(bob.clone(), alice_did));
+        assert_ok!(Identity::authorize_join_to_identity(
+            charlie.clone(),
+            alice_did
+        ));
+
+        // A key without the permission cannot freeze.
+        assert_err!(
+            Identity::freeze_signing_keys(charlie.clone(), alice_did),
+            "Only master key or an operator key of an identity is able to execute this operation"
+        );
+        assert_ok!(Identity::freeze_signing_keys(bob.clone(), alice_did));
+        assert!(Identity::is_did_frozen(alice_did));
+
+        // Nor unfreeze.
+        assert_err!(
+            Identity::unfreeze_signing_keys(charlie, alice_did),
+            "Only master key of an identity is able to execute this operation"
+        );
+        assert_ok!(Identity::unfreeze_signing_keys(bob.clone(), alice_did));
+        assert!(!Identity::is_did_frozen(alice_did));
+
+        // An individually frozen emergency key loses the permission.
+        assert_ok!(Identity::freeze_signing_key(
+            alice,
+            alice_did,
+            Signatory::AccountKey(bob_key)
+        ));
+        assert_err!(
+            Identity::freeze_signing_keys(bob, alice_did),
+            "Only master key or an operator key of an identity is able to execute this operation"
+        );
+    });
+}
+
+#[test]
+fn move_signing_key_with_consent_of_the_new_did() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let bob_signer = Signatory::AccountKey(AccountKey::from(AccountKeyring::Bob.public().0));
+        let charlie_did = register_keyring_account(AccountKeyring::Charlie).unwrap();
+        let charlie = Origin::signed(AccountKeyring::Charlie.public());
+        register_keyring_account(AccountKeyring::Dave).unwrap();
+        let dave = Origin::signed(AccountKeyring::Dave.public());
+
+        let bob_item = SigningItem::new(bob_signer, vec![Permission::Operator]);
+        assert_ok!(Identity::add_signing_items(
+            alice.clone(),
+            alice_did,
+            vec![bob_item.clone()]
+        ));
+        assert_ok!(Identity::authorize_join_to_identity(bob, alice_did));
+
+        // The new DID consents with an authorization given to the old one.
+        let consent = |origin: Origin, signer: Signatory| {
+            assert_ok!(Identity::add_authorization(
+                origin,
+                Signatory::from(alice_did),
+                AuthorizationData::MoveSigningKey(signer),
+                None
+            ));
+            Identity::last_authorization(Signatory::from(alice_did))
+        };
+
+        // Dave's consent cannot be used to move the key to Charlie's identity.
+        let dave_consent = consent(dave, bob_signer);
+        assert!(Identity::move_signing_key(
+            alice.clone(),
+            alice_did,
+            charlie_did,
+            bob_signer,
+            dave_consent
+        )
+        .is_err());
+        // The consent is only for the signer it names.
+        let charlie_key =
+            Signatory::AccountKey(AccountKey::from(AccountKeyring::Charlie.public().0));
+        let wrong_consent = consent(charlie.clone(), charlie_key);
+        assert_err!(
+            Identity::move_signing_key(
+                alice.clone(),
+                alice_did,
+                charlie_did,
+                bob_signer,
+                wrong_consent
+            ),
+            Error::<TestStorage>::UnknownAuthorization
+        );
+
+        let charlie_consent = consent(charlie.clone(), bob_signer);
+        // Only the master key of the old DID can move its keys.
+        assert_err!(
+            Identity::move_signing_key(
+                charlie,
+                alice_did,
+                charlie_did,
+                bob_signer,
+                charlie_consent
+            ),
+            "Only master key of an identity is able to execute this operation"
+        );
+        assert_ok!(Identity::move_signing_key(
+            alice.clone(),
+            alice_did,
+            charlie_did,
+            bob_signer,
+            charlie_consent
+        ));
+        // The consent is consumed.
+        assert!(!<identity::Authorizations<TestStorage>>::exists((
+            Signatory::from(alice_did),
+            charlie_consent
+        )));
+
+        assert!(Identity::did_records(alice_did).signing_items.is_empty());
+        assert_eq!(
+            Identity::did_records(charlie_did).signing_items,
+            vec![bob_item]
+        );
+        assert_eq!(
+            Identity::get_identity(&AccountKey::from(AccountKeyring::Bob.public().0)),
+            Some(charlie_did)
+        );
+        assert!(!Identity::is_signer_authorized(alice_did, &bob_signer));
+        assert!(Identity::is_signer_authorized(charlie_did, &bob_signer));
+        assert_eq!(
+            Identity::cached_signing_item_permissions((alice_did, bob_signer)),
+            None
+        );
+        assert_eq!(
+            Identity::cached_signing_item_permissions((charlie_did, bob_signer)),
+            Some(vec![Permission::Operator])
+        );
+    });
+}
+
+#[test]
+fn authorizations_issued_by_lists_pending_authorizations() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_id = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let bob_did = Signatory::from(bob_id);
+        let charlie_did =
+            Signatory::from(register_keyring_account(AccountKeyring::Charlie).unwrap());
+        let data = AuthorizationData::TransferTicker(Ticker::from_slice(b"TICKER"));
+
+        assert!(Identity::authorizations_issued_by(alice_did).is_empty());
+        let issued = [bob_did, charlie_did, bob_did]
+            .iter()
+            .map(|target| {
+                assert_ok!(Identity::add_authorization(
+                    alice.clone(),
+                    *target,
+                    data.clone(),
+                    None
+                ));
+                (*target, Identity::last_authorization(*target))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(Identity::authorizations_issued_by(alice_did), issued);
+        assert!(Identity::authorizations_issued_by(bob_id).is_empty());
+
+        assert_ok!(Identity::remove_authorization(
+            alice,
+            issued[0].0,
+            issued[0].1
+        ));
+        assert_eq!(
+            Identity::authorizations_issued_by(alice_did),
+            issued[1..].to_vec()
+        );
+    });
+}
+
+#[test]
+fn suspended_signing_key_is_not_authorized_until_restored() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let bob_signer = Signatory::AccountKey(AccountKey::from(AccountKeyring::Bob.public().0));
+
+        assert_ok!(Identity::add_signing_items(
+            alice.clone(),
+            alice_did,
+            vec![SigningItem::new(bob_signer, vec![Permission::Operator])]
+        ));
+        assert_ok!(Identity::authorize_join_to_identity(bob.clone(), alice_did));
+
+        // Only the master key can suspend.
+        assert_err!(
+            Identity::suspend_signing_key(bob.clone(), alice_did, bob_signer),
+            "Only master key of an identity is able to execute this operation"
+        );
+        assert_ok!(Identity::suspend_signing_key(
+            alice.clone(),
+            alice_did,
+            bob_signer
+        ));
+        assert_err!(
+            Identity::suspend_signing_key(alice.clone(), alice_did, bob_signer),
+            Error::<TestStorage>::SigningKeyAlreadySuspended
+        );
+        assert_eq!(
+            Identity::suspended_permissions((alice_did, bob_signer)),
+            Some(vec![Permission::Operator])
+        );
+        assert_eq!(
+            Identity::cached_signing_item_permissions((alice_did, bob_signer)),
+            Some(vec![])
+        );
+        assert!(!Identity::is_signer_authorized(alice_did, &bob_signer));
+        assert_err!(
+            Identity::freeze_signing_keys(bob.clone(), alice_did),
+            "Only master key or an operator key of an identity is able to execute this operation"
+        );
+
+        // Permissions granted while suspended do not authorize the key either, and they are
+        // merged with the suspended ones when it is restored.
+        assert_ok!(Identity::set_permission_to_signer(
+            alice.clone(),
+            alice_did,
+            bob_signer,
+            vec![Permission::Admin, Permission::Operator]
+        ));
+        assert_err!(
+            Identity::freeze_signing_keys(bob.clone(), alice_did),
+            "Only master key or an operator key of an identity is able to execute this operation"
+        );
+
+        assert_ok!(Identity::restore_signing_key(
+            alice.clone(),
+            alice_did,
+            bob_signer
+        ));
+        assert_err!(
+            Identity::restore_signing_key(alice, alice_did, bob_signer),
+            Error::<TestStorage>::SigningKeyNotSuspended
+        );
+        assert_eq!(
+            Identity::suspended_permissions((alice_did, bob_signer)),
+            None
+        );
+        assert_eq!(
+            Identity::cached_signing_item_permissions((alice_did, bob_signer)),
+            Some(vec![Permission::Admin, Permission::Operator])
+        );
+        assert!(Identity::is_signer_authorized(alice_did, &bob_signer));
+        assert_ok!(Identity::freeze_signing_keys(bob, alice_did));
+    });
+}
+
+#[test]
+fn active_signer_cannot_be_removed() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let charlie_did = register_keyring_account(AccountKeyring::Charlie).unwrap();
+        let charlie = Origin::signed(AccountKeyring::Charlie.public());
+
+        assert_ok!(Identity::add_signing_items(
+            alice.clone(),
+            alice_did,
+            vec![SigningItem::from(bob_did), SigningItem::from(charlie_did)]
+        ));
+        assert_ok!(Identity::authorize_join_to_identity(bob.clone(), alice_did));
+        assert_ok!(Identity::authorize_join_to_identity(charlie, alice_did));
+
+        // Alice's identity becomes a signing item of Bob's identity, so it can act for it.
+        assert_ok!(Identity::add_signing_items(
+            bob,
+            bob_did,
+            vec![SigningItem::from(alice_did)]
+        ));
+        assert_ok!(Identity::authorize_join_to_identity(alice.clone(), bob_did));
+
+        // Bob's identity is the one acting in the forwarded call, so it cannot be removed.
+        Identity::set_current_did(Some(alice_did));
+        let call = Box::new(Call::Identity(identity::Call::remove_signing_items(
+            alice_did,
+            vec![
+                Signatory::Identity(charlie_did),
+                Signatory::Identity(bob_did),
+            ],
+        )));
+        assert_ok!(Identity::forwarded_call(alice.clone(), bob_did, 0, call));
+        assert_eq!(Identity::did_records(alice_did).signing_items.len(), 2);
+
+        // Other signing items can still be removed.
+        Identity::set_current_did(Some(alice_did));
+        let call = Box::new(Call::Identity(identity::Call::remove_signing_items(
+            alice_did,
+            vec![Signatory::Identity(charlie_did)],
+        )));
+        assert_ok!(Identity::forwarded_call(alice, bob_did, 1, call));
+        assert_eq!(
+            Identity::did_records(alice_did).signing_items,
+            vec![SigningItem::from(bob_did)]
+        );
+    });
+}
+
+#[test]
+fn set_claim_value_type_annotates_existing_claim() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let bob_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let issuer_did = register_keyring_account(AccountKeyring::Charlie).unwrap();
+        let issuer = Origin::signed(AccountKeyring::Charlie.public());
+        let claim_key = b"age".to_vec();
+
+        assert_ok!(Identity::add_claim(
+            issuer.clone(),
+            alice_did,
+            claim_key.clone(),
+            issuer_did,
+            100u64,
+            ClaimValue {
+                data_type: DataTypes::VecU8,
+                value: 42u8.to_be_bytes().to_vec(),
+            }
+        ));
+
+        // Only the issuer can annotate the claim.
+        assert_err!(
+            Identity::set_claim_value_type(
+                Origin::signed(AccountKeyring::Bob.public()),
+                alice_did,
+                claim_key.clone(),
+                issuer_did,
+                DataTypes::U8
+            ),
+            "Sender must hold a claim issuer's signing key"
+        );
+        assert_err!(
+            Identity::set_claim_value_type(
+                issuer.clone(),
+                bob_did,
+                claim_key.clone(),
+                issuer_did,
+                DataTypes::U8
+            ),
+            Error::<TestStorage>::ClaimNotFound
+        );
+
+        assert_ok!(Identity::set_claim_value_type(
+            issuer,
+            alice_did,
+            claim_key.clone(),
+            issuer_did,
+            DataTypes::U8
+        ));
+        let claim = Identity::claims((
+            alice_did,
+            ClaimMetaData {
+                claim_key,
+                claim_issuer: issuer_did,
+            },
+        ));
+        assert_eq!(claim.claim_value.data_type, DataTypes::U8);
+        assert_eq!(claim.claim_value.value, 42u8.to_be_bytes().to_vec());
+    });
+}
+
+#[test]
+fn migrate_claim_types_tags_untyped_claims_once() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let bob_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let issuer_did = register_keyring_account(AccountKeyring::Charlie).unwrap();
+        let issuer = Origin::signed(AccountKeyring::Charlie.public());
+        let claim = |did: IdentityId, key: &[u8], data_type: DataTypes| ClaimRecord {
+            did,
+            claim_key: key.to_vec(),
+            expiry: 100u64,
+            claim_value: ClaimValue {
+                data_type,
+                value: b"value".to_vec(),
+            },
+        };
+        let meta = |key: &[u8]| ClaimMetaData {
+            claim_key: key.to_vec(),
+            claim_issuer: issuer_did,
+        };
+
+        assert_ok!(Identity::add_claims_batch(
+            issuer,
+            issuer_did,
+            vec![
+                claim(alice_did, b"a", DataTypes::VecU8),
+                claim(alice_did, b"b", DataTypes::Bool),
+                claim(alice_did, b"c", DataTypes::VecU8),
+                claim(bob_did, b"a", DataTypes::VecU8),
+            ]
+        ));
+        // Claims added before value types existed have no declared type.
+        for (did, key) in &[(alice_did, b"a"), (alice_did, b"b"), (bob_did, b"a")] {
+            <identity::TypedClaims>::remove((*did, meta(&key[..])));
+        }
+
+        assert!(Identity::migrate_claim_types(
+            Origin::signed(AccountKeyring::Alice.public()),
+            vec![alice_did, bob_did],
+            DataTypes::U64
+        )
+        .is_err());
+        assert_ok!(Identity::migrate_claim_types(
+            frame_system::RawOrigin::Root.into(),
+            vec![alice_did, bob_did],
+            DataTypes::U64
+        ));
+
+        let data_type =
+            |did: IdentityId, key: &[u8]| Identity::claims((did, meta(key))).claim_value.data_type;
+        assert_eq!(data_type(alice_did, b"a"), DataTypes::U64);
+        assert_eq!(data_type(bob_did, b"a"), DataTypes::U64);
+        // Claims with a non default type are kept.
+        assert_eq!(data_type(alice_did, b"b"), DataTypes::Bool);
+        // So are claims explicitly typed as raw bytes.
+        assert_eq!(data_type(alice_did, b"c"), DataTypes::VecU8);
+        assert!(Identity::is_claim_typed((bob_did, meta(b"a"))));
+
+        // Migrated claims are not migrated again.
+        assert_ok!(Identity::migrate_claim_types(
+            frame_system::RawOrigin::Root.into(),
+            vec![alice_did, bob_did],
+            DataTypes::U8
+        ));
+        assert_eq!(data_type(alice_did, b"a"), DataTypes::U64);
+
+        // The claims of all the DIDs must fit in a batch.
+        let dids = (0..11).map(|_| alice_did).collect::<Vec<_>>();
+        assert_err!(
+            Identity::migrate_claim_types(
+                frame_system::RawOrigin::Root.into(),
+                dids,
+                DataTypes::U8
+            ),
+            Error::<TestStorage>::BatchTooLarge
+        );
+    });
+}
+
+#[test]
+fn set_did_creation_fee_by_owner_or_root() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice = AccountKeyring::Alice.public();
+        let bob = AccountKeyring::Bob.public();
+        assert_eq!(Identity::owner(), alice);
+        assert_eq!(Identity::did_creation_fee(), 250);
+
+        assert_err!(
+            Identity::set_did_creation_fee(Origin::signed(bob), 0),
+            Error::<TestStorage>::Unauthorized
+        );
+        assert_eq!(Identity::did_creation_fee(), 250);
+
+        assert_ok!(Identity::set_did_creation_fee(Origin::signed(alice), 1_000));
+        assert_eq!(Identity::did_creation_fee(), 1_000);
+
+        // `register_did` charges the new fee.
+        Balances::make_free_balance_be(&bob, 5_000);
+        assert_ok!(Identity::register_did(Origin::signed(bob), vec![]));
+        assert_eq!(Balances::free_balance(&bob), 4_000);
+
+        assert_ok!(Identity::set_did_creation_fee(
+            frame_system::RawOrigin::Root.into(),
+            500
+        ));
+        assert_eq!(Identity::did_creation_fee(), 500);
+    });
+}
+
+#[test]
+fn authorizations_of_type_filters_by_type_and_expiry() {
+    ExtBuilder::default().build().execute_with(|| {
+        let _alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_did = Signatory::from(register_keyring_account(AccountKeyring::Bob).unwrap());
+        Timestamp::set_timestamp(100);
+
+        let add = |data: AuthorizationData, expiry: Option<u64>| {
+            assert_ok!(Identity::add_authorization(
+                alice.clone(),
+                bob_did,
+                data,
+                expiry
+            ));
+            Identity::last_authorization(bob_did)
+        };
+
+        let ticker_id = add(
+            AuthorizationData::TransferTicker(Ticker::from_slice(b"TICKER")),
+            None,
+        );
+        let multisig_id = add(AuthorizationData::AddMultiSigSigner, None);
+        let expired_ticker_id = add(
+            AuthorizationData::TransferTicker(Ticker::from_slice(b"EXPIRED")),
+            Some(200),
+        );
+        let _expired_multisig_id = add(AuthorizationData::AddMultiSigSigner, Some(200));
+        Timestamp::set_timestamp(500);
+
+        let ids = |include_expired: bool| {
+            Identity::authorizations_of_type(
+                bob_did,
+                AuthorizationType::TransferTicker,
+                include_expired,
+            )
+            .into_iter()
+            .map(|(auth_id, auth)| {
+                assert_eq!(
+                    AuthorizationType::from(&auth.authorization_data),
+                    AuthorizationType::TransferTicker
+                );
+                auth_id
+            })
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(false), vec![ticker_id]);
+        assert_eq!(ids(true), vec![expired_ticker_id, ticker_id]);
+
+        let multisig_auths =
+            Identity::authorizations_of_type(bob_did, AuthorizationType::AddMultiSigSigner, false);
+        assert_eq!(multisig_auths.len(), 1);
+        assert_eq!(multisig_auths[0].0, multisig_id);
+    });
+}
+
+#[test]
+fn batch_accept_authorization_reports_skipped_auths() {
+    ExtBuilder::default().build().execute_with(|| {
+        let _alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let bob_did = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+        let mock_data = AuthorizationData::Custom(Ticker::from_slice(b"MOCKAUTH"));
+        let add = || {
+            assert_ok!(Identity::add_authorization(
+                alice.clone(),
+                Signatory::from(bob_did),
+                mock_data.clone(),
+                None
+            ));
+            Identity::last_authorization(Signatory::from(bob_did))
+        };
+        let first_id = add();
+        let second_id = add();
+        let missing_id = second_id + 100;
+
+        // Events are only recorded after genesis.
+        System::set_block_number(1);
+        let events_of = |auth_ids: Vec<u64>| {
+            let before = System::events().len();
+            assert_ok!(Identity::batch_accept_authorization(bob.clone(), auth_ids));
+            System::events().len() - before
+        };
+
+        // Accepting a valid auth on its own gives the baseline number of events.
+        let accepted_events = events_of(vec![first_id]);
+        assert!(!<identity::Authorizations<TestStorage>>::exists((
+            Signatory::from(bob_did),
+            first_id
+        )));
+
+        // The missing auth is skipped with one extra event, and the valid one is still accepted.
+        assert_eq!(events_of(vec![second_id, missing_id]), accepted_events + 1);
+        assert!(!<identity::Authorizations<TestStorage>>::exists((
+            Signatory::from(bob_did),
+            second_id
+        )));
+        assert_eq!(events_of(vec![missing_id]), 1);
+    });
+}
+
+#[test]
+fn freeze_and_unfreeze_emit_events() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let _ = register_keyring_account(AccountKeyring::Bob).unwrap();
+        let bob = Origin::signed(AccountKeyring::Bob.public());
+
+        // Events are only recorded after genesis.
+        System::set_block_number(1);
+        let events_since = |before: usize| System::events().len() - before;
+
+        // A rejected freeze emits nothing.
+        let before = System::events().len();
+        assert!(Identity::freeze_signing_keys(bob, alice_did).is_err());
+        assert_eq!(events_since(before), 0);
+
+        // `DidFrozen` is emitted on freeze.
+        let before = System::events().len();
+        assert_ok!(Identity::freeze_signing_keys(alice.clone(), alice_did));
+        assert!(Identity::is_did_frozen(alice_did));
+        assert_eq!(events_since(before), 1);
+
+        // `DidUnfrozen` is emitted on unfreeze.
+        let before = System::events().len();
+        assert_ok!(Identity::unfreeze_signing_keys(alice, alice_did));
+        assert!(!Identity::is_did_frozen(alice_did));
+        assert_eq!(events_since(before), 1);
+    });
+}
+
+#[test]
+fn keyset_changes_emit_the_updated_keyset() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let alice_key = AccountKey::from(AccountKeyring::Alice.public().0);
+        let bob_signer = Signatory::from(AccountKey::from(AccountKeyring::Bob.public().0));
+
+        assert_ok!(Identity::add_signing_items(
+            alice.clone(),
+            alice_did,
+            vec![SigningItem::new(bob_signer, vec![])]
+        ));
+        assert_ok!(Identity::authorize_join_to_identity(
+            Origin::signed(AccountKeyring::Bob.public()),
+            alice_did
+        ));
+
+        // Events are only recorded after genesis.
+        System::set_block_number(1);
+        let events_since = |before: usize| System::events().len() - before;
+
+        // The granular event is followed by the consolidated keyset.
+        let before = System::events().len();
+        assert_ok!(Identity::set_permission_to_signer(
+            alice.clone(),
+            alice_did,
+            bob_signer,
+            vec![Permission::Operator]
+        ));
+        assert_eq!(events_since(before), 2);
+        assert_eq!(
+            Identity::did_records(alice_did).signing_items,
+            vec![SigningItem::new(bob_signer, vec![Permission::Operator])]
+        );
+
+        // Nothing is emitted when the keyset does not change.
+        let before = System::events().len();
+        assert_ok!(Identity::set_permission_to_signer(
+            alice,
+            alice_did,
+            Signatory::from(alice_key),
+            vec![Permission::Operator]
+        ));
+        assert_eq!(events_since(before), 0);
+    });
+}
@@ -109,6 +109,53 @@ fn join_multisig() {
     });
 }
 
+#[test]
+fn join_multisig_through_accept_authorization() {
+    ExtBuilder::default().build().execute_with(|| {
+        let alice_did = register_keyring_account(AccountKeyring::Alice).unwrap();
+        let alice = Origin::signed(AccountKeyring::Alice.public());
+        let charlie = Origin::signed(AccountKeyring::Charlie.public());
+        let charlie_signer = Signatory::from(
+            AccountKey::try_from(AccountKeyring::Charlie.public().encode()).unwrap(),
+        );
+
+        let musig_address = MultiSig::get_next_multisig_address(AccountKeyring::Alice.public());
+
+        assert_ok!(MultiSig::create_multisig(
+            alice.clone(),
+            vec![Signatory::from(alice_did), charlie_signer],
+            1,
+        ));
+
+        // Both an identity and a key accept through the generic authorization path.
+        let alice_auth_id = Identity::last_authorization(Signatory::from(alice_did));
+        assert_ok!(Identity::accept_authorization(alice.clone(), alice_auth_id));
+        let charlie_auth_id = Identity::last_authorization(charlie_signer);
+        assert_ok!(Identity::accept_authorization(
+            charlie.clone(),
+            charlie_auth_id
+        ));
+
+        assert_eq!(
+            MultiSig::ms_signers((musig_address.clone(), Signatory::from(alice_did))),
+            true
+        );
+        assert_eq!(
+            MultiSig::ms_signers((musig_address.clone(), charlie_signer)),
+            true
+        );
+        // The authorizations are consumed.
+        assert_err!(
+            Identity::accept_authorization(alice, alice_auth_id),
+            "Invalid auth"
+        );
+        assert_err!(
+            Identity::accept_authorization(charlie, charlie_auth_id),
+            "Invalid auth"
+        );
+    });
+}
+
 #[test]
 fn change_multisig_sigs_required() {
     ExtBuilder::default().build().execute_with(|| {
@@ -1,9 +1,9 @@
 use crate::{asset, exemption, general_tm, multisig, percentage_tm, statistics, utils};
 
-use polymesh_primitives::{AccountKey, IdentityId, Signatory};
+use polymesh_primitives::{AccountKey, AuthorizationData, IdentityId, Signatory, Ticker};
 use polymesh_runtime_balances as balances;
 use polymesh_runtime_common::traits::{
-    asset::AcceptTransfer, group::GroupTrait, multisig::AddSignerMultiSig, CommonTrait,
+    asset::AcceptTransfer, authorization::AuthorizationHandler, group::GroupTrait, CommonTrait,
 };
 use polymesh_runtime_group as group;
 use polymesh_runtime_identity as identity;
@@ -141,17 +141,24 @@ impl group::Trait<group::Instance1> for TestStorage {
     type MembershipChanged = ();
 }
 
+parameter_types! {
+    pub const MaxBatchSize: u32 = 10;
+}
+
 impl identity::Trait for TestStorage {
     type Event = Event;
     type Proposal = Call;
-    type AddSignerMultiSigTarget = TestStorage;
+    type AddSignerMultiSigTarget = MultiSig;
     type KycServiceProviders = TestStorage;
     type Balances = balances::Module<TestStorage>;
+    type MaxBatchSize = MaxBatchSize;
+    type AuthorizationHandlers = MockAuthorizationHandler;
+    type ClaimFeeCollector = ();
 }
 
 impl GroupTrait for TestStorage {
     fn get_members() -> Vec<IdentityId> {
-        unimplemented!()
+        KycServiceProviders::get_members()
     }
 
     fn is_member(_did: &IdentityId) -> bool {
@@ -159,18 +166,31 @@ impl GroupTrait for TestStorage {
     }
 }
 
-impl AddSignerMultiSig for TestStorage {
-    fn accept_multisig_signer(_: Signatory, _: u64) -> DispatchResult {
-        unimplemented!()
+impl AcceptTransfer for TestStorage {
+    fn accept_ticker_transfer(to_did: IdentityId, auth_id: u64) -> DispatchResult {
+        consume_mock_auth(Signatory::from(to_did), auth_id)
+    }
+    fn accept_token_ownership_transfer(to_did: IdentityId, auth_id: u64) -> DispatchResult {
+        consume_mock_auth(Signatory::from(to_did), auth_id)
     }
 }
 
-impl AcceptTransfer for TestStorage {
-    fn accept_ticker_transfer(_: IdentityId, _: u64) -> DispatchResult {
-        Ok(())
+/// Accepts the authorization `auth_id` of `signer` by just consuming it.
+fn consume_mock_auth(signer: Signatory, auth_id: u64) -> DispatchResult {
+    let auth = Identity::authorizations((signer, auth_id));
+    Identity::consume_auth(auth.authorized_by, signer, auth_id)
+}
+
+/// Accepts `Custom` authorizations of the `MOCKAUTH` ticker by consuming them.
+pub struct MockAuthorizationHandler;
+
+impl AuthorizationHandler for MockAuthorizationHandler {
+    fn handles(authorization_data: &AuthorizationData) -> bool {
+        *authorization_data == AuthorizationData::Custom(Ticker::from_slice(b"MOCKAUTH"))
     }
-    fn accept_token_ownership_transfer(_: IdentityId, _: u64) -> DispatchResult {
-        Ok(())
+
+    fn accept(signer: Signatory, _: &AuthorizationData, auth_id: u64) -> DispatchResult {
+        consume_mock_auth(signer, auth_id)
     }
 }
 
@@ -185,9 +205,16 @@ impl general_tm::Trait for TestStorage {
     type Asset = asset::Module<TestStorage>;
 }
 
+parameter_types! {
+    pub const MaxCustodiansPerHolder: u32 = 4;
+    pub const MaxIdentifiersPerToken: u32 = 3;
+}
+
 impl asset::Trait for TestStorage {
     type Event = Event;
     type Currency = balances::Module<TestStorage>;
+    type MaxCustodiansPerHolder = MaxCustodiansPerHolder;
+    type MaxIdentifiersPerToken = MaxIdentifiersPerToken;
 }
 
 impl exemption::Trait for TestStorage {
@@ -251,6 +278,7 @@ pub type Identity = identity::Module<TestStorage>;
 pub type Balances = balances::Module<TestStorage>;
 pub type Asset = asset::Module<TestStorage>;
 pub type MultiSig = multisig::Module<TestStorage>;
+pub type KycServiceProviders = group::Module<TestStorage, group::Instance1>;
 
 pub fn make_account(
     id: AccountId,
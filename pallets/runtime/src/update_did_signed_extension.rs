@@ -1,6 +1,6 @@
 use crate::{runtime, Runtime};
 
-use polymesh_primitives::{AccountKey, IdentityId, TransactionError};
+use polymesh_primitives::{AccountKey, IdentityId, Signatory, TransactionError};
 use polymesh_runtime_common::identity::LinkedKeyInfo;
 use polymesh_runtime_identity as identity;
 
@@ -68,6 +68,9 @@ impl<T: frame_system::Trait + Send + Sync> SignedExtension for UpdateDid<T> {
     /// that identity has been validated by any KYC.
     /// The current identity will be accesible through `Identity::current_did`.
     ///
+    /// A signing key restricted to some calls, see `Identity::set_signing_item_allowed_calls`,
+    /// can only be used for those calls.
+    ///
     /// Only the following methods can be called with no identity:
     ///     - `identity::register_did`
     fn validate(
@@ -77,6 +80,18 @@ impl<T: frame_system::Trait + Send + Sync> SignedExtension for UpdateDid<T> {
         _: DispatchInfo,
         _: usize,
     ) -> TransactionValidity {
+        // Signing keys restricted to some calls cannot be used for any other call.
+        if let Some(id) = Self::identity_from_key(who) {
+            if let Ok(who_key) = AccountKey::try_from(who.encode()) {
+                let signer = Signatory::AccountKey(who_key);
+                if !Identity::is_call_allowed(id, &signer, &call.encode()) {
+                    return Err(
+                        InvalidTransaction::Custom(TransactionError::CallNotAllowed as u8).into(),
+                    );
+                }
+            }
+        }
+
         match call {
             // Add here any function from any module which does *not* need a current identity.
             Call::Identity(identity::Call::register_did(..))
@@ -122,11 +137,12 @@ mod tests {
         Runtime,
     };
 
-    use polymesh_primitives::TransactionError;
+    use polymesh_primitives::{AccountKey, Signatory, SigningItem, TransactionError};
     use polymesh_runtime_identity as identity;
 
+    use codec::Encode;
     use core::default::Default;
-    use frame_support::dispatch::DispatchInfo;
+    use frame_support::{assert_ok, dispatch::DispatchInfo};
     use sp_runtime::{
         traits::SignedExtension,
         transaction_validity::{InvalidTransaction, ValidTransaction},
@@ -134,6 +150,7 @@ mod tests {
     use test_client::AccountKeyring;
 
     type Call = runtime::Call;
+    type Origin = <TestStorage as frame_system::Trait>::Origin;
     type IdentityCall = identity::Call<Runtime>;
 
     #[test]
@@ -182,5 +199,34 @@ mod tests {
             update_did_se.validate(&charlie_signed, &freeze_call1, dispatch_info, 0usize),
             Err(InvalidTransaction::Custom(TransactionError::MissingIdentity as u8).into())
         );
+
+        // A signing key restricted to some calls cannot be used for any other call.
+        let bob_signed = AccountKeyring::Bob.public();
+        let bob_key = Signatory::AccountKey(AccountKey::from(bob_signed.0));
+        assert_ok!(Identity::add_signing_items(
+            Origin::signed(alice_signed.clone()),
+            alice_id,
+            vec![SigningItem::new(bob_key, vec![])]
+        ));
+        assert_ok!(Identity::authorize_join_to_identity(
+            Origin::signed(bob_signed.clone()),
+            alice_id
+        ));
+        let freeze_call2 = Call::Identity(IdentityCall::freeze_signing_keys(alice_id));
+        assert_ok!(Identity::set_signing_item_allowed_calls(
+            Origin::signed(alice_signed.clone()),
+            alice_id,
+            bob_key,
+            Some(vec![freeze_call2.encode()[..2].to_vec()])
+        ));
+        assert_eq!(
+            update_did_se.validate(&bob_signed, &freeze_call2, dispatch_info, 0),
+            valid_transaction_ok
+        );
+        <UpdateDid<TestStorage>>::post_dispatch((), dispatch_info, 0);
+        assert_eq!(
+            update_did_se.validate(&bob_signed, &add_signing_items_1, dispatch_info, 0),
+            Err(InvalidTransaction::Custom(TransactionError::CallNotAllowed as u8).into())
+        );
     }
 }
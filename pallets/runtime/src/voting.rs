@@ -534,12 +534,19 @@ mod tests {
         type MembershipChanged = ();
     }
 
+    parameter_types! {
+        pub const MaxBatchSize: u32 = 10;
+    }
+
     impl identity::Trait for Test {
         type Event = ();
         type Proposal = Call<Test>;
         type AddSignerMultiSigTarget = Test;
         type KycServiceProviders = Test;
         type Balances = balances::Module<Test>;
+        type MaxBatchSize = MaxBatchSize;
+        type AuthorizationHandlers = ();
+        type ClaimFeeCollector = ();
     }
 
     impl GroupTrait for Test {
@@ -558,9 +565,16 @@ mod tests {
         }
     }
 
+    parameter_types! {
+        pub const MaxCustodiansPerHolder: u32 = 10;
+        pub const MaxIdentifiersPerToken: u32 = 10;
+    }
+
     impl asset::Trait for Test {
         type Event = ();
         type Currency = balances::Module<Test>;
+        type MaxCustodiansPerHolder = MaxCustodiansPerHolder;
+        type MaxIdentifiersPerToken = MaxIdentifiersPerToken;
     }
 
     impl statistics::Trait for Test {}
@@ -602,7 +616,9 @@ mod tests {
                 max_ticker_length: 12,
                 registration_length: Some(10000),
             },
+            min_ticker_length: 0,
             fee_collector: AccountKeyring::Dave.public().into(),
+            initial_balances: vec![],
         }
         .assimilate_storage(&mut t)
         .unwrap();
@@ -21,6 +21,13 @@ pub enum AuthorizationData {
     Custom(Ticker),
     /// No authorization data
     NoData,
+    /// Authorization to join an identity as a signing key
+    JoinIdentity(IdentityId),
+    /// Consent of the issuer of this authorization to take over the authorizations issued by
+    /// its target
+    ReassignAuthorizations,
+    /// Consent of the issuer of this authorization to receive the signing key from its target
+    MoveSigningKey(Signatory),
 }
 
 impl Default for AuthorizationData {
@@ -29,6 +36,43 @@ impl Default for AuthorizationData {
     }
 }
 
+/// Kind of an authorization, i.e. the variant of its `AuthorizationData` without the data.
+#[allow(missing_docs)]
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum AuthorizationType {
+    AttestMasterKeyRotation,
+    RotateMasterKey,
+    TransferTicker,
+    AddMultiSigSigner,
+    TransferTokenOwnership,
+    Custom,
+    NoData,
+    JoinIdentity,
+    ReassignAuthorizations,
+    MoveSigningKey,
+}
+
+impl From<&AuthorizationData> for AuthorizationType {
+    fn from(authorization_data: &AuthorizationData) -> Self {
+        match authorization_data {
+            AuthorizationData::AttestMasterKeyRotation(..) => {
+                AuthorizationType::AttestMasterKeyRotation
+            }
+            AuthorizationData::RotateMasterKey(..) => AuthorizationType::RotateMasterKey,
+            AuthorizationData::TransferTicker(..) => AuthorizationType::TransferTicker,
+            AuthorizationData::AddMultiSigSigner => AuthorizationType::AddMultiSigSigner,
+            AuthorizationData::TransferTokenOwnership(..) => {
+                AuthorizationType::TransferTokenOwnership
+            }
+            AuthorizationData::Custom(..) => AuthorizationType::Custom,
+            AuthorizationData::NoData => AuthorizationType::NoData,
+            AuthorizationData::JoinIdentity(..) => AuthorizationType::JoinIdentity,
+            AuthorizationData::ReassignAuthorizations => AuthorizationType::ReassignAuthorizations,
+            AuthorizationData::MoveSigningKey(..) => AuthorizationType::MoveSigningKey,
+        }
+    }
+}
+
 /// Status of an Authorization after consume is called on it.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, PartialOrd, Ord)]
 pub enum AuthorizationError {
@@ -73,3 +117,12 @@ pub struct Authorization<U> {
     /// Authorization number starts with 1.
     pub previous_authorization: u64,
 }
+
+/// Position of an authorization in the list of pending authorizations of its issuer.
+#[derive(Encode, Decode, Default, Clone, PartialEq, Debug)]
+pub struct IssuedAuthorizationLinks {
+    /// (target, auth id) of the previous authorization of the same issuer.
+    pub previous: Option<(Signatory, u64)>,
+    /// (target, auth id) of the next authorization of the same issuer.
+    pub next: Option<(Signatory, u64)>,
+}
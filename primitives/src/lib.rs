@@ -90,11 +90,14 @@ pub mod traits;
 pub use authorization::Authorization;
 pub use authorization::AuthorizationData;
 pub use authorization::AuthorizationError;
+pub use authorization::AuthorizationType;
+pub use authorization::IssuedAuthorizationLinks;
 
 /// Generic links that contains information about a key/identity for example ownership of a ticker
 pub mod link;
 pub use link::Link;
 pub use link::LinkData;
+pub use link::LinkDataTag;
 
 pub mod ticker;
 pub use ticker::Ticker;
@@ -115,4 +118,6 @@ pub enum TransactionError {
     MissingIdentity = 1,
     /// KYC is required
     RequiredKYC = 2,
+    /// The signing key is not allowed to be used for this call.
+    CallNotAllowed = 3,
 }
@@ -21,6 +21,27 @@ impl Default for LinkData {
     }
 }
 
+/// Kind of a link, i.e. the variant of its `LinkData` without the data.
+#[allow(missing_docs)]
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LinkDataTag {
+    DocumentOwned,
+    TickerOwned,
+    TokenOwned,
+    NoData,
+}
+
+impl From<&LinkData> for LinkDataTag {
+    fn from(link_data: &LinkData) -> Self {
+        match link_data {
+            LinkData::DocumentOwned(..) => LinkDataTag::DocumentOwned,
+            LinkData::TickerOwned(..) => LinkDataTag::TickerOwned,
+            LinkData::TokenOwned(..) => LinkDataTag::TokenOwned,
+            LinkData::NoData => LinkDataTag::NoData,
+        }
+    }
+}
+
 /// Link struct. Connects an Identity to some data.
 #[derive(Encode, Decode, Default, Clone, PartialEq, Debug)]
 pub struct Link<U> {
@@ -22,6 +22,8 @@ pub enum Permission {
     Operator,
     SpendFunds,
     Custom(u8),
+    /// Allows freezing and unfreezing the signing keys of the identity.
+    EmergencyFreeze,
 }
 
 /// Signing key type.
@@ -221,11 +221,16 @@ fn testnet_genesis(
                 max_ticker_length: 12,
                 registration_length: Some(5184000000),
             },
+            min_ticker_length: 0,
             fee_collector: get_account_id_from_seed::<sr25519::Public>("Dave"),
+            initial_balances: vec![],
         }),
         identity: Some(IdentityConfig {
             owner: get_account_id_from_seed::<sr25519::Public>("Dave"),
             did_creation_fee: 250,
+            claim_expiry_grace_period: 0,
+            claim_fee: 0,
+            identities: vec![],
         }),
         simple_token: Some(SimpleTokenConfig { creation_fee: 1000 }),
         balances: Some(BalancesConfig {